@@ -0,0 +1,11 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS idx_user_sessions_id_token_hash;
+DROP INDEX IF EXISTS idx_user_sessions_user_id;
+
+ALTER TABLE user_sessions
+    DROP COLUMN IF EXISTS id_token_hash,
+    DROP COLUMN IF EXISTS last_used_at,
+    DROP COLUMN IF EXISTS ip_address,
+    DROP COLUMN IF EXISTS user_agent,
+    DROP COLUMN IF EXISTS provider,
+    ALTER COLUMN access_token TYPE VARCHAR(255);
@@ -0,0 +1,15 @@
+-- Add up migration script here
+-- one session per login, so a user can be logged in from several devices
+ALTER TABLE user_sessions
+    ALTER COLUMN access_token TYPE TEXT,
+    ADD COLUMN provider VARCHAR(50) NOT NULL DEFAULT 'email',
+    ADD COLUMN user_agent TEXT,
+    ADD COLUMN ip_address VARCHAR(64),
+    ADD COLUMN last_used_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    ADD COLUMN id_token_hash TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_user_sessions_user_id ON user_sessions(user_id);
+
+-- provider id tokens can't carry a session id, the hash of the one handed out finds the session.
+-- sessions of earlier logins have none & have to login again
+CREATE INDEX IF NOT EXISTS idx_user_sessions_id_token_hash ON user_sessions(id_token_hash);
@@ -0,0 +1,7 @@
+-- Add down migration script here
+-- sessions without a plaintext refresh token can't be restored
+DELETE FROM user_sessions WHERE refresh_token IS NULL;
+ALTER TABLE user_sessions ALTER COLUMN refresh_token SET NOT NULL;
+
+DROP INDEX IF EXISTS idx_refresh_tokens_session_id;
+DROP TABLE IF EXISTS refresh_tokens;
@@ -0,0 +1,21 @@
+-- Add up migration script here
+-- every session is a refresh token family, each token can only be used once
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+    id VARCHAR(255) PRIMARY KEY NOT NULL,
+    session_id VARCHAR(255) NOT NULL,
+    token_hash VARCHAR(255) UNIQUE NOT NULL,
+    rotated_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (session_id) REFERENCES user_sessions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_session_id ON refresh_tokens(session_id);
+
+-- move the current refresh token of every session into the family
+INSERT INTO refresh_tokens (id, session_id, token_hash, created_at)
+SELECT gen_random_uuid()::TEXT, id, encode(sha256(refresh_token::BYTEA), 'hex'), created_at
+FROM user_sessions;
+
+-- only provider refresh tokens are kept, our own are looked up by hash
+ALTER TABLE user_sessions ALTER COLUMN refresh_token DROP NOT NULL;
+UPDATE user_sessions SET refresh_token = NULL WHERE provider = 'email';
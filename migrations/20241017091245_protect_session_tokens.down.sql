@@ -0,0 +1,7 @@
+-- Add down migration script here
+-- protected tokens can't be turned back into plaintext, those sessions have to login again
+DELETE FROM user_sessions WHERE token_key_version IS NOT NULL;
+DELETE FROM refresh_tokens WHERE keyed = TRUE;
+
+ALTER TABLE refresh_tokens DROP COLUMN IF EXISTS keyed;
+ALTER TABLE user_sessions DROP COLUMN IF EXISTS token_key_version;
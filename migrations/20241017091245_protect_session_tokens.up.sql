@@ -0,0 +1,7 @@
+-- Add up migration script here
+-- rows are converted on startup since the keys only live in the app config
+-- NULL means the session tokens are still stored in plaintext
+ALTER TABLE user_sessions ADD COLUMN token_key_version INTEGER;
+
+-- false means the hash is a plain sha256 digest without the app key
+ALTER TABLE refresh_tokens ADD COLUMN keyed BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,3 @@
+ALTER TABLE user_oauth_providers DROP CONSTRAINT IF EXISTS user_oauth_providers_provider_check;
+ALTER TABLE user_oauth_providers
+    ADD CONSTRAINT user_oauth_providers_provider_check CHECK (provider IN ('google', 'discord', 'email'));
@@ -0,0 +1,4 @@
+-- generic openid connect providers are named in the config, only keep the name format in check
+ALTER TABLE user_oauth_providers DROP CONSTRAINT IF EXISTS user_oauth_providers_provider_check;
+ALTER TABLE user_oauth_providers
+    ADD CONSTRAINT user_oauth_providers_provider_check CHECK (provider ~ '^[a-z0-9_-]+$');
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS user_oauth_providers_email_user_id_key;
+ALTER TABLE user_oauth_providers DROP CONSTRAINT IF EXISTS user_oauth_providers_provider_provider_user_id_key;
+ALTER TABLE user_oauth_providers
+    ADD CONSTRAINT user_oauth_providers_user_id_provider_key UNIQUE (user_id, provider);
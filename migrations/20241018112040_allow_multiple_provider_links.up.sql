@@ -0,0 +1,6 @@
+-- a user may link several identities of the same provider, but an identity belongs to one user
+ALTER TABLE user_oauth_providers DROP CONSTRAINT IF EXISTS user_oauth_providers_user_id_provider_key;
+ALTER TABLE user_oauth_providers
+    ADD CONSTRAINT user_oauth_providers_provider_provider_user_id_key UNIQUE (provider, provider_user_id);
+-- password logins are still one per user
+CREATE UNIQUE INDEX user_oauth_providers_email_user_id_key ON user_oauth_providers (user_id) WHERE provider = 'email';
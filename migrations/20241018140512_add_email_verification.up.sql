@@ -0,0 +1,23 @@
+-- Add up migration script here
+ALTER TABLE users ADD COLUMN email_verified_at TIMESTAMPTZ;
+-- the address of a provider login was vouched for by the provider, password accounts stay
+-- unverified & keep logging in through EMAIL_VERIFICATION_REQUIRED_SINCE
+UPDATE users SET email_verified_at = created_at
+WHERE EXISTS (
+    SELECT 1 FROM user_oauth_providers
+    WHERE user_oauth_providers.user_id = users.id AND user_oauth_providers.provider <> 'email'
+);
+
+-- single use tokens mailed to users, only the hash is stored
+CREATE TABLE IF NOT EXISTS user_tokens (
+    id VARCHAR(255) PRIMARY KEY NOT NULL,
+    user_id VARCHAR(255) NOT NULL,
+    purpose VARCHAR(50) NOT NULL,
+    token_hash VARCHAR(255) UNIQUE NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_tokens_user_id_purpose ON user_tokens(user_id, purpose);
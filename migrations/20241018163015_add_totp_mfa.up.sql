@@ -0,0 +1,27 @@
+-- Add up migration script here
+-- users of these roles have to login with a second factor
+ALTER TABLE roles ADD COLUMN mfa_required BOOLEAN NOT NULL DEFAULT false;
+UPDATE roles SET mfa_required = true WHERE name = 'IMMORTAL_USER';
+
+-- the secret is encrypted with the token encryption keys, unconfirmed until the first valid code
+CREATE TABLE IF NOT EXISTS user_totp (
+    user_id VARCHAR(255) PRIMARY KEY NOT NULL,
+    secret TEXT NOT NULL,
+    confirmed_at TIMESTAMPTZ,
+    -- time step of the last accepted code, a code is only accepted once
+    last_used_step BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS user_recovery_codes (
+    id VARCHAR(255) PRIMARY KEY NOT NULL,
+    user_id VARCHAR(255) NOT NULL,
+    code_hash VARCHAR(255) NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_recovery_codes_user_id ON user_recovery_codes(user_id);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS webauthn_credentials;
+DELETE FROM user_oauth_providers WHERE provider = 'webauthn';
+ALTER TABLE user_oauth_providers ALTER COLUMN provider_user_id TYPE VARCHAR(255);
@@ -0,0 +1,22 @@
+-- Add up migration script here
+-- passkeys log in as the `webauthn` provider, one link per credential with the credential id
+-- as provider_user_id, ids can be up to 1023 bytes so base64url doesn't fit into 255 chars
+ALTER TABLE user_oauth_providers ALTER COLUMN provider_user_id TYPE TEXT;
+
+CREATE TABLE IF NOT EXISTS webauthn_credentials (
+    id VARCHAR(255) PRIMARY KEY NOT NULL,
+    user_id VARCHAR(255) NOT NULL,
+    -- unlinking the provider link removes the passkey
+    oauth_provider_id VARCHAR(255) UNIQUE NOT NULL,
+    credential_id TEXT UNIQUE NOT NULL,
+    -- COSE encoded public key
+    public_key BYTEA NOT NULL,
+    sign_count BIGINT NOT NULL DEFAULT 0,
+    name VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMPTZ,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (oauth_provider_id) REFERENCES user_oauth_providers(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_webauthn_credentials_user_id ON webauthn_credentials(user_id);
@@ -18,3 +18,52 @@ pub struct EmailLoginRequest {
     #[validate(length(min = 1, message = "Password is required"))]
     pub password: String,
 }
+
+// the token arrives as query param from the mailed link or in the body from an app
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct VerifyEmailRequest {
+    #[validate(length(min = 1, message = "Token is required"))]
+    pub token: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct ResendEmailVerificationRequest {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct ForgotPasswordRequest {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct MagicLinkRequest {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct RedeemMagicLinkRequest {
+    #[validate(length(min = 1, message = "Token is required"))]
+    pub token: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct ResetPasswordRequest {
+    #[validate(length(min = 1, message = "Token is required"))]
+    pub token: String,
+
+    #[validate(length(min = 8, message = "Password must be at least 8 characters long"))]
+    pub new_password: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct ChangePasswordRequest {
+    #[validate(length(min = 1, message = "Current password is required"))]
+    pub current_password: String,
+
+    #[validate(length(min = 8, message = "Password must be at least 8 characters long"))]
+    pub new_password: String,
+}
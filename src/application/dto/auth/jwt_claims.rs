@@ -1,19 +1,34 @@
 use serde::{Deserialize, Serialize};
 
+// a single client id or a list of them, both forms are allowed by the spec
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Default for Audience {
+    fn default() -> Self {
+        Self::One(String::new())
+    }
+}
+
+// standard openid connect id token claims, provider specific claims are ignored
 #[derive(Debug, Default, Serialize, Deserialize)]
-pub struct GoogleJwtClaims {
-    pub iss: String,                 // Issuer, usually the Google accounts URL
-    pub azp: String,                 // Authorized party, usually the client ID of your app
-    pub aud: String,                 // Audience, usually your app's client ID
-    pub sub: String,                 // Subject, which is the unique Google user ID
-    pub hd: Option<String>,          // Hosted domain, if applicable (optional field)
-    pub email: String,               // User's email
-    pub email_verified: bool,        // Whether the email has been verified
-    pub at_hash: Option<String>,     // Access token hash (optional)
-    pub name: String,                // Full name of the user
-    pub picture: String,             // URL of the user's profile picture
-    pub given_name: Option<String>,  // Given name of the user (optional)
-    pub family_name: Option<String>, // Family name of the user (optional)
-    pub iat: u64,                    // Issued at timestamp (seconds since the epoch)
-    pub exp: u64,                    // Expiration timestamp (seconds since the epoch)
+pub struct IdTokenClaims {
+    pub iss: String,                        // Issuer, the provider that signed the token
+    pub sub: String,                        // Subject, the unique user ID at the provider
+    pub aud: Audience,                      // Audience, usually your app's client ID
+    pub azp: Option<String>,                // Authorized party, usually the client ID of your app
+    pub email: Option<String>,              // User's email
+    pub email_verified: Option<bool>,       // Whether the email has been verified
+    pub name: Option<String>,               // Full name of the user
+    pub picture: Option<String>,            // URL of the user's profile picture
+    pub given_name: Option<String>,         // Given name of the user (optional)
+    pub family_name: Option<String>,        // Family name of the user (optional)
+    pub nonce: Option<String>, // Value from the authorization request, binds the token to a login
+    pub preferred_username: Option<String>, // Login name at the provider (optional)
+    pub iat: u64,              // Issued at timestamp (seconds since the epoch)
+    pub exp: u64,              // Expiration timestamp (seconds since the epoch)
 }
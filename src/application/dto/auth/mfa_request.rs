@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::infra::common::constants::MFA_CHALLENGE_LIFETIME_SECONDS;
+
+// kept in redis between the password step and the second factor
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct MfaChallenge {
+    pub user_id: String,
+    // the login the challenge belongs to, the session is created for it
+    pub provider: String,
+    // encrypted provider tokens of an oauth login, they end up in the session
+    #[serde(default)]
+    pub provider_tokens: Option<String>,
+    pub attempts: u32,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl MfaChallenge {
+    pub fn new(user_id: String, provider: String) -> Self {
+        Self {
+            user_id,
+            provider,
+            provider_tokens: None,
+            attempts: 0,
+            expires_at: chrono::Utc::now()
+                + chrono::Duration::seconds(MFA_CHALLENGE_LIFETIME_SECONDS),
+        }
+    }
+
+    pub fn with_provider_tokens(mut self, provider_tokens: String) -> Self {
+        self.provider_tokens = Some(provider_tokens);
+        self
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct MfaTokenRequest {
+    #[validate(length(min = 1, message = "Mfa token is required"))]
+    pub mfa_token: String,
+}
+
+// the code is either the current totp code or one of the recovery codes
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct MfaLoginRequest {
+    #[validate(length(min = 1, message = "Mfa token is required"))]
+    pub mfa_token: String,
+
+    #[validate(length(min = 1, message = "Code is required"))]
+    pub code: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct MfaCodeRequest {
+    #[validate(length(min = 1, message = "Code is required"))]
+    pub code: String,
+}
@@ -0,0 +1,28 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MfaChallengeResponse {
+    pub mfa_token: String,
+    // the user's role requires mfa but nothing is enrolled yet, setup totp with the mfa token first
+    pub enrolment_required: bool,
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TotpEnrolmentResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+// shown once, only the hashes are stored
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MfaStatusResponse {
+    pub required: bool,
+    pub totp_enabled: bool,
+    pub recovery_codes_left: i64,
+}
@@ -1,4 +1,10 @@
 pub mod email_request;
 pub mod jwt_claims;
+pub mod mfa_request;
+pub mod mfa_response;
 pub mod oauth2_request;
 pub mod oauth2_response;
+pub mod session_response;
+pub mod token_response;
+pub mod webauthn_request;
+pub mod webauthn_response;
@@ -1,4 +1,24 @@
-#[derive(serde::Deserialize, Debug, Clone)]
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
 pub struct Oauth2Request {
     pub code: String,
+    pub state: String,
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Default)]
+pub struct OauthUrlRequest {
+    pub return_to: Option<String>,
+}
+
+// kept in redis between the authorize redirect and the callback
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct OauthLoginState {
+    pub provider: String,
+    pub code_verifier: String,
+    pub nonce: String,
+    // already checked against the allowlist
+    #[serde(default)]
+    pub return_to: Option<String>,
+    // set when a logged in user links the provider instead of logging in with it
+    #[serde(default)]
+    pub link_user_id: Option<String>,
 }
@@ -1,21 +1,45 @@
 use uuid::Uuid;
 
-use crate::domain::entities::user::User;
+use crate::domain::entities::{user::User, user_oauth_provider::UserOauthProvider};
 
-#[derive(serde::Deserialize, Debug, Clone)]
-pub struct GoogleTokenResponse {
-    pub id_token: String,
+use super::mfa_response::MfaChallengeResponse;
+
+// standard token endpoint response (RFC 6749), shared by every oauth provider
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct OauthTokenResponse {
     pub access_token: String,
-    pub token_type: String,
-    pub expires_in: i32,
-    pub refresh_token: String,
-    pub scope: String,
+    pub token_type: Option<String>,
+    pub expires_in: Option<i64>,
+    // missing when the provider keeps the previous refresh token
+    pub refresh_token: Option<String>,
+    // only openid connect providers return an id token
+    pub id_token: Option<String>,
+    pub scope: Option<String>,
+}
+
+// what the oauth callback did, with where the browser should go next
+#[derive(Debug, Clone)]
+pub enum OauthCallbackResult {
+    LoggedIn {
+        access_token: String,
+        refresh_token: String,
+        return_to: Option<String>,
+    },
+    Linked {
+        oauth_provider: UserOauthProvider,
+        return_to: Option<String>,
+    },
+    // no tokens yet, the client continues at /mfa/verify with the mfa token
+    MfaRequired {
+        challenge: MfaChallengeResponse,
+        return_to: Option<String>,
+    },
 }
 
 #[derive(serde::Deserialize, Debug, Clone)]
-pub struct GoogleTokenError {
+pub struct OauthTokenError {
     pub error: String,
-    pub error_description: String,
+    pub error_description: Option<String>,
 }
 
 #[derive(serde::Deserialize, Debug, Clone)]
@@ -29,18 +53,89 @@ pub struct GoogleUserResult {
     pub email_verified: bool,
 }
 
-impl From<&GoogleUserResult> for User {
-    fn from(google_user: &GoogleUserResult) -> Self {
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct DiscordUserResult {
+    pub id: String,
+    pub username: String,
+    pub global_name: Option<String>,
+    pub avatar: Option<String>,
+    // only present with the `email` scope & when the account has one
+    pub email: Option<String>,
+    #[serde(default)]
+    pub verified: bool,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct GithubUserResult {
+    pub id: i64,
+    pub login: String,
+    pub name: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct GithubEmailResult {
+    pub email: String,
+    pub primary: bool,
+    pub verified: bool,
+}
+
+// github answers token requests with 200 even when they fail
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum GithubTokenResult {
+    Token(OauthTokenResponse),
+    Error(OauthTokenError),
+}
+
+// standard claims from an openid connect userinfo endpoint
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct OidcUserInfoResult {
+    pub sub: String,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub name: Option<String>,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub preferred_username: Option<String>,
+    pub picture: Option<String>,
+}
+
+// user profile as reported by an oauth provider
+#[derive(Debug, Clone)]
+pub struct OauthUserInfo {
+    pub provider_user_id: String,
+    pub email: String,
+    pub email_verified: bool,
+    pub name: Option<String>,
+    pub picture: Option<String>,
+}
+
+impl From<GoogleUserResult> for OauthUserInfo {
+    fn from(google_user: GoogleUserResult) -> Self {
+        Self {
+            provider_user_id: google_user.sub,
+            email: google_user.email,
+            email_verified: google_user.email_verified,
+            name: Some(google_user.name),
+            picture: google_user.picture,
+        }
+    }
+}
+
+impl From<&OauthUserInfo> for User {
+    fn from(user_info: &OauthUserInfo) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
-            fullname: Some(google_user.name.clone()),
-            email: google_user.email.clone(),
+            fullname: user_info.name.clone(),
+            email: user_info.email.clone(),
             password_hash: None,
-            avatar_url: google_user.picture.clone(),
+            avatar_url: user_info.picture.clone(),
             is_active: true,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
             deleted_at: None,
+            email_verified_at: user_info.email_verified.then(chrono::Utc::now),
         }
     }
 }
@@ -0,0 +1,10 @@
+use serde::Serialize;
+
+use crate::domain::entities::user_session::UserSession;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionResponse {
+    #[serde(flatten)]
+    pub session: UserSession,
+    pub current: bool,
+}
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+// how login & refresh hand out tokens, `?delivery=body` is meant for clients without a cookie jar
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TokenDeliveryQuery {
+    pub delivery: Option<String>,
+}
+
+impl TokenDeliveryQuery {
+    pub fn in_body(&self) -> bool {
+        self.delivery.as_deref() == Some("body")
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub token_type: &'static str,
+    // only when the login enrolled totp, shown once like after any other enrolment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovery_codes: Option<Vec<String>>,
+}
+
+impl TokenResponse {
+    pub fn bearer(access_token: String, refresh_token: String) -> Self {
+        Self {
+            access_token,
+            refresh_token,
+            token_type: "Bearer",
+            recovery_codes: None,
+        }
+    }
+}
+
+// email logins of users with a second factor stop at the mfa challenge
+pub enum EmailLoginResult {
+    LoggedIn {
+        access_token: String,
+        refresh_token: String,
+    },
+    MfaRequired(super::mfa_response::MfaChallengeResponse),
+}
+
+pub struct MfaLoginResult {
+    pub access_token: String,
+    pub refresh_token: String,
+    // set when the code confirmed an enrolment forced by the user's role
+    pub recovery_codes: Option<Vec<String>>,
+}
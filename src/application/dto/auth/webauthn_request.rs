@@ -0,0 +1,49 @@
+use serde::Deserialize;
+use validator::Validate;
+
+// the `PublicKeyCredential` from `navigator.credentials.create()`, binary fields base64url encoded
+// the way `toJSON()` does it
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct WebauthnRegistrationRequest {
+    #[validate(length(min = 1, message = "Credential id is required"))]
+    pub id: String,
+
+    pub response: AttestationResponse,
+
+    // lets users tell their passkeys apart, e.g. "work laptop"
+    #[validate(length(max = 255, message = "Name must be at most 255 characters"))]
+    pub name: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AttestationResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+
+    #[serde(rename = "attestationObject")]
+    pub attestation_object: String,
+}
+
+// the `PublicKeyCredential` from `navigator.credentials.get()`
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct WebauthnLoginRequest {
+    #[validate(length(min = 1, message = "Credential id is required"))]
+    pub id: String,
+
+    pub response: AssertionResponse,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AssertionResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+
+    #[serde(rename = "authenticatorData")]
+    pub authenticator_data: String,
+
+    pub signature: String,
+
+    // discoverable credentials always return the user id they were created for
+    #[serde(rename = "userHandle")]
+    pub user_handle: Option<String>,
+}
@@ -0,0 +1,76 @@
+use serde::Serialize;
+
+// options for `navigator.credentials.create()`, binary values are base64url encoded,
+// `PublicKeyCredential.parseCreationOptionsFromJSON()` understands them as is
+#[derive(Debug, Clone, Serialize)]
+pub struct WebauthnCreationOptions {
+    #[serde(rename = "publicKey")]
+    pub public_key: PublicKeyCreationOptions,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyCreationOptions {
+    pub rp: RelyingParty,
+    pub user: WebauthnUser,
+    pub challenge: String,
+    pub pub_key_cred_params: Vec<CredentialParameter>,
+    pub timeout: u64,
+    pub exclude_credentials: Vec<CredentialDescriptor>,
+    pub authenticator_selection: AuthenticatorSelection,
+    pub attestation: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RelyingParty {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebauthnUser {
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialParameter {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub alg: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialDescriptor {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticatorSelection {
+    pub resident_key: String,
+    pub require_resident_key: bool,
+    pub user_verification: String,
+}
+
+// options for `navigator.credentials.get()`, no allowed credentials so the browser offers
+// every passkey it has for the rp
+#[derive(Debug, Clone, Serialize)]
+pub struct WebauthnRequestOptions {
+    #[serde(rename = "publicKey")]
+    pub public_key: PublicKeyRequestOptions,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyRequestOptions {
+    pub challenge: String,
+    pub timeout: u64,
+    pub rp_id: String,
+    pub allow_credentials: Vec<CredentialDescriptor>,
+    pub user_verification: String,
+}
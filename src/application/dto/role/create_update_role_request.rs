@@ -10,7 +10,13 @@ pub struct CreateOrUpdateRole {
 
     pub is_default: bool,
 
+    // kept as is on update when left out
+    pub mfa_required: Option<bool>,
+
     pub permissions: Option<Vec<String>>,
+
+    // ids of the roles this role inherits permissions from
+    pub inherits: Option<Vec<String>>,
 }
 
 impl From<&CreateOrUpdateRole> for Role {
@@ -19,6 +25,7 @@ impl From<&CreateOrUpdateRole> for Role {
             id: uuid::Uuid::new_v4().to_string(),
             name: req.name.clone(),
             is_default: req.is_default,
+            mfa_required: req.mfa_required.unwrap_or(false),
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
             deleted_at: None,
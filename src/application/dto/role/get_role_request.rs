@@ -7,4 +7,5 @@ pub struct RoleWithPermission {
     #[serde(flatten)]
     pub role: Role,
     pub permissions: Vec<String>,
+    pub inherits: Vec<String>,
 }
@@ -0,0 +1,8 @@
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct AssignRoleRequest {
+    #[validate(length(min = 1, message = "Role id is required"))]
+    pub role_id: String,
+}
@@ -0,0 +1,11 @@
+use serde::Serialize;
+
+use crate::domain::entities::{role::Role, user::User, user_oauth_provider::UserOauthProvider};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UserDetail {
+    #[serde(flatten)]
+    pub user: User,
+    pub roles: Vec<Role>,
+    pub providers: Vec<UserOauthProvider>,
+}
@@ -0,0 +1,3 @@
+pub mod assign_role_request;
+pub mod get_user_request;
+pub mod update_user_request;
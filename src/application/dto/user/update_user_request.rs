@@ -0,0 +1,15 @@
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct UpdateUserRequest {
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "Fullname must be between 1 and 255 characters"
+    ))]
+    pub fullname: Option<String>,
+
+    #[validate(url(message = "Avatar url must be a valid url"))]
+    pub avatar_url: Option<String>,
+}
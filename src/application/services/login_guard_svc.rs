@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use tracing::warn;
+
+use crate::{
+    domain::repositories::redis_repo::RedisRepository,
+    infra::{
+        common::constants::{
+            LOGIN_DELAY_AFTER_FAILURES, LOGIN_FAILURE_WINDOW_SECONDS, LOGIN_LOCKOUT_AFTER_FAILURES,
+            LOGIN_LOCKOUT_SECONDS, LOGIN_MAX_FAILURES_PER_IP,
+        },
+        errors::app_error::AppError,
+    },
+};
+
+// failed password logins are counted per email & per ip. an email has to wait a bit longer
+// after every failure until it's locked for a while, an ip only has a hard limit.
+// emails are counted whether they're registered or not, so the answers look the same.
+// the ip is the one ClientInfo resolved from the peer, a client can't pick a fresh one per try
+#[derive(Clone)]
+pub struct LoginGuardService<C> {
+    redis_repo: Arc<C>,
+}
+
+impl<C> LoginGuardService<C>
+where
+    C: RedisRepository,
+{
+    pub fn new(redis_repo: Arc<C>) -> Self {
+        Self { redis_repo }
+    }
+
+    pub async fn check(&self, email: &str, ip_address: Option<&str>) -> Result<(), AppError> {
+        if self.redis_repo.get_value(&blocked_key(email)).await.is_ok() {
+            return Err(AppError::TooManyRequests);
+        }
+
+        if let Some(ip_address) = ip_address {
+            let failures = self
+                .redis_repo
+                .get_value(&ip_key(ip_address))
+                .await
+                .ok()
+                .and_then(|failures| failures.parse::<i64>().ok())
+                .unwrap_or(0);
+            if failures >= LOGIN_MAX_FAILURES_PER_IP {
+                return Err(AppError::TooManyRequests);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn record_failure(
+        &self,
+        email: &str,
+        ip_address: Option<&str>,
+    ) -> Result<(), AppError> {
+        let failures = self
+            .redis_repo
+            .increment(&account_key(email), LOGIN_FAILURE_WINDOW_SECONDS)
+            .await?;
+        if let Some(ip_address) = ip_address {
+            self.redis_repo
+                .increment(&ip_key(ip_address), LOGIN_FAILURE_WINDOW_SECONDS)
+                .await?;
+        }
+
+        if let Some(block_seconds) = block_seconds(failures) {
+            if failures == LOGIN_LOCKOUT_AFTER_FAILURES {
+                warn!(
+                    "Locking email login of {} after {} failures",
+                    email, failures
+                );
+            }
+            self.redis_repo
+                .set_value_with_expiry(&blocked_key(email), "1", block_seconds)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    // after a successful login or when an admin unlocks the account
+    pub async fn clear(&self, email: &str) -> Result<(), AppError> {
+        self.redis_repo.delete_value(&account_key(email)).await?;
+        self.redis_repo.delete_value(&blocked_key(email)).await?;
+
+        Ok(())
+    }
+}
+
+// 1s, 2s, 4s ... from the first delayed failure on, the lockout once there are too many
+fn block_seconds(failures: i64) -> Option<u64> {
+    if failures >= LOGIN_LOCKOUT_AFTER_FAILURES {
+        return Some(LOGIN_LOCKOUT_SECONDS);
+    }
+    if failures >= LOGIN_DELAY_AFTER_FAILURES {
+        return Some(1 << (failures - LOGIN_DELAY_AFTER_FAILURES));
+    }
+
+    None
+}
+
+fn account_key(email: &str) -> String {
+    format!("login_failures_account_{}", email.trim().to_lowercase())
+}
+
+fn blocked_key(email: &str) -> String {
+    format!("login_blocked_{}", email.trim().to_lowercase())
+}
+
+fn ip_key(ip_address: &str) -> String {
+    format!("login_failures_ip_{}", ip_address)
+}
@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use crate::{
+    domain::entities::user::User,
+    infra::{
+        errors::app_error::AppError,
+        mail::mailer::{Mail, Mailer},
+    },
+};
+
+// composes the mails sent by the auth flows
+#[derive(Clone)]
+pub struct MailService {
+    mailer: Arc<dyn Mailer>,
+    app_name: String,
+    email_verification_url: String,
+    password_reset_url: String,
+    magic_link_url: String,
+}
+
+impl MailService {
+    pub fn new(
+        mailer: Arc<dyn Mailer>,
+        app_name: String,
+        email_verification_url: String,
+        password_reset_url: String,
+        magic_link_url: String,
+    ) -> Self {
+        Self {
+            mailer,
+            app_name,
+            email_verification_url,
+            password_reset_url,
+            magic_link_url,
+        }
+    }
+
+    pub async fn send_email_verification(&self, user: &User, token: &str) -> Result<(), AppError> {
+        let link = with_token(&self.email_verification_url, token)?;
+
+        self.mailer
+            .send(&Mail {
+                to: user.email.clone(),
+                subject: format!("Verify your email for {}", self.app_name),
+                body: format!(
+                    "Hi {},\n\nPlease verify your email address by opening the link below, it is valid for 24 hours.\n\n{}\n\nIf you didn't create an account you can ignore this email.",
+                    user.fullname.as_deref().unwrap_or(&user.email),
+                    link
+                ),
+            })
+            .await
+    }
+
+    pub async fn send_password_reset(&self, user: &User, token: &str) -> Result<(), AppError> {
+        let link = with_token(&self.password_reset_url, token)?;
+
+        self.mailer
+            .send(&Mail {
+                to: user.email.clone(),
+                subject: format!("Reset your {} password", self.app_name),
+                body: format!(
+                    "Hi {},\n\nSomeone asked to reset the password of your account. Open the link below to choose a new one, it is valid for 1 hour.\n\n{}\n\nIf it wasn't you, you can ignore this email, your password stays the same.",
+                    user.fullname.as_deref().unwrap_or(&user.email),
+                    link
+                ),
+            })
+            .await
+    }
+
+    pub async fn send_magic_link(&self, user: &User, token: &str) -> Result<(), AppError> {
+        let link = with_token(&self.magic_link_url, token)?;
+
+        self.mailer
+            .send(&Mail {
+                to: user.email.clone(),
+                subject: format!("Your {} login link", self.app_name),
+                body: format!(
+                    "Hi {},\n\nOpen the link below to login, it is valid for 15 minutes and can only be used once.\n\n{}\n\nIf you didn't ask for it, you can ignore this email.",
+                    user.fullname.as_deref().unwrap_or(&user.email),
+                    link
+                ),
+            })
+            .await
+    }
+}
+
+fn with_token(url: &str, token: &str) -> Result<String, AppError> {
+    reqwest::Url::parse_with_params(url, &[("token", token)])
+        .map(|url| url.to_string())
+        .map_err(|err| AppError::ProcessError(err.to_string()))
+}
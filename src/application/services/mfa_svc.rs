@@ -0,0 +1,184 @@
+use std::sync::Arc;
+
+use crate::{
+    application::dto::auth::mfa_response::{MfaStatusResponse, TotpEnrolmentResponse},
+    domain::{
+        entities::{
+            user::User,
+            user_mfa::{UserRecoveryCode, UserTotp},
+        },
+        repositories::{role_repo::RoleRepository, user_mfa_repo::UserMfaRepository},
+    },
+    infra::{
+        errors::app_error::AppError,
+        utils::{
+            token_cipher::TokenCipher,
+            totp::{generate_recovery_codes, normalize_recovery_code, Totp},
+        },
+    },
+};
+
+#[derive(Clone)]
+pub struct MfaService<M, R> {
+    user_mfa_repo: Arc<M>,
+    role_repo: Arc<R>,
+    token_cipher: Arc<TokenCipher>,
+    issuer: String,
+}
+
+impl<M, R> MfaService<M, R>
+where
+    M: UserMfaRepository,
+    R: RoleRepository,
+{
+    pub fn new(
+        user_mfa_repo: Arc<M>,
+        role_repo: Arc<R>,
+        token_cipher: Arc<TokenCipher>,
+        issuer: String,
+    ) -> Self {
+        Self {
+            user_mfa_repo,
+            role_repo,
+            token_cipher,
+            issuer,
+        }
+    }
+
+    // one role with the policy is enough
+    pub async fn is_required(&self, user_id: &str) -> Result<bool, AppError> {
+        Ok(self
+            .role_repo
+            .get_roles_by_user_id(user_id)
+            .await?
+            .iter()
+            .any(|role| role.mfa_required))
+    }
+
+    pub async fn find_totp(&self, user_id: &str) -> Result<Option<UserTotp>, AppError> {
+        match self.user_mfa_repo.find_totp(user_id).await {
+            Ok(user_totp) => Ok(Some(user_totp)),
+            Err(AppError::SqlxError(sqlx::Error::RowNotFound)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub async fn is_totp_enabled(&self, user_id: &str) -> Result<bool, AppError> {
+        Ok(self
+            .find_totp(user_id)
+            .await?
+            .is_some_and(|user_totp| user_totp.is_confirmed()))
+    }
+
+    pub async fn status(&self, user_id: &str) -> Result<MfaStatusResponse, AppError> {
+        Ok(MfaStatusResponse {
+            required: self.is_required(user_id).await?,
+            totp_enabled: self.is_totp_enabled(user_id).await?,
+            recovery_codes_left: self
+                .user_mfa_repo
+                .count_unused_recovery_codes(user_id)
+                .await?,
+        })
+    }
+
+    // an unconfirmed enrolment is simply replaced, a confirmed one has to be disabled first
+    pub async fn start_totp_enrolment(
+        &self,
+        user: &User,
+    ) -> Result<TotpEnrolmentResponse, AppError> {
+        if self.is_totp_enabled(&user.id).await? {
+            return Err(AppError::ResourceExist(
+                "Totp is already enabled".to_string(),
+            ));
+        }
+
+        let secret = Totp::generate_secret();
+        let totp = Totp::new(&secret, &self.issuer, &user.email)?;
+
+        self.user_mfa_repo
+            .save_totp(&UserTotp::new(
+                user.id.clone(),
+                self.token_cipher.encrypt(&secret)?,
+            ))
+            .await?;
+
+        Ok(TotpEnrolmentResponse {
+            otpauth_uri: totp.otpauth_uri(),
+            secret,
+        })
+    }
+
+    pub async fn confirm_totp_enrolment(&self, user: &User, code: &str) -> Result<(), AppError> {
+        let user_totp = self.find_totp(&user.id).await?.ok_or(AppError::NotFound(
+            "Start the totp enrolment first".to_string(),
+        ))?;
+        if user_totp.is_confirmed() {
+            return Err(AppError::ResourceExist(
+                "Totp is already enabled".to_string(),
+            ));
+        }
+
+        let step = self
+            .totp(user, &user_totp)?
+            .verify(code)
+            .ok_or(AppError::InvalidMfaCode)?;
+        self.user_mfa_repo.confirm_totp(&user.id, step).await
+    }
+
+    // the code is a totp code when it's all digits, otherwise a recovery code
+    pub async fn verify_code(&self, user: &User, code: &str) -> Result<(), AppError> {
+        let user_totp = self
+            .find_totp(&user.id)
+            .await?
+            .filter(|user_totp| user_totp.is_confirmed())
+            .ok_or(AppError::InvalidMfaCode)?;
+
+        let valid = if code.trim().chars().all(|c| c.is_ascii_digit()) {
+            match self.totp(user, &user_totp)?.verify(code) {
+                Some(step) => self.user_mfa_repo.use_totp_step(&user.id, step).await?,
+                None => false,
+            }
+        } else {
+            let code_hash = self.token_cipher.hash(&normalize_recovery_code(code));
+            self.user_mfa_repo
+                .use_recovery_code(&user.id, &code_hash)
+                .await?
+        };
+
+        if !valid {
+            return Err(AppError::InvalidMfaCode);
+        }
+
+        Ok(())
+    }
+
+    // replaces all previous codes
+    pub async fn regenerate_recovery_codes(&self, user_id: &str) -> Result<Vec<String>, AppError> {
+        let codes = generate_recovery_codes();
+        let recovery_codes: Vec<UserRecoveryCode> = codes
+            .iter()
+            .map(|code| {
+                UserRecoveryCode::new(
+                    user_id.to_string(),
+                    self.token_cipher.hash(&normalize_recovery_code(code)),
+                )
+            })
+            .collect();
+
+        self.user_mfa_repo
+            .replace_recovery_codes(user_id, &recovery_codes)
+            .await?;
+
+        Ok(codes)
+    }
+
+    pub async fn disable_totp(&self, user_id: &str) -> Result<(), AppError> {
+        self.user_mfa_repo.delete_totp(user_id).await
+    }
+
+    fn totp(&self, user: &User, user_totp: &UserTotp) -> Result<Totp, AppError> {
+        let secret = self.token_cipher.decrypt(&user_totp.secret)?;
+
+        Totp::new(&secret, &self.issuer, &user.email)
+    }
+}
@@ -1,2 +1,6 @@
+pub mod login_guard_svc;
+pub mod mail_svc;
+pub mod mfa_svc;
 pub mod oauth_svc;
 pub mod redis_svc;
+pub mod user_token_svc;
@@ -1,14 +1,15 @@
-use std::{collections::HashMap, sync::Arc};
+use std::sync::Arc;
 
-use chrono::TimeZone;
 use tracing::info;
 
 use crate::{
-    application::dto::auth::oauth2_response::{
-        GoogleTokenError, GoogleTokenResponse, GoogleUserResult,
+    application::dto::auth::{
+        oauth2_request::OauthLoginState,
+        oauth2_response::{OauthTokenResponse, OauthUserInfo},
     },
     domain::{
         entities::{
+            refresh_token::RefreshToken,
             user::{User, UserFull},
             user_oauth_provider::UserOauthProvider,
             user_role::UserRole,
@@ -20,19 +21,24 @@ use crate::{
         },
     },
     infra::{
-        config::AppConfig,
+        common::constants::SESSION_LIFETIME_SECONDS,
         errors::app_error::AppError,
-        oauth2::{constants::GOOGLE_PROVIDER, google::GOOGLE_TOKEN_ENDPOINT},
+        oauth2::{constants::MAGIC_LINK_PROVIDER, registry::OauthProviderRegistry},
+        rbac::Rbac,
+        utils::{client_info::ClientInfo, jwt_maker::TokenProfile, token_cipher::TokenCipher},
     },
 };
 
 #[derive(Clone)]
 pub struct OauthService<U, R, S, O> {
-    cfg: Arc<AppConfig>,
+    providers: Arc<OauthProviderRegistry>,
+    rbac: Arc<Rbac>,
+    token_cipher: Arc<TokenCipher>,
     user_repo: Arc<U>,
     role_repo: Arc<R>,
     user_session_repo: Arc<S>,
     oauth_provider_repo: Arc<O>,
+    auto_link_verified_email: bool,
 }
 
 impl<U, R, S, O> OauthService<U, R, S, O>
@@ -42,141 +48,212 @@ where
     S: UserSessionRepository,
     O: OauthProviderRepository,
 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        cfg: Arc<AppConfig>,
+        providers: Arc<OauthProviderRegistry>,
+        rbac: Arc<Rbac>,
+        token_cipher: Arc<TokenCipher>,
         user_repo: Arc<U>,
         role_repo: Arc<R>,
         user_session_repo: Arc<S>,
         oauth_provider_repo: Arc<O>,
+        auto_link_verified_email: bool,
     ) -> Self {
         Self {
-            cfg,
+            providers,
+            rbac,
+            token_cipher,
             user_repo,
             role_repo,
             user_session_repo,
             oauth_provider_repo,
+            auto_link_verified_email,
         }
     }
 
-    pub async fn google_login(
+    // login the user linked to the provider identity, register a new one when nobody has the email
+    pub async fn oauth_login(
         &self,
         db_pool: &sqlx::PgPool,
+        provider_name: &str,
         code: &str,
-    ) -> Result<GoogleTokenResponse, AppError> {
-        let mut data = HashMap::new();
-
-        data.insert("code".to_string(), code.to_string());
-        data.insert("client_id".to_string(), self.cfg.google_client_id.clone());
-        data.insert(
-            "client_secret".to_string(),
-            self.cfg.google_client_secret.clone(),
-        );
-        data.insert(
-            "redirect_uri".to_string(),
-            self.cfg.google_redirect_url.clone(),
-        );
-        data.insert("grant_type".to_string(), "authorization_code".to_string());
-
-        let client = reqwest::Client::new();
-
-        let response = client.post(GOOGLE_TOKEN_ENDPOINT).form(&data).send().await;
-
-        match response {
-            Ok(r) => {
-                if r.status().is_success() {
-                    let resp = r.json::<GoogleTokenResponse>().await?;
-
-                    let user_info = self
-                        .fetch_google_user(&resp.id_token, &resp.access_token)
-                        .await?;
-
-                    // if user already registered just return google auth response
-                    if let Ok(u) = self.user_repo.find_by_email(&user_info.email).await {
-                        let _session = self
-                            .get_or_create_session(
-                                &u.id,
-                                &resp.access_token,
-                                &resp.refresh_token,
-                                Some(60 * 60 * 24 * 7),
-                            )
+        login_state: &OauthLoginState,
+    ) -> Result<(User, OauthTokenResponse), AppError> {
+        let (user_info, tokens) = self
+            .fetch_oauth_identity(provider_name, code, login_state)
+            .await?;
+
+        let user = match self
+            .oauth_provider_repo
+            .get_by_provider_and_id(provider_name, &user_info.provider_user_id)
+            .await
+        {
+            Ok(oauth_provider) => self.user_repo.find_by_id(&oauth_provider.user_id).await?,
+            Err(AppError::SqlxError(sqlx::Error::RowNotFound)) => {
+                match self.user_repo.find_by_email(&user_info.email).await {
+                    // taking over an account only because the emails match needs the provider
+                    // to vouch for the email, and the policy has to allow it at all. an unverified
+                    // local account could have been registered by someone else with this email
+                    Ok(user) => {
+                        if !(self.auto_link_verified_email
+                            && user_info.email_verified
+                            && user.is_email_verified())
+                        {
+                            return Err(AppError::AccountLinkRequired(user_info.email));
+                        }
+
+                        self.oauth_provider_repo
+                            .create(&UserOauthProvider::new(
+                                user.id.clone(),
+                                provider_name.to_string(),
+                                user_info.provider_user_id.clone(),
+                            ))
                             .await?;
-                        return Ok(resp);
-                    }
+                        info!(
+                            "Linked {} identity to User {} by verified email",
+                            provider_name, user.id
+                        );
 
+                        user
+                    }
                     // register user first & attached role
-                    let user_data = self.register_user_from_google(db_pool, &user_info).await?;
-                    let _session = self
-                        .get_or_create_session(
-                            &user_data.id,
-                            &resp.access_token,
-                            &resp.refresh_token,
-                            Some(60 * 60 * 24 * 7),
-                        )
-                        .await?;
-
-                    Ok(resp)
-                } else {
-                    let err_resp = r.json::<GoogleTokenError>().await?;
-
-                    tracing::error!("{:?}", err_resp);
-                    if err_resp.error == "invalid_grant" {
-                        return Err(AppError::Unauthorized);
+                    Err(AppError::SqlxError(sqlx::Error::RowNotFound)) => {
+                        self.register_oauth_user(db_pool, provider_name, &user_info)
+                            .await?
                     }
-
-                    Err(AppError::ProcessError(err_resp.error))
+                    Err(err) => return Err(err),
                 }
             }
-            Err(err) => {
-                tracing::error!("{}", err);
-                Err(AppError::Oauth2FailedToAuthorize)
-            }
+            Err(err) => return Err(err),
+        };
+
+        if !user.is_accessible() {
+            return Err(AppError::UserInactive);
         }
+
+        Ok((user, tokens))
     }
 
-    pub async fn fetch_google_user(
+    // attach the provider identity to a user that is already logged in
+    pub async fn link_oauth_identity(
         &self,
-        id_token: &str,
-        access_token: &str,
-    ) -> Result<GoogleUserResult, AppError> {
-        let client = reqwest::Client::new();
-
-        let response = client
-            .get(format!(
-                "https://www.googleapis.com/oauth2/v3/userinfo?alt=json&access_token={}",
-                access_token
-            ))
-            .header("Authorization", format!("Bearer {}", id_token))
-            .send()
-            .await;
-
-        match response {
-            Ok(r) => {
-                if r.status().is_success() {
-                    let resp = r.json::<GoogleUserResult>().await?;
-
-                    Ok(resp)
-                } else {
-                    let err_resp = r.json::<GoogleTokenError>().await?;
-
-                    tracing::error!("{:?}", err_resp);
-                    if err_resp.error == "invalid_grant" {
-                        return Err(AppError::Unauthorized);
-                    }
+        user_id: &str,
+        provider_name: &str,
+        code: &str,
+        login_state: &OauthLoginState,
+    ) -> Result<UserOauthProvider, AppError> {
+        let (user_info, tokens) = self
+            .fetch_oauth_identity(provider_name, code, login_state)
+            .await?;
 
-                    Err(AppError::ProcessError(err_resp.error))
-                }
+        // the tokens were only needed to find out who the user is at the provider
+        if let Err(err) = self
+            .providers
+            .get(provider_name)?
+            .revoke_token(&tokens.access_token, tokens.refresh_token.as_deref())
+            .await
+        {
+            tracing::error!("failed to revoke {} token: {}", provider_name, err);
+        }
+
+        match self
+            .oauth_provider_repo
+            .get_by_provider_and_id(provider_name, &user_info.provider_user_id)
+            .await
+        {
+            Ok(oauth_provider) if oauth_provider.user_id == user_id => Ok(oauth_provider),
+            Ok(_) => Err(AppError::ResourceExist(format!(
+                "{} account is linked to another user",
+                provider_name
+            ))),
+            Err(AppError::SqlxError(sqlx::Error::RowNotFound)) => {
+                let oauth_provider = self
+                    .oauth_provider_repo
+                    .create(&UserOauthProvider::new(
+                        user_id.to_string(),
+                        provider_name.to_string(),
+                        user_info.provider_user_id,
+                    ))
+                    .await?;
+                info!("Linked {} identity to User {}", provider_name, user_id);
+
+                Ok(oauth_provider)
             }
-            Err(err) => {
-                tracing::error!("{}", err);
-                Err(AppError::Oauth2FailedToAuthorize)
+            Err(err) => Err(err),
+        }
+    }
+
+    // exchange the code & find out who logged in at the provider
+    async fn fetch_oauth_identity(
+        &self,
+        provider_name: &str,
+        code: &str,
+        login_state: &OauthLoginState,
+    ) -> Result<(OauthUserInfo, OauthTokenResponse), AppError> {
+        let provider = self.providers.get(provider_name)?;
+
+        let tokens = provider
+            .exchange_code(code, &login_state.code_verifier)
+            .await?;
+
+        // an id token minted for another login can't be injected into this one
+        if let Some(id_token) = &tokens.id_token {
+            let claims = provider.verify_id_token(id_token).await?;
+            if claims.nonce.as_deref() != Some(login_state.nonce.as_str()) {
+                tracing::warn!(
+                    "[Security:Oauth] {} id token nonce does not match the login",
+                    provider_name
+                );
+                return Err(AppError::Oauth2FailedToAuthorize);
             }
         }
+
+        let user_info = provider.fetch_user_info(&tokens).await?;
+
+        Ok((user_info, tokens))
+    }
+
+    // provider tokens of a login waiting for its second factor, encrypted like the ones in sessions
+    pub fn seal_provider_tokens(&self, tokens: &OauthTokenResponse) -> Result<String, AppError> {
+        self.token_cipher.encrypt(&serde_json::to_string(tokens)?)
+    }
+
+    pub fn unseal_provider_tokens(&self, sealed: &str) -> Result<OauthTokenResponse, AppError> {
+        Ok(serde_json::from_str(&self.token_cipher.decrypt(sealed)?)?)
+    }
+
+    pub fn new_provider_session(
+        &self,
+        session_id: String,
+        provider_name: &str,
+        user_id: &str,
+        tokens: &OauthTokenResponse,
+        client_info: &ClientInfo,
+    ) -> UserSession {
+        UserSession::new(
+            session_id,
+            user_id.to_string(),
+            provider_name.to_string(),
+            tokens.access_token.clone(),
+            tokens
+                .refresh_token
+                .clone()
+                .filter(|token| !token.is_empty()),
+            Some(chrono::Utc::now() + chrono::Duration::seconds(SESSION_LIFETIME_SECONDS)),
+            client_info,
+        )
     }
 
-    pub async fn register_user_from_google(
+    // the id token handed out with the session, see `get_active_id_token_session`
+    pub fn bind_id_token(&self, session: &mut UserSession, id_token: &str) {
+        session.bind_id_token(&self.token_cipher, id_token);
+    }
+
+    pub async fn register_oauth_user(
         &self,
         db_pool: &sqlx::PgPool,
-        google_user_info: &GoogleUserResult,
+        provider_name: &str,
+        user_info: &OauthUserInfo,
     ) -> Result<User, AppError> {
         let mut tx = db_pool.begin().await?;
 
@@ -191,76 +268,137 @@ where
                 _ => AppError::ProcessError(err.to_string()),
             })?;
 
-        let user = User::from(google_user_info);
+        let user = User::from(user_info);
         let user_oauth_provider = UserOauthProvider::new(
             user.id.clone(),
-            GOOGLE_PROVIDER.to_string(),
-            google_user_info.sub.clone(),
+            provider_name.to_string(),
+            user_info.provider_user_id.clone(),
         );
         let user_role = UserRole::new(user.id.clone(), default_role.id.clone());
 
         // insert user, user_oauth_provider, user_role
-        let (user, _user_oauth_provider, _user_role) = self
+        let (user, _user_oauth_provider, user_role) = self
             .user_repo
             .tx_register_user(&mut tx, &user, &user_oauth_provider, &user_role)
             .await?;
 
         tx.commit().await?;
 
+        self.rbac
+            .assign_role(&user_role.user_id, &user_role.role_id)
+            .await?;
+
         Ok(user)
     }
 
+    // a refresh token belongs to exactly one session, re-using it keeps the same session
     pub async fn get_or_create_session(
         &self,
-        user_id: &str,
-        access_token: &str,
+        mut session: UserSession,
         refresh_token: &str,
-        expires_at: Option<i64>,
     ) -> Result<UserSession, AppError> {
-        // convert expires_at from i64 into DateTime
-        let expires_at =
-            expires_at.map(
-                |expires_at| match chrono::Utc.timestamp_opt(expires_at, 0) {
-                    chrono::LocalResult::Single(expires_at) => expires_at,
-                    _ => chrono::Utc::now(),
-                },
-            );
-
-        if let Ok(mut exist_session) = self.user_session_repo.find_by_user_id(user_id).await {
-            if exist_session.refresh_token != refresh_token {
-                // change refresh token
+        let token_hash = self.token_cipher.hash(refresh_token);
+
+        if let Ok(mut exist_session) = self
+            .user_session_repo
+            .find_by_refresh_token_hash(&token_hash)
+            .await
+        {
+            if exist_session.user_id == session.user_id {
                 exist_session.update(
-                    access_token.to_string(),
-                    refresh_token.to_string(),
-                    expires_at,
+                    session.access_token,
+                    session.refresh_token,
+                    session.expires_at,
                 );
+                exist_session.id_token_hash = session.id_token_hash;
+                exist_session.seal(&self.token_cipher)?;
 
                 info!(
-                    "Exist Session and Not Matched Refresh Token, Updated Session: {:?}",
-                    exist_session
+                    "Exist Session with Matched Refresh Token: {}",
+                    exist_session.id
                 );
 
                 self.user_session_repo
                     .update_token(&exist_session)
                     .await
                     .map_err(|err| AppError::ProcessError(err.to_string()))?;
-            }
 
-            return Ok(exist_session);
+                return Ok(exist_session);
+            }
         }
 
-        let session = UserSession::new(
-            user_id.to_string(),
-            access_token.to_string(),
-            refresh_token.to_string(),
-            expires_at,
-        );
+        session.seal(&self.token_cipher)?;
         let session = self
             .user_session_repo
             .create(session)
             .await
             .map_err(|err| AppError::ProcessError(err.to_string()))?;
 
+        // google only hands out a refresh token on the first consent
+        if !refresh_token.is_empty() {
+            self.user_session_repo
+                .create_refresh_token(RefreshToken::new(session.id.clone(), token_hash))
+                .await?;
+        }
+
+        Ok(session)
+    }
+
+    pub async fn get_active_session(
+        &self,
+        session_id: &str,
+        user_id: &str,
+    ) -> Result<UserSession, AppError> {
+        let session = self
+            .user_session_repo
+            .find_by_id(session_id)
+            .await
+            .map_err(|err| match err {
+                AppError::SqlxError(sqlx::Error::RowNotFound) => AppError::SessionExpired,
+                _ => err,
+            })?;
+
+        self.ensure_active(session, user_id).await
+    }
+
+    // provider id tokens can't carry our session id, they only work with the session of the login
+    // that handed them out. tokens minted outside our callback & tokens of revoked sessions don't
+    pub async fn get_active_id_token_session(
+        &self,
+        provider: &str,
+        id_token: &str,
+        user_id: &str,
+    ) -> Result<UserSession, AppError> {
+        let session = self
+            .user_session_repo
+            .find_by_id_token_hash(&self.token_cipher.hash(id_token))
+            .await
+            .map_err(|err| match err {
+                AppError::SqlxError(sqlx::Error::RowNotFound) => AppError::SessionExpired,
+                _ => err,
+            })?;
+
+        if session.provider != provider {
+            return Err(AppError::SessionExpired);
+        }
+
+        self.ensure_active(session, user_id).await
+    }
+
+    async fn ensure_active(
+        &self,
+        session: UserSession,
+        user_id: &str,
+    ) -> Result<UserSession, AppError> {
+        if session.user_id != user_id || session.is_expired() {
+            return Err(AppError::SessionExpired);
+        }
+
+        // avoid writing on every request, last used is good enough with 5 minutes precision
+        if session.last_used_at < chrono::Utc::now() - chrono::Duration::minutes(5) {
+            self.user_session_repo.touch(&session.id).await?;
+        }
+
         Ok(session)
     }
 
@@ -287,6 +425,10 @@ where
                 err
             })?;
 
+        if !user.is_accessible() {
+            return Err(AppError::UserInactive);
+        }
+
         let roles = self
             .role_repo
             .get_roles_by_user_id(&user.id)
@@ -301,59 +443,96 @@ where
         Ok(user_full)
     }
 
-    pub async fn google_revoke_token(&self, access_token: &str) -> Result<(), AppError> {
-        let client = reqwest::Client::new();
-        let url = format!(
-            "https://oauth2.googleapis.com/revoke?token={}",
-            access_token
-        );
+    // users holding our own tokens, the session tells which provider they logged in with
+    pub async fn get_session_user(&self, session: &UserSession) -> Result<UserFull, AppError> {
+        let oauth_providers = self
+            .oauth_provider_repo
+            .find_by_user_id(&session.user_id)
+            .await?;
 
-        let _ = client
-            .post(url)
-            .send()
+        // a magic link proves the email, not a linked identity, any link of the user will do
+        let oauth_provider = oauth_providers
+            .iter()
+            .find(|oauth_provider| oauth_provider.provider == session.provider)
+            .or_else(|| match session.provider.as_str() {
+                MAGIC_LINK_PROVIDER => oauth_providers.first(),
+                _ => None,
+            })
+            .ok_or(AppError::UnauthorizedError(
+                "Login provider is no longer linked".to_string(),
+            ))?;
+
+        self.get_current_oauth_user(&oauth_provider.provider, &oauth_provider.provider_user_id)
             .await
-            .map_err(|err| AppError::ProcessError(err.to_string()))?;
+    }
+
+    pub async fn get_token_profile(&self, user: &User) -> Result<TokenProfile, AppError> {
+        let roles = self
+            .role_repo
+            .get_roles_by_user_id(&user.id)
+            .await?
+            .into_iter()
+            .map(|role| role.name)
+            .collect();
+        let scopes = self.rbac.get_user_scopes(&user.id).await;
+
+        Ok(TokenProfile {
+            name: user.fullname.clone().unwrap_or_else(|| user.email.clone()),
+            roles,
+            scopes,
+        })
+    }
+
+    // revoke the provider grant when there's one, then remove the session
+    pub async fn revoke_session(&self, session: &UserSession) -> Result<(), AppError> {
+        if !session.holds_own_tokens() {
+            let provider = self.providers.get(&session.provider)?;
+            let access_token = self.token_cipher.decrypt(&session.access_token)?;
+            let refresh_token = session
+                .refresh_token
+                .as_deref()
+                .map(|token| self.token_cipher.decrypt(token))
+                .transpose()?;
+            if let Err(err) = provider
+                .revoke_token(&access_token, refresh_token.as_deref())
+                .await
+            {
+                tracing::error!("failed to revoke {} token: {}", session.provider, err);
+            }
+        }
+
+        self.user_session_repo.delete_by_id(&session.id).await?;
 
         Ok(())
     }
 
-    pub async fn google_refresh_token(
+    // log the user out everywhere, except from the session that is kept when given
+    pub async fn revoke_user_sessions(
         &self,
-        refresh_token: &str,
-    ) -> Result<GoogleTokenResponse, AppError> {
-        let client = reqwest::Client::new();
-        let mut payload = HashMap::new();
-        payload.insert("client_id", self.cfg.google_client_id.as_str());
-        payload.insert("client_secret", self.cfg.google_client_secret.as_str());
-        payload.insert("refresh_token", refresh_token);
-        payload.insert("grant_type", "refresh_token");
-
-        let r = client
-            .post("https://oauth2.googleapis.com/token")
-            .json(&payload)
-            .send()
-            .await;
-
-        match r {
-            Ok(r) => {
-                if r.status().is_success() {
-                    let resp = r.json::<GoogleTokenResponse>().await?;
-                    Ok(resp)
-                } else {
-                    let err_resp = r.json::<GoogleTokenError>().await?;
-
-                    tracing::error!("{:?}", err_resp);
-                    if err_resp.error == "invalid_grant" {
-                        return Err(AppError::Unauthorized);
-                    }
-
-                    Err(AppError::ProcessError(err_resp.error))
-                }
-            }
-            Err(err) => {
-                tracing::error!("{:?}", err);
-                Err(AppError::ProcessError(err.to_string()))
-            }
+        user_id: &str,
+        keep_session_id: Option<&str>,
+    ) -> Result<(), AppError> {
+        let sessions = self.user_session_repo.find_all_by_user_id(user_id).await?;
+
+        for session in sessions
+            .iter()
+            .filter(|session| Some(session.id.as_str()) != keep_session_id)
+        {
+            tracing::info!("Revoking Session {} of User {}...", session.id, user_id);
+            self.revoke_session(session).await?;
         }
+
+        Ok(())
+    }
+
+    pub async fn refresh_provider_token(
+        &self,
+        provider_name: &str,
+        refresh_token: &str,
+    ) -> Result<OauthTokenResponse, AppError> {
+        self.providers
+            .get(provider_name)?
+            .refresh_token(refresh_token)
+            .await
     }
 }
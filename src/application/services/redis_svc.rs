@@ -1,8 +1,15 @@
 use std::{collections::HashMap, sync::Arc};
 
 use crate::{
+    application::dto::auth::{mfa_request::MfaChallenge, oauth2_request::OauthLoginState},
     domain::{entities::user::UserFull, repositories::redis_repo::RedisRepository},
-    infra::errors::app_error::AppError,
+    infra::{
+        common::constants::{
+            MAGIC_LINK_LIFETIME_SECONDS, OAUTH_LOGIN_STATE_LIFETIME_SECONDS,
+            WEBAUTHN_CHALLENGE_LIFETIME_SECONDS,
+        },
+        errors::app_error::AppError,
+    },
 };
 
 #[derive(Clone)]
@@ -55,6 +62,181 @@ where
         Ok(user)
     }
 
+    // a login has 10 minutes to come back from the provider
+    pub async fn set_oauth_login_state(
+        &self,
+        state: &str,
+        login_state: &OauthLoginState,
+    ) -> Result<(), AppError> {
+        let redis_key = format!("oauth_state_{}", state);
+        let login_state_json = serde_json::to_string(login_state)?;
+        self.redis_repo
+            .set_value_with_expiry(
+                &redis_key,
+                &login_state_json,
+                OAUTH_LOGIN_STATE_LIFETIME_SECONDS,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    // the state is consumed on first use, a replayed callback finds nothing
+    pub async fn take_oauth_login_state(
+        &self,
+        state: &str,
+    ) -> Result<Option<OauthLoginState>, AppError> {
+        let redis_key = format!("oauth_state_{}", state);
+        let login_state = match self.redis_repo.take_value(&redis_key).await? {
+            Some(login_state_str) => Some(serde_json::from_str(&login_state_str)?),
+            None => None,
+        };
+
+        Ok(login_state)
+    }
+
+    // keeps the original expiry, failed attempts don't extend the challenge
+    pub async fn set_mfa_challenge(
+        &self,
+        mfa_token: &str,
+        challenge: &MfaChallenge,
+    ) -> Result<(), AppError> {
+        let expiry = (challenge.expires_at - chrono::Utc::now()).num_seconds();
+        if expiry <= 0 {
+            return Ok(());
+        }
+
+        let redis_key = format!("mfa_challenge_{}", mfa_token);
+        let challenge_json = serde_json::to_string(challenge)?;
+        self.redis_repo
+            .set_value_with_expiry(&redis_key, &challenge_json, expiry as u64)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_mfa_challenge(
+        &self,
+        mfa_token: &str,
+    ) -> Result<Option<MfaChallenge>, AppError> {
+        let redis_key = format!("mfa_challenge_{}", mfa_token);
+        let challenge = match self.redis_repo.get_value(&redis_key).await {
+            Ok(challenge_str) => Some(serde_json::from_str(&challenge_str)?),
+            Err(_) => None,
+        };
+
+        Ok(challenge)
+    }
+
+    // taken while a code is checked, so parallel guesses can't share one challenge
+    pub async fn take_mfa_challenge(
+        &self,
+        mfa_token: &str,
+    ) -> Result<Option<MfaChallenge>, AppError> {
+        let redis_key = format!("mfa_challenge_{}", mfa_token);
+        let challenge = match self.redis_repo.take_value(&redis_key).await? {
+            Some(challenge_str) => Some(serde_json::from_str(&challenge_str)?),
+            None => None,
+        };
+
+        Ok(challenge)
+    }
+
+    // only the last started registration of a user can be finished
+    pub async fn set_webauthn_registration(
+        &self,
+        user_id: &str,
+        challenge: &str,
+    ) -> Result<(), AppError> {
+        let redis_key = format!("webauthn_registration_{}", user_id);
+        self.redis_repo
+            .set_value_with_expiry(&redis_key, challenge, WEBAUTHN_CHALLENGE_LIFETIME_SECONDS)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn take_webauthn_registration(
+        &self,
+        user_id: &str,
+    ) -> Result<Option<String>, AppError> {
+        let redis_key = format!("webauthn_registration_{}", user_id);
+
+        self.redis_repo.take_value(&redis_key).await
+    }
+
+    // a passkey login isn't tied to a user until it comes back, so it's keyed by its challenge
+    pub async fn set_webauthn_login(&self, challenge: &str) -> Result<(), AppError> {
+        let redis_key = format!("webauthn_login_{}", challenge);
+        self.redis_repo
+            .set_value_with_expiry(&redis_key, "1", WEBAUTHN_CHALLENGE_LIFETIME_SECONDS)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn take_webauthn_login(&self, challenge: &str) -> Result<bool, AppError> {
+        let redis_key = format!("webauthn_login_{}", challenge);
+
+        Ok(self.redis_repo.take_value(&redis_key).await?.is_some())
+    }
+
+    // only the hash of the link token is stored, the value is the user it logs in
+    pub async fn set_magic_link(&self, token_hash: &str, user_id: &str) -> Result<(), AppError> {
+        let redis_key = format!("magic_link_{}", token_hash);
+        self.redis_repo
+            .set_value_with_expiry(&redis_key, user_id, MAGIC_LINK_LIFETIME_SECONDS)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn take_magic_link(&self, token_hash: &str) -> Result<Option<String>, AppError> {
+        let redis_key = format!("magic_link_{}", token_hash);
+
+        self.redis_repo.take_value(&redis_key).await
+    }
+
+    // fixed window counter, returns the hits of the current window including this one
+    pub async fn count_hit(&self, key: &str, window_seconds: u64) -> Result<i64, AppError> {
+        let redis_key = format!("rate_limit_{}", key);
+
+        self.redis_repo.increment(&redis_key, window_seconds).await
+    }
+
+    // sliding window over two fixed windows, the previous one counts with the part that still
+    // overlaps the window ending now. returns the hits including this one & the seconds until
+    // the current window ends
+    pub async fn count_sliding_window(
+        &self,
+        key: &str,
+        window_seconds: u64,
+    ) -> Result<(u64, u64), AppError> {
+        let now = chrono::Utc::now().timestamp() as u64;
+        let window = now / window_seconds;
+        let elapsed = now % window_seconds;
+
+        let current = self
+            .redis_repo
+            .increment(
+                &format!("rate_limit_{}_{}", key, window),
+                window_seconds * 2,
+            )
+            .await?;
+        let previous = self
+            .redis_repo
+            .get_value(&format!("rate_limit_{}_{}", key, window - 1))
+            .await
+            .ok()
+            .and_then(|hits| hits.parse::<u64>().ok())
+            .unwrap_or(0);
+
+        let overlap = (window_seconds - elapsed) as f64 / window_seconds as f64;
+        let hits = (previous as f64 * overlap) as u64 + current as u64;
+
+        Ok((hits, window_seconds - elapsed))
+    }
+
     pub async fn remove_current_user(&self, user_id: &str) -> Result<(), AppError> {
         let redis_key = format!("current_user_{}", user_id);
         self.redis_repo.delete_value(&redis_key).await?;
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use crate::{
+    domain::{entities::user_token::UserToken, repositories::user_token_repo::UserTokenRepository},
+    infra::{
+        errors::app_error::AppError,
+        utils::{token_cipher::TokenCipher, token_hash::random_token},
+    },
+};
+
+#[derive(Clone)]
+pub struct UserTokenService<T> {
+    user_token_repo: Arc<T>,
+    token_cipher: Arc<TokenCipher>,
+}
+
+impl<T> UserTokenService<T>
+where
+    T: UserTokenRepository,
+{
+    pub fn new(user_token_repo: Arc<T>, token_cipher: Arc<TokenCipher>) -> Self {
+        Self {
+            user_token_repo,
+            token_cipher,
+        }
+    }
+
+    // a new token replaces the ones still open for the same purpose, only the plaintext token
+    // returned here can be mailed, the database only has its hash
+    pub async fn issue(
+        &self,
+        user_id: &str,
+        purpose: &str,
+        lifetime: chrono::Duration,
+    ) -> Result<String, AppError> {
+        self.user_token_repo
+            .delete_unconsumed(user_id, purpose)
+            .await?;
+
+        let token = random_token();
+        self.user_token_repo
+            .create(&UserToken::new(
+                user_id.to_string(),
+                purpose.to_string(),
+                self.token_cipher.hash(&token),
+                chrono::Utc::now() + lifetime,
+            ))
+            .await?;
+
+        Ok(token)
+    }
+
+    // unknown, expired & already used tokens all look the same to the caller
+    pub async fn consume(&self, purpose: &str, token: &str) -> Result<UserToken, AppError> {
+        let invalid = || AppError::UnauthorizedError("Invalid or expired token".to_string());
+
+        let user_token = self
+            .user_token_repo
+            .find_by_hash(purpose, &self.token_cipher.hash(token))
+            .await
+            .map_err(|err| match err {
+                AppError::SqlxError(sqlx::Error::RowNotFound) => invalid(),
+                _ => err,
+            })?;
+
+        if !user_token.is_usable() || !self.user_token_repo.consume(&user_token.id).await? {
+            return Err(invalid());
+        }
+
+        Ok(user_token)
+    }
+}
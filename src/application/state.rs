@@ -2,20 +2,30 @@ use std::sync::Arc;
 
 use crate::infra::{
     config::AppConfig,
+    mail::mailer::mailer_from_config,
+    oauth2::registry::OauthProviderRegistry,
     rbac::Rbac,
     repositories::{
         pg_oauth_provider::PgOauthProviderRepository, pg_role_repo::PgRoleRepository,
-        pg_user_repo::PgUserRepository, pg_user_session::PgUserSessionRepository,
+        pg_user_mfa::PgUserMfaRepository, pg_user_repo::PgUserRepository,
+        pg_user_session::PgUserSessionRepository, pg_user_token::PgUserTokenRepository,
+        pg_webauthn_credential::PgWebauthnCredentialRepository,
         redis_repo_impl::RedisRepositoryImpl,
     },
-    utils::{google_jwt::GoogleJwtMaker, jwt_maker::JwtMaker},
+    utils::{
+        jwt_maker::JwtMaker, return_to::ReturnToAllowlist, token_cipher::TokenCipher,
+        webauthn::WebauthnVerifier,
+    },
 };
 use bb8_redis::{bb8::Pool, RedisConnectionManager};
 use sqlx::PgPool;
 
 use super::{
-    services::{oauth_svc::OauthService, redis_svc::RedisService},
-    usecases::{auth::init::AuthUsecase, role::init::RoleUsecase},
+    services::{
+        login_guard_svc::LoginGuardService, mail_svc::MailService, mfa_svc::MfaService,
+        oauth_svc::OauthService, redis_svc::RedisService, user_token_svc::UserTokenService,
+    },
+    usecases::{auth::init::AuthUsecase, role::init::RoleUsecase, user::init::UserUsecase},
 };
 
 #[derive(Clone)]
@@ -23,7 +33,7 @@ pub struct AppState {
     pub cfg: Arc<AppConfig>,
     pub db_pool: PgPool,
     pub jwt_maker: Arc<JwtMaker>,
-    pub google_jwt_maker: Arc<GoogleJwtMaker>,
+    pub oauth_providers: Arc<OauthProviderRegistry>,
     pub rbac: Arc<Rbac>,
     pub svc: Arc<Service>,
     pub uc: Arc<Usecase>,
@@ -34,6 +44,7 @@ pub struct AppState {
 pub struct Usecase {
     pub role: Arc<RoleUsecase>,
     pub auth: Arc<AuthUsecase>,
+    pub user: Arc<UserUsecase>,
 }
 
 /* End Usecases list */
@@ -49,6 +60,10 @@ pub struct Service {
         >,
     >,
     pub redis: Arc<RedisService<RedisRepositoryImpl>>,
+    pub user_token: Arc<UserTokenService<PgUserTokenRepository>>,
+    pub mail: Arc<MailService>,
+    pub mfa: Arc<MfaService<PgUserMfaRepository, PgRoleRepository>>,
+    pub login_guard: Arc<LoginGuardService<RedisRepositoryImpl>>,
 }
 
 impl AppState {
@@ -59,8 +74,9 @@ impl AppState {
         rbac: Arc<Rbac>,
     ) -> Self {
         // utils or tooling
-        let jwt_maker = Arc::new(JwtMaker::new(cfg.jwt_secret.clone()));
-        let google_jwt_maker = Arc::new(GoogleJwtMaker::new(cfg.clone()));
+        let jwt_maker = Arc::new(JwtMaker::new(&cfg));
+        let oauth_providers = Arc::new(OauthProviderRegistry::from_config(&cfg));
+        let token_cipher = Arc::new(TokenCipher::new(&cfg));
         let redis_repo = Arc::new(RedisRepositoryImpl::new(redis_pool.clone()));
 
         // repos list
@@ -68,35 +84,85 @@ impl AppState {
         let user_repo = Arc::new(PgUserRepository::new(db_pool.clone()));
         let user_session_repo = Arc::new(PgUserSessionRepository::new(db_pool.clone()));
         let oauth_provider_repo = Arc::new(PgOauthProviderRepository::new(db_pool.clone()));
+        let user_token_repo = Arc::new(PgUserTokenRepository::new(db_pool.clone()));
+        let user_mfa_repo = Arc::new(PgUserMfaRepository::new(db_pool.clone()));
+        let webauthn_credential_repo =
+            Arc::new(PgWebauthnCredentialRepository::new(db_pool.clone()));
 
         // services list
         let redis_svc = Arc::new(RedisService::new(redis_repo.clone()));
         let oauth_svc = Arc::new(OauthService::new(
-            cfg.clone(),
+            oauth_providers.clone(),
+            rbac.clone(),
+            token_cipher.clone(),
             user_repo.clone(),
             role_repo.clone(),
             user_session_repo.clone(),
             oauth_provider_repo.clone(),
+            cfg.oauth_auto_link_verified_email,
+        ));
+        let user_token_svc = Arc::new(UserTokenService::new(
+            user_token_repo.clone(),
+            token_cipher.clone(),
+        ));
+        let mail_svc = Arc::new(MailService::new(
+            mailer_from_config(&cfg),
+            cfg.app_name.clone(),
+            cfg.email_verification_url.clone(),
+            cfg.password_reset_url.clone(),
+            cfg.magic_link_url.clone(),
         ));
+        let mfa_svc = Arc::new(MfaService::new(
+            user_mfa_repo.clone(),
+            role_repo.clone(),
+            token_cipher.clone(),
+            cfg.app_name.clone(),
+        ));
+
+        let login_guard_svc = Arc::new(LoginGuardService::new(redis_repo.clone()));
 
         // service registration
         let svc = Arc::new(Service {
             oauth: oauth_svc,
             redis: redis_svc,
+            user_token: user_token_svc,
+            mail: mail_svc,
+            mfa: mfa_svc,
+            login_guard: login_guard_svc,
         });
 
         // usecase registration
         let uc = Arc::new(Usecase {
             role: Arc::new(RoleUsecase::new(role_repo.clone(), rbac.clone())),
             auth: Arc::new(AuthUsecase::new(
-                cfg.clone(),
+                oauth_providers.clone(),
+                Arc::new(ReturnToAllowlist::from_config(&cfg)),
                 svc.oauth.clone(),
                 rbac.clone(),
                 user_repo.clone(),
                 role_repo.clone(),
                 user_session_repo.clone(),
+                oauth_provider_repo.clone(),
+                webauthn_credential_repo.clone(),
                 jwt_maker.clone(),
+                Arc::new(WebauthnVerifier::from_config(&cfg)),
+                token_cipher.clone(),
+                svc.redis.clone(),
+                svc.user_token.clone(),
+                svc.mail.clone(),
+                svc.mfa.clone(),
+                svc.login_guard.clone(),
+                cfg.email_verification_required,
+                cfg.email_verification_required_since,
+            )),
+            user: Arc::new(UserUsecase::new(
+                user_repo.clone(),
+                role_repo.clone(),
+                oauth_provider_repo.clone(),
+                user_session_repo.clone(),
+                rbac.clone(),
                 svc.redis.clone(),
+                svc.login_guard.clone(),
             )),
         });
 
@@ -104,7 +170,7 @@ impl AppState {
             cfg,
             db_pool,
             jwt_maker,
-            google_jwt_maker,
+            oauth_providers,
             rbac,
             svc,
             uc,
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use validator::Validate;
+
+use crate::{
+    application::{
+        dto::auth::email_request::ChangePasswordRequest, services::oauth_svc::OauthService,
+    },
+    domain::repositories::{
+        oauth_provider_repo::OauthProviderRepository, role_repo::RoleRepository,
+        user_repo::UserRepository, user_session_repo::UserSessionRepository,
+    },
+    infra::{
+        errors::app_error::AppError,
+        utils::password::{hash_password, verify_password},
+    },
+};
+
+#[derive(Clone)]
+pub struct ChangePassword<U, R, S, O> {
+    user_repo: Arc<U>,
+    oauth_svc: Arc<OauthService<U, R, S, O>>,
+}
+
+impl<U, R, S, O> ChangePassword<U, R, S, O>
+where
+    U: UserRepository,
+    R: RoleRepository,
+    S: UserSessionRepository,
+    O: OauthProviderRepository,
+{
+    pub fn new(user_repo: Arc<U>, oauth_svc: Arc<OauthService<U, R, S, O>>) -> Self {
+        Self {
+            user_repo,
+            oauth_svc,
+        }
+    }
+
+    // the user stays logged in on the current session, every other one is revoked
+    pub async fn execute(
+        &self,
+        user_id: &str,
+        current_session_id: Option<&str>,
+        req: ChangePasswordRequest,
+    ) -> Result<(), AppError> {
+        req.validate()?;
+
+        let current_session_id = current_session_id.ok_or(AppError::UnauthorizedError(
+            "current session can't be resolved".to_string(),
+        ))?;
+
+        // the cached current user has no password hash, read it from the database
+        let mut user = self.user_repo.find_by_id(user_id).await?;
+        let password_hash = user.password_hash.clone().ok_or(AppError::ProcessError(
+            "Account has no password to change".to_string(),
+        ))?;
+
+        let current_password = req.current_password.clone();
+        tokio::task::spawn_blocking(move || {
+            verify_password(&password_hash, current_password.as_bytes())
+        })
+        .await?
+        .map_err(|_err| AppError::UnauthorizedError(String::from("Invalid Credentials")))?;
+
+        let new_password = req.new_password.clone();
+        let hashed_pass =
+            tokio::task::spawn_blocking(move || hash_password(new_password.as_bytes())).await??;
+        user.change_password(hashed_pass);
+        self.user_repo.update_password(&user.id, &user).await?;
+
+        self.oauth_svc
+            .revoke_user_sessions(&user.id, Some(current_session_id))
+            .await
+    }
+}
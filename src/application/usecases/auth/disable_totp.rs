@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use validator::Validate;
+
+use crate::{
+    application::{dto::auth::mfa_request::MfaCodeRequest, services::mfa_svc::MfaService},
+    domain::{
+        entities::user::User,
+        repositories::{role_repo::RoleRepository, user_mfa_repo::UserMfaRepository},
+    },
+    infra::errors::app_error::AppError,
+};
+
+#[derive(Clone)]
+pub struct DisableTotp<R, M> {
+    mfa_svc: Arc<MfaService<M, R>>,
+}
+
+impl<R, M> DisableTotp<R, M>
+where
+    R: RoleRepository,
+    M: UserMfaRepository,
+{
+    pub fn new(mfa_svc: Arc<MfaService<M, R>>) -> Self {
+        Self { mfa_svc }
+    }
+
+    // a recovery code works too, so a lost authenticator can be replaced.
+    // when a role requires mfa the next login asks for a new enrolment
+    pub async fn execute(&self, user: &User, req: MfaCodeRequest) -> Result<(), AppError> {
+        req.validate()?;
+
+        self.mfa_svc.verify_code(user, &req.code).await?;
+
+        self.mfa_svc.disable_totp(&user.id).await
+    }
+}
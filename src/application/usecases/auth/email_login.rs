@@ -1,81 +1,188 @@
 use std::sync::Arc;
 
+use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
-    application::{dto::auth::email_request::EmailLoginRequest, services::oauth_svc::OauthService},
-    domain::repositories::{
-        oauth_provider_repo::OauthProviderRepository, role_repo::RoleRepository,
-        user_repo::UserRepository, user_session_repo::UserSessionRepository,
+    application::{
+        dto::auth::{
+            email_request::EmailLoginRequest, mfa_request::MfaChallenge,
+            mfa_response::MfaChallengeResponse, token_response::EmailLoginResult,
+        },
+        services::{
+            login_guard_svc::LoginGuardService, mfa_svc::MfaService, oauth_svc::OauthService,
+            redis_svc::RedisService,
+        },
+    },
+    domain::{
+        entities::user_session::UserSession,
+        repositories::{
+            oauth_provider_repo::OauthProviderRepository, redis_repo::RedisRepository,
+            role_repo::RoleRepository, user_mfa_repo::UserMfaRepository, user_repo::UserRepository,
+            user_session_repo::UserSessionRepository,
+        },
     },
     infra::{
+        common::constants::{MFA_CHALLENGE_LIFETIME_SECONDS, SESSION_LIFETIME_SECONDS},
         errors::app_error::AppError,
-        utils::{jwt_maker::JwtMaker, password::verify_password},
+        oauth2::constants::EMAIL_PROVIDER,
+        utils::{
+            client_info::ClientInfo,
+            jwt_maker::JwtMaker,
+            password::{dummy_password_hash, verify_password},
+            token_hash::random_token,
+        },
     },
 };
 
 #[derive(Clone)]
-pub struct EmailLogin<U, R, S, O> {
+pub struct EmailLogin<U, R, S, O, M, C> {
     user_repo: Arc<U>,
     jwt_maker: Arc<JwtMaker>,
     oauth_svc: Arc<OauthService<U, R, S, O>>,
+    mfa_svc: Arc<MfaService<M, R>>,
+    redis_svc: Arc<RedisService<C>>,
+    login_guard_svc: Arc<LoginGuardService<C>>,
+    email_verification_required: bool,
+    email_verification_required_since: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-impl<U, R, S, O> EmailLogin<U, R, S, O>
+impl<U, R, S, O, M, C> EmailLogin<U, R, S, O, M, C>
 where
     U: UserRepository,
     R: RoleRepository,
     S: UserSessionRepository,
     O: OauthProviderRepository,
+    M: UserMfaRepository,
+    C: RedisRepository,
 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         user_repo: Arc<U>,
         jwt_maker: Arc<JwtMaker>,
         oauth_svc: Arc<OauthService<U, R, S, O>>,
+        mfa_svc: Arc<MfaService<M, R>>,
+        redis_svc: Arc<RedisService<C>>,
+        login_guard_svc: Arc<LoginGuardService<C>>,
+        email_verification_required: bool,
+        email_verification_required_since: Option<chrono::DateTime<chrono::Utc>>,
     ) -> Self {
         Self {
             user_repo,
             jwt_maker,
             oauth_svc,
+            mfa_svc,
+            redis_svc,
+            login_guard_svc,
+            email_verification_required,
+            email_verification_required_since,
         }
     }
 
-    // TODO: implement single sign on ? so when new user login, other session will be terminated
-    pub async fn execute(&self, req: EmailLoginRequest) -> Result<(String, String), AppError> {
+    // every login creates its own session, so other devices stay logged in
+    pub async fn execute(
+        &self,
+        req: EmailLoginRequest,
+        client: &ClientInfo,
+    ) -> Result<EmailLoginResult, AppError> {
         req.validate()?;
 
-        let user = self
-            .user_repo
-            .find_by_email(&req.email)
-            .await
-            .map_err(|err| match err {
-                AppError::SqlxError(sqlx::Error::RowNotFound) => AppError::UserNotExist(req.email),
-                _ => AppError::ProcessError(err.to_string()),
-            })?;
+        // the peer address, or what our trusted proxies forwarded, never a header of the client
+        let ip_address = client.ip_address.as_deref();
+        self.login_guard_svc.check(&req.email, ip_address).await?;
+
+        // unknown emails go through the same password check & answer as a wrong password
+        let user = match self.user_repo.find_by_email(&req.email).await {
+            Ok(user) => Some(user),
+            Err(AppError::SqlxError(sqlx::Error::RowNotFound)) => None,
+            Err(err) => return Err(AppError::ProcessError(err.to_string())),
+        };
 
         let cloned_pass = req.password.clone();
-        tokio::task::spawn_blocking(move || {
-            verify_password(
-                &user.password_hash.unwrap_or_default(),
-                cloned_pass.as_bytes(),
-            )
+        let password_hash = user
+            .as_ref()
+            .and_then(|user| user.password_hash.clone())
+            .unwrap_or_else(|| dummy_password_hash().to_string());
+        let verified = tokio::task::spawn_blocking(move || {
+            verify_password(&password_hash, cloned_pass.as_bytes())
         })
         .await?
-        .map_err(|_err| AppError::UnauthorizedError(String::from("Invalid Credentials")))?;
+        .is_ok();
+
+        let user = match user {
+            Some(user) if verified => user,
+            _ => {
+                self.login_guard_svc
+                    .record_failure(&req.email, ip_address)
+                    .await?;
 
-        let access_token = self.jwt_maker.make_token(user.id.clone(), 1)?;
-        let refresh_token = self.jwt_maker.make_refresh_token(user.id.clone(), 24 * 7)?;
+                return Err(AppError::UnauthorizedError(String::from(
+                    "Invalid Credentials",
+                )));
+            }
+        };
+        self.login_guard_svc.clear(&req.email).await?;
+
+        if !user.is_accessible() {
+            return Err(AppError::UserInactive);
+        }
+
+        // checked after the password so the error doesn't tell whether an email is registered
+        // accounts created before verification was required keep logging in unverified
+        let verification_required = self.email_verification_required
+            && self
+                .email_verification_required_since
+                .is_none_or(|since| user.created_at >= since);
+        if verification_required && !user.is_email_verified() {
+            return Err(AppError::EmailNotVerified(user.email));
+        }
+
+        // the session is only created once the second factor is checked
+        let totp_enabled = self.mfa_svc.is_totp_enabled(&user.id).await?;
+        if totp_enabled || self.mfa_svc.is_required(&user.id).await? {
+            let mfa_token = random_token();
+            self.redis_svc
+                .set_mfa_challenge(
+                    &mfa_token,
+                    &MfaChallenge::new(user.id.clone(), EMAIL_PROVIDER.to_string()),
+                )
+                .await?;
+
+            return Ok(EmailLoginResult::MfaRequired(MfaChallengeResponse {
+                mfa_token,
+                enrolment_required: !totp_enabled,
+                expires_in: MFA_CHALLENGE_LIFETIME_SECONDS,
+            }));
+        }
+
+        let session_id = Uuid::new_v4().to_string();
+        let profile = self.oauth_svc.get_token_profile(&user).await?;
+        let access_token =
+            self.jwt_maker
+                .make_token(user.id.clone(), session_id.clone(), profile, 1)?;
+        let refresh_token =
+            self.jwt_maker
+                .make_refresh_token(user.id.clone(), session_id.clone(), 24 * 7)?;
 
         let _session = self
             .oauth_svc
             .get_or_create_session(
-                &user.id,
-                &access_token,
+                UserSession::new(
+                    session_id,
+                    user.id.clone(),
+                    EMAIL_PROVIDER.to_string(),
+                    access_token.clone(),
+                    None,
+                    Some(chrono::Utc::now() + chrono::Duration::seconds(SESSION_LIFETIME_SECONDS)),
+                    client,
+                ),
                 &refresh_token,
-                Some(60 * 60 * 24 * 7),
             )
             .await?;
 
-        Ok((access_token, refresh_token))
+        Ok(EmailLoginResult::LoggedIn {
+            access_token,
+            refresh_token,
+        })
     }
 }
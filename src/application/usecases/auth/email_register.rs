@@ -3,32 +3,54 @@ use std::sync::Arc;
 use validator::Validate;
 
 use crate::{
-    application::dto::auth::email_request::EmailRegisterRequest,
+    application::{
+        dto::auth::email_request::EmailRegisterRequest,
+        services::{mail_svc::MailService, user_token_svc::UserTokenService},
+    },
     domain::{
-        entities::{user::User, user_oauth_provider::UserOauthProvider, user_role::UserRole},
-        repositories::{role_repo::RoleRepository, user_repo::UserRepository},
+        entities::{
+            user::User, user_oauth_provider::UserOauthProvider, user_role::UserRole,
+            user_token::EMAIL_VERIFICATION_PURPOSE,
+        },
+        repositories::{
+            role_repo::RoleRepository, user_repo::UserRepository,
+            user_token_repo::UserTokenRepository,
+        },
     },
     infra::{
-        errors::app_error::AppError, oauth2::constants::EMAIL_PROVIDER,
-        utils::password::hash_password,
+        common::constants::EMAIL_VERIFICATION_LIFETIME_SECONDS, errors::app_error::AppError,
+        oauth2::constants::EMAIL_PROVIDER, rbac::Rbac, utils::password::hash_password,
     },
 };
 
 #[derive(Clone)]
-pub struct EmailRegister<U, R> {
+pub struct EmailRegister<U, R, T> {
     user_repo: Arc<U>,
     role_repo: Arc<R>,
+    rbac: Arc<Rbac>,
+    user_token_svc: Arc<UserTokenService<T>>,
+    mail_svc: Arc<MailService>,
 }
 
-impl<U, R> EmailRegister<U, R>
+impl<U, R, T> EmailRegister<U, R, T>
 where
     U: UserRepository,
     R: RoleRepository,
+    T: UserTokenRepository,
 {
-    pub fn new(user_repo: Arc<U>, role_repo: Arc<R>) -> Self {
+    pub fn new(
+        user_repo: Arc<U>,
+        role_repo: Arc<R>,
+        rbac: Arc<Rbac>,
+        user_token_svc: Arc<UserTokenService<T>>,
+        mail_svc: Arc<MailService>,
+    ) -> Self {
         Self {
             user_repo,
             role_repo,
+            rbac,
+            user_token_svc,
+            mail_svc,
         }
     }
 
@@ -69,7 +91,7 @@ where
         );
         let user_role = UserRole::new(new_user.id.clone(), default_role.id.clone());
 
-        let (user, _user_oauth_provider, _user_role) = self
+        let (user, _user_oauth_provider, user_role) = self
             .user_repo
             .tx_register_user(&mut tx, &new_user, &user_oauth_provider, &user_role)
             .await
@@ -77,6 +99,32 @@ where
 
         tx.commit().await?;
 
+        self.rbac
+            .assign_role(&user_role.user_id, &user_role.role_id)
+            .await?;
+
+        // the account exists at this point, a failed mail can be retried with the resend endpoint
+        if let Err(err) = self.send_verification(&user).await {
+            tracing::error!(
+                "[EmailRegister] failed to send verification mail to {}: {}",
+                user.id,
+                err
+            );
+        }
+
         Ok(user)
     }
+
+    async fn send_verification(&self, user: &User) -> Result<(), AppError> {
+        let token = self
+            .user_token_svc
+            .issue(
+                &user.id,
+                EMAIL_VERIFICATION_PURPOSE,
+                chrono::Duration::seconds(EMAIL_VERIFICATION_LIFETIME_SECONDS),
+            )
+            .await?;
+
+        self.mail_svc.send_email_verification(user, &token).await
+    }
 }
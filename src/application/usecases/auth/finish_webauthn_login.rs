@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    application::{
+        dto::auth::webauthn_request::WebauthnLoginRequest,
+        services::{oauth_svc::OauthService, redis_svc::RedisService},
+    },
+    domain::{
+        entities::user_session::UserSession,
+        repositories::{
+            oauth_provider_repo::OauthProviderRepository, redis_repo::RedisRepository,
+            role_repo::RoleRepository, user_repo::UserRepository,
+            user_session_repo::UserSessionRepository,
+            webauthn_credential_repo::WebauthnCredentialRepository,
+        },
+    },
+    infra::{
+        common::constants::SESSION_LIFETIME_SECONDS,
+        errors::app_error::AppError,
+        oauth2::constants::WEBAUTHN_PROVIDER,
+        utils::{
+            client_info::ClientInfo,
+            jwt_maker::JwtMaker,
+            webauthn::{user_handle, WebauthnVerifier},
+        },
+    },
+};
+
+#[derive(Clone)]
+pub struct FinishWebauthnLogin<U, R, S, O, W, C> {
+    user_repo: Arc<U>,
+    webauthn_credential_repo: Arc<W>,
+    jwt_maker: Arc<JwtMaker>,
+    webauthn: Arc<WebauthnVerifier>,
+    oauth_svc: Arc<OauthService<U, R, S, O>>,
+    redis_svc: Arc<RedisService<C>>,
+}
+
+impl<U, R, S, O, W, C> FinishWebauthnLogin<U, R, S, O, W, C>
+where
+    U: UserRepository,
+    R: RoleRepository,
+    S: UserSessionRepository,
+    O: OauthProviderRepository,
+    W: WebauthnCredentialRepository,
+    C: RedisRepository,
+{
+    pub fn new(
+        user_repo: Arc<U>,
+        webauthn_credential_repo: Arc<W>,
+        jwt_maker: Arc<JwtMaker>,
+        webauthn: Arc<WebauthnVerifier>,
+        oauth_svc: Arc<OauthService<U, R, S, O>>,
+        redis_svc: Arc<RedisService<C>>,
+    ) -> Self {
+        Self {
+            user_repo,
+            webauthn_credential_repo,
+            jwt_maker,
+            webauthn,
+            oauth_svc,
+            redis_svc,
+        }
+    }
+
+    // a verified passkey is possession & user verification in one, it doesn't ask for mfa
+    pub async fn execute(
+        &self,
+        req: WebauthnLoginRequest,
+        client: &ClientInfo,
+    ) -> Result<(String, String), AppError> {
+        req.validate()?;
+
+        let challenge = self
+            .webauthn
+            .client_challenge(&req.response.client_data_json)?;
+        if !self.redis_svc.take_webauthn_login(&challenge).await? {
+            return Err(AppError::UnauthorizedError(
+                "Invalid or expired passkey challenge, try to login again".to_string(),
+            ));
+        }
+
+        let credential = self
+            .webauthn_credential_repo
+            .find_by_credential_id(req.id.trim_end_matches('='))
+            .await
+            .map_err(|err| match err {
+                AppError::SqlxError(sqlx::Error::RowNotFound) => {
+                    AppError::InvalidPasskey("passkey is not registered".to_string())
+                }
+                _ => err,
+            })?;
+
+        if let Some(handle) = &req.response.user_handle {
+            if handle.trim_end_matches('=') != user_handle(&credential.user_id) {
+                return Err(AppError::InvalidPasskey(
+                    "passkey belongs to another user".to_string(),
+                ));
+            }
+        }
+
+        let sign_count = self.webauthn.verify_assertion(
+            &challenge,
+            &req.response.client_data_json,
+            &req.response.authenticator_data,
+            &req.response.signature,
+            &credential.public_key,
+            credential.sign_count,
+        )?;
+        let updated = self
+            .webauthn_credential_repo
+            .update_sign_count(&credential.id, credential.sign_count, sign_count)
+            .await?;
+        if !updated {
+            return Err(AppError::InvalidPasskey(
+                "passkey was used by another login at the same time".to_string(),
+            ));
+        }
+
+        let user = self.user_repo.find_by_id(&credential.user_id).await?;
+        if !user.is_accessible() {
+            return Err(AppError::UserInactive);
+        }
+
+        let session_id = Uuid::new_v4().to_string();
+        let profile = self.oauth_svc.get_token_profile(&user).await?;
+        let access_token =
+            self.jwt_maker
+                .make_token(user.id.clone(), session_id.clone(), profile, 1)?;
+        let refresh_token =
+            self.jwt_maker
+                .make_refresh_token(user.id.clone(), session_id.clone(), 24 * 7)?;
+
+        let _session = self
+            .oauth_svc
+            .get_or_create_session(
+                UserSession::new(
+                    session_id,
+                    user.id.clone(),
+                    WEBAUTHN_PROVIDER.to_string(),
+                    access_token.clone(),
+                    None,
+                    Some(chrono::Utc::now() + chrono::Duration::seconds(SESSION_LIFETIME_SECONDS)),
+                    client,
+                ),
+                &refresh_token,
+            )
+            .await?;
+
+        Ok((access_token, refresh_token))
+    }
+}
@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use tracing::info;
+use validator::Validate;
+
+use crate::{
+    application::{
+        dto::auth::webauthn_request::WebauthnRegistrationRequest, services::redis_svc::RedisService,
+    },
+    domain::{
+        entities::{
+            user::User, user_oauth_provider::UserOauthProvider,
+            webauthn_credential::WebauthnCredential,
+        },
+        repositories::{
+            redis_repo::RedisRepository, webauthn_credential_repo::WebauthnCredentialRepository,
+        },
+    },
+    infra::{
+        errors::app_error::AppError, oauth2::constants::WEBAUTHN_PROVIDER,
+        utils::webauthn::WebauthnVerifier,
+    },
+};
+
+#[derive(Clone)]
+pub struct FinishWebauthnRegistration<W, C> {
+    webauthn_credential_repo: Arc<W>,
+    webauthn: Arc<WebauthnVerifier>,
+    redis_svc: Arc<RedisService<C>>,
+}
+
+impl<W, C> FinishWebauthnRegistration<W, C>
+where
+    W: WebauthnCredentialRepository,
+    C: RedisRepository,
+{
+    pub fn new(
+        webauthn_credential_repo: Arc<W>,
+        webauthn: Arc<WebauthnVerifier>,
+        redis_svc: Arc<RedisService<C>>,
+    ) -> Self {
+        Self {
+            webauthn_credential_repo,
+            webauthn,
+            redis_svc,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        user: &User,
+        req: WebauthnRegistrationRequest,
+    ) -> Result<WebauthnCredential, AppError> {
+        req.validate()?;
+
+        let challenge = self
+            .redis_svc
+            .take_webauthn_registration(&user.id)
+            .await?
+            .ok_or(AppError::UnauthorizedError(
+                "Invalid or expired passkey registration, try to start again".to_string(),
+            ))?;
+
+        let registered = self.webauthn.verify_registration(
+            &challenge,
+            &req.response.client_data_json,
+            &req.response.attestation_object,
+        )?;
+        if registered.credential_id != req.id.trim_end_matches('=') {
+            return Err(AppError::InvalidPasskey(
+                "credential id does not match".to_string(),
+            ));
+        }
+
+        if self
+            .webauthn_credential_repo
+            .find_by_credential_id(&registered.credential_id)
+            .await
+            .is_ok()
+        {
+            return Err(AppError::ResourceExist(
+                "Passkey is already registered".to_string(),
+            ));
+        }
+
+        let oauth_provider = UserOauthProvider::new(
+            user.id.clone(),
+            WEBAUTHN_PROVIDER.to_string(),
+            registered.credential_id.clone(),
+        );
+        let name = req
+            .name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+        let credential = self
+            .webauthn_credential_repo
+            .create_with_link(
+                &oauth_provider,
+                &WebauthnCredential::new(
+                    user.id.clone(),
+                    oauth_provider.id.clone(),
+                    registered.credential_id,
+                    registered.public_key,
+                    registered.sign_count,
+                    name,
+                ),
+            )
+            .await?;
+        info!("Registered passkey {} for User {}", credential.id, user.id);
+
+        Ok(credential)
+    }
+}
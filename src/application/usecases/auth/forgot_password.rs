@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use validator::Validate;
+
+use crate::{
+    application::{
+        dto::auth::email_request::ForgotPasswordRequest,
+        services::{mail_svc::MailService, user_token_svc::UserTokenService},
+    },
+    domain::{
+        entities::user_token::PASSWORD_RESET_PURPOSE,
+        repositories::{user_repo::UserRepository, user_token_repo::UserTokenRepository},
+    },
+    infra::{common::constants::PASSWORD_RESET_LIFETIME_SECONDS, errors::app_error::AppError},
+};
+
+#[derive(Clone)]
+pub struct ForgotPassword<U, T> {
+    user_repo: Arc<U>,
+    user_token_svc: Arc<UserTokenService<T>>,
+    mail_svc: Arc<MailService>,
+}
+
+impl<U, T> ForgotPassword<U, T>
+where
+    U: UserRepository,
+    T: UserTokenRepository,
+{
+    pub fn new(
+        user_repo: Arc<U>,
+        user_token_svc: Arc<UserTokenService<T>>,
+        mail_svc: Arc<MailService>,
+    ) -> Self {
+        Self {
+            user_repo,
+            user_token_svc,
+            mail_svc,
+        }
+    }
+
+    // succeeds for unknown emails too, so it can't be used to probe for accounts.
+    // users who only login with a provider have no password to reset
+    pub async fn execute(&self, req: ForgotPasswordRequest) -> Result<(), AppError> {
+        req.validate()?;
+
+        let user = match self.user_repo.find_by_email(&req.email).await {
+            Ok(user) => user,
+            Err(AppError::SqlxError(sqlx::Error::RowNotFound)) => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        if user.password_hash.is_none() || !user.is_accessible() {
+            return Ok(());
+        }
+
+        let token = self
+            .user_token_svc
+            .issue(
+                &user.id,
+                PASSWORD_RESET_PURPOSE,
+                chrono::Duration::seconds(PASSWORD_RESET_LIFETIME_SECONDS),
+            )
+            .await?;
+
+        self.mail_svc.send_password_reset(&user, &token).await
+    }
+}
@@ -0,0 +1,26 @@
+use std::sync::Arc;
+
+use crate::{
+    application::{dto::auth::mfa_response::MfaStatusResponse, services::mfa_svc::MfaService},
+    domain::repositories::{role_repo::RoleRepository, user_mfa_repo::UserMfaRepository},
+    infra::errors::app_error::AppError,
+};
+
+#[derive(Clone)]
+pub struct GetMfaStatus<R, M> {
+    mfa_svc: Arc<MfaService<M, R>>,
+}
+
+impl<R, M> GetMfaStatus<R, M>
+where
+    R: RoleRepository,
+    M: UserMfaRepository,
+{
+    pub fn new(mfa_svc: Arc<MfaService<M, R>>) -> Self {
+        Self { mfa_svc }
+    }
+
+    pub async fn execute(&self, user_id: &str) -> Result<MfaStatusResponse, AppError> {
+        self.mfa_svc.status(user_id).await
+    }
+}
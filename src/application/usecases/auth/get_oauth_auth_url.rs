@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use crate::{
+    application::{
+        dto::auth::oauth2_request::{OauthLoginState, OauthUrlRequest},
+        services::redis_svc::RedisService,
+    },
+    domain::repositories::redis_repo::RedisRepository,
+    infra::{
+        errors::app_error::AppError,
+        oauth2::{provider::AuthorizeRequest, registry::OauthProviderRegistry},
+        utils::{
+            return_to::ReturnToAllowlist,
+            token_hash::{pkce_challenge, random_token},
+        },
+    },
+};
+
+#[derive(Clone)]
+pub struct GetOauthAuthUrl<R> {
+    providers: Arc<OauthProviderRegistry>,
+    return_to_allowlist: Arc<ReturnToAllowlist>,
+    redis_svc: Arc<RedisService<R>>,
+}
+
+impl<R> GetOauthAuthUrl<R>
+where
+    R: RedisRepository,
+{
+    pub fn new(
+        providers: Arc<OauthProviderRegistry>,
+        return_to_allowlist: Arc<ReturnToAllowlist>,
+        redis_svc: Arc<RedisService<R>>,
+    ) -> Self {
+        Self {
+            providers,
+            return_to_allowlist,
+            redis_svc,
+        }
+    }
+
+    // every login gets its own state, PKCE verifier & nonce, checked again in the callback,
+    // `link_user_id` turns the login into linking the provider to that user. returns the url &
+    // the state, which the browser keeps in a cookie so the callback only works for it
+    pub async fn execute(
+        &self,
+        provider: &str,
+        req: OauthUrlRequest,
+        link_user_id: Option<String>,
+    ) -> Result<(String, String), AppError> {
+        let oauth_provider = self.providers.get(provider)?;
+        let return_to = req
+            .return_to
+            .map(|return_to| self.return_to_allowlist.check(&return_to))
+            .transpose()?;
+
+        let state = random_token();
+        let login_state = OauthLoginState {
+            provider: provider.to_string(),
+            code_verifier: random_token(),
+            nonce: random_token(),
+            return_to,
+            link_user_id,
+        };
+
+        let url = oauth_provider
+            .authorize_url(&AuthorizeRequest {
+                state: state.clone(),
+                code_challenge: pkce_challenge(&login_state.code_verifier),
+                nonce: login_state.nonce.clone(),
+            })
+            .await?;
+
+        self.redis_svc
+            .set_oauth_login_state(&state, &login_state)
+            .await?;
+
+        Ok((url, state))
+    }
+}
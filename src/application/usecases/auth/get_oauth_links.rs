@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use crate::{
+    domain::{
+        entities::user_oauth_provider::UserOauthProvider,
+        repositories::oauth_provider_repo::OauthProviderRepository,
+    },
+    infra::errors::app_error::AppError,
+};
+
+#[derive(Clone)]
+pub struct GetOauthLinks<O> {
+    oauth_provider_repo: Arc<O>,
+}
+
+impl<O> GetOauthLinks<O>
+where
+    O: OauthProviderRepository,
+{
+    pub fn new(oauth_provider_repo: Arc<O>) -> Self {
+        Self {
+            oauth_provider_repo,
+        }
+    }
+
+    pub async fn execute(&self, user_id: &str) -> Result<Vec<UserOauthProvider>, AppError> {
+        self.oauth_provider_repo.find_by_user_id(user_id).await
+    }
+}
@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use crate::{
+    application::dto::auth::session_response::SessionResponse,
+    domain::repositories::user_session_repo::UserSessionRepository,
+    infra::errors::app_error::AppError,
+};
+
+#[derive(Clone)]
+pub struct GetUserSessions<S> {
+    user_session_repo: Arc<S>,
+}
+
+impl<S> GetUserSessions<S>
+where
+    S: UserSessionRepository,
+{
+    pub fn new(user_session_repo: Arc<S>) -> Self {
+        Self { user_session_repo }
+    }
+
+    pub async fn execute(
+        &self,
+        user_id: &str,
+        current_session_id: Option<&str>,
+    ) -> Result<Vec<SessionResponse>, AppError> {
+        let sessions = self
+            .user_session_repo
+            .find_all_by_user_id(user_id)
+            .await?
+            .into_iter()
+            .filter(|session| !session.is_expired())
+            .map(|session| SessionResponse {
+                current: current_session_id == Some(session.id.as_str()),
+                session,
+            })
+            .collect();
+
+        Ok(sessions)
+    }
+}
@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use crate::{
+    domain::{
+        entities::webauthn_credential::WebauthnCredential,
+        repositories::webauthn_credential_repo::WebauthnCredentialRepository,
+    },
+    infra::errors::app_error::AppError,
+};
+
+#[derive(Clone)]
+pub struct GetWebauthnCredentials<W> {
+    webauthn_credential_repo: Arc<W>,
+}
+
+impl<W> GetWebauthnCredentials<W>
+where
+    W: WebauthnCredentialRepository,
+{
+    pub fn new(webauthn_credential_repo: Arc<W>) -> Self {
+        Self {
+            webauthn_credential_repo,
+        }
+    }
+
+    pub async fn execute(&self, user_id: &str) -> Result<Vec<WebauthnCredential>, AppError> {
+        self.webauthn_credential_repo.find_by_user_id(user_id).await
+    }
+}
@@ -1,34 +1,58 @@
 use std::sync::Arc;
 
 use crate::{
-    application::services::{oauth_svc::OauthService, redis_svc::RedisService},
+    application::services::{
+        login_guard_svc::LoginGuardService, mail_svc::MailService, mfa_svc::MfaService,
+        oauth_svc::OauthService, redis_svc::RedisService, user_token_svc::UserTokenService,
+    },
     infra::{
-        config::AppConfig,
+        oauth2::registry::OauthProviderRegistry,
         rbac::Rbac,
         repositories::{
             pg_oauth_provider::PgOauthProviderRepository, pg_role_repo::PgRoleRepository,
-            pg_user_repo::PgUserRepository, pg_user_session::PgUserSessionRepository,
+            pg_user_mfa::PgUserMfaRepository, pg_user_repo::PgUserRepository,
+            pg_user_session::PgUserSessionRepository, pg_user_token::PgUserTokenRepository,
+            pg_webauthn_credential::PgWebauthnCredentialRepository,
             redis_repo_impl::RedisRepositoryImpl,
         },
-        utils::jwt_maker::JwtMaker,
+        utils::{
+            jwt_maker::JwtMaker, return_to::ReturnToAllowlist, token_cipher::TokenCipher,
+            webauthn::WebauthnVerifier,
+        },
     },
 };
 
 use super::{
-    email_login::EmailLogin, email_register::EmailRegister, get_google_auth_url::GetGoogleAuthUrl,
-    oauth2_login::Oauth2Login, oauth2_logout::Oauth2Logout, refresh_oauth_token::RefreshOauthToken,
-    seed_super_admin::SeedSuperAdmin,
+    change_password::ChangePassword, confirm_totp_enrolment::ConfirmTotpEnrolment,
+    disable_totp::DisableTotp, email_login::EmailLogin, email_register::EmailRegister,
+    finish_webauthn_login::FinishWebauthnLogin,
+    finish_webauthn_registration::FinishWebauthnRegistration, forgot_password::ForgotPassword,
+    get_mfa_status::GetMfaStatus, get_oauth_auth_url::GetOauthAuthUrl,
+    get_oauth_links::GetOauthLinks, get_user_sessions::GetUserSessions,
+    get_webauthn_credentials::GetWebauthnCredentials, oauth2_login::Oauth2Login,
+    oauth2_logout::Oauth2Logout, redeem_magic_link::RedeemMagicLink,
+    refresh_oauth_token::RefreshOauthToken, regenerate_recovery_codes::RegenerateRecoveryCodes,
+    resend_email_verification::ResendEmailVerification, reset_password::ResetPassword,
+    revoke_other_sessions::RevokeOtherSessions, revoke_session::RevokeSession,
+    seal_session_tokens::SealSessionTokens, seed_super_admin::SeedSuperAdmin,
+    send_magic_link::SendMagicLink, start_totp_enrolment::StartTotpEnrolment,
+    start_webauthn_login::StartWebauthnLogin,
+    start_webauthn_registration::StartWebauthnRegistration,
+    unlink_oauth_provider::UnlinkOauthProvider, verify_email::VerifyEmail,
+    verify_mfa_login::VerifyMfaLogin,
 };
 
 #[derive(Clone)]
 pub struct AuthUsecase {
-    pub get_google_auth_url: Arc<GetGoogleAuthUrl>,
+    pub get_oauth_auth_url: Arc<GetOauthAuthUrl<RedisRepositoryImpl>>,
     pub oauth2_login: Arc<
         Oauth2Login<
             PgUserRepository,
             PgRoleRepository,
             PgUserSessionRepository,
             PgOauthProviderRepository,
+            PgUserMfaRepository,
+            RedisRepositoryImpl,
         >,
     >,
     pub oauth2_logout: Arc<
@@ -39,13 +63,16 @@ pub struct AuthUsecase {
             PgOauthProviderRepository,
         >,
     >,
-    pub email_register: Arc<EmailRegister<PgUserRepository, PgRoleRepository>>,
+    pub email_register:
+        Arc<EmailRegister<PgUserRepository, PgRoleRepository, PgUserTokenRepository>>,
     pub email_login: Arc<
         EmailLogin<
             PgUserRepository,
             PgRoleRepository,
             PgUserSessionRepository,
             PgOauthProviderRepository,
+            PgUserMfaRepository,
+            RedisRepositoryImpl,
         >,
     >,
     pub seed_super_admin: Arc<SeedSuperAdmin<PgUserRepository, PgRoleRepository>>,
@@ -57,11 +84,114 @@ pub struct AuthUsecase {
             PgOauthProviderRepository,
         >,
     >,
+    pub get_user_sessions: Arc<GetUserSessions<PgUserSessionRepository>>,
+    pub revoke_session: Arc<
+        RevokeSession<
+            PgUserRepository,
+            PgRoleRepository,
+            PgUserSessionRepository,
+            PgOauthProviderRepository,
+        >,
+    >,
+    pub revoke_other_sessions: Arc<
+        RevokeOtherSessions<
+            PgUserRepository,
+            PgRoleRepository,
+            PgUserSessionRepository,
+            PgOauthProviderRepository,
+        >,
+    >,
+    pub seal_session_tokens: Arc<SealSessionTokens<PgUserSessionRepository>>,
+    pub get_oauth_links: Arc<GetOauthLinks<PgOauthProviderRepository>>,
+    pub unlink_oauth_provider: Arc<
+        UnlinkOauthProvider<
+            PgUserRepository,
+            PgRoleRepository,
+            PgUserSessionRepository,
+            PgOauthProviderRepository,
+            RedisRepositoryImpl,
+        >,
+    >,
+    pub verify_email:
+        Arc<VerifyEmail<PgUserRepository, PgUserTokenRepository, RedisRepositoryImpl>>,
+    pub resend_email_verification:
+        Arc<ResendEmailVerification<PgUserRepository, PgUserTokenRepository>>,
+    pub forgot_password: Arc<ForgotPassword<PgUserRepository, PgUserTokenRepository>>,
+    pub reset_password: Arc<
+        ResetPassword<
+            PgUserRepository,
+            PgRoleRepository,
+            PgUserSessionRepository,
+            PgOauthProviderRepository,
+            PgUserTokenRepository,
+            RedisRepositoryImpl,
+        >,
+    >,
+    pub change_password: Arc<
+        ChangePassword<
+            PgUserRepository,
+            PgRoleRepository,
+            PgUserSessionRepository,
+            PgOauthProviderRepository,
+        >,
+    >,
+    pub verify_mfa_login: Arc<
+        VerifyMfaLogin<
+            PgUserRepository,
+            PgRoleRepository,
+            PgUserSessionRepository,
+            PgOauthProviderRepository,
+            PgUserMfaRepository,
+            RedisRepositoryImpl,
+        >,
+    >,
+    pub start_totp_enrolment: Arc<
+        StartTotpEnrolment<
+            PgUserRepository,
+            PgRoleRepository,
+            PgUserMfaRepository,
+            RedisRepositoryImpl,
+        >,
+    >,
+    pub confirm_totp_enrolment: Arc<ConfirmTotpEnrolment<PgRoleRepository, PgUserMfaRepository>>,
+    pub disable_totp: Arc<DisableTotp<PgRoleRepository, PgUserMfaRepository>>,
+    pub regenerate_recovery_codes:
+        Arc<RegenerateRecoveryCodes<PgRoleRepository, PgUserMfaRepository>>,
+    pub get_mfa_status: Arc<GetMfaStatus<PgRoleRepository, PgUserMfaRepository>>,
+    pub start_webauthn_registration:
+        Arc<StartWebauthnRegistration<PgWebauthnCredentialRepository, RedisRepositoryImpl>>,
+    pub finish_webauthn_registration:
+        Arc<FinishWebauthnRegistration<PgWebauthnCredentialRepository, RedisRepositoryImpl>>,
+    pub start_webauthn_login: Arc<StartWebauthnLogin<RedisRepositoryImpl>>,
+    pub finish_webauthn_login: Arc<
+        FinishWebauthnLogin<
+            PgUserRepository,
+            PgRoleRepository,
+            PgUserSessionRepository,
+            PgOauthProviderRepository,
+            PgWebauthnCredentialRepository,
+            RedisRepositoryImpl,
+        >,
+    >,
+    pub get_webauthn_credentials: Arc<GetWebauthnCredentials<PgWebauthnCredentialRepository>>,
+    pub send_magic_link: Arc<SendMagicLink<PgUserRepository, RedisRepositoryImpl>>,
+    pub redeem_magic_link: Arc<
+        RedeemMagicLink<
+            PgUserRepository,
+            PgRoleRepository,
+            PgUserSessionRepository,
+            PgOauthProviderRepository,
+            PgUserMfaRepository,
+            RedisRepositoryImpl,
+        >,
+    >,
 }
 
 impl AuthUsecase {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        cfg: Arc<AppConfig>,
+        providers: Arc<OauthProviderRegistry>,
+        return_to_allowlist: Arc<ReturnToAllowlist>,
         oauth_svc: Arc<
             OauthService<
                 PgUserRepository,
@@ -74,24 +204,51 @@ impl AuthUsecase {
         user_repo: Arc<PgUserRepository>,
         role_repo: Arc<PgRoleRepository>,
         user_session_repo: Arc<PgUserSessionRepository>,
+        oauth_provider_repo: Arc<PgOauthProviderRepository>,
+        webauthn_credential_repo: Arc<PgWebauthnCredentialRepository>,
         jwt_maker: Arc<JwtMaker>,
+        webauthn: Arc<WebauthnVerifier>,
+        token_cipher: Arc<TokenCipher>,
         redis_svc: Arc<RedisService<RedisRepositoryImpl>>,
+        user_token_svc: Arc<UserTokenService<PgUserTokenRepository>>,
+        mail_svc: Arc<MailService>,
+        mfa_svc: Arc<MfaService<PgUserMfaRepository, PgRoleRepository>>,
+        login_guard_svc: Arc<LoginGuardService<RedisRepositoryImpl>>,
+        email_verification_required: bool,
+        email_verification_required_since: Option<chrono::DateTime<chrono::Utc>>,
     ) -> Self {
-        let get_google_auth_url = Arc::new(GetGoogleAuthUrl::new(
-            cfg.google_client_id.clone(),
-            cfg.google_redirect_url.clone(),
+        let get_oauth_auth_url = Arc::new(GetOauthAuthUrl::new(
+            providers.clone(),
+            return_to_allowlist,
+            redis_svc.clone(),
+        ));
+        let oauth2_login = Arc::new(Oauth2Login::new(
+            jwt_maker.clone(),
+            oauth_svc.clone(),
+            mfa_svc.clone(),
+            redis_svc.clone(),
         ));
-        let oauth2_login = Arc::new(Oauth2Login::new(oauth_svc.clone()));
         let oauth2_logout = Arc::new(Oauth2Logout::new(
             user_session_repo.clone(),
             oauth_svc.clone(),
             redis_svc.clone(),
         ));
-        let email_register = Arc::new(EmailRegister::new(user_repo.clone(), role_repo.clone()));
+        let email_register = Arc::new(EmailRegister::new(
+            user_repo.clone(),
+            role_repo.clone(),
+            rbac.clone(),
+            user_token_svc.clone(),
+            mail_svc.clone(),
+        ));
         let email_login = Arc::new(EmailLogin::new(
             user_repo.clone(),
             jwt_maker.clone(),
             oauth_svc.clone(),
+            mfa_svc.clone(),
+            redis_svc.clone(),
+            login_guard_svc.clone(),
+            email_verification_required,
+            email_verification_required_since,
         ));
         let seed_super_admin = Arc::new(SeedSuperAdmin::new(
             user_repo.clone(),
@@ -100,18 +257,143 @@ impl AuthUsecase {
         ));
         let refresh_oauth_token = Arc::new(RefreshOauthToken::new(
             jwt_maker.clone(),
+            token_cipher.clone(),
+            user_repo.clone(),
+            user_session_repo.clone(),
+            oauth_svc.clone(),
+        ));
+
+        let get_user_sessions = Arc::new(GetUserSessions::new(user_session_repo.clone()));
+        let revoke_session = Arc::new(RevokeSession::new(
+            user_session_repo.clone(),
+            oauth_svc.clone(),
+        ));
+        let revoke_other_sessions = Arc::new(RevokeOtherSessions::new(oauth_svc.clone()));
+        let seal_session_tokens = Arc::new(SealSessionTokens::new(
+            user_session_repo.clone(),
+            token_cipher.clone(),
+        ));
+
+        let get_oauth_links = Arc::new(GetOauthLinks::new(oauth_provider_repo.clone()));
+        let unlink_oauth_provider = Arc::new(UnlinkOauthProvider::new(
+            oauth_provider_repo.clone(),
             user_session_repo.clone(),
             oauth_svc.clone(),
+            redis_svc.clone(),
+        ));
+
+        let verify_email = Arc::new(VerifyEmail::new(
+            user_repo.clone(),
+            user_token_svc.clone(),
+            redis_svc.clone(),
+        ));
+        let resend_email_verification = Arc::new(ResendEmailVerification::new(
+            user_repo.clone(),
+            user_token_svc.clone(),
+            mail_svc.clone(),
+        ));
+
+        let forgot_password = Arc::new(ForgotPassword::new(
+            user_repo.clone(),
+            user_token_svc.clone(),
+            mail_svc.clone(),
+        ));
+        let reset_password = Arc::new(ResetPassword::new(
+            user_repo.clone(),
+            oauth_svc.clone(),
+            user_token_svc.clone(),
+            redis_svc.clone(),
+        ));
+        let change_password = Arc::new(ChangePassword::new(user_repo.clone(), oauth_svc.clone()));
+
+        let verify_mfa_login = Arc::new(VerifyMfaLogin::new(
+            user_repo.clone(),
+            jwt_maker.clone(),
+            oauth_svc.clone(),
+            mfa_svc.clone(),
+            redis_svc.clone(),
+        ));
+        let start_totp_enrolment = Arc::new(StartTotpEnrolment::new(
+            user_repo.clone(),
+            mfa_svc.clone(),
+            redis_svc.clone(),
+        ));
+        let confirm_totp_enrolment = Arc::new(ConfirmTotpEnrolment::new(mfa_svc.clone()));
+        let disable_totp = Arc::new(DisableTotp::new(mfa_svc.clone()));
+        let regenerate_recovery_codes = Arc::new(RegenerateRecoveryCodes::new(mfa_svc.clone()));
+        let get_mfa_status = Arc::new(GetMfaStatus::new(mfa_svc.clone()));
+
+        let start_webauthn_registration = Arc::new(StartWebauthnRegistration::new(
+            webauthn_credential_repo.clone(),
+            webauthn.clone(),
+            redis_svc.clone(),
+        ));
+        let finish_webauthn_registration = Arc::new(FinishWebauthnRegistration::new(
+            webauthn_credential_repo.clone(),
+            webauthn.clone(),
+            redis_svc.clone(),
+        ));
+        let start_webauthn_login =
+            Arc::new(StartWebauthnLogin::new(webauthn.clone(), redis_svc.clone()));
+        let finish_webauthn_login = Arc::new(FinishWebauthnLogin::new(
+            user_repo.clone(),
+            webauthn_credential_repo.clone(),
+            jwt_maker.clone(),
+            webauthn.clone(),
+            oauth_svc.clone(),
+            redis_svc.clone(),
+        ));
+        let get_webauthn_credentials = Arc::new(GetWebauthnCredentials::new(
+            webauthn_credential_repo.clone(),
+        ));
+
+        let send_magic_link = Arc::new(SendMagicLink::new(
+            user_repo.clone(),
+            token_cipher.clone(),
+            redis_svc.clone(),
+            mail_svc.clone(),
+        ));
+        let redeem_magic_link = Arc::new(RedeemMagicLink::new(
+            user_repo.clone(),
+            jwt_maker.clone(),
+            token_cipher.clone(),
+            oauth_svc.clone(),
+            mfa_svc.clone(),
+            redis_svc.clone(),
         ));
 
         Self {
-            get_google_auth_url,
+            get_oauth_auth_url,
             oauth2_login,
             oauth2_logout,
             email_register,
             email_login,
             seed_super_admin,
             refresh_oauth_token,
+            get_user_sessions,
+            revoke_session,
+            revoke_other_sessions,
+            seal_session_tokens,
+            get_oauth_links,
+            unlink_oauth_provider,
+            verify_email,
+            resend_email_verification,
+            forgot_password,
+            reset_password,
+            change_password,
+            verify_mfa_login,
+            start_totp_enrolment,
+            confirm_totp_enrolment,
+            disable_totp,
+            regenerate_recovery_codes,
+            get_mfa_status,
+            start_webauthn_registration,
+            finish_webauthn_registration,
+            start_webauthn_login,
+            finish_webauthn_login,
+            get_webauthn_credentials,
+            send_magic_link,
+            redeem_magic_link,
         }
     }
 }
@@ -1,8 +1,32 @@
+pub mod change_password;
+pub mod confirm_totp_enrolment;
+pub mod disable_totp;
 pub mod email_login;
 pub mod email_register;
-pub mod get_google_auth_url;
+pub mod finish_webauthn_login;
+pub mod finish_webauthn_registration;
+pub mod forgot_password;
+pub mod get_mfa_status;
+pub mod get_oauth_auth_url;
+pub mod get_oauth_links;
+pub mod get_user_sessions;
+pub mod get_webauthn_credentials;
 pub mod init;
 pub mod oauth2_login;
 pub mod oauth2_logout;
+pub mod redeem_magic_link;
 pub mod refresh_oauth_token;
+pub mod regenerate_recovery_codes;
+pub mod resend_email_verification;
+pub mod reset_password;
+pub mod revoke_other_sessions;
+pub mod revoke_session;
+pub mod seal_session_tokens;
 pub mod seed_super_admin;
+pub mod send_magic_link;
+pub mod start_totp_enrolment;
+pub mod start_webauthn_login;
+pub mod start_webauthn_registration;
+pub mod unlink_oauth_provider;
+pub mod verify_email;
+pub mod verify_mfa_login;
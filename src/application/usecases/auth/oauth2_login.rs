@@ -1,43 +1,168 @@
 use std::sync::Arc;
 
+use uuid::Uuid;
+
 use crate::{
-    application::{dto::auth::oauth2_request::Oauth2Request, services::oauth_svc::OauthService},
+    application::{
+        dto::auth::{
+            mfa_request::MfaChallenge, mfa_response::MfaChallengeResponse,
+            oauth2_request::Oauth2Request, oauth2_response::OauthCallbackResult,
+        },
+        services::{mfa_svc::MfaService, oauth_svc::OauthService, redis_svc::RedisService},
+    },
     domain::repositories::{
-        oauth_provider_repo::OauthProviderRepository, role_repo::RoleRepository,
-        user_repo::UserRepository, user_session_repo::UserSessionRepository,
+        oauth_provider_repo::OauthProviderRepository, redis_repo::RedisRepository,
+        role_repo::RoleRepository, user_mfa_repo::UserMfaRepository, user_repo::UserRepository,
+        user_session_repo::UserSessionRepository,
+    },
+    infra::{
+        common::constants::MFA_CHALLENGE_LIFETIME_SECONDS,
+        errors::app_error::AppError,
+        utils::{client_info::ClientInfo, jwt_maker::JwtMaker, token_hash::random_token},
     },
-    infra::{errors::app_error::AppError, oauth2::constants::GOOGLE_PROVIDER},
 };
 
 #[derive(Clone)]
-pub struct Oauth2Login<U, R, S, O> {
+pub struct Oauth2Login<U, R, S, O, M, C> {
+    jwt_maker: Arc<JwtMaker>,
     oauth_svc: Arc<OauthService<U, R, S, O>>,
+    mfa_svc: Arc<MfaService<M, R>>,
+    redis_svc: Arc<RedisService<C>>,
 }
 
-impl<U, R, S, O> Oauth2Login<U, R, S, O>
+impl<U, R, S, O, M, C> Oauth2Login<U, R, S, O, M, C>
 where
     U: UserRepository,
     R: RoleRepository,
     S: UserSessionRepository,
     O: OauthProviderRepository,
+    M: UserMfaRepository,
+    C: RedisRepository,
 {
-    pub fn new(oauth_svc: Arc<OauthService<U, R, S, O>>) -> Self {
-        Self { oauth_svc }
+    pub fn new(
+        jwt_maker: Arc<JwtMaker>,
+        oauth_svc: Arc<OauthService<U, R, S, O>>,
+        mfa_svc: Arc<MfaService<M, R>>,
+        redis_svc: Arc<RedisService<C>>,
+    ) -> Self {
+        Self {
+            jwt_maker,
+            oauth_svc,
+            mfa_svc,
+            redis_svc,
+        }
     }
 
-    // user can register/login
+    // user can register/login, or link the provider when the login was started for that.
+    // `state_cookie` is the state the browser got when it started the login
     pub async fn execute(
         &self,
         db_pool: &sqlx::PgPool,
         provider: String,
         req: Oauth2Request,
-    ) -> Result<(String, String), AppError> {
-        if provider == GOOGLE_PROVIDER {
-            let google_resp = self.oauth_svc.google_login(db_pool, &req.code).await?;
+        state_cookie: Option<&str>,
+        client: &ClientInfo,
+    ) -> Result<OauthCallbackResult, AppError> {
+        // a callback url started by someone else, e.g. sent to a victim to log them into the
+        // sender's account
+        if state_cookie != Some(req.state.as_str()) {
+            return Err(AppError::UnauthorizedError(
+                "Login was not started from this browser, try to login again".to_string(),
+            ));
+        }
 
-            return Ok((google_resp.id_token, google_resp.refresh_token));
+        // unknown, expired or replayed state
+        let login_state = self
+            .redis_svc
+            .take_oauth_login_state(&req.state)
+            .await?
+            .filter(|login_state| login_state.provider == provider)
+            .ok_or(AppError::UnauthorizedError(
+                "Invalid or expired login state, try to login again".to_string(),
+            ))?;
+
+        if let Some(link_user_id) = &login_state.link_user_id {
+            let oauth_provider = self
+                .oauth_svc
+                .link_oauth_identity(link_user_id, &provider, &req.code, &login_state)
+                .await?;
+
+            return Ok(OauthCallbackResult::Linked {
+                oauth_provider,
+                return_to: login_state.return_to,
+            });
         }
 
-        Err(AppError::InvalidOauthProvider)
+        let (user, tokens) = self
+            .oauth_svc
+            .oauth_login(db_pool, &provider, &req.code, &login_state)
+            .await?;
+
+        // same policy as email logins, the provider tokens wait in the challenge & the session
+        // is only created once the second factor is checked
+        let totp_enabled = self.mfa_svc.is_totp_enabled(&user.id).await?;
+        if totp_enabled || self.mfa_svc.is_required(&user.id).await? {
+            let mfa_token = random_token();
+            let challenge = MfaChallenge::new(user.id.clone(), provider.clone())
+                .with_provider_tokens(self.oauth_svc.seal_provider_tokens(&tokens)?);
+            self.redis_svc
+                .set_mfa_challenge(&mfa_token, &challenge)
+                .await?;
+
+            return Ok(OauthCallbackResult::MfaRequired {
+                challenge: MfaChallengeResponse {
+                    mfa_token,
+                    enrolment_required: !totp_enabled,
+                    expires_in: MFA_CHALLENGE_LIFETIME_SECONDS,
+                },
+                return_to: login_state.return_to,
+            });
+        }
+
+        let session_id = Uuid::new_v4().to_string();
+
+        // clients authenticate with the id token, the middleware verifies it against the provider
+        // & only accepts it with the session it is bound to here
+        if let Some(id_token) = tokens.id_token.clone() {
+            let refresh_token = tokens.refresh_token.clone().unwrap_or_default();
+            let mut session = self
+                .oauth_svc
+                .new_provider_session(session_id, &provider, &user.id, &tokens, client);
+            self.oauth_svc.bind_id_token(&mut session, &id_token);
+            let _session = self
+                .oauth_svc
+                .get_or_create_session(session, &refresh_token)
+                .await?;
+
+            return Ok(OauthCallbackResult::LoggedIn {
+                access_token: id_token,
+                refresh_token,
+                return_to: login_state.return_to,
+            });
+        }
+
+        // without an id token we hand out our own tokens, the provider tokens stay in the session
+        let profile = self.oauth_svc.get_token_profile(&user).await?;
+        let access_token =
+            self.jwt_maker
+                .make_token(user.id.clone(), session_id.clone(), profile, 1)?;
+        let refresh_token =
+            self.jwt_maker
+                .make_refresh_token(user.id.clone(), session_id.clone(), 24 * 7)?;
+
+        let _session = self
+            .oauth_svc
+            .get_or_create_session(
+                self.oauth_svc
+                    .new_provider_session(session_id, &provider, &user.id, &tokens, client),
+                &refresh_token,
+            )
+            .await?;
+
+        Ok(OauthCallbackResult::LoggedIn {
+            access_token,
+            refresh_token,
+            return_to: login_state.return_to,
+        })
     }
 }
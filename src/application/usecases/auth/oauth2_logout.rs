@@ -6,11 +6,7 @@ use crate::{
         oauth_provider_repo::OauthProviderRepository, role_repo::RoleRepository,
         user_repo::UserRepository, user_session_repo::UserSessionRepository,
     },
-    infra::{
-        errors::app_error::AppError,
-        oauth2::constants::{EMAIL_PROVIDER, GOOGLE_PROVIDER},
-        repositories::redis_repo_impl::RedisRepositoryImpl,
-    },
+    infra::{errors::app_error::AppError, repositories::redis_repo_impl::RedisRepositoryImpl},
 };
 
 #[derive(Clone)]
@@ -39,34 +35,25 @@ where
         }
     }
 
-    pub async fn execute(&self, provider: &str, user_id: &str) -> Result<(), AppError> {
+    // only the current session is terminated, other devices stay logged in
+    pub async fn execute(&self, user_id: &str, session_id: Option<&str>) -> Result<(), AppError> {
+        let session_id = session_id.ok_or(AppError::Unauthorized)?;
+
         let user_session = self
             .user_session_repo
-            .find_by_user_id(user_id)
+            .find_by_id(session_id)
             .await
             .map_err(|err| match err {
                 AppError::SqlxError(sqlx::Error::RowNotFound) => AppError::Unauthorized,
                 _ => err,
             })?;
 
-        match provider {
-            GOOGLE_PROVIDER => {
-                self.oauth_svc
-                    .google_revoke_token(&user_session.access_token)
-                    .await?;
-            }
-            EMAIL_PROVIDER => {}
-            _ => return Err(AppError::InvalidOauthProvider),
+        if user_session.user_id != user_id {
+            return Err(AppError::Unauthorized);
         }
 
-        // remove user session
-        self.user_session_repo
-            .delete_by_id(&user_session.id)
-            .await
-            .map_err(|err| match err {
-                AppError::SqlxError(sqlx::Error::RowNotFound) => AppError::Unauthorized,
-                _ => err,
-            })?;
+        // revoke provider token & remove user session
+        self.oauth_svc.revoke_session(&user_session).await?;
 
         // remove current user in redis
         self.redis_svc.remove_current_user(user_id).await?;
@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    application::{
+        dto::auth::{
+            email_request::RedeemMagicLinkRequest, mfa_request::MfaChallenge,
+            mfa_response::MfaChallengeResponse, token_response::EmailLoginResult,
+        },
+        services::{mfa_svc::MfaService, oauth_svc::OauthService, redis_svc::RedisService},
+    },
+    domain::{
+        entities::user_session::UserSession,
+        repositories::{
+            oauth_provider_repo::OauthProviderRepository, redis_repo::RedisRepository,
+            role_repo::RoleRepository, user_mfa_repo::UserMfaRepository, user_repo::UserRepository,
+            user_session_repo::UserSessionRepository,
+        },
+    },
+    infra::{
+        common::constants::{MFA_CHALLENGE_LIFETIME_SECONDS, SESSION_LIFETIME_SECONDS},
+        errors::app_error::AppError,
+        oauth2::constants::MAGIC_LINK_PROVIDER,
+        utils::{
+            client_info::ClientInfo, jwt_maker::JwtMaker, token_cipher::TokenCipher,
+            token_hash::random_token,
+        },
+    },
+};
+
+#[derive(Clone)]
+pub struct RedeemMagicLink<U, R, S, O, M, C> {
+    user_repo: Arc<U>,
+    jwt_maker: Arc<JwtMaker>,
+    token_cipher: Arc<TokenCipher>,
+    oauth_svc: Arc<OauthService<U, R, S, O>>,
+    mfa_svc: Arc<MfaService<M, R>>,
+    redis_svc: Arc<RedisService<C>>,
+}
+
+impl<U, R, S, O, M, C> RedeemMagicLink<U, R, S, O, M, C>
+where
+    U: UserRepository,
+    R: RoleRepository,
+    S: UserSessionRepository,
+    O: OauthProviderRepository,
+    M: UserMfaRepository,
+    C: RedisRepository,
+{
+    pub fn new(
+        user_repo: Arc<U>,
+        jwt_maker: Arc<JwtMaker>,
+        token_cipher: Arc<TokenCipher>,
+        oauth_svc: Arc<OauthService<U, R, S, O>>,
+        mfa_svc: Arc<MfaService<M, R>>,
+        redis_svc: Arc<RedisService<C>>,
+    ) -> Self {
+        Self {
+            user_repo,
+            jwt_maker,
+            token_cipher,
+            oauth_svc,
+            mfa_svc,
+            redis_svc,
+        }
+    }
+
+    // the link is taken on first use, opening it twice doesn't give a second session
+    pub async fn execute(
+        &self,
+        req: RedeemMagicLinkRequest,
+        client: &ClientInfo,
+    ) -> Result<EmailLoginResult, AppError> {
+        req.validate()?;
+
+        let user_id = self
+            .redis_svc
+            .take_magic_link(&self.token_cipher.hash(&req.token))
+            .await?
+            .ok_or(AppError::UnauthorizedError(
+                "Invalid or expired login link".to_string(),
+            ))?;
+
+        let mut user = self.user_repo.find_by_id(&user_id).await?;
+        if !user.is_accessible() {
+            return Err(AppError::UserInactive);
+        }
+
+        // the mail arrived, so the address is proven
+        if !user.is_email_verified() {
+            user.verify_email();
+            self.user_repo.update(&user.id, user.clone()).await?;
+            self.redis_svc.remove_current_user(&user.id).await?;
+        }
+
+        // a link only proves access to the mailbox, the second factor still applies
+        let totp_enabled = self.mfa_svc.is_totp_enabled(&user.id).await?;
+        if totp_enabled || self.mfa_svc.is_required(&user.id).await? {
+            let mfa_token = random_token();
+            self.redis_svc
+                .set_mfa_challenge(
+                    &mfa_token,
+                    &MfaChallenge::new(user.id.clone(), MAGIC_LINK_PROVIDER.to_string()),
+                )
+                .await?;
+
+            return Ok(EmailLoginResult::MfaRequired(MfaChallengeResponse {
+                mfa_token,
+                enrolment_required: !totp_enabled,
+                expires_in: MFA_CHALLENGE_LIFETIME_SECONDS,
+            }));
+        }
+
+        let session_id = Uuid::new_v4().to_string();
+        let profile = self.oauth_svc.get_token_profile(&user).await?;
+        let access_token =
+            self.jwt_maker
+                .make_token(user.id.clone(), session_id.clone(), profile, 1)?;
+        let refresh_token =
+            self.jwt_maker
+                .make_refresh_token(user.id.clone(), session_id.clone(), 24 * 7)?;
+
+        let _session = self
+            .oauth_svc
+            .get_or_create_session(
+                UserSession::new(
+                    session_id,
+                    user.id.clone(),
+                    MAGIC_LINK_PROVIDER.to_string(),
+                    access_token.clone(),
+                    None,
+                    Some(chrono::Utc::now() + chrono::Duration::seconds(SESSION_LIFETIME_SECONDS)),
+                    client,
+                ),
+                &refresh_token,
+            )
+            .await?;
+
+        Ok(EmailLoginResult::LoggedIn {
+            access_token,
+            refresh_token,
+        })
+    }
+}
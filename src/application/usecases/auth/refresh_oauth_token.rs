@@ -2,19 +2,27 @@ use std::sync::Arc;
 
 use crate::{
     application::services::oauth_svc::OauthService,
-    domain::repositories::{
-        oauth_provider_repo::OauthProviderRepository, role_repo::RoleRepository,
-        user_repo::UserRepository, user_session_repo::UserSessionRepository,
+    domain::{
+        entities::{refresh_token::RefreshToken, user::User, user_session::UserSession},
+        repositories::{
+            oauth_provider_repo::OauthProviderRepository, role_repo::RoleRepository,
+            user_repo::UserRepository, user_session_repo::UserSessionRepository,
+        },
     },
     infra::{
+        common::constants::SESSION_LIFETIME_SECONDS,
         errors::app_error::AppError,
-        oauth2::constants::{EMAIL_PROVIDER, GOOGLE_PROVIDER},
-        utils::jwt_maker::JwtMaker,
+        utils::{
+            jwt_maker::{unverified_issuer, JwtMaker},
+            token_cipher::TokenCipher,
+        },
     },
 };
 
 pub struct RefreshOauthToken<U, R, S, O> {
     jwt_maker: Arc<JwtMaker>,
+    token_cipher: Arc<TokenCipher>,
+    user_repo: Arc<U>,
     user_session_repo: Arc<S>,
     oauth_svc: Arc<OauthService<U, R, S, O>>,
 }
@@ -28,70 +36,158 @@ where
 {
     pub fn new(
         jwt_maker: Arc<JwtMaker>,
+        token_cipher: Arc<TokenCipher>,
+        user_repo: Arc<U>,
         user_session_repo: Arc<S>,
         oauth_svc: Arc<OauthService<U, R, S, O>>,
     ) -> Self {
         Self {
             jwt_maker,
+            token_cipher,
+            user_repo,
             user_session_repo,
             oauth_svc,
         }
     }
 
-    pub async fn execute(
-        &self,
-        provider: &str,
-        refresh_token: &str,
-    ) -> Result<(String, String), AppError> {
-        match provider {
-            GOOGLE_PROVIDER => self.google_refresh_token(refresh_token).await,
-            EMAIL_PROVIDER => self.email_refresh_token(refresh_token).await,
-            _ => Err(AppError::InvalidOauthProvider),
+    // the provider comes from the session the refresh token belongs to
+    pub async fn execute(&self, refresh_token: &str) -> Result<(String, String), AppError> {
+        let (current_token, session) = self.consume_refresh_token(refresh_token).await?;
+
+        // providers without id tokens hand out our own tokens, same as email logins
+        let own_token = session.holds_own_tokens()
+            || unverified_issuer(refresh_token).as_deref() == Some(self.jwt_maker.issuer());
+
+        if own_token {
+            self.own_refresh_token(refresh_token, current_token, session)
+                .await
+        } else {
+            self.provider_refresh_token(refresh_token, current_token, session)
+                .await
         }
     }
 
-    async fn google_refresh_token(
+    async fn provider_refresh_token(
         &self,
         refresh_token: &str,
+        current_token: RefreshToken,
+        mut session: UserSession,
     ) -> Result<(String, String), AppError> {
-        let mut session = self
-            .user_session_repo
-            .find_by_refresh_token(refresh_token)
-            .await
-            .map_err(|err| match err {
-                AppError::SqlxError(sqlx::Error::RowNotFound) => {
-                    AppError::UnauthorizedError("Invalid Session, try to relogin".to_string())
-                }
-                _ => AppError::ProcessError(err.to_string()),
-            })?;
+        if session.is_expired() {
+            return Err(AppError::RefreshTokenExpired);
+        }
+
+        self.ensure_user_accessible(&session).await?;
 
-        if let Some(expire_at) = session.expires_at {
-            if expire_at < chrono::Utc::now() {
-                return Err(AppError::RefreshTokenExpired);
+        let r = self
+            .oauth_svc
+            .refresh_provider_token(&session.provider, refresh_token)
+            .await?;
+        let id_token = r.id_token.ok_or(AppError::Oauth2FailedToAuthorize)?;
+
+        // providers like google keep the refresh token, only rotate when a new one is handed out
+        let next_refresh_token = match r.refresh_token.filter(|token| !token.is_empty()) {
+            Some(new_refresh_token) => {
+                self.rotate(&current_token, &new_refresh_token).await?;
+                new_refresh_token
             }
-        }
+            None => refresh_token.to_string(),
+        };
 
-        let r = self.oauth_svc.google_refresh_token(refresh_token).await?;
         session.update(
             r.access_token.clone(),
-            r.refresh_token.clone(),
-            Some(chrono::Utc::now() + chrono::Duration::seconds(60 * 60 * 24 * 7)),
+            Some(next_refresh_token.clone()),
+            Some(chrono::Utc::now() + chrono::Duration::seconds(SESSION_LIFETIME_SECONDS)),
         );
+        session.bind_id_token(&self.token_cipher, &id_token);
+        session.seal(&self.token_cipher)?;
 
         self.user_session_repo.update_token(&session).await?;
 
-        Ok((r.id_token, r.refresh_token))
+        Ok((id_token, next_refresh_token))
     }
 
-    async fn email_refresh_token(&self, refresh_token: &str) -> Result<(String, String), AppError> {
+    async fn own_refresh_token(
+        &self,
+        refresh_token: &str,
+        current_token: RefreshToken,
+        mut session: UserSession,
+    ) -> Result<(String, String), AppError> {
         let claims = self
             .jwt_maker
             .verify_refresh_token(refresh_token)
             .map_err(|_| AppError::RefreshTokenExpired)?;
 
-        let mut session = self
+        // token from another session or signed for another user
+        if session.id != claims.sid || session.user_id != claims.sub {
+            return Err(AppError::UnauthorizedError(
+                "Invalid Session, try to relogin".to_string(),
+            ));
+        }
+
+        if session.is_expired() {
+            return Err(AppError::RefreshTokenExpired);
+        }
+
+        let user = self.ensure_user_accessible(&session).await?;
+
+        // roles & scopes are resolved again so role changes show up on the next refresh
+        let profile = self.oauth_svc.get_token_profile(&user).await?;
+        let new_access_token =
+            self.jwt_maker
+                .make_token(claims.sub.clone(), session.id.clone(), profile, 1)?;
+        let new_refresh_token =
+            self.jwt_maker
+                .make_refresh_token(claims.sub.clone(), session.id.clone(), 24 * 7)?;
+
+        let in_a_week = chrono::Utc::now() + chrono::Duration::seconds(SESSION_LIFETIME_SECONDS);
+        if session.holds_own_tokens() {
+            session.update(new_access_token.clone(), None, Some(in_a_week));
+        } else {
+            session.unseal(&self.token_cipher)?;
+
+            // keep the provider grant alive, fails when the user revoked our app on the provider,
+            // providers like github hand out tokens that never expire & have nothing to refresh
+            match session.refresh_token.clone() {
+                Some(provider_refresh_token) => {
+                    let r = self
+                        .oauth_svc
+                        .refresh_provider_token(&session.provider, &provider_refresh_token)
+                        .await?;
+
+                    session.update(
+                        r.access_token,
+                        Some(
+                            r.refresh_token
+                                .filter(|token| !token.is_empty())
+                                .unwrap_or(provider_refresh_token),
+                        ),
+                        Some(in_a_week),
+                    );
+                }
+                None => {
+                    let access_token = session.access_token.clone();
+                    session.update(access_token, None, Some(in_a_week));
+                }
+            }
+        }
+        session.seal(&self.token_cipher)?;
+
+        self.rotate(&current_token, &new_refresh_token).await?;
+        self.user_session_repo.update_token(&session).await?;
+
+        Ok((new_access_token, new_refresh_token))
+    }
+
+    // find the presented token and its session, a token that was already rotated means
+    // it has been stolen or replayed so the whole family goes away with its session
+    async fn consume_refresh_token(
+        &self,
+        refresh_token: &str,
+    ) -> Result<(RefreshToken, UserSession), AppError> {
+        let current_token = self
             .user_session_repo
-            .find_by_user_id(&claims.sub)
+            .find_refresh_token(&self.token_cipher.hash(refresh_token))
             .await
             .map_err(|err| match err {
                 AppError::SqlxError(sqlx::Error::RowNotFound) => {
@@ -100,26 +196,76 @@ where
                 _ => AppError::ProcessError(err.to_string()),
             })?;
 
-        if let Some(expire_at) = session.expires_at {
-            if expire_at < chrono::Utc::now() {
-                return Err(AppError::RefreshTokenExpired);
-            }
+        if current_token.is_rotated() {
+            return Err(self.revoke_token_family(&current_token).await);
         }
 
-        let new_access_token = self.jwt_maker.make_token(claims.sub.clone(), 1)?;
-        let new_refresh_token = self
-            .jwt_maker
-            .make_refresh_token(claims.sub.clone(), 24 * 7)?;
+        let session = self
+            .user_session_repo
+            .find_by_id(&current_token.session_id)
+            .await
+            .map_err(|err| match err {
+                AppError::SqlxError(sqlx::Error::RowNotFound) => {
+                    AppError::UnauthorizedError("Invalid Session, try to relogin".to_string())
+                }
+                _ => AppError::ProcessError(err.to_string()),
+            })?;
 
-        let in_a_week = chrono::Utc::now() + chrono::Duration::seconds(60 * 60 * 24 * 7);
-        session.update(
-            new_access_token.clone(),
-            new_refresh_token.clone(),
-            Some(in_a_week),
+        Ok((current_token, session))
+    }
+
+    async fn rotate(
+        &self,
+        current_token: &RefreshToken,
+        new_refresh_token: &str,
+    ) -> Result<(), AppError> {
+        // another request rotated the same token in between
+        if !self
+            .user_session_repo
+            .rotate_refresh_token(&current_token.id)
+            .await?
+        {
+            return Err(self.revoke_token_family(current_token).await);
+        }
+
+        self.user_session_repo
+            .create_refresh_token(RefreshToken::new(
+                current_token.session_id.clone(),
+                self.token_cipher.hash(new_refresh_token),
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_token_family(&self, token: &RefreshToken) -> AppError {
+        tracing::warn!(
+            "[Security:RefreshToken] Reuse of rotated refresh token {} detected, revoking session {}",
+            token.id,
+            token.session_id
         );
 
-        self.user_session_repo.update_token(&session).await?;
+        // refresh tokens of the session are removed by the cascade
+        if let Err(err) = self.user_session_repo.delete_by_id(&token.session_id).await {
+            tracing::error!(
+                "failed to revoke session {} after refresh token reuse: {}",
+                token.session_id,
+                err
+            );
+        }
 
-        Ok((new_access_token, new_refresh_token))
+        AppError::UnauthorizedError("Invalid Session, try to relogin".to_string())
+    }
+
+    // deactivated users lose their session instead of getting a new token
+    async fn ensure_user_accessible(&self, session: &UserSession) -> Result<User, AppError> {
+        let user = self.user_repo.find_by_id(&session.user_id).await?;
+
+        if !user.is_accessible() {
+            self.user_session_repo.delete_by_id(&session.id).await?;
+            return Err(AppError::UserInactive);
+        }
+
+        Ok(user)
     }
 }
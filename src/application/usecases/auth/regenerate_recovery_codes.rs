@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use validator::Validate;
+
+use crate::{
+    application::{
+        dto::auth::{mfa_request::MfaCodeRequest, mfa_response::RecoveryCodesResponse},
+        services::mfa_svc::MfaService,
+    },
+    domain::{
+        entities::user::User,
+        repositories::{role_repo::RoleRepository, user_mfa_repo::UserMfaRepository},
+    },
+    infra::errors::app_error::AppError,
+};
+
+#[derive(Clone)]
+pub struct RegenerateRecoveryCodes<R, M> {
+    mfa_svc: Arc<MfaService<M, R>>,
+}
+
+impl<R, M> RegenerateRecoveryCodes<R, M>
+where
+    R: RoleRepository,
+    M: UserMfaRepository,
+{
+    pub fn new(mfa_svc: Arc<MfaService<M, R>>) -> Self {
+        Self { mfa_svc }
+    }
+
+    // the old codes stop working
+    pub async fn execute(
+        &self,
+        user: &User,
+        req: MfaCodeRequest,
+    ) -> Result<RecoveryCodesResponse, AppError> {
+        req.validate()?;
+
+        self.mfa_svc.verify_code(user, &req.code).await?;
+
+        Ok(RecoveryCodesResponse {
+            recovery_codes: self.mfa_svc.regenerate_recovery_codes(&user.id).await?,
+        })
+    }
+}
@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use validator::Validate;
+
+use crate::{
+    application::{
+        dto::auth::email_request::ResendEmailVerificationRequest,
+        services::{mail_svc::MailService, user_token_svc::UserTokenService},
+    },
+    domain::{
+        entities::user_token::EMAIL_VERIFICATION_PURPOSE,
+        repositories::{user_repo::UserRepository, user_token_repo::UserTokenRepository},
+    },
+    infra::{common::constants::EMAIL_VERIFICATION_LIFETIME_SECONDS, errors::app_error::AppError},
+};
+
+#[derive(Clone)]
+pub struct ResendEmailVerification<U, T> {
+    user_repo: Arc<U>,
+    user_token_svc: Arc<UserTokenService<T>>,
+    mail_svc: Arc<MailService>,
+}
+
+impl<U, T> ResendEmailVerification<U, T>
+where
+    U: UserRepository,
+    T: UserTokenRepository,
+{
+    pub fn new(
+        user_repo: Arc<U>,
+        user_token_svc: Arc<UserTokenService<T>>,
+        mail_svc: Arc<MailService>,
+    ) -> Self {
+        Self {
+            user_repo,
+            user_token_svc,
+            mail_svc,
+        }
+    }
+
+    // succeeds for unknown & verified emails too, so it can't be used to probe for accounts
+    pub async fn execute(&self, req: ResendEmailVerificationRequest) -> Result<(), AppError> {
+        req.validate()?;
+
+        let user = match self.user_repo.find_by_email(&req.email).await {
+            Ok(user) => user,
+            Err(AppError::SqlxError(sqlx::Error::RowNotFound)) => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        if user.is_email_verified() || !user.is_accessible() {
+            return Ok(());
+        }
+
+        let token = self
+            .user_token_svc
+            .issue(
+                &user.id,
+                EMAIL_VERIFICATION_PURPOSE,
+                chrono::Duration::seconds(EMAIL_VERIFICATION_LIFETIME_SECONDS),
+            )
+            .await?;
+
+        self.mail_svc.send_email_verification(&user, &token).await
+    }
+}
@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use validator::Validate;
+
+use crate::{
+    application::{
+        dto::auth::email_request::ResetPasswordRequest,
+        services::{
+            oauth_svc::OauthService, redis_svc::RedisService, user_token_svc::UserTokenService,
+        },
+    },
+    domain::{
+        entities::user_token::PASSWORD_RESET_PURPOSE,
+        repositories::{
+            oauth_provider_repo::OauthProviderRepository, redis_repo::RedisRepository,
+            role_repo::RoleRepository, user_repo::UserRepository,
+            user_session_repo::UserSessionRepository, user_token_repo::UserTokenRepository,
+        },
+    },
+    infra::{errors::app_error::AppError, utils::password::hash_password},
+};
+
+#[derive(Clone)]
+pub struct ResetPassword<U, R, S, O, T, C> {
+    user_repo: Arc<U>,
+    oauth_svc: Arc<OauthService<U, R, S, O>>,
+    user_token_svc: Arc<UserTokenService<T>>,
+    redis_svc: Arc<RedisService<C>>,
+}
+
+impl<U, R, S, O, T, C> ResetPassword<U, R, S, O, T, C>
+where
+    U: UserRepository,
+    R: RoleRepository,
+    S: UserSessionRepository,
+    O: OauthProviderRepository,
+    T: UserTokenRepository,
+    C: RedisRepository,
+{
+    pub fn new(
+        user_repo: Arc<U>,
+        oauth_svc: Arc<OauthService<U, R, S, O>>,
+        user_token_svc: Arc<UserTokenService<T>>,
+        redis_svc: Arc<RedisService<C>>,
+    ) -> Self {
+        Self {
+            user_repo,
+            oauth_svc,
+            user_token_svc,
+            redis_svc,
+        }
+    }
+
+    // whoever asked for the reset may not be the one holding the sessions, so all of them go
+    pub async fn execute(&self, req: ResetPasswordRequest) -> Result<(), AppError> {
+        req.validate()?;
+
+        let user_token = self
+            .user_token_svc
+            .consume(PASSWORD_RESET_PURPOSE, &req.token)
+            .await?;
+
+        let mut user = self.user_repo.find_by_id(&user_token.user_id).await?;
+        if !user.is_accessible() {
+            return Err(AppError::UserInactive);
+        }
+
+        let new_password = req.new_password.clone();
+        let hashed_pass =
+            tokio::task::spawn_blocking(move || hash_password(new_password.as_bytes())).await??;
+        user.change_password(hashed_pass);
+        self.user_repo.update_password(&user.id, &user).await?;
+
+        // the reset link was mailed to the address, so it's proven to be theirs
+        if !user.is_email_verified() {
+            user.verify_email();
+            self.user_repo.update(&user.id, user.clone()).await?;
+        }
+
+        self.oauth_svc.revoke_user_sessions(&user.id, None).await?;
+        self.redis_svc.remove_current_user(&user.id).await?;
+
+        Ok(())
+    }
+}
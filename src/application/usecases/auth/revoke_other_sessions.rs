@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use crate::{
+    application::services::oauth_svc::OauthService,
+    domain::repositories::{
+        oauth_provider_repo::OauthProviderRepository, role_repo::RoleRepository,
+        user_repo::UserRepository, user_session_repo::UserSessionRepository,
+    },
+    infra::errors::app_error::AppError,
+};
+
+#[derive(Clone)]
+pub struct RevokeOtherSessions<U, R, S, O> {
+    oauth_svc: Arc<OauthService<U, R, S, O>>,
+}
+
+impl<U, R, S, O> RevokeOtherSessions<U, R, S, O>
+where
+    U: UserRepository,
+    R: RoleRepository,
+    S: UserSessionRepository,
+    O: OauthProviderRepository,
+{
+    pub fn new(oauth_svc: Arc<OauthService<U, R, S, O>>) -> Self {
+        Self { oauth_svc }
+    }
+
+    // log out everywhere else, the current session is kept
+    pub async fn execute(
+        &self,
+        user_id: &str,
+        current_session_id: Option<&str>,
+    ) -> Result<(), AppError> {
+        let current_session_id = current_session_id.ok_or(AppError::UnauthorizedError(
+            "current session can't be resolved".to_string(),
+        ))?;
+
+        self.oauth_svc
+            .revoke_user_sessions(user_id, Some(current_session_id))
+            .await
+    }
+}
@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use tracing::info;
+
+use crate::{
+    application::services::oauth_svc::OauthService,
+    domain::repositories::{
+        oauth_provider_repo::OauthProviderRepository, role_repo::RoleRepository,
+        user_repo::UserRepository, user_session_repo::UserSessionRepository,
+    },
+    infra::errors::app_error::AppError,
+};
+
+#[derive(Clone)]
+pub struct RevokeSession<U, R, S, O> {
+    user_session_repo: Arc<S>,
+    oauth_svc: Arc<OauthService<U, R, S, O>>,
+}
+
+impl<U, R, S, O> RevokeSession<U, R, S, O>
+where
+    U: UserRepository,
+    R: RoleRepository,
+    S: UserSessionRepository,
+    O: OauthProviderRepository,
+{
+    pub fn new(user_session_repo: Arc<S>, oauth_svc: Arc<OauthService<U, R, S, O>>) -> Self {
+        Self {
+            user_session_repo,
+            oauth_svc,
+        }
+    }
+
+    pub async fn execute(&self, user_id: &str, session_id: &str) -> Result<(), AppError> {
+        let session = self.user_session_repo.find_by_id(session_id).await?;
+
+        // never reveal sessions of other users
+        if session.user_id != user_id {
+            return Err(AppError::ResourceNotFound);
+        }
+
+        info!("Revoking Session {} of User {}...", session.id, user_id);
+        self.oauth_svc.revoke_session(&session).await?;
+
+        Ok(())
+    }
+}
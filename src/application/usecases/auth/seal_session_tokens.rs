@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use tracing::info;
+
+use crate::{
+    domain::repositories::user_session_repo::UserSessionRepository,
+    infra::{errors::app_error::AppError, utils::token_cipher::TokenCipher},
+};
+
+#[derive(Clone)]
+pub struct SealSessionTokens<S> {
+    user_session_repo: Arc<S>,
+    token_cipher: Arc<TokenCipher>,
+}
+
+impl<S> SealSessionTokens<S>
+where
+    S: UserSessionRepository,
+{
+    pub fn new(user_session_repo: Arc<S>, token_cipher: Arc<TokenCipher>) -> Self {
+        Self {
+            user_session_repo,
+            token_cipher,
+        }
+    }
+
+    // converts plaintext rows and rows encrypted with an older key version
+    pub async fn execute(&self) -> Result<(), AppError> {
+        let refresh_tokens = self
+            .user_session_repo
+            .find_all_unkeyed_refresh_tokens()
+            .await?;
+        for refresh_token in &refresh_tokens {
+            self.user_session_repo
+                .update_refresh_token_hash(
+                    &refresh_token.id,
+                    &self.token_cipher.keyed_digest(&refresh_token.token_hash),
+                )
+                .await?;
+        }
+
+        let sessions = self
+            .user_session_repo
+            .find_all_unsealed(self.token_cipher.current_version())
+            .await?;
+        let session_count = sessions.len();
+        for mut session in sessions {
+            if session.token_key_version.is_some() {
+                session.unseal(&self.token_cipher)?;
+            } else {
+                // plaintext rows bound their id token by the plain sha256 digest
+                session.id_token_hash = session
+                    .id_token_hash
+                    .map(|digest| self.token_cipher.keyed_digest(&digest));
+            }
+            session.seal(&self.token_cipher)?;
+
+            self.user_session_repo.update_token(&session).await?;
+        }
+
+        if !refresh_tokens.is_empty() || session_count > 0 {
+            info!(
+                "Sealed {} refresh tokens and {} sessions with the current token keys",
+                refresh_tokens.len(),
+                session_count
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -56,11 +56,12 @@ where
             Ok(role) => role,
             Err(err) => match err {
                 AppError::SqlxError(sqlx::Error::RowNotFound) => {
-                    let new_super_admin_role = Role::new(
+                    let mut new_super_admin_role = Role::new(
                         Uuid::new_v4().to_string(),
                         SUPER_ADMIN_ROLE.to_string(),
                         false,
                     );
+                    new_super_admin_role.require_mfa();
 
                     let mut enforcer = self.rbac.enforcer.write().await;
 
@@ -83,7 +84,9 @@ where
         let hashed_pass =
             tokio::task::spawn_blocking(move || hash_password(cloned_pass.as_bytes())).await??;
 
-        let new_user = User::new(req.email, Some(hashed_pass));
+        let mut new_user = User::new(req.email, Some(hashed_pass));
+        // seeded from the environment, nobody is there to click a verification link
+        new_user.verify_email();
         // for email provider we set provider_user_id same like user_id
         let user_oauth_provider = UserOauthProvider::new(
             new_user.id.clone(),
@@ -92,7 +95,7 @@ where
         );
         let user_role = UserRole::new(new_user.id.clone(), super_role.id.clone());
 
-        let (_user, _user_oauth_provider, _user_role) = self
+        let (_user, _user_oauth_provider, user_role) = self
             .user_repo
             .tx_register_user(&mut tx, &new_user, &user_oauth_provider, &user_role)
             .await
@@ -100,6 +103,10 @@ where
 
         tx.commit().await?;
 
+        self.rbac
+            .assign_role(&user_role.user_id, &user_role.role_id)
+            .await?;
+
         Ok(())
     }
 }
@@ -0,0 +1,95 @@
+use std::sync::Arc;
+
+use validator::Validate;
+
+use crate::{
+    application::{
+        dto::auth::email_request::MagicLinkRequest,
+        services::{mail_svc::MailService, redis_svc::RedisService},
+    },
+    domain::repositories::{redis_repo::RedisRepository, user_repo::UserRepository},
+    infra::{
+        common::constants::{
+            MAGIC_LINK_MAX_PER_EMAIL, MAGIC_LINK_MAX_PER_IP, MAGIC_LINK_RATE_LIMIT_WINDOW_SECONDS,
+        },
+        errors::app_error::AppError,
+        utils::{client_info::ClientInfo, token_cipher::TokenCipher, token_hash::random_token},
+    },
+};
+
+#[derive(Clone)]
+pub struct SendMagicLink<U, C> {
+    user_repo: Arc<U>,
+    token_cipher: Arc<TokenCipher>,
+    redis_svc: Arc<RedisService<C>>,
+    mail_svc: Arc<MailService>,
+}
+
+impl<U, C> SendMagicLink<U, C>
+where
+    U: UserRepository,
+    C: RedisRepository,
+{
+    pub fn new(
+        user_repo: Arc<U>,
+        token_cipher: Arc<TokenCipher>,
+        redis_svc: Arc<RedisService<C>>,
+        mail_svc: Arc<MailService>,
+    ) -> Self {
+        Self {
+            user_repo,
+            token_cipher,
+            redis_svc,
+            mail_svc,
+        }
+    }
+
+    // succeeds for unknown emails too, so it can't be used to probe for accounts.
+    // the limits count every request, known email or not
+    pub async fn execute(
+        &self,
+        req: MagicLinkRequest,
+        client: &ClientInfo,
+    ) -> Result<(), AppError> {
+        req.validate()?;
+
+        let email_hits = self
+            .redis_svc
+            .count_hit(
+                &format!("magic_link_email_{}", req.email.to_lowercase()),
+                MAGIC_LINK_RATE_LIMIT_WINDOW_SECONDS,
+            )
+            .await?;
+        if email_hits > MAGIC_LINK_MAX_PER_EMAIL {
+            return Err(AppError::TooManyRequests);
+        }
+        if let Some(ip_address) = &client.ip_address {
+            let ip_hits = self
+                .redis_svc
+                .count_hit(
+                    &format!("magic_link_ip_{}", ip_address),
+                    MAGIC_LINK_RATE_LIMIT_WINDOW_SECONDS,
+                )
+                .await?;
+            if ip_hits > MAGIC_LINK_MAX_PER_IP {
+                return Err(AppError::TooManyRequests);
+            }
+        }
+
+        let user = match self.user_repo.find_by_email(&req.email).await {
+            Ok(user) => user,
+            Err(AppError::SqlxError(sqlx::Error::RowNotFound)) => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        if !user.is_accessible() {
+            return Ok(());
+        }
+
+        let token = random_token();
+        self.redis_svc
+            .set_magic_link(&self.token_cipher.hash(&token), &user.id)
+            .await?;
+
+        self.mail_svc.send_magic_link(&user, &token).await
+    }
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use validator::Validate;
+
+use crate::{
+    application::{
+        dto::auth::{mfa_request::MfaTokenRequest, mfa_response::TotpEnrolmentResponse},
+        services::{mfa_svc::MfaService, redis_svc::RedisService},
+    },
+    domain::{
+        entities::user::User,
+        repositories::{
+            redis_repo::RedisRepository, role_repo::RoleRepository,
+            user_mfa_repo::UserMfaRepository, user_repo::UserRepository,
+        },
+    },
+    infra::errors::app_error::AppError,
+};
+
+#[derive(Clone)]
+pub struct StartTotpEnrolment<U, R, M, C> {
+    user_repo: Arc<U>,
+    mfa_svc: Arc<MfaService<M, R>>,
+    redis_svc: Arc<RedisService<C>>,
+}
+
+impl<U, R, M, C> StartTotpEnrolment<U, R, M, C>
+where
+    U: UserRepository,
+    R: RoleRepository,
+    M: UserMfaRepository,
+    C: RedisRepository,
+{
+    pub fn new(
+        user_repo: Arc<U>,
+        mfa_svc: Arc<MfaService<M, R>>,
+        redis_svc: Arc<RedisService<C>>,
+    ) -> Self {
+        Self {
+            user_repo,
+            mfa_svc,
+            redis_svc,
+        }
+    }
+
+    pub async fn execute(&self, user: &User) -> Result<TotpEnrolmentResponse, AppError> {
+        self.mfa_svc.start_totp_enrolment(user).await
+    }
+
+    // users forced into mfa by their role enrol during login, before they have a session
+    pub async fn execute_with_challenge(
+        &self,
+        req: MfaTokenRequest,
+    ) -> Result<TotpEnrolmentResponse, AppError> {
+        req.validate()?;
+
+        let challenge = self
+            .redis_svc
+            .get_mfa_challenge(&req.mfa_token)
+            .await?
+            .ok_or(AppError::UnauthorizedError(
+                "Invalid or expired mfa token, try to login again".to_string(),
+            ))?;
+        let user = self.user_repo.find_by_id(&challenge.user_id).await?;
+
+        self.mfa_svc.start_totp_enrolment(&user).await
+    }
+}
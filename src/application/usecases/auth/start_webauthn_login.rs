@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use crate::{
+    application::{
+        dto::auth::webauthn_response::{PublicKeyRequestOptions, WebauthnRequestOptions},
+        services::redis_svc::RedisService,
+    },
+    domain::repositories::redis_repo::RedisRepository,
+    infra::{
+        common::constants::WEBAUTHN_CHALLENGE_LIFETIME_SECONDS,
+        errors::app_error::AppError,
+        utils::{token_hash::random_token, webauthn::WebauthnVerifier},
+    },
+};
+
+#[derive(Clone)]
+pub struct StartWebauthnLogin<C> {
+    webauthn: Arc<WebauthnVerifier>,
+    redis_svc: Arc<RedisService<C>>,
+}
+
+impl<C> StartWebauthnLogin<C>
+where
+    C: RedisRepository,
+{
+    pub fn new(webauthn: Arc<WebauthnVerifier>, redis_svc: Arc<RedisService<C>>) -> Self {
+        Self {
+            webauthn,
+            redis_svc,
+        }
+    }
+
+    pub async fn execute(&self) -> Result<WebauthnRequestOptions, AppError> {
+        let challenge = random_token();
+        self.redis_svc.set_webauthn_login(&challenge).await?;
+
+        Ok(WebauthnRequestOptions {
+            public_key: PublicKeyRequestOptions {
+                challenge,
+                timeout: WEBAUTHN_CHALLENGE_LIFETIME_SECONDS * 1000,
+                rp_id: self.webauthn.rp_id().to_string(),
+                allow_credentials: vec![],
+                user_verification: "required".to_string(),
+            },
+        })
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use crate::{
+    application::{
+        dto::auth::webauthn_response::{
+            AuthenticatorSelection, CredentialDescriptor, CredentialParameter,
+            PublicKeyCreationOptions, RelyingParty, WebauthnCreationOptions, WebauthnUser,
+        },
+        services::redis_svc::RedisService,
+    },
+    domain::{
+        entities::user::User,
+        repositories::{
+            redis_repo::RedisRepository, webauthn_credential_repo::WebauthnCredentialRepository,
+        },
+    },
+    infra::{
+        common::constants::WEBAUTHN_CHALLENGE_LIFETIME_SECONDS,
+        errors::app_error::AppError,
+        utils::{
+            token_hash::random_token,
+            webauthn::{user_handle, WebauthnVerifier, COSE_ALG_ES256, COSE_ALG_RS256},
+        },
+    },
+};
+
+#[derive(Clone)]
+pub struct StartWebauthnRegistration<W, C> {
+    webauthn_credential_repo: Arc<W>,
+    webauthn: Arc<WebauthnVerifier>,
+    redis_svc: Arc<RedisService<C>>,
+}
+
+impl<W, C> StartWebauthnRegistration<W, C>
+where
+    W: WebauthnCredentialRepository,
+    C: RedisRepository,
+{
+    pub fn new(
+        webauthn_credential_repo: Arc<W>,
+        webauthn: Arc<WebauthnVerifier>,
+        redis_svc: Arc<RedisService<C>>,
+    ) -> Self {
+        Self {
+            webauthn_credential_repo,
+            webauthn,
+            redis_svc,
+        }
+    }
+
+    // discoverable credentials only, so the passkey can log in without typing an email
+    pub async fn execute(&self, user: &User) -> Result<WebauthnCreationOptions, AppError> {
+        let challenge = random_token();
+        self.redis_svc
+            .set_webauthn_registration(&user.id, &challenge)
+            .await?;
+
+        // the authenticator refuses to create a second passkey for the same account
+        let exclude_credentials = self
+            .webauthn_credential_repo
+            .find_by_user_id(&user.id)
+            .await?
+            .into_iter()
+            .map(|credential| CredentialDescriptor {
+                kind: "public-key".to_string(),
+                id: credential.credential_id,
+            })
+            .collect();
+
+        Ok(WebauthnCreationOptions {
+            public_key: PublicKeyCreationOptions {
+                rp: RelyingParty {
+                    id: self.webauthn.rp_id().to_string(),
+                    name: self.webauthn.rp_name().to_string(),
+                },
+                user: WebauthnUser {
+                    id: user_handle(&user.id),
+                    name: user.email.clone(),
+                    display_name: user.fullname.clone().unwrap_or_else(|| user.email.clone()),
+                },
+                challenge,
+                pub_key_cred_params: [COSE_ALG_ES256, COSE_ALG_RS256]
+                    .into_iter()
+                    .map(|alg| CredentialParameter {
+                        kind: "public-key".to_string(),
+                        alg,
+                    })
+                    .collect(),
+                timeout: WEBAUTHN_CHALLENGE_LIFETIME_SECONDS * 1000,
+                exclude_credentials,
+                authenticator_selection: AuthenticatorSelection {
+                    resident_key: "required".to_string(),
+                    require_resident_key: true,
+                    user_verification: "required".to_string(),
+                },
+                attestation: "none".to_string(),
+            },
+        })
+    }
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use tracing::info;
+
+use crate::{
+    application::services::{oauth_svc::OauthService, redis_svc::RedisService},
+    domain::repositories::{
+        oauth_provider_repo::OauthProviderRepository, redis_repo::RedisRepository,
+        role_repo::RoleRepository, user_repo::UserRepository,
+        user_session_repo::UserSessionRepository,
+    },
+    infra::{errors::app_error::AppError, oauth2::constants::EMAIL_PROVIDER},
+};
+
+#[derive(Clone)]
+pub struct UnlinkOauthProvider<U, R, S, O, C> {
+    oauth_provider_repo: Arc<O>,
+    user_session_repo: Arc<S>,
+    oauth_svc: Arc<OauthService<U, R, S, O>>,
+    redis_svc: Arc<RedisService<C>>,
+}
+
+impl<U, R, S, O, C> UnlinkOauthProvider<U, R, S, O, C>
+where
+    U: UserRepository,
+    R: RoleRepository,
+    S: UserSessionRepository,
+    O: OauthProviderRepository,
+    C: RedisRepository,
+{
+    pub fn new(
+        oauth_provider_repo: Arc<O>,
+        user_session_repo: Arc<S>,
+        oauth_svc: Arc<OauthService<U, R, S, O>>,
+        redis_svc: Arc<RedisService<C>>,
+    ) -> Self {
+        Self {
+            oauth_provider_repo,
+            user_session_repo,
+            oauth_svc,
+            redis_svc,
+        }
+    }
+
+    // users always keep at least one way to login
+    pub async fn execute(&self, user_id: &str, oauth_provider_id: &str) -> Result<(), AppError> {
+        let oauth_providers = self.oauth_provider_repo.find_by_user_id(user_id).await?;
+
+        let oauth_provider = oauth_providers
+            .iter()
+            .find(|oauth_provider| oauth_provider.id == oauth_provider_id)
+            .ok_or(AppError::ResourceNotFound)?;
+
+        if oauth_provider.provider == EMAIL_PROVIDER {
+            return Err(AppError::ProcessError(
+                "Password login can't be unlinked".to_string(),
+            ));
+        }
+        if oauth_providers.len() == 1 {
+            return Err(AppError::ProcessError(
+                "The last login method can't be unlinked".to_string(),
+            ));
+        }
+
+        self.oauth_provider_repo
+            .delete_by_id(&oauth_provider.id)
+            .await?;
+        info!(
+            "Unlinked {} identity {} from User {}",
+            oauth_provider.provider, oauth_provider.id, user_id
+        );
+
+        // sessions only know the provider, so they go once no identity of it is left
+        let provider_still_linked = oauth_providers.iter().any(|other| {
+            other.id != oauth_provider.id && other.provider == oauth_provider.provider
+        });
+        if !provider_still_linked {
+            let sessions = self.user_session_repo.find_all_by_user_id(user_id).await?;
+            for session in sessions
+                .iter()
+                .filter(|session| session.provider == oauth_provider.provider)
+            {
+                info!("Revoking Session {} of User {}...", session.id, user_id);
+                self.oauth_svc.revoke_session(session).await?;
+            }
+        }
+
+        self.redis_svc.remove_current_user(user_id).await?;
+
+        Ok(())
+    }
+}
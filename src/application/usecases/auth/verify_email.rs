@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use validator::Validate;
+
+use crate::{
+    application::{
+        dto::auth::email_request::VerifyEmailRequest,
+        services::{redis_svc::RedisService, user_token_svc::UserTokenService},
+    },
+    domain::{
+        entities::user_token::EMAIL_VERIFICATION_PURPOSE,
+        repositories::{
+            redis_repo::RedisRepository, user_repo::UserRepository,
+            user_token_repo::UserTokenRepository,
+        },
+    },
+    infra::errors::app_error::AppError,
+};
+
+#[derive(Clone)]
+pub struct VerifyEmail<U, T, C> {
+    user_repo: Arc<U>,
+    user_token_svc: Arc<UserTokenService<T>>,
+    redis_svc: Arc<RedisService<C>>,
+}
+
+impl<U, T, C> VerifyEmail<U, T, C>
+where
+    U: UserRepository,
+    T: UserTokenRepository,
+    C: RedisRepository,
+{
+    pub fn new(
+        user_repo: Arc<U>,
+        user_token_svc: Arc<UserTokenService<T>>,
+        redis_svc: Arc<RedisService<C>>,
+    ) -> Self {
+        Self {
+            user_repo,
+            user_token_svc,
+            redis_svc,
+        }
+    }
+
+    pub async fn execute(&self, req: VerifyEmailRequest) -> Result<(), AppError> {
+        req.validate()?;
+
+        let user_token = self
+            .user_token_svc
+            .consume(EMAIL_VERIFICATION_PURPOSE, &req.token)
+            .await?;
+
+        let mut user = self.user_repo.find_by_id(&user_token.user_id).await?;
+        if user.is_email_verified() {
+            return Ok(());
+        }
+
+        user.verify_email();
+        self.user_repo.update(&user.id, user.clone()).await?;
+        self.redis_svc.remove_current_user(&user.id).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    application::{
+        dto::auth::{mfa_request::MfaLoginRequest, token_response::MfaLoginResult},
+        services::{mfa_svc::MfaService, oauth_svc::OauthService, redis_svc::RedisService},
+    },
+    domain::{
+        entities::user_session::UserSession,
+        repositories::{
+            oauth_provider_repo::OauthProviderRepository, redis_repo::RedisRepository,
+            role_repo::RoleRepository, user_mfa_repo::UserMfaRepository, user_repo::UserRepository,
+            user_session_repo::UserSessionRepository,
+        },
+    },
+    infra::{
+        common::constants::{MFA_CHALLENGE_MAX_ATTEMPTS, SESSION_LIFETIME_SECONDS},
+        errors::app_error::AppError,
+        utils::{client_info::ClientInfo, jwt_maker::JwtMaker},
+    },
+};
+
+#[derive(Clone)]
+pub struct VerifyMfaLogin<U, R, S, O, M, C> {
+    user_repo: Arc<U>,
+    jwt_maker: Arc<JwtMaker>,
+    oauth_svc: Arc<OauthService<U, R, S, O>>,
+    mfa_svc: Arc<MfaService<M, R>>,
+    redis_svc: Arc<RedisService<C>>,
+}
+
+impl<U, R, S, O, M, C> VerifyMfaLogin<U, R, S, O, M, C>
+where
+    U: UserRepository,
+    R: RoleRepository,
+    S: UserSessionRepository,
+    O: OauthProviderRepository,
+    M: UserMfaRepository,
+    C: RedisRepository,
+{
+    pub fn new(
+        user_repo: Arc<U>,
+        jwt_maker: Arc<JwtMaker>,
+        oauth_svc: Arc<OauthService<U, R, S, O>>,
+        mfa_svc: Arc<MfaService<M, R>>,
+        redis_svc: Arc<RedisService<C>>,
+    ) -> Self {
+        Self {
+            user_repo,
+            jwt_maker,
+            oauth_svc,
+            mfa_svc,
+            redis_svc,
+        }
+    }
+
+    // second step of the email, magic link & oauth logins, for a pending enrolment the code
+    // confirms it & the recovery codes are handed out with the tokens. oauth logins get our own
+    // tokens too, the provider tokens are kept in the session like for providers without id tokens
+    pub async fn execute(
+        &self,
+        req: MfaLoginRequest,
+        client: &ClientInfo,
+    ) -> Result<MfaLoginResult, AppError> {
+        req.validate()?;
+
+        let mut challenge = self
+            .redis_svc
+            .take_mfa_challenge(&req.mfa_token)
+            .await?
+            .ok_or(AppError::UnauthorizedError(
+                "Invalid or expired mfa token, try to login again".to_string(),
+            ))?;
+
+        let user = self.user_repo.find_by_id(&challenge.user_id).await?;
+        if !user.is_accessible() {
+            return Err(AppError::UserInactive);
+        }
+
+        let enrolment = !self.mfa_svc.is_totp_enabled(&user.id).await?;
+        let verified = if enrolment {
+            self.mfa_svc.confirm_totp_enrolment(&user, &req.code).await
+        } else {
+            self.mfa_svc.verify_code(&user, &req.code).await
+        };
+
+        // the challenge is handed back for another try until it runs out of attempts
+        if let Err(err) = verified {
+            if matches!(err, AppError::InvalidMfaCode) {
+                challenge.attempts += 1;
+            }
+            if challenge.attempts < MFA_CHALLENGE_MAX_ATTEMPTS {
+                self.redis_svc
+                    .set_mfa_challenge(&req.mfa_token, &challenge)
+                    .await?;
+            }
+
+            return Err(err);
+        }
+
+        let recovery_codes = if enrolment {
+            Some(self.mfa_svc.regenerate_recovery_codes(&user.id).await?)
+        } else {
+            None
+        };
+
+        let session_id = Uuid::new_v4().to_string();
+        let profile = self.oauth_svc.get_token_profile(&user).await?;
+        let access_token =
+            self.jwt_maker
+                .make_token(user.id.clone(), session_id.clone(), profile, 1)?;
+        let refresh_token =
+            self.jwt_maker
+                .make_refresh_token(user.id.clone(), session_id.clone(), 24 * 7)?;
+
+        let session = match &challenge.provider_tokens {
+            Some(provider_tokens) => self.oauth_svc.new_provider_session(
+                session_id,
+                &challenge.provider,
+                &user.id,
+                &self.oauth_svc.unseal_provider_tokens(provider_tokens)?,
+                client,
+            ),
+            None => UserSession::new(
+                session_id,
+                user.id.clone(),
+                challenge.provider.clone(),
+                access_token.clone(),
+                None,
+                Some(chrono::Utc::now() + chrono::Duration::seconds(SESSION_LIFETIME_SECONDS)),
+                client,
+            ),
+        };
+        let _session = self
+            .oauth_svc
+            .get_or_create_session(session, &refresh_token)
+            .await?;
+
+        Ok(MfaLoginResult {
+            access_token,
+            refresh_token,
+            recovery_codes,
+        })
+    }
+}
@@ -29,6 +29,12 @@ where
             ));
         }
 
+        if let Some(inherits) = &req.inherits {
+            for parent_id in inherits {
+                self.role_repo.find_by_id(parent_id).await?;
+            }
+        }
+
         let role_req = Role::from(&req);
 
         let mut enforcer = self.rbac.enforcer.write().await;
@@ -46,9 +52,14 @@ where
                 let _ = enforcer.add_policy(policy).await;
             }
         }
+        drop(enforcer);
 
         let role = self.role_repo.create(role_req).await?;
 
+        if let Some(inherits) = req.inherits {
+            self.rbac.set_role_parents(&role.id, &inherits).await?;
+        }
+
         Ok(role)
     }
 }
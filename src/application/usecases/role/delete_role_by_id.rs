@@ -40,6 +40,10 @@ where
         for policy in current_policies {
             enforcer.remove_policy(policy).await?;
         }
+        drop(enforcer);
+
+        info!("Removing role groupings for Role with id {}...", id);
+        self.rbac.remove_role_groupings(&role.id).await?;
 
         Ok(())
     }
@@ -38,7 +38,13 @@ where
             .map(|policy| format!("{}:{}", policy[1], policy[2]))
             .collect::<Vec<String>>();
 
-        let role_with_permissions = RoleWithPermission { role, permissions };
+        let inherits = self.rbac.get_role_parents(&role.id).await;
+
+        let role_with_permissions = RoleWithPermission {
+            role,
+            permissions,
+            inherits,
+        };
 
         Ok(role_with_permissions)
     }
@@ -5,7 +5,7 @@ use crate::infra::{rbac::Rbac, repositories::pg_role_repo::PgRoleRepository};
 use super::{
     create_role::CreateRole, delete_role_by_id::DeleteRoleById, get_all_role::GetAllRole,
     get_paginated_role::GetPaginatedRole, get_role_by_id::GetRoleById,
-    update_role_by_id::UpdateRoleById,
+    sync_user_roles::SyncUserRoles, update_role_by_id::UpdateRoleById,
 };
 
 #[derive(Clone)]
@@ -16,6 +16,7 @@ pub struct RoleUsecase {
     pub create_role: Arc<CreateRole<PgRoleRepository>>,
     pub update_role_by_id: Arc<UpdateRoleById<PgRoleRepository>>,
     pub delete_role_by_id: Arc<DeleteRoleById<PgRoleRepository>>,
+    pub sync_user_roles: Arc<SyncUserRoles<PgRoleRepository>>,
 }
 
 impl RoleUsecase {
@@ -26,6 +27,7 @@ impl RoleUsecase {
         let create_role = Arc::new(CreateRole::new(role_repo.clone(), rbac.clone()));
         let update_role = Arc::new(UpdateRoleById::new(role_repo.clone(), rbac.clone()));
         let delete_role_by_id = Arc::new(DeleteRoleById::new(role_repo.clone(), rbac.clone()));
+        let sync_user_roles = Arc::new(SyncUserRoles::new(role_repo.clone(), rbac.clone()));
 
         Self {
             get_paginated_role,
@@ -34,6 +36,7 @@ impl RoleUsecase {
             create_role,
             update_role_by_id: update_role,
             delete_role_by_id,
+            sync_user_roles,
         }
     }
 }
@@ -4,4 +4,5 @@ pub mod get_all_role;
 pub mod get_paginated_role;
 pub mod get_role_by_id;
 pub mod init;
+pub mod sync_user_roles;
 pub mod update_role_by_id;
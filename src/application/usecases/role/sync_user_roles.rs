@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use crate::{
+    domain::repositories::role_repo::RoleRepository,
+    infra::{errors::app_error::AppError, rbac::Rbac},
+};
+
+#[derive(Clone)]
+pub struct SyncUserRoles<R> {
+    role_repo: Arc<R>,
+    rbac: Arc<Rbac>,
+}
+
+impl<R> SyncUserRoles<R>
+where
+    R: RoleRepository,
+{
+    pub fn new(role_repo: Arc<R>, rbac: Arc<Rbac>) -> Self {
+        Self { role_repo, rbac }
+    }
+
+    pub async fn execute(&self) -> Result<(), AppError> {
+        let user_roles = self.role_repo.find_all_user_roles().await?;
+        let role_ids = self
+            .role_repo
+            .find_all()
+            .await?
+            .into_iter()
+            .map(|role| role.id)
+            .collect::<Vec<String>>();
+
+        self.rbac.sync_user_roles(&user_roles, &role_ids).await?;
+
+        Ok(())
+    }
+}
@@ -31,7 +31,26 @@ where
 
         let mut role = self.role_repo.find_by_id(id).await?;
 
-        role.update(&req.name, req.is_default);
+        if let Some(inherits) = &req.inherits {
+            for parent_id in inherits {
+                self.role_repo.find_by_id(parent_id).await?;
+
+                // a parent that already inherits from this role would close a loop
+                if parent_id == &role.id || self.rbac.inherits_from(parent_id, &role.id).await {
+                    return Err(AppError::ProcessError(format!(
+                        "role {} already inherits from this role",
+                        parent_id
+                    )));
+                }
+            }
+        }
+
+        role.update(
+            &req.name,
+            req.is_default,
+            req.mfa_required.unwrap_or(role.mfa_required),
+        );
+        self.role_repo.update(&role.id, role.clone()).await?;
 
         let mut enforcer = self.rbac.enforcer.write().await;
         let current_policies = enforcer.get_filtered_policy(0, vec![role.id.clone()]);
@@ -81,6 +100,12 @@ where
                 enforcer.remove_policy(policy).await?;
             }
         }
+        drop(enforcer);
+
+        // parents are left as they are when the request doesn't mention them
+        if let Some(inherits) = req.inherits {
+            self.rbac.set_role_parents(&role.id, &inherits).await?;
+        }
 
         Ok(())
     }
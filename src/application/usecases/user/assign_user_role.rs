@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use tracing::info;
+use validator::Validate;
+
+use crate::{
+    application::{
+        dto::user::assign_role_request::AssignRoleRequest, services::redis_svc::RedisService,
+    },
+    domain::{
+        entities::{user::UserFull, user_role::UserRole},
+        repositories::{
+            redis_repo::RedisRepository, role_repo::RoleRepository, user_repo::UserRepository,
+        },
+    },
+    infra::{errors::app_error::AppError, rbac::Rbac},
+};
+
+#[derive(Clone)]
+pub struct AssignUserRole<U, R, C> {
+    user_repo: Arc<U>,
+    role_repo: Arc<R>,
+    rbac: Arc<Rbac>,
+    redis_svc: Arc<RedisService<C>>,
+}
+
+impl<U, R, C> AssignUserRole<U, R, C>
+where
+    U: UserRepository,
+    R: RoleRepository,
+    C: RedisRepository,
+{
+    pub fn new(
+        user_repo: Arc<U>,
+        role_repo: Arc<R>,
+        rbac: Arc<Rbac>,
+        redis_svc: Arc<RedisService<C>>,
+    ) -> Self {
+        Self {
+            user_repo,
+            role_repo,
+            rbac,
+            redis_svc,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        actor: &UserFull,
+        user_id: &str,
+        req: AssignRoleRequest,
+    ) -> Result<UserRole, AppError> {
+        req.validate()?;
+
+        if actor.user.id == user_id {
+            return Err(AppError::ProcessError(
+                "Cannot change your own roles".to_string(),
+            ));
+        }
+
+        let user = self.user_repo.find_by_id(user_id).await?;
+        let role = self.role_repo.find_by_id(&req.role_id).await?;
+
+        if !self.rbac.can_manage_role(actor, &role).await {
+            return Err(AppError::Forbidden);
+        }
+
+        let current_roles = self.role_repo.get_roles_by_user_id(&user.id).await?;
+        if current_roles.iter().any(|current| current.id == role.id) {
+            return Err(AppError::ResourceExist(format!(
+                "User already has role {}",
+                role.name
+            )));
+        }
+
+        info!("Assigning Role {} to User {}...", role.id, user.id);
+        let user_role = self
+            .role_repo
+            .assign_to_user(UserRole::new(user.id.clone(), role.id.clone()))
+            .await
+            .map_err(|err| match err {
+                // a concurrent assign of the same role got in between the check & the insert
+                AppError::SqlxError(sqlx::Error::Database(db_err))
+                    if db_err.is_unique_violation() =>
+                {
+                    AppError::ResourceExist(format!("User already has role {}", role.name))
+                }
+                _ => err,
+            })?;
+
+        self.rbac.assign_role(&user.id, &role.id).await?;
+
+        // cached current user still holds the previous roles
+        self.redis_svc.remove_current_user(&user.id).await?;
+
+        Ok(user_role)
+    }
+}
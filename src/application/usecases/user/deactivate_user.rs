@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use tracing::info;
+
+use crate::{
+    application::services::redis_svc::RedisService,
+    domain::{
+        entities::user::UserFull,
+        repositories::{
+            redis_repo::RedisRepository, role_repo::RoleRepository, user_repo::UserRepository,
+            user_session_repo::UserSessionRepository,
+        },
+    },
+    infra::{errors::app_error::AppError, rbac::Rbac},
+};
+
+#[derive(Clone)]
+pub struct DeactivateUser<U, R, S, C> {
+    user_repo: Arc<U>,
+    role_repo: Arc<R>,
+    user_session_repo: Arc<S>,
+    rbac: Arc<Rbac>,
+    redis_svc: Arc<RedisService<C>>,
+}
+
+impl<U, R, S, C> DeactivateUser<U, R, S, C>
+where
+    U: UserRepository,
+    R: RoleRepository,
+    S: UserSessionRepository,
+    C: RedisRepository,
+{
+    pub fn new(
+        user_repo: Arc<U>,
+        role_repo: Arc<R>,
+        user_session_repo: Arc<S>,
+        rbac: Arc<Rbac>,
+        redis_svc: Arc<RedisService<C>>,
+    ) -> Self {
+        Self {
+            user_repo,
+            role_repo,
+            user_session_repo,
+            rbac,
+            redis_svc,
+        }
+    }
+
+    pub async fn execute(&self, actor: &UserFull, id: &str) -> Result<(), AppError> {
+        if actor.user.id == id {
+            return Err(AppError::ProcessError(
+                "Cannot deactivate your own account".to_string(),
+            ));
+        }
+
+        let mut user = self.user_repo.find_by_id(id).await?;
+
+        if user.deleted_at.is_some() {
+            return Err(AppError::ResourceNotFound);
+        }
+
+        let roles = self.role_repo.get_roles_by_user_id(&user.id).await?;
+        if !self.rbac.can_manage_user(actor, &roles).await {
+            return Err(AppError::Forbidden);
+        }
+
+        info!("Deactivating User with id {}...", id);
+        user.deactivate();
+
+        self.user_repo.update(&user.id, user.clone()).await?;
+
+        // kill every active session so the user is logged out everywhere
+        self.user_session_repo.delete_by_user_id(&user.id).await?;
+        self.redis_svc.remove_current_user(&user.id).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use crate::{
+    domain::{entities::user::User, repositories::user_repo::UserRepository},
+    infra::{
+        errors::app_error::AppError,
+        utils::pagination::{PaginatedResponse, PaginationMeta},
+    },
+};
+
+#[derive(Clone)]
+pub struct GetPaginatedUser<U> {
+    user_repo: Arc<U>,
+}
+
+impl<U> GetPaginatedUser<U>
+where
+    U: UserRepository,
+{
+    pub fn new(user_repo: Arc<U>) -> Self {
+        Self { user_repo }
+    }
+
+    pub async fn execute(
+        &self,
+        page: i64,
+        limit: i64,
+        search: Option<String>,
+    ) -> Result<PaginatedResponse<User>, AppError> {
+        if page < 1 || limit < 1 {
+            return Err(AppError::ProcessError(
+                "page & limit must be at least 1".to_string(),
+            ));
+        }
+
+        let (users, total_items) = self.user_repo.paginate(page, limit, search).await?;
+
+        let total_pages = (total_items as f64 / limit as f64).ceil() as i64;
+
+        let pagination = PaginationMeta {
+            total_items,
+            total_pages,
+            current_page: page as i32,
+            items_per_page: limit as i32,
+        };
+
+        Ok(PaginatedResponse {
+            items: users,
+            pagination,
+        })
+    }
+}
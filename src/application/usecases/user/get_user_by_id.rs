@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use crate::{
+    application::dto::user::get_user_request::UserDetail,
+    domain::repositories::{
+        oauth_provider_repo::OauthProviderRepository, role_repo::RoleRepository,
+        user_repo::UserRepository,
+    },
+    infra::errors::app_error::AppError,
+};
+
+#[derive(Clone)]
+pub struct GetUserById<U, R, O> {
+    user_repo: Arc<U>,
+    role_repo: Arc<R>,
+    oauth_provider_repo: Arc<O>,
+}
+
+impl<U, R, O> GetUserById<U, R, O>
+where
+    U: UserRepository,
+    R: RoleRepository,
+    O: OauthProviderRepository,
+{
+    pub fn new(user_repo: Arc<U>, role_repo: Arc<R>, oauth_provider_repo: Arc<O>) -> Self {
+        Self {
+            user_repo,
+            role_repo,
+            oauth_provider_repo,
+        }
+    }
+
+    pub async fn execute(&self, id: &str) -> Result<UserDetail, AppError> {
+        let user = self.user_repo.find_by_id(id).await?;
+
+        if user.deleted_at.is_some() {
+            return Err(AppError::ResourceNotFound);
+        }
+
+        let roles = self.role_repo.get_roles_by_user_id(&user.id).await?;
+        let providers = self.oauth_provider_repo.find_by_user_id(&user.id).await?;
+
+        Ok(UserDetail {
+            user,
+            roles,
+            providers,
+        })
+    }
+}
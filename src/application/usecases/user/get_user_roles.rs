@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use crate::{
+    domain::{
+        entities::role::Role,
+        repositories::{role_repo::RoleRepository, user_repo::UserRepository},
+    },
+    infra::errors::app_error::AppError,
+};
+
+#[derive(Clone)]
+pub struct GetUserRoles<U, R> {
+    user_repo: Arc<U>,
+    role_repo: Arc<R>,
+}
+
+impl<U, R> GetUserRoles<U, R>
+where
+    U: UserRepository,
+    R: RoleRepository,
+{
+    pub fn new(user_repo: Arc<U>, role_repo: Arc<R>) -> Self {
+        Self {
+            user_repo,
+            role_repo,
+        }
+    }
+
+    pub async fn execute(&self, user_id: &str) -> Result<Vec<Role>, AppError> {
+        let user = self.user_repo.find_by_id(user_id).await?;
+
+        let roles = self.role_repo.get_roles_by_user_id(&user.id).await?;
+
+        Ok(roles)
+    }
+}
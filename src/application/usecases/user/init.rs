@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use crate::{
+    application::services::{login_guard_svc::LoginGuardService, redis_svc::RedisService},
+    infra::{
+        rbac::Rbac,
+        repositories::{
+            pg_oauth_provider::PgOauthProviderRepository, pg_role_repo::PgRoleRepository,
+            pg_user_repo::PgUserRepository, pg_user_session::PgUserSessionRepository,
+            redis_repo_impl::RedisRepositoryImpl,
+        },
+    },
+};
+
+use super::{
+    assign_user_role::AssignUserRole, deactivate_user::DeactivateUser,
+    delete_user_by_id::DeleteUserById, get_paginated_user::GetPaginatedUser,
+    get_user_by_id::GetUserById, get_user_roles::GetUserRoles, reactivate_user::ReactivateUser,
+    revoke_user_role::RevokeUserRole, unlock_user::UnlockUser, update_user_by_id::UpdateUserById,
+};
+
+#[derive(Clone)]
+pub struct UserUsecase {
+    pub get_paginated_user: Arc<GetPaginatedUser<PgUserRepository>>,
+    pub get_user_by_id:
+        Arc<GetUserById<PgUserRepository, PgRoleRepository, PgOauthProviderRepository>>,
+    pub update_user_by_id:
+        Arc<UpdateUserById<PgUserRepository, PgRoleRepository, RedisRepositoryImpl>>,
+    pub deactivate_user: Arc<
+        DeactivateUser<
+            PgUserRepository,
+            PgRoleRepository,
+            PgUserSessionRepository,
+            RedisRepositoryImpl,
+        >,
+    >,
+    pub reactivate_user:
+        Arc<ReactivateUser<PgUserRepository, PgRoleRepository, RedisRepositoryImpl>>,
+    pub delete_user_by_id: Arc<
+        DeleteUserById<
+            PgUserRepository,
+            PgRoleRepository,
+            PgUserSessionRepository,
+            RedisRepositoryImpl,
+        >,
+    >,
+    pub get_user_roles: Arc<GetUserRoles<PgUserRepository, PgRoleRepository>>,
+    pub assign_user_role:
+        Arc<AssignUserRole<PgUserRepository, PgRoleRepository, RedisRepositoryImpl>>,
+    pub revoke_user_role:
+        Arc<RevokeUserRole<PgUserRepository, PgRoleRepository, RedisRepositoryImpl>>,
+    pub unlock_user: Arc<UnlockUser<PgUserRepository, PgRoleRepository, RedisRepositoryImpl>>,
+}
+
+impl UserUsecase {
+    pub fn new(
+        user_repo: Arc<PgUserRepository>,
+        role_repo: Arc<PgRoleRepository>,
+        oauth_provider_repo: Arc<PgOauthProviderRepository>,
+        user_session_repo: Arc<PgUserSessionRepository>,
+        rbac: Arc<Rbac>,
+        redis_svc: Arc<RedisService<RedisRepositoryImpl>>,
+        login_guard_svc: Arc<LoginGuardService<RedisRepositoryImpl>>,
+    ) -> Self {
+        let get_paginated_user = Arc::new(GetPaginatedUser::new(user_repo.clone()));
+        let get_user_by_id = Arc::new(GetUserById::new(
+            user_repo.clone(),
+            role_repo.clone(),
+            oauth_provider_repo.clone(),
+        ));
+        let update_user_by_id = Arc::new(UpdateUserById::new(
+            user_repo.clone(),
+            role_repo.clone(),
+            rbac.clone(),
+            redis_svc.clone(),
+        ));
+        let deactivate_user = Arc::new(DeactivateUser::new(
+            user_repo.clone(),
+            role_repo.clone(),
+            user_session_repo.clone(),
+            rbac.clone(),
+            redis_svc.clone(),
+        ));
+        let reactivate_user = Arc::new(ReactivateUser::new(
+            user_repo.clone(),
+            role_repo.clone(),
+            rbac.clone(),
+            redis_svc.clone(),
+        ));
+        let delete_user_by_id = Arc::new(DeleteUserById::new(
+            user_repo.clone(),
+            role_repo.clone(),
+            user_session_repo.clone(),
+            rbac.clone(),
+            redis_svc.clone(),
+        ));
+        let get_user_roles = Arc::new(GetUserRoles::new(user_repo.clone(), role_repo.clone()));
+        let assign_user_role = Arc::new(AssignUserRole::new(
+            user_repo.clone(),
+            role_repo.clone(),
+            rbac.clone(),
+            redis_svc.clone(),
+        ));
+        let revoke_user_role = Arc::new(RevokeUserRole::new(
+            user_repo.clone(),
+            role_repo.clone(),
+            rbac.clone(),
+            redis_svc.clone(),
+        ));
+        let unlock_user = Arc::new(UnlockUser::new(
+            user_repo.clone(),
+            role_repo.clone(),
+            rbac.clone(),
+            login_guard_svc.clone(),
+        ));
+
+        Self {
+            get_paginated_user,
+            get_user_by_id,
+            update_user_by_id,
+            deactivate_user,
+            reactivate_user,
+            delete_user_by_id,
+            get_user_roles,
+            assign_user_role,
+            revoke_user_role,
+            unlock_user,
+        }
+    }
+}
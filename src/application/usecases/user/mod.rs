@@ -0,0 +1,11 @@
+pub mod assign_user_role;
+pub mod deactivate_user;
+pub mod delete_user_by_id;
+pub mod get_paginated_user;
+pub mod get_user_by_id;
+pub mod get_user_roles;
+pub mod init;
+pub mod reactivate_user;
+pub mod revoke_user_role;
+pub mod unlock_user;
+pub mod update_user_by_id;
@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use tracing::info;
+
+use crate::{
+    application::services::redis_svc::RedisService,
+    domain::{
+        entities::user::UserFull,
+        repositories::{
+            redis_repo::RedisRepository, role_repo::RoleRepository, user_repo::UserRepository,
+        },
+    },
+    infra::{errors::app_error::AppError, rbac::Rbac},
+};
+
+#[derive(Clone)]
+pub struct RevokeUserRole<U, R, C> {
+    user_repo: Arc<U>,
+    role_repo: Arc<R>,
+    rbac: Arc<Rbac>,
+    redis_svc: Arc<RedisService<C>>,
+}
+
+impl<U, R, C> RevokeUserRole<U, R, C>
+where
+    U: UserRepository,
+    R: RoleRepository,
+    C: RedisRepository,
+{
+    pub fn new(
+        user_repo: Arc<U>,
+        role_repo: Arc<R>,
+        rbac: Arc<Rbac>,
+        redis_svc: Arc<RedisService<C>>,
+    ) -> Self {
+        Self {
+            user_repo,
+            role_repo,
+            rbac,
+            redis_svc,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        actor: &UserFull,
+        user_id: &str,
+        role_id: &str,
+    ) -> Result<(), AppError> {
+        if actor.user.id == user_id {
+            return Err(AppError::ProcessError(
+                "Cannot change your own roles".to_string(),
+            ));
+        }
+
+        let user = self.user_repo.find_by_id(user_id).await?;
+
+        let current_roles = self.role_repo.get_roles_by_user_id(&user.id).await?;
+        let Some(role) = current_roles.iter().find(|current| current.id == role_id) else {
+            return Err(AppError::ResourceNotFound);
+        };
+
+        if !self.rbac.can_manage_role(actor, role).await {
+            return Err(AppError::Forbidden);
+        }
+
+        info!("Revoking Role {} from User {}...", role_id, user.id);
+        self.role_repo.revoke_from_user(&user.id, role_id).await?;
+
+        self.rbac.revoke_role(&user.id, role_id).await?;
+
+        // make sure the revoked role is not served from cache anymore
+        self.redis_svc.remove_current_user(&user.id).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use tracing::info;
+
+use crate::{
+    application::services::login_guard_svc::LoginGuardService,
+    domain::{
+        entities::user::UserFull,
+        repositories::{
+            redis_repo::RedisRepository, role_repo::RoleRepository, user_repo::UserRepository,
+        },
+    },
+    infra::{errors::app_error::AppError, rbac::Rbac},
+};
+
+#[derive(Clone)]
+pub struct UnlockUser<U, R, C> {
+    user_repo: Arc<U>,
+    role_repo: Arc<R>,
+    rbac: Arc<Rbac>,
+    login_guard_svc: Arc<LoginGuardService<C>>,
+}
+
+impl<U, R, C> UnlockUser<U, R, C>
+where
+    U: UserRepository,
+    R: RoleRepository,
+    C: RedisRepository,
+{
+    pub fn new(
+        user_repo: Arc<U>,
+        role_repo: Arc<R>,
+        rbac: Arc<Rbac>,
+        login_guard_svc: Arc<LoginGuardService<C>>,
+    ) -> Self {
+        Self {
+            user_repo,
+            role_repo,
+            rbac,
+            login_guard_svc,
+        }
+    }
+
+    // lifts the lockout & delay after failed logins, the failures start counting from zero
+    pub async fn execute(&self, actor: &UserFull, id: &str) -> Result<(), AppError> {
+        let user = self.user_repo.find_by_id(id).await?;
+
+        if user.deleted_at.is_some() {
+            return Err(AppError::ResourceNotFound);
+        }
+
+        let roles = self.role_repo.get_roles_by_user_id(&user.id).await?;
+        if !self.rbac.can_manage_user(actor, &roles).await {
+            return Err(AppError::Forbidden);
+        }
+
+        info!("Unlocking email login of User with id {}...", id);
+        self.login_guard_svc.clear(&user.email).await?;
+
+        Ok(())
+    }
+}
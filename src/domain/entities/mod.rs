@@ -1,6 +1,10 @@
 pub mod permission;
+pub mod refresh_token;
 pub mod role;
 pub mod user;
+pub mod user_mfa;
 pub mod user_oauth_provider;
 pub mod user_role;
 pub mod user_session;
+pub mod user_token;
+pub mod webauthn_credential;
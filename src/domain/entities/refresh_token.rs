@@ -0,0 +1,31 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+// a refresh token issued for a session, the session is the token family
+#[derive(Clone, Debug, Serialize)]
+pub struct RefreshToken {
+    pub id: String,
+    pub session_id: String,
+    pub token_hash: String,
+    pub rotated_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    // false for digests stored before hashes were keyed with the app key
+    pub keyed: bool,
+}
+
+impl RefreshToken {
+    pub fn new(session_id: String, token_hash: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            session_id,
+            token_hash,
+            rotated_at: None,
+            created_at: chrono::Utc::now(),
+            keyed: true,
+        }
+    }
+
+    pub fn is_rotated(&self) -> bool {
+        self.rotated_at.is_some()
+    }
+}
@@ -5,6 +5,8 @@ pub struct Role {
     pub id: String,
     pub name: String,
     pub is_default: bool,
+    // users with this role have to login with a second factor
+    pub mfa_required: bool,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
     pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
@@ -16,15 +18,22 @@ impl Role {
             id,
             name,
             is_default,
+            mfa_required: false,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
             deleted_at: None,
         }
     }
 
-    pub fn update(&mut self, name: &str, is_default: bool) {
+    pub fn update(&mut self, name: &str, is_default: bool, mfa_required: bool) {
         self.name = name.to_owned();
         self.is_default = is_default;
+        self.mfa_required = mfa_required;
+        self.updated_at = chrono::Utc::now();
+    }
+
+    pub fn require_mfa(&mut self) {
+        self.mfa_required = true;
         self.updated_at = chrono::Utc::now();
     }
 }
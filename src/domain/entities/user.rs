@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use super::{role::Role, user_oauth_provider::UserOauthProvider};
+use crate::infra::common::constants::SUPER_ADMIN_ROLE;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct User {
@@ -15,6 +16,7 @@ pub struct User {
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
     pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub email_verified_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl User {
@@ -36,17 +38,56 @@ impl User {
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
             deleted_at: None,
+            email_verified_at: None,
         }
     }
 
+    // deactivated & soft deleted users are not allowed to login or use their sessions
+    pub fn is_accessible(&self) -> bool {
+        self.is_active && self.deleted_at.is_none()
+    }
+
+    // a new address has to be verified again
     pub fn change_email(&mut self, email: String) {
+        if self.email != email {
+            self.email_verified_at = None;
+        }
         self.email = email;
         self.updated_at = chrono::Utc::now();
     }
 
+    pub fn is_email_verified(&self) -> bool {
+        self.email_verified_at.is_some()
+    }
+
+    pub fn verify_email(&mut self) {
+        self.email_verified_at = Some(chrono::Utc::now());
+        self.updated_at = chrono::Utc::now();
+    }
+
+    pub fn change_password(&mut self, password_hash: String) {
+        self.password_hash = Some(password_hash);
+        self.updated_at = chrono::Utc::now();
+    }
+
+    // fields left out keep their current value
     pub fn update(&mut self, fullname: Option<String>, avatar_url: Option<String>) {
-        self.fullname = fullname;
-        self.avatar_url = avatar_url;
+        if fullname.is_some() {
+            self.fullname = fullname;
+        }
+        if avatar_url.is_some() {
+            self.avatar_url = avatar_url;
+        }
+        self.updated_at = chrono::Utc::now();
+    }
+
+    pub fn deactivate(&mut self) {
+        self.is_active = false;
+        self.updated_at = chrono::Utc::now();
+    }
+
+    pub fn reactivate(&mut self) {
+        self.is_active = true;
         self.updated_at = chrono::Utc::now();
     }
 
@@ -56,6 +97,11 @@ impl User {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserCount {
+    pub total_items: Option<i64>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct UserFull {
     #[serde(flatten)]
@@ -73,4 +119,8 @@ impl UserFull {
             roles,
         }
     }
+
+    pub fn is_super_admin(&self) -> bool {
+        self.roles.iter().any(|role| role.name == SUPER_ADMIN_ROLE)
+    }
 }
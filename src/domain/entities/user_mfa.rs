@@ -0,0 +1,55 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+// totp second factor of a user, the secret is stored encrypted
+#[derive(Clone, Debug, Serialize)]
+pub struct UserTotp {
+    pub user_id: String,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub confirmed_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(skip_serializing)]
+    pub last_used_step: Option<i64>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl UserTotp {
+    pub fn new(user_id: String, secret: String) -> Self {
+        Self {
+            user_id,
+            secret,
+            confirmed_at: None,
+            last_used_step: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    // an enrolment only counts once the user proved it with a valid code
+    pub fn is_confirmed(&self) -> bool {
+        self.confirmed_at.is_some()
+    }
+}
+
+// single use fallback for a lost authenticator, only the hash is stored
+#[derive(Clone, Debug, Serialize)]
+pub struct UserRecoveryCode {
+    pub id: String,
+    pub user_id: String,
+    pub code_hash: String,
+    pub used_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl UserRecoveryCode {
+    pub fn new(user_id: String, code_hash: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            user_id,
+            code_hash,
+            used_at: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+}
@@ -1,41 +1,117 @@
 use serde::Serialize;
-use uuid::Uuid;
+
+use crate::infra::{
+    errors::app_error::AppError,
+    oauth2::constants::{EMAIL_PROVIDER, MAGIC_LINK_PROVIDER, WEBAUTHN_PROVIDER},
+    utils::{client_info::ClientInfo, token_cipher::TokenCipher},
+};
 
 #[derive(Clone, Debug, Serialize)]
 pub struct UserSession {
     pub id: String,
     pub user_id: String,
+    pub provider: String,
+    #[serde(skip_serializing)]
     pub access_token: String,
-    pub refresh_token: String,
+    // only provider refresh tokens are stored, our own tokens live hashed in `refresh_tokens`
+    #[serde(skip_serializing)]
+    pub refresh_token: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
     pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_used_at: chrono::DateTime<chrono::Utc>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    // encryption key version of the stored tokens, none while they are still plaintext
+    #[serde(skip_serializing)]
+    pub token_key_version: Option<i32>,
+    // hash of the provider id token the login handed out, those tokens can't carry our session id
+    #[serde(skip_serializing)]
+    pub id_token_hash: Option<String>,
 }
 
 impl UserSession {
     pub fn new(
+        id: String,
         user_id: String,
+        provider: String,
         access_token: String,
-        refresh_token: String,
+        refresh_token: Option<String>,
         expires_at: Option<chrono::DateTime<chrono::Utc>>,
+        client: &ClientInfo,
     ) -> Self {
         Self {
-            id: Uuid::new_v4().to_string(),
+            id,
             user_id,
+            provider,
             access_token,
             refresh_token,
+            user_agent: client.user_agent.clone(),
+            ip_address: client.ip_address.clone(),
             expires_at,
+            last_used_at: chrono::Utc::now(),
             created_at: chrono::Utc::now(),
+            token_key_version: None,
+            id_token_hash: None,
         }
     }
 
+    // email, passkey & magic link logins get our own tokens, there's no provider grant behind the session
+    pub fn holds_own_tokens(&self) -> bool {
+        [EMAIL_PROVIDER, WEBAUTHN_PROVIDER, MAGIC_LINK_PROVIDER].contains(&self.provider.as_str())
+    }
+
+    // the id token the client authenticates with from now on, earlier ones stop working
+    pub fn bind_id_token(&mut self, cipher: &TokenCipher, id_token: &str) {
+        self.id_token_hash = Some(cipher.hash(id_token));
+    }
+
     pub fn update(
         &mut self,
         access_token: String,
-        refresh_token: String,
+        refresh_token: Option<String>,
         expires_at: Option<chrono::DateTime<chrono::Utc>>,
     ) {
         self.access_token = access_token;
         self.refresh_token = refresh_token;
         self.expires_at = expires_at;
+        self.last_used_at = chrono::Utc::now();
+    }
+
+    // protect plaintext tokens before they are stored, our own access token is only
+    // needed for auditing so it is hashed, provider tokens are encrypted for revocation
+    pub fn seal(&mut self, cipher: &TokenCipher) -> Result<(), AppError> {
+        if self.holds_own_tokens() {
+            self.access_token = cipher.hash(&self.access_token);
+            self.refresh_token = None;
+        } else {
+            self.access_token = cipher.encrypt(&self.access_token)?;
+            self.refresh_token = self
+                .refresh_token
+                .as_deref()
+                .map(|token| cipher.encrypt(token))
+                .transpose()?;
+        }
+        self.token_key_version = Some(cipher.current_version());
+
+        Ok(())
+    }
+
+    // plaintext provider tokens, only meant for sealed sessions of external providers
+    pub fn unseal(&mut self, cipher: &TokenCipher) -> Result<(), AppError> {
+        self.access_token = cipher.decrypt(&self.access_token)?;
+        self.refresh_token = self
+            .refresh_token
+            .as_deref()
+            .map(|token| cipher.decrypt(token))
+            .transpose()?;
+        self.token_key_version = None;
+
+        Ok(())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .map(|expires_at| expires_at < chrono::Utc::now())
+            .unwrap_or(false)
     }
 }
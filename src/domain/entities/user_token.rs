@@ -0,0 +1,40 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+pub const EMAIL_VERIFICATION_PURPOSE: &str = "email_verification";
+pub const PASSWORD_RESET_PURPOSE: &str = "password_reset";
+
+// single use token mailed to a user, the purpose keeps tokens of different flows apart
+#[derive(Clone, Debug, Serialize)]
+pub struct UserToken {
+    pub id: String,
+    pub user_id: String,
+    pub purpose: String,
+    pub token_hash: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub consumed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl UserToken {
+    pub fn new(
+        user_id: String,
+        purpose: String,
+        token_hash: String,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            user_id,
+            purpose,
+            token_hash,
+            expires_at,
+            consumed_at: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    pub fn is_usable(&self) -> bool {
+        self.consumed_at.is_none() && self.expires_at > chrono::Utc::now()
+    }
+}
@@ -0,0 +1,41 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+// passkey of a user, each one logs in through its own `webauthn` provider link
+#[derive(Clone, Debug, Serialize)]
+pub struct WebauthnCredential {
+    pub id: String,
+    pub user_id: String,
+    pub oauth_provider_id: String,
+    pub credential_id: String,
+    #[serde(skip_serializing)]
+    pub public_key: Vec<u8>,
+    #[serde(skip_serializing)]
+    pub sign_count: i64,
+    pub name: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl WebauthnCredential {
+    pub fn new(
+        user_id: String,
+        oauth_provider_id: String,
+        credential_id: String,
+        public_key: Vec<u8>,
+        sign_count: i64,
+        name: Option<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            user_id,
+            oauth_provider_id,
+            credential_id,
+            public_key,
+            sign_count,
+            name,
+            created_at: chrono::Utc::now(),
+            last_used_at: None,
+        }
+    }
+}
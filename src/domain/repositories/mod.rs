@@ -2,5 +2,8 @@ pub mod oauth_provider_repo;
 pub mod permission_repo;
 pub mod redis_repo;
 pub mod role_repo;
+pub mod user_mfa_repo;
 pub mod user_repo;
 pub mod user_session_repo;
+pub mod user_token_repo;
+pub mod webauthn_credential_repo;
@@ -9,4 +9,7 @@ pub trait OauthProviderRepository {
         provider: &str,
         provider_id: &str,
     ) -> Result<UserOauthProvider, AppError>;
+    async fn find_by_user_id(&self, user_id: &str) -> Result<Vec<UserOauthProvider>, AppError>;
+    async fn create(&self, entity: &UserOauthProvider) -> Result<UserOauthProvider, AppError>;
+    async fn delete_by_id(&self, id: &str) -> Result<(), AppError>;
 }
@@ -11,5 +11,9 @@ pub trait RedisRepository {
         expiry: u64,
     ) -> Result<(), AppError>;
     async fn delete_value(&self, key: &str) -> Result<(), AppError>;
+    // read & remove in one step, so a value can only be consumed once
+    async fn take_value(&self, key: &str) -> Result<Option<String>, AppError>;
     async fn set_expiry(&self, key: &str, expiry: i64) -> Result<(), AppError>;
+    // counts up & returns the new value, the expiry is set when the key is created
+    async fn increment(&self, key: &str, expiry: u64) -> Result<i64, AppError>;
 }
@@ -1,4 +1,7 @@
-use crate::{domain::entities::role::Role, infra::errors::app_error::AppError};
+use crate::{
+    domain::entities::{role::Role, user_role::UserRole},
+    infra::errors::app_error::AppError,
+};
 
 #[async_trait::async_trait]
 pub trait RoleRepository {
@@ -17,4 +20,7 @@ pub trait RoleRepository {
     async fn delete(&self, id: &str) -> Result<(), AppError>;
 
     async fn get_roles_by_user_id(&self, user_id: &str) -> Result<Vec<Role>, AppError>;
+    async fn find_all_user_roles(&self) -> Result<Vec<UserRole>, AppError>;
+    async fn assign_to_user(&self, entity: UserRole) -> Result<UserRole, AppError>;
+    async fn revoke_from_user(&self, user_id: &str, role_id: &str) -> Result<(), AppError>;
 }
@@ -0,0 +1,24 @@
+use crate::{
+    domain::entities::user_mfa::{UserRecoveryCode, UserTotp},
+    infra::errors::app_error::AppError,
+};
+
+#[async_trait::async_trait]
+pub trait UserMfaRepository {
+    async fn find_totp(&self, user_id: &str) -> Result<UserTotp, AppError>;
+    // a new enrolment replaces the previous secret
+    async fn save_totp(&self, entity: &UserTotp) -> Result<UserTotp, AppError>;
+    // stores the step of an accepted code, false when that step or a later one was used already
+    async fn use_totp_step(&self, user_id: &str, step: i64) -> Result<bool, AppError>;
+    async fn confirm_totp(&self, user_id: &str, step: i64) -> Result<(), AppError>;
+    async fn delete_totp(&self, user_id: &str) -> Result<(), AppError>;
+
+    async fn replace_recovery_codes(
+        &self,
+        user_id: &str,
+        codes: &[UserRecoveryCode],
+    ) -> Result<(), AppError>;
+    // false when the code doesn't exist or was used already
+    async fn use_recovery_code(&self, user_id: &str, code_hash: &str) -> Result<bool, AppError>;
+    async fn count_unused_recovery_codes(&self, user_id: &str) -> Result<i64, AppError>;
+}
@@ -5,6 +5,12 @@ use crate::{
 
 #[async_trait::async_trait]
 pub trait UserRepository {
+    async fn paginate(
+        &self,
+        page: i64,
+        limit: i64,
+        search: Option<String>,
+    ) -> Result<(Vec<User>, i64), AppError>;
     async fn find_by_email(&self, email: &str) -> Result<User, AppError>;
     async fn find_by_id(&self, id: &str) -> Result<User, AppError>;
     async fn tx_create(
@@ -19,4 +25,7 @@ pub trait UserRepository {
         user_oauth_provider: &UserOauthProvider,
         user_role: &UserRole,
     ) -> Result<(User, UserOauthProvider, UserRole), AppError>;
+    async fn update(&self, id: &str, entity: User) -> Result<(), AppError>;
+    // separate from `update`, cached users don't carry the password hash
+    async fn update_password(&self, id: &str, entity: &User) -> Result<(), AppError>;
 }
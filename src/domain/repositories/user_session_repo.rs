@@ -1,10 +1,27 @@
-use crate::{domain::entities::user_session::UserSession, infra::errors::app_error::AppError};
+use crate::{
+    domain::entities::{refresh_token::RefreshToken, user_session::UserSession},
+    infra::errors::app_error::AppError,
+};
 
 #[async_trait::async_trait]
 pub trait UserSessionRepository {
-    async fn find_by_user_id(&self, user_id: &str) -> Result<UserSession, AppError>;
-    async fn find_by_refresh_token(&self, refresh_token: &str) -> Result<UserSession, AppError>;
+    async fn find_by_id(&self, session_id: &str) -> Result<UserSession, AppError>;
+    async fn find_all_by_user_id(&self, user_id: &str) -> Result<Vec<UserSession>, AppError>;
+    async fn find_by_refresh_token_hash(&self, token_hash: &str) -> Result<UserSession, AppError>;
+    async fn find_by_id_token_hash(&self, id_token_hash: &str) -> Result<UserSession, AppError>;
     async fn create(&self, entity: UserSession) -> Result<UserSession, AppError>;
     async fn update_token(&self, session: &UserSession) -> Result<(), AppError>;
+    async fn touch(&self, session_id: &str) -> Result<(), AppError>;
     async fn delete_by_id(&self, session_id: &str) -> Result<(), AppError>;
+    async fn delete_by_user_id(&self, user_id: &str) -> Result<(), AppError>;
+
+    async fn find_refresh_token(&self, token_hash: &str) -> Result<RefreshToken, AppError>;
+    async fn create_refresh_token(&self, entity: RefreshToken) -> Result<RefreshToken, AppError>;
+    // returns false when the token was already rotated by a concurrent request
+    async fn rotate_refresh_token(&self, id: &str) -> Result<bool, AppError>;
+
+    // rows which still need to be protected with the current keys
+    async fn find_all_unsealed(&self, key_version: i32) -> Result<Vec<UserSession>, AppError>;
+    async fn find_all_unkeyed_refresh_tokens(&self) -> Result<Vec<RefreshToken>, AppError>;
+    async fn update_refresh_token_hash(&self, id: &str, token_hash: &str) -> Result<(), AppError>;
 }
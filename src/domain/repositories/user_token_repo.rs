@@ -0,0 +1,10 @@
+use crate::{domain::entities::user_token::UserToken, infra::errors::app_error::AppError};
+
+#[async_trait::async_trait]
+pub trait UserTokenRepository {
+    async fn create(&self, entity: &UserToken) -> Result<UserToken, AppError>;
+    async fn find_by_hash(&self, purpose: &str, token_hash: &str) -> Result<UserToken, AppError>;
+    // marks the token as used, false when another request consumed it first
+    async fn consume(&self, id: &str) -> Result<bool, AppError>;
+    async fn delete_unconsumed(&self, user_id: &str, purpose: &str) -> Result<(), AppError>;
+}
@@ -0,0 +1,28 @@
+use crate::{
+    domain::entities::{
+        user_oauth_provider::UserOauthProvider, webauthn_credential::WebauthnCredential,
+    },
+    infra::errors::app_error::AppError,
+};
+
+#[async_trait::async_trait]
+pub trait WebauthnCredentialRepository {
+    async fn find_by_credential_id(
+        &self,
+        credential_id: &str,
+    ) -> Result<WebauthnCredential, AppError>;
+    async fn find_by_user_id(&self, user_id: &str) -> Result<Vec<WebauthnCredential>, AppError>;
+    // the provider link & the credential are created together
+    async fn create_with_link(
+        &self,
+        oauth_provider: &UserOauthProvider,
+        entity: &WebauthnCredential,
+    ) -> Result<WebauthnCredential, AppError>;
+    // false when a parallel login moved the sign count first
+    async fn update_sign_count(
+        &self,
+        id: &str,
+        previous_sign_count: i64,
+        sign_count: i64,
+    ) -> Result<bool, AppError>;
+}
@@ -1 +1,17 @@
 pub const SUPER_ADMIN_ROLE: &str = "IMMORTAL_USER";
+pub const SESSION_LIFETIME_SECONDS: i64 = 60 * 60 * 24 * 7;
+pub const EMAIL_VERIFICATION_LIFETIME_SECONDS: i64 = 60 * 60 * 24;
+pub const PASSWORD_RESET_LIFETIME_SECONDS: i64 = 60 * 60;
+pub const OAUTH_LOGIN_STATE_LIFETIME_SECONDS: u64 = 60 * 10;
+pub const MFA_CHALLENGE_LIFETIME_SECONDS: i64 = 60 * 5;
+pub const MFA_CHALLENGE_MAX_ATTEMPTS: u32 = 5;
+pub const WEBAUTHN_CHALLENGE_LIFETIME_SECONDS: u64 = 60 * 5;
+pub const MAGIC_LINK_LIFETIME_SECONDS: u64 = 60 * 15;
+pub const MAGIC_LINK_RATE_LIMIT_WINDOW_SECONDS: u64 = 60 * 15;
+pub const MAGIC_LINK_MAX_PER_EMAIL: i64 = 3;
+pub const MAGIC_LINK_MAX_PER_IP: i64 = 10;
+pub const LOGIN_FAILURE_WINDOW_SECONDS: u64 = 60 * 15;
+pub const LOGIN_DELAY_AFTER_FAILURES: i64 = 3;
+pub const LOGIN_LOCKOUT_AFTER_FAILURES: i64 = 10;
+pub const LOGIN_LOCKOUT_SECONDS: u64 = 60 * 15;
+pub const LOGIN_MAX_FAILURES_PER_IP: i64 = 50;
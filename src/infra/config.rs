@@ -17,21 +17,248 @@ pub struct AppConfig {
     #[envconfig(from = "REDIS_URL")]
     pub redis_url: String,
 
-    #[envconfig(from = "JWT_SECRET")]
-    pub jwt_secret: String,
+    #[envconfig(from = "JWT_ISSUER")]
+    pub jwt_issuer: String,
+
+    #[envconfig(from = "JWT_AUDIENCE")]
+    pub jwt_audience: String,
+
+    #[envconfig(from = "JWT_SIGNING_KEY")]
+    pub jwt_signing_key: String,
+
+    #[envconfig(from = "JWT_VERIFICATION_KEYS")]
+    pub jwt_verification_keys: String,
 
     #[envconfig(from = "ALLOWED_ORIGINS")]
     pub allowed_origins: String,
 
+    // where oauth logins may redirect after the callback, seperate by comma
+    #[envconfig(from = "RETURN_TO_ORIGINS")]
+    pub return_to_origins: Option<String>,
+
+    // failed browser logins are redirected here with `?error=<error_code>`
+    #[envconfig(from = "LOGIN_ERROR_URL")]
+    pub login_error_url: Option<String>,
+
+    // google login is only enabled when all of these are set
     #[envconfig(from = "GOOGLE_CLIENT_ID")]
-    pub google_client_id: String,
+    pub google_client_id: Option<String>,
 
     #[envconfig(from = "GOOGLE_CLIENT_SECRET")]
-    pub google_client_secret: String,
+    pub google_client_secret: Option<String>,
 
     #[envconfig(from = "GOOGLE_REDIRECT_URI")]
-    pub google_redirect_url: String,
+    pub google_redirect_url: Option<String>,
+
+    // discord login is only enabled when all of these are set
+    #[envconfig(from = "DISCORD_CLIENT_ID")]
+    pub discord_client_id: Option<String>,
+
+    #[envconfig(from = "DISCORD_CLIENT_SECRET")]
+    pub discord_client_secret: Option<String>,
+
+    #[envconfig(from = "DISCORD_REDIRECT_URI")]
+    pub discord_redirect_url: Option<String>,
+
+    // point these to a local stand-in server when testing
+    #[envconfig(
+        from = "DISCORD_AUTHORIZE_URL",
+        default = "https://discord.com/oauth2/authorize"
+    )]
+    pub discord_authorize_url: String,
+
+    #[envconfig(from = "DISCORD_API_URL", default = "https://discord.com/api")]
+    pub discord_api_url: String,
+
+    // github login is only enabled when all of these are set
+    #[envconfig(from = "GITHUB_CLIENT_ID")]
+    pub github_client_id: Option<String>,
+
+    #[envconfig(from = "GITHUB_CLIENT_SECRET")]
+    pub github_client_secret: Option<String>,
+
+    #[envconfig(from = "GITHUB_REDIRECT_URI")]
+    pub github_redirect_url: Option<String>,
+
+    // point these to a local mock when testing
+    #[envconfig(from = "GITHUB_OAUTH_URL", default = "https://github.com/login/oauth")]
+    pub github_oauth_url: String,
+
+    #[envconfig(from = "GITHUB_API_URL", default = "https://api.github.com")]
+    pub github_api_url: String,
+
+    // log provider users into an existing account with the same email, only when the provider
+    // verified that email, otherwise the user has to login first and link the provider
+    #[envconfig(from = "OAUTH_AUTO_LINK_VERIFIED_EMAIL", default = "false")]
+    pub oauth_auto_link_verified_email: bool,
+
+    // names of the generic openid connect providers, each one is configured with
+    // OIDC_<NAME>_ISSUER, OIDC_<NAME>_CLIENT_ID, OIDC_<NAME>_CLIENT_SECRET & OIDC_<NAME>_REDIRECT_URI
+    #[envconfig(from = "OIDC_PROVIDERS")]
+    pub oidc_providers: Option<String>,
+
+    // `smtp` or `log`, the log transport only prints mails & optionally writes them to MAIL_LOG_DIR
+    #[envconfig(from = "MAIL_TRANSPORT", default = "log")]
+    pub mail_transport: String,
+
+    #[envconfig(from = "MAIL_FROM", default = "no-reply@localhost")]
+    pub mail_from: String,
+
+    #[envconfig(from = "MAIL_LOG_DIR")]
+    pub mail_log_dir: Option<String>,
+
+    #[envconfig(from = "SMTP_HOST")]
+    pub smtp_host: Option<String>,
+
+    #[envconfig(from = "SMTP_PORT", default = "587")]
+    pub smtp_port: u16,
+
+    #[envconfig(from = "SMTP_USERNAME")]
+    pub smtp_username: Option<String>,
+
+    #[envconfig(from = "SMTP_PASSWORD")]
+    pub smtp_password: Option<String>,
+
+    // link mailed after email registration, the token is appended as `?token=`
+    #[envconfig(
+        from = "EMAIL_VERIFICATION_URL",
+        default = "http://localhost:8800/oauth/email/verify"
+    )]
+    pub email_verification_url: String,
+
+    // page of the frontend that asks for the new password & posts it with the `?token=`
+    #[envconfig(
+        from = "PASSWORD_RESET_URL",
+        default = "http://localhost:3000/reset-password"
+    )]
+    pub password_reset_url: String,
+
+    // page of the frontend that posts the `?token=` of the mailed login link
+    #[envconfig(from = "MAGIC_LINK_URL", default = "http://localhost:3000/magic-link")]
+    pub magic_link_url: String,
+
+    // email logins are refused until the address is verified
+    #[envconfig(from = "EMAIL_VERIFICATION_REQUIRED", default = "false")]
+    pub email_verification_required: bool,
+
+    // accounts created before this instant (RFC 3339) can still login unverified
+    #[envconfig(from = "EMAIL_VERIFICATION_REQUIRED_SINCE")]
+    pub email_verification_required_since: Option<chrono::DateTime<chrono::Utc>>,
+
+    // passkeys are bound to this domain, the origins are where the browser runs the ceremony,
+    // seperate by comma
+    #[envconfig(from = "WEBAUTHN_RP_ID", default = "localhost")]
+    pub webauthn_rp_id: String,
+
+    // shown by the authenticator, falls back to APP_NAME
+    #[envconfig(from = "WEBAUTHN_RP_NAME")]
+    pub webauthn_rp_name: Option<String>,
+
+    #[envconfig(from = "WEBAUTHN_ORIGINS", default = "http://localhost:8800")]
+    pub webauthn_origins: String,
+
+    // ips or cidrs of the reverse proxies in front of the api, seperate by comma. forwarded
+    // headers are ignored unless the request comes from one of them
+    #[envconfig(from = "TRUSTED_PROXIES")]
+    pub trusted_proxies: Option<String>,
+
+    // `<requests>/<seconds>`, the oauth routes are limited per ip, the api per user
+    #[envconfig(from = "RATE_LIMIT_OAUTH", default = "30/60")]
+    pub rate_limit_oauth: String,
+
+    #[envconfig(from = "RATE_LIMIT_API", default = "300/60")]
+    pub rate_limit_api: String,
+
+    #[envconfig(from = "TOKEN_HASH_KEY")]
+    pub token_hash_key: String,
+
+    #[envconfig(from = "TOKEN_ENCRYPTION_KEYS")]
+    pub token_encryption_keys: String,
+
+    #[envconfig(from = "TOKEN_ENCRYPTION_KEY_VERSION")]
+    pub token_encryption_key_version: i32,
 
     #[envconfig(from = "SUPER_KEY")]
     pub super_key: String,
 }
+
+#[derive(Debug, Clone)]
+pub struct OidcProviderConfig {
+    pub name: String,
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_url: String,
+    pub scopes: String,
+}
+
+// names end up in `user_oauth_providers.provider` & urls, so they can't take over a built-in one
+const RESERVED_PROVIDER_NAMES: [&str; 6] = [
+    "email",
+    "google",
+    "discord",
+    "github",
+    "webauthn",
+    "magic_link",
+];
+
+impl AppConfig {
+    pub fn oidc_provider_configs(&self) -> Vec<OidcProviderConfig> {
+        let names = self.oidc_providers.clone().unwrap_or_default();
+        let mut configs: Vec<OidcProviderConfig> = vec![];
+
+        for name in names
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            let valid_name = name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+            if !valid_name || name.len() > 50 {
+                panic!(
+                    "OIDC_PROVIDERS name {} must be lowercase letters, digits, - or _",
+                    name
+                );
+            }
+            if RESERVED_PROVIDER_NAMES.contains(&name)
+                || configs.iter().any(|config| config.name == name)
+            {
+                panic!("OIDC_PROVIDERS name {} is already in use", name);
+            }
+
+            // `a-b` & `a_b` would read each other's settings
+            let prefix = oidc_env_prefix(name);
+            if let Some(config) = configs
+                .iter()
+                .find(|config| oidc_env_prefix(&config.name) == prefix)
+            {
+                panic!(
+                    "OIDC_PROVIDERS names {} and {} both read {}_* settings",
+                    config.name, name, prefix
+                );
+            }
+
+            let var = |key: &str| {
+                std::env::var(format!("{}_{}", prefix, key))
+                    .unwrap_or_else(|_| panic!("{}_{} must be set", prefix, key))
+            };
+
+            configs.push(OidcProviderConfig {
+                name: name.to_string(),
+                issuer: var("ISSUER"),
+                client_id: var("CLIENT_ID"),
+                client_secret: var("CLIENT_SECRET"),
+                redirect_url: var("REDIRECT_URI"),
+                scopes: std::env::var(format!("{}_SCOPES", prefix))
+                    .unwrap_or_else(|_| "openid email profile".to_string()),
+            });
+        }
+
+        configs
+    }
+}
+
+fn oidc_env_prefix(name: &str) -> String {
+    format!("OIDC_{}", name.to_uppercase().replace('-', "_"))
+}
@@ -87,11 +87,37 @@ pub enum AppError {
 
     #[error("you are not allowed to perform this action")]
     Forbidden,
+
+    #[error("User is deactivated")]
+    UserInactive,
+
+    #[error("Return url {0} is not allowed")]
+    InvalidReturnTo(String),
+
+    #[error("Email {0} is not verified")]
+    EmailNotVerified(String),
+
+    #[error("Invalid mfa code")]
+    InvalidMfaCode,
+
+    #[error("Invalid passkey: {0}")]
+    InvalidPasskey(String),
+
+    #[error("Too many requests")]
+    TooManyRequests,
+
+    #[error("Account with email {0} already exists, link the provider from that account")]
+    AccountLinkRequired(String),
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> axum::response::Response {
-        let (status, error_code, message) = match &self {
+impl AppError {
+    // stable code for clients, also used for the login error page
+    pub fn error_code(&self) -> String {
+        self.parts().1
+    }
+
+    fn parts(&self) -> (StatusCode, String, String) {
+        match self {
             AppError::ProcessError(value) => (
                 StatusCode::UNPROCESSABLE_ENTITY,
                 "process_error".to_string(),
@@ -180,12 +206,54 @@ impl IntoResponse for AppError {
                 "forbidden".to_string(),
                 "you are not allowed to perform this action".to_string(),
             ),
+            AppError::UserInactive => (
+                StatusCode::FORBIDDEN,
+                "user_inactive".to_string(),
+                "Your account is deactivated, please contact Administrator".to_string(),
+            ),
+            AppError::InvalidReturnTo(value) => (
+                StatusCode::BAD_REQUEST,
+                "invalid_return_to".to_string(),
+                format!("Return url {} is not allowed", value),
+            ),
+            AppError::EmailNotVerified(_) => (
+                StatusCode::FORBIDDEN,
+                "email_not_verified".to_string(),
+                "Please verify your email address before logging in".to_string(),
+            ),
+            AppError::InvalidMfaCode => (
+                StatusCode::UNAUTHORIZED,
+                "invalid_mfa_code".to_string(),
+                "The code is invalid or was used already".to_string(),
+            ),
+            AppError::InvalidPasskey(value) => (
+                StatusCode::UNAUTHORIZED,
+                "invalid_passkey".to_string(),
+                format!("Passkey could not be verified: {}", value),
+            ),
+            AppError::TooManyRequests => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "too_many_requests".to_string(),
+                "Too many requests, please try again later".to_string(),
+            ),
+            AppError::AccountLinkRequired(_) => (
+                StatusCode::CONFLICT,
+                "account_link_required".to_string(),
+                "An account with this email already exists, login & link this provider from your account"
+                    .to_string(),
+            ),
             _ => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "internal_server_error".to_string(),
                 "An internal server error occurred.".to_string(),
             ),
-        };
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> axum::response::Response {
+        let (status, error_code, message) = self.parts();
 
         let body = Json(json!({
             "error_code": error_code,
@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+
+use crate::infra::{config::AppConfig, errors::app_error::AppError};
+
+use super::mailer::{Mail, Mailer};
+
+// local development transport, mails are printed & written as files so links can be clicked
+pub struct LogMailer {
+    from: String,
+    dir: Option<PathBuf>,
+}
+
+impl LogMailer {
+    pub fn new(cfg: &AppConfig) -> Self {
+        Self {
+            from: cfg.mail_from.clone(),
+            dir: cfg
+                .mail_log_dir
+                .clone()
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, mail: &Mail) -> Result<(), AppError> {
+        tracing::info!(
+            "[Mail:Log] to: {}, subject: {}\n{}",
+            mail.to,
+            mail.subject,
+            mail.body
+        );
+
+        if let Some(dir) = &self.dir {
+            tokio::fs::create_dir_all(dir).await?;
+
+            let file_name = format!(
+                "{}-{}.eml",
+                chrono::Utc::now().format("%Y%m%d%H%M%S%3f"),
+                uuid::Uuid::new_v4()
+            );
+            let content = format!(
+                "From: {}\r\nTo: {}\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}\r\n",
+                self.from, mail.to, mail.subject, mail.body
+            );
+            tokio::fs::write(dir.join(file_name), content).await?;
+        }
+
+        Ok(())
+    }
+}
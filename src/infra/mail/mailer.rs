@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use crate::infra::{config::AppConfig, errors::app_error::AppError};
+
+use super::{log::LogMailer, smtp::SmtpMailer};
+
+#[derive(Debug, Clone)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+// transport used to deliver mails, picked with MAIL_TRANSPORT
+#[async_trait::async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, mail: &Mail) -> Result<(), AppError>;
+}
+
+pub fn mailer_from_config(cfg: &AppConfig) -> Arc<dyn Mailer> {
+    match cfg.mail_transport.as_str() {
+        "smtp" => Arc::new(SmtpMailer::new(cfg)),
+        "log" => Arc::new(LogMailer::new(cfg)),
+        transport => panic!(
+            "MAIL_TRANSPORT {} is not supported, use smtp or log",
+            transport
+        ),
+    }
+}
@@ -0,0 +1,3 @@
+pub mod log;
+pub mod mailer;
+pub mod smtp;
@@ -0,0 +1,62 @@
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+
+use crate::infra::{config::AppConfig, errors::app_error::AppError};
+
+use super::mailer::{Mail, Mailer};
+
+pub struct SmtpMailer {
+    from: Mailbox,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpMailer {
+    pub fn new(cfg: &AppConfig) -> Self {
+        let host = cfg
+            .smtp_host
+            .clone()
+            .expect("SMTP_HOST must be set when MAIL_TRANSPORT=smtp");
+
+        // STARTTLS, the connection is refused when the server can't upgrade it
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)
+            .expect("SMTP_HOST must be a valid host")
+            .port(cfg.smtp_port);
+        if let (Some(username), Some(password)) = (&cfg.smtp_username, &cfg.smtp_password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        Self {
+            from: cfg
+                .mail_from
+                .parse()
+                .expect("MAIL_FROM must be a valid mailbox"),
+            transport: builder.build(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, mail: &Mail) -> Result<(), AppError> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(mail
+                .to
+                .parse()
+                .map_err(|_| AppError::ProcessError(format!("Invalid recipient {}", mail.to)))?)
+            .subject(&mail.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(mail.body.clone())
+            .map_err(|err| AppError::ProcessError(err.to_string()))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|err| AppError::ProcessError(format!("Failed to send mail: {}", err)))?;
+
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@ pub mod config;
 pub mod data;
 pub mod errors;
 pub mod graceful;
+pub mod mail;
 pub mod oauth2;
 pub mod rbac;
 pub mod repositories;
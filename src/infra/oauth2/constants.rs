@@ -1,2 +1,6 @@
 pub const GOOGLE_PROVIDER: &str = "google";
+pub const DISCORD_PROVIDER: &str = "discord";
+pub const GITHUB_PROVIDER: &str = "github";
 pub const EMAIL_PROVIDER: &str = "email";
+pub const WEBAUTHN_PROVIDER: &str = "webauthn";
+pub const MAGIC_LINK_PROVIDER: &str = "magic_link";
@@ -0,0 +1,148 @@
+use crate::{
+    application::dto::auth::oauth2_response::{
+        DiscordUserResult, OauthTokenResponse, OauthUserInfo,
+    },
+    infra::errors::app_error::AppError,
+};
+
+use super::{
+    constants::DISCORD_PROVIDER,
+    provider::{send_token_request, AuthorizeRequest, OauthProvider},
+};
+
+pub const DISCORD_CDN_URL: &str = "https://cdn.discordapp.com";
+
+// plain oauth2 without id tokens, users logging in with discord get our own tokens
+#[derive(Clone)]
+pub struct DiscordProvider {
+    client_id: String,
+    client_secret: String,
+    redirect_url: String,
+    authorize_url: String,
+    api_url: String,
+    http: reqwest::Client,
+}
+
+impl DiscordProvider {
+    pub fn new(
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+        authorize_url: String,
+        api_url: String,
+    ) -> Self {
+        Self {
+            client_id,
+            client_secret,
+            redirect_url,
+            authorize_url,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}{}", self.api_url, path)
+    }
+}
+
+#[async_trait::async_trait]
+impl OauthProvider for DiscordProvider {
+    fn name(&self) -> &str {
+        DISCORD_PROVIDER
+    }
+
+    async fn authorize_url(&self, req: &AuthorizeRequest) -> Result<String, AppError> {
+        reqwest::Url::parse_with_params(
+            &self.authorize_url,
+            &[
+                ("client_id", self.client_id.as_str()),
+                ("redirect_uri", self.redirect_url.as_str()),
+                ("scope", "identify email"),
+                ("prompt", "consent"),
+                ("response_type", "code"),
+                ("state", req.state.as_str()),
+                ("code_challenge", req.code_challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map(|url| url.to_string())
+        .map_err(|err| AppError::ProcessError(err.to_string()))
+    }
+
+    async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<OauthTokenResponse, AppError> {
+        let request = self.http.post(self.endpoint("/oauth2/token")).form(&[
+            ("code", code),
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+            ("redirect_uri", self.redirect_url.as_str()),
+            ("grant_type", "authorization_code"),
+            ("code_verifier", code_verifier),
+        ]);
+
+        send_token_request(request).await
+    }
+
+    async fn fetch_user_info(
+        &self,
+        tokens: &OauthTokenResponse,
+    ) -> Result<OauthUserInfo, AppError> {
+        let request = self
+            .http
+            .get(self.endpoint("/users/@me"))
+            .bearer_auth(&tokens.access_token);
+
+        let user = send_token_request::<DiscordUserResult>(request).await?;
+
+        // accounts registered with a phone number may not have an email
+        let email = user.email.ok_or(AppError::ProcessError(
+            "Discord account has no email address".to_string(),
+        ))?;
+
+        Ok(OauthUserInfo {
+            picture: user
+                .avatar
+                .map(|avatar| format!("{}/avatars/{}/{}.png", DISCORD_CDN_URL, user.id, avatar)),
+            provider_user_id: user.id,
+            email,
+            email_verified: user.verified,
+            name: Some(user.global_name.unwrap_or(user.username)),
+        })
+    }
+
+    async fn refresh_token(&self, refresh_token: &str) -> Result<OauthTokenResponse, AppError> {
+        let request = self.http.post(self.endpoint("/oauth2/token")).form(&[
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+            ("refresh_token", refresh_token),
+            ("grant_type", "refresh_token"),
+        ]);
+
+        send_token_request(request).await
+    }
+
+    async fn revoke_token(
+        &self,
+        access_token: &str,
+        refresh_token: Option<&str>,
+    ) -> Result<(), AppError> {
+        let token = refresh_token.unwrap_or(access_token);
+
+        self.http
+            .post(self.endpoint("/oauth2/token/revoke"))
+            .form(&[
+                ("token", token),
+                ("client_id", self.client_id.as_str()),
+                ("client_secret", self.client_secret.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|err| AppError::ProcessError(err.to_string()))?;
+
+        Ok(())
+    }
+}
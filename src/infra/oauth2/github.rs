@@ -0,0 +1,185 @@
+use reqwest::header;
+
+use crate::{
+    application::dto::auth::oauth2_response::{
+        GithubEmailResult, GithubTokenResult, GithubUserResult, OauthTokenResponse, OauthUserInfo,
+    },
+    infra::errors::app_error::AppError,
+};
+
+use super::{
+    constants::GITHUB_PROVIDER,
+    provider::{send_token_request, AuthorizeRequest, OauthProvider},
+};
+
+// github rejects api requests without a user agent
+const GITHUB_USER_AGENT: &str = env!("CARGO_PKG_NAME");
+
+// plain oauth2 without id tokens, users logging in with github get our own tokens
+#[derive(Clone)]
+pub struct GithubProvider {
+    client_id: String,
+    client_secret: String,
+    redirect_url: String,
+    oauth_url: String,
+    api_url: String,
+    http: reqwest::Client,
+}
+
+impl GithubProvider {
+    pub fn new(
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+        oauth_url: String,
+        api_url: String,
+    ) -> Self {
+        Self {
+            client_id,
+            client_secret,
+            redirect_url,
+            oauth_url: oauth_url.trim_end_matches('/').to_string(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    fn api_request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        self.http
+            .request(method, format!("{}{}", self.api_url, path))
+            .header(header::ACCEPT, "application/vnd.github+json")
+            .header(header::USER_AGENT, GITHUB_USER_AGENT)
+    }
+
+    async fn send_github_token_request(
+        &self,
+        params: &[(&str, &str)],
+    ) -> Result<OauthTokenResponse, AppError> {
+        let request = self
+            .http
+            .post(format!("{}/access_token", self.oauth_url))
+            .header(header::ACCEPT, "application/json")
+            .form(params);
+
+        match send_token_request::<GithubTokenResult>(request).await? {
+            GithubTokenResult::Token(tokens) => Ok(tokens),
+            GithubTokenResult::Error(err_resp) => {
+                tracing::error!("{:?}", err_resp);
+                // github uses bad_verification_code for expired or reused codes
+                if err_resp.error == "bad_verification_code"
+                    || err_resp.error == "bad_refresh_token"
+                {
+                    return Err(AppError::Unauthorized);
+                }
+
+                Err(AppError::ProcessError(err_resp.error))
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl OauthProvider for GithubProvider {
+    fn name(&self) -> &str {
+        GITHUB_PROVIDER
+    }
+
+    async fn authorize_url(&self, req: &AuthorizeRequest) -> Result<String, AppError> {
+        reqwest::Url::parse_with_params(
+            &format!("{}/authorize", self.oauth_url),
+            &[
+                ("client_id", self.client_id.as_str()),
+                ("redirect_uri", self.redirect_url.as_str()),
+                ("scope", "read:user user:email"),
+                ("state", req.state.as_str()),
+                ("code_challenge", req.code_challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map(|url| url.to_string())
+        .map_err(|err| AppError::ProcessError(err.to_string()))
+    }
+
+    async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<OauthTokenResponse, AppError> {
+        self.send_github_token_request(&[
+            ("code", code),
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+            ("redirect_uri", self.redirect_url.as_str()),
+            ("code_verifier", code_verifier),
+        ])
+        .await
+    }
+
+    async fn fetch_user_info(
+        &self,
+        tokens: &OauthTokenResponse,
+    ) -> Result<OauthUserInfo, AppError> {
+        let user = send_token_request::<GithubUserResult>(
+            self.api_request(reqwest::Method::GET, "/user")
+                .bearer_auth(&tokens.access_token),
+        )
+        .await?;
+
+        // the profile email is optional & may be unverified, only trust the primary verified one
+        let emails = send_token_request::<Vec<GithubEmailResult>>(
+            self.api_request(reqwest::Method::GET, "/user/emails")
+                .bearer_auth(&tokens.access_token),
+        )
+        .await?;
+        let email = emails
+            .into_iter()
+            .find(|email| email.primary && email.verified)
+            .ok_or(AppError::ProcessError(
+                "Github account has no verified primary email".to_string(),
+            ))?;
+
+        Ok(OauthUserInfo {
+            provider_user_id: user.id.to_string(),
+            email: email.email,
+            email_verified: true,
+            name: Some(user.name.unwrap_or(user.login)),
+            picture: user.avatar_url,
+        })
+    }
+
+    // only github apps with expiring tokens hand out refresh tokens
+    async fn refresh_token(&self, refresh_token: &str) -> Result<OauthTokenResponse, AppError> {
+        self.send_github_token_request(&[
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+            ("refresh_token", refresh_token),
+            ("grant_type", "refresh_token"),
+        ])
+        .await
+    }
+
+    // only this token, deleting the grant would log the user out of every session & device
+    async fn revoke_token(
+        &self,
+        access_token: &str,
+        _refresh_token: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.api_request(
+            reqwest::Method::DELETE,
+            &format!("/applications/{}/token", self.client_id),
+        )
+        .basic_auth(&self.client_id, Some(&self.client_secret))
+        .json(&serde_json::json!({ "access_token": access_token }))
+        .send()
+        .await
+        .map_err(|err| AppError::ProcessError(err.to_string()))
+        .and_then(|resp| match resp.status() {
+            // 404 when the token was revoked or expired already
+            status if status.is_success() || status == reqwest::StatusCode::NOT_FOUND => Ok(()),
+            status => Err(AppError::ProcessError(format!(
+                "Github token revocation failed with {}",
+                status
+            ))),
+        })
+    }
+}
@@ -1,2 +1,141 @@
+use crate::{
+    application::dto::auth::{
+        jwt_claims::IdTokenClaims,
+        oauth2_response::{GoogleUserResult, OauthTokenResponse, OauthUserInfo},
+    },
+    infra::{errors::app_error::AppError, utils::id_token_verifier::IdTokenVerifier},
+};
+
+use super::{
+    constants::GOOGLE_PROVIDER,
+    provider::{send_token_request, AuthorizeRequest, OauthProvider},
+};
+
 pub const GOOGLE_OAUTH_ENDPOINT: &str = "https://accounts.google.com/o/oauth2/v2/auth";
 pub const GOOGLE_TOKEN_ENDPOINT: &str = "https://oauth2.googleapis.com/token";
+pub const GOOGLE_USERINFO_ENDPOINT: &str = "https://www.googleapis.com/oauth2/v3/userinfo";
+pub const GOOGLE_REVOKE_ENDPOINT: &str = "https://oauth2.googleapis.com/revoke";
+pub const GOOGLE_JWKS_ENDPOINT: &str = "https://www.googleapis.com/oauth2/v3/certs";
+// google signs id tokens with either form of the issuer
+pub const GOOGLE_ISSUERS: [&str; 2] = ["https://accounts.google.com", "accounts.google.com"];
+
+#[derive(Clone)]
+pub struct GoogleProvider {
+    client_id: String,
+    client_secret: String,
+    redirect_url: String,
+    http: reqwest::Client,
+    id_token_verifier: IdTokenVerifier,
+}
+
+impl GoogleProvider {
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> Self {
+        Self {
+            id_token_verifier: IdTokenVerifier::google(client_id.clone()),
+            client_id,
+            client_secret,
+            redirect_url,
+            http: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl OauthProvider for GoogleProvider {
+    fn name(&self) -> &str {
+        GOOGLE_PROVIDER
+    }
+
+    fn issuers(&self) -> Vec<String> {
+        GOOGLE_ISSUERS.iter().map(|iss| iss.to_string()).collect()
+    }
+
+    async fn authorize_url(&self, req: &AuthorizeRequest) -> Result<String, AppError> {
+        let scopes = [
+            "https://www.googleapis.com/auth/userinfo.profile",
+            "https://www.googleapis.com/auth/userinfo.email",
+        ];
+
+        // offline access & consent prompt so google always hands out a refresh token
+        reqwest::Url::parse_with_params(
+            GOOGLE_OAUTH_ENDPOINT,
+            &[
+                ("client_id", self.client_id.as_str()),
+                ("redirect_uri", self.redirect_url.as_str()),
+                ("scope", scopes.join(" ").as_str()),
+                ("prompt", "consent"),
+                ("response_type", "code"),
+                ("access_type", "offline"),
+                ("state", req.state.as_str()),
+                ("code_challenge", req.code_challenge.as_str()),
+                ("code_challenge_method", "S256"),
+                ("nonce", req.nonce.as_str()),
+            ],
+        )
+        .map(|url| url.to_string())
+        .map_err(|err| AppError::ProcessError(err.to_string()))
+    }
+
+    async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<OauthTokenResponse, AppError> {
+        let request = self.http.post(GOOGLE_TOKEN_ENDPOINT).form(&[
+            ("code", code),
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+            ("redirect_uri", self.redirect_url.as_str()),
+            ("grant_type", "authorization_code"),
+            ("code_verifier", code_verifier),
+        ]);
+
+        send_token_request(request).await
+    }
+
+    async fn fetch_user_info(
+        &self,
+        tokens: &OauthTokenResponse,
+    ) -> Result<OauthUserInfo, AppError> {
+        let request = self
+            .http
+            .get(GOOGLE_USERINFO_ENDPOINT)
+            .bearer_auth(&tokens.access_token);
+
+        let user = send_token_request::<GoogleUserResult>(request).await?;
+
+        Ok(user.into())
+    }
+
+    async fn refresh_token(&self, refresh_token: &str) -> Result<OauthTokenResponse, AppError> {
+        let request = self.http.post(GOOGLE_TOKEN_ENDPOINT).form(&[
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+            ("refresh_token", refresh_token),
+            ("grant_type", "refresh_token"),
+        ]);
+
+        send_token_request(request).await
+    }
+
+    async fn revoke_token(
+        &self,
+        access_token: &str,
+        refresh_token: Option<&str>,
+    ) -> Result<(), AppError> {
+        let token = refresh_token.unwrap_or(access_token);
+
+        self.http
+            .post(GOOGLE_REVOKE_ENDPOINT)
+            .form(&[("token", token)])
+            .send()
+            .await
+            .map_err(|err| AppError::ProcessError(err.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn verify_id_token(&self, id_token: &str) -> Result<IdTokenClaims, AppError> {
+        self.id_token_verifier.verify_token(id_token).await
+    }
+}
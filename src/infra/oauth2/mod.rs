@@ -1,2 +1,7 @@
 pub mod constants;
+pub mod discord;
+pub mod github;
 pub mod google;
+pub mod oidc;
+pub mod provider;
+pub mod registry;
@@ -0,0 +1,266 @@
+use serde::Deserialize;
+use tokio::sync::OnceCell;
+
+use crate::{
+    application::dto::auth::{
+        jwt_claims::IdTokenClaims,
+        oauth2_response::{OauthTokenResponse, OauthUserInfo, OidcUserInfoResult},
+    },
+    infra::{
+        config::OidcProviderConfig, errors::app_error::AppError,
+        utils::id_token_verifier::IdTokenVerifier,
+    },
+};
+
+use super::provider::{send_token_request, AuthorizeRequest, OauthProvider};
+
+// the parts of `/.well-known/openid-configuration` we rely on
+#[derive(Deserialize, Debug, Clone)]
+struct OidcDiscovery {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: Option<String>,
+    jwks_uri: String,
+    revocation_endpoint: Option<String>,
+}
+
+// endpoints are discovered on first use, so an unreachable idp doesn't stop the server from starting
+pub struct OidcProvider {
+    cfg: OidcProviderConfig,
+    http: reqwest::Client,
+    discovery: OnceCell<(OidcDiscovery, IdTokenVerifier)>,
+}
+
+impl OidcProvider {
+    pub fn new(cfg: OidcProviderConfig) -> Self {
+        Self {
+            cfg,
+            http: reqwest::Client::new(),
+            discovery: OnceCell::new(),
+        }
+    }
+
+    async fn discovery(&self) -> Result<&(OidcDiscovery, IdTokenVerifier), AppError> {
+        self.discovery
+            .get_or_try_init(|| async {
+                let url = format!(
+                    "{}/.well-known/openid-configuration",
+                    self.cfg.issuer.trim_end_matches('/')
+                );
+                let discovery = self
+                    .http
+                    .get(&url)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json::<OidcDiscovery>()
+                    .await?;
+
+                // the document must describe the issuer we were configured with (OIDC Discovery 4.3)
+                if discovery.issuer != self.cfg.issuer {
+                    return Err(AppError::ProcessError(format!(
+                        "{} discovery returned issuer {}, expected {}",
+                        self.cfg.name, discovery.issuer, self.cfg.issuer
+                    )));
+                }
+
+                let verifier = IdTokenVerifier::new(
+                    self.cfg.client_id.clone(),
+                    vec![discovery.issuer.clone()],
+                    discovery.jwks_uri.clone(),
+                );
+
+                Ok((discovery, verifier))
+            })
+            .await
+    }
+
+    async fn fetch_userinfo(
+        &self,
+        userinfo_endpoint: &str,
+        access_token: &str,
+    ) -> Result<OidcUserInfoResult, AppError> {
+        let request = self.http.get(userinfo_endpoint).bearer_auth(access_token);
+
+        send_token_request(request).await
+    }
+}
+
+#[async_trait::async_trait]
+impl OauthProvider for OidcProvider {
+    fn name(&self) -> &str {
+        &self.cfg.name
+    }
+
+    fn issuers(&self) -> Vec<String> {
+        vec![self.cfg.issuer.clone()]
+    }
+
+    async fn authorize_url(&self, req: &AuthorizeRequest) -> Result<String, AppError> {
+        let (discovery, _) = self.discovery().await?;
+
+        reqwest::Url::parse_with_params(
+            &discovery.authorization_endpoint,
+            &[
+                ("client_id", self.cfg.client_id.as_str()),
+                ("redirect_uri", self.cfg.redirect_url.as_str()),
+                ("scope", self.cfg.scopes.as_str()),
+                ("response_type", "code"),
+                ("state", req.state.as_str()),
+                ("code_challenge", req.code_challenge.as_str()),
+                ("code_challenge_method", "S256"),
+                ("nonce", req.nonce.as_str()),
+            ],
+        )
+        .map(|url| url.to_string())
+        .map_err(|err| AppError::ProcessError(err.to_string()))
+    }
+
+    async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<OauthTokenResponse, AppError> {
+        let (discovery, _) = self.discovery().await?;
+
+        let request = self.http.post(&discovery.token_endpoint).form(&[
+            ("code", code),
+            ("client_id", self.cfg.client_id.as_str()),
+            ("client_secret", self.cfg.client_secret.as_str()),
+            ("redirect_uri", self.cfg.redirect_url.as_str()),
+            ("grant_type", "authorization_code"),
+            ("code_verifier", code_verifier),
+        ]);
+
+        send_token_request(request).await
+    }
+
+    // standard claims come from the id token, the userinfo endpoint fills in what it left out
+    async fn fetch_user_info(
+        &self,
+        tokens: &OauthTokenResponse,
+    ) -> Result<OauthUserInfo, AppError> {
+        let (discovery, verifier) = self.discovery().await?;
+
+        let id_token = tokens
+            .id_token
+            .as_deref()
+            .ok_or(AppError::Oauth2FailedToAuthorize)?;
+        let claims = verifier.verify_token(id_token).await?;
+
+        let userinfo = match (&claims.email, &discovery.userinfo_endpoint) {
+            (None, Some(userinfo_endpoint)) => {
+                let userinfo = self
+                    .fetch_userinfo(userinfo_endpoint, &tokens.access_token)
+                    .await?;
+
+                // userinfo for another subject must not be merged into this login
+                if userinfo.sub != claims.sub {
+                    return Err(AppError::Oauth2FailedToAuthorize);
+                }
+
+                Some(userinfo)
+            }
+            _ => None,
+        };
+
+        user_info_from_claims(claims, userinfo)
+    }
+
+    async fn refresh_token(&self, refresh_token: &str) -> Result<OauthTokenResponse, AppError> {
+        let (discovery, _) = self.discovery().await?;
+
+        let request = self.http.post(&discovery.token_endpoint).form(&[
+            ("client_id", self.cfg.client_id.as_str()),
+            ("client_secret", self.cfg.client_secret.as_str()),
+            ("refresh_token", refresh_token),
+            ("grant_type", "refresh_token"),
+        ]);
+
+        send_token_request(request).await
+    }
+
+    async fn revoke_token(
+        &self,
+        access_token: &str,
+        refresh_token: Option<&str>,
+    ) -> Result<(), AppError> {
+        let token = refresh_token.unwrap_or(access_token);
+
+        let (discovery, _) = self.discovery().await?;
+
+        // not every idp supports revocation (RFC 7009), e.g. azure ad
+        let Some(revocation_endpoint) = &discovery.revocation_endpoint else {
+            tracing::info!("{} has no revocation endpoint, skip revoke", self.cfg.name);
+            return Ok(());
+        };
+
+        self.http
+            .post(revocation_endpoint)
+            .form(&[
+                ("token", token),
+                ("client_id", self.cfg.client_id.as_str()),
+                ("client_secret", self.cfg.client_secret.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|err| AppError::ProcessError(err.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn verify_id_token(&self, id_token: &str) -> Result<IdTokenClaims, AppError> {
+        let (_, verifier) = self.discovery().await?;
+
+        verifier.verify_token(id_token).await
+    }
+}
+
+fn user_info_from_claims(
+    claims: IdTokenClaims,
+    userinfo: Option<OidcUserInfoResult>,
+) -> Result<OauthUserInfo, AppError> {
+    let (email, email_verified, name, given_name, family_name, preferred_username, picture) =
+        match userinfo {
+            Some(userinfo) => (
+                userinfo.email,
+                userinfo.email_verified,
+                claims.name.or(userinfo.name),
+                claims.given_name.or(userinfo.given_name),
+                claims.family_name.or(userinfo.family_name),
+                claims.preferred_username.or(userinfo.preferred_username),
+                claims.picture.or(userinfo.picture),
+            ),
+            None => (
+                claims.email,
+                claims.email_verified,
+                claims.name,
+                claims.given_name,
+                claims.family_name,
+                claims.preferred_username,
+                claims.picture,
+            ),
+        };
+
+    let email = email.ok_or(AppError::ProcessError(
+        "Identity provider did not share an email address".to_string(),
+    ))?;
+
+    let name = name
+        .or_else(|| match (given_name, family_name) {
+            (Some(given_name), Some(family_name)) => {
+                Some(format!("{} {}", given_name, family_name))
+            }
+            (given_name, family_name) => given_name.or(family_name),
+        })
+        .or(preferred_username);
+
+    Ok(OauthUserInfo {
+        provider_user_id: claims.sub,
+        email,
+        email_verified: email_verified.unwrap_or(false),
+        name,
+        picture,
+    })
+}
@@ -0,0 +1,81 @@
+use serde::de::DeserializeOwned;
+
+use crate::{
+    application::dto::auth::{
+        jwt_claims::IdTokenClaims,
+        oauth2_response::{OauthTokenError, OauthTokenResponse, OauthUserInfo},
+    },
+    infra::errors::app_error::AppError,
+};
+
+// per login values sent to the authorization endpoint
+pub struct AuthorizeRequest {
+    pub state: String,
+    pub code_challenge: String,
+    // only used by openid connect providers
+    pub nonce: String,
+}
+
+// everything the login, refresh, logout & auth middleware flows need from an external identity provider
+#[async_trait::async_trait]
+pub trait OauthProvider: Send + Sync {
+    // name stored in `user_oauth_providers.provider` & `user_sessions.provider`
+    fn name(&self) -> &str;
+
+    // issuers of the tokens handed to our clients, used to route bearer tokens to this provider
+    fn issuers(&self) -> Vec<String> {
+        vec![]
+    }
+
+    // may need to reach the provider first, e.g. for openid connect discovery
+    async fn authorize_url(&self, req: &AuthorizeRequest) -> Result<String, AppError>;
+
+    async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<OauthTokenResponse, AppError>;
+
+    async fn fetch_user_info(&self, tokens: &OauthTokenResponse)
+        -> Result<OauthUserInfo, AppError>;
+
+    async fn refresh_token(&self, refresh_token: &str) -> Result<OauthTokenResponse, AppError>;
+
+    // revoking the refresh token ends the whole grant on most providers, access tokens may already be expired
+    async fn revoke_token(
+        &self,
+        access_token: &str,
+        refresh_token: Option<&str>,
+    ) -> Result<(), AppError>;
+
+    // plain oauth2 providers have no id token
+    async fn verify_id_token(&self, _id_token: &str) -> Result<IdTokenClaims, AppError> {
+        Err(AppError::InvalidToken)
+    }
+}
+
+// send a request to a token endpoint & map the standard oauth error response
+pub async fn send_token_request<T: DeserializeOwned>(
+    request: reqwest::RequestBuilder,
+) -> Result<T, AppError> {
+    match request.send().await {
+        Ok(r) => {
+            if r.status().is_success() {
+                Ok(r.json::<T>().await?)
+            } else {
+                let err_resp = r.json::<OauthTokenError>().await?;
+
+                tracing::error!("{:?}", err_resp);
+                if err_resp.error == "invalid_grant" {
+                    return Err(AppError::Unauthorized);
+                }
+
+                Err(AppError::ProcessError(err_resp.error))
+            }
+        }
+        Err(err) => {
+            tracing::error!("{}", err);
+            Err(AppError::Oauth2FailedToAuthorize)
+        }
+    }
+}
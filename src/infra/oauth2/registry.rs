@@ -0,0 +1,91 @@
+use std::{collections::HashMap, sync::Arc};
+
+use tracing::info;
+
+use crate::infra::{config::AppConfig, errors::app_error::AppError};
+
+use super::{
+    discord::DiscordProvider, github::GithubProvider, google::GoogleProvider, oidc::OidcProvider,
+    provider::OauthProvider,
+};
+
+// oauth providers enabled through config, looked up by the name used in urls & sessions
+#[derive(Clone, Default)]
+pub struct OauthProviderRegistry {
+    providers: HashMap<String, Arc<dyn OauthProvider>>,
+}
+
+impl OauthProviderRegistry {
+    pub fn from_config(cfg: &AppConfig) -> Self {
+        let mut registry = Self::default();
+
+        if let (Some(client_id), Some(client_secret), Some(redirect_url)) = (
+            non_empty(&cfg.google_client_id),
+            non_empty(&cfg.google_client_secret),
+            non_empty(&cfg.google_redirect_url),
+        ) {
+            registry.register(Arc::new(GoogleProvider::new(
+                client_id,
+                client_secret,
+                redirect_url,
+            )));
+        }
+
+        if let (Some(client_id), Some(client_secret), Some(redirect_url)) = (
+            non_empty(&cfg.discord_client_id),
+            non_empty(&cfg.discord_client_secret),
+            non_empty(&cfg.discord_redirect_url),
+        ) {
+            registry.register(Arc::new(DiscordProvider::new(
+                client_id,
+                client_secret,
+                redirect_url,
+                cfg.discord_authorize_url.clone(),
+                cfg.discord_api_url.clone(),
+            )));
+        }
+
+        if let (Some(client_id), Some(client_secret), Some(redirect_url)) = (
+            non_empty(&cfg.github_client_id),
+            non_empty(&cfg.github_client_secret),
+            non_empty(&cfg.github_redirect_url),
+        ) {
+            registry.register(Arc::new(GithubProvider::new(
+                client_id,
+                client_secret,
+                redirect_url,
+                cfg.github_oauth_url.clone(),
+                cfg.github_api_url.clone(),
+            )));
+        }
+
+        for oidc_cfg in cfg.oidc_provider_configs() {
+            registry.register(Arc::new(OidcProvider::new(oidc_cfg)));
+        }
+
+        registry
+    }
+
+    pub fn register(&mut self, provider: Arc<dyn OauthProvider>) {
+        info!("Registering Oauth Provider {}", provider.name());
+        self.providers.insert(provider.name().to_string(), provider);
+    }
+
+    pub fn get(&self, name: &str) -> Result<Arc<dyn OauthProvider>, AppError> {
+        self.providers
+            .get(name)
+            .cloned()
+            .ok_or(AppError::InvalidOauthProvider)
+    }
+
+    pub fn find_by_issuer(&self, issuer: &str) -> Option<Arc<dyn OauthProvider>> {
+        self.providers
+            .values()
+            .find(|provider| provider.issuers().iter().any(|iss| iss == issuer))
+            .cloned()
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value.clone().filter(|value| !value.is_empty())
+}
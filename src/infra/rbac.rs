@@ -4,7 +4,25 @@ use casbin::{CoreApi, Enforcer, MgmtApi, RbacApi};
 use tokio::sync::RwLock;
 use tracing::info;
 
-use crate::domain::entities::role::Role;
+use crate::{
+    domain::entities::{role::Role, user::UserFull, user_role::UserRole},
+    infra::common::constants::SUPER_ADMIN_ROLE,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct AccessDecision {
+    pub allowed: bool,
+    pub matched_role: Option<Role>,
+}
+
+impl AccessDecision {
+    pub fn matched_role_name(&self) -> &str {
+        self.matched_role
+            .as_ref()
+            .map(|role| role.name.as_str())
+            .unwrap_or_default()
+    }
+}
 
 #[derive(Clone)]
 pub struct Rbac {
@@ -16,28 +34,231 @@ impl Rbac {
         Self { enforcer }
     }
 
+    // users are casbin subjects, role membership & role hierarchy are resolved through `g` policies.
+    // when access is granted we look for the user's direct role that carries the permission
     pub async fn check_access(
         &self,
-        roles: &[Role],
+        user: &UserFull,
         object: &str,
         action: &str,
-    ) -> Result<bool, casbin::Error> {
-        let roles = roles.to_owned();
-        if roles.is_empty() {
-            return Ok(false);
+    ) -> Result<AccessDecision, casbin::Error> {
+        let enforcer = self.enforcer.read().await;
+
+        if !enforcer.enforce((user.user.id.as_str(), object, action))? {
+            return Ok(AccessDecision::default());
+        }
+
+        for role in &user.roles {
+            if enforcer.enforce((role.id.as_str(), object, action))? {
+                return Ok(AccessDecision {
+                    allowed: true,
+                    matched_role: Some(role.clone()),
+                });
+            }
+        }
+
+        Ok(AccessDecision {
+            allowed: true,
+            matched_role: None,
+        })
+    }
+
+    // "object:action" pairs the user is granted through all of its roles, inherited ones included
+    pub async fn get_user_scopes(&self, user_id: &str) -> Vec<String> {
+        let mut enforcer = self.enforcer.write().await;
+
+        let mut scopes: Vec<String> = enforcer
+            .get_implicit_permissions_for_user(user_id, None)
+            .into_iter()
+            .filter_map(|policy| match (policy.get(1), policy.get(2)) {
+                (Some(object), Some(action)) => Some(format!("{}:{}", object, action)),
+                _ => None,
+            })
+            .collect();
+        scopes.sort();
+        scopes.dedup();
+
+        scopes
+    }
+
+    pub async fn assign_role(&self, user_id: &str, role_id: &str) -> Result<(), casbin::Error> {
+        let mut enforcer = self.enforcer.write().await;
+
+        let grouping = vec![user_id.to_string(), role_id.to_string()];
+        if !enforcer.has_grouping_policy(grouping.clone()) {
+            enforcer.add_grouping_policy(grouping).await?;
         }
 
+        Ok(())
+    }
+
+    pub async fn revoke_role(&self, user_id: &str, role_id: &str) -> Result<(), casbin::Error> {
+        let mut enforcer = self.enforcer.write().await;
+
+        let grouping = vec![user_id.to_string(), role_id.to_string()];
+        if enforcer.has_grouping_policy(grouping.clone()) {
+            enforcer.remove_grouping_policy(grouping).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_role_parents(&self, role_id: &str) -> Vec<String> {
+        let enforcer = self.enforcer.read().await;
+
+        enforcer
+            .get_filtered_grouping_policy(0, vec![role_id.to_string()])
+            .into_iter()
+            .filter_map(|grouping| grouping.get(1).cloned())
+            .collect()
+    }
+
+    // whether `role_id` inherits from `ancestor_id`, directly or through other roles
+    pub async fn inherits_from(&self, role_id: &str, ancestor_id: &str) -> bool {
         let enforcer = self.enforcer.read().await;
 
-        // for now get the first one
-        let subject = match roles.first() {
-            Some(role) => role.id.clone(),
-            None => String::default(),
-        };
+        let mut visited = vec![role_id.to_string()];
+        let mut pending = vec![role_id.to_string()];
+        while let Some(current) = pending.pop() {
+            for grouping in enforcer.get_filtered_grouping_policy(0, vec![current]) {
+                let Some(parent_id) = grouping.get(1) else {
+                    continue;
+                };
+                if parent_id == ancestor_id {
+                    return true;
+                }
+                if !visited.contains(parent_id) {
+                    visited.push(parent_id.clone());
+                    pending.push(parent_id.clone());
+                }
+            }
+        }
+
+        false
+    }
+
+    // admins only hand out & take away roles they hold themselves, the super admin role stays
+    // with super admins
+    pub async fn can_manage_role(&self, actor: &UserFull, role: &Role) -> bool {
+        if actor.is_super_admin() {
+            return true;
+        }
+        if role.name == SUPER_ADMIN_ROLE {
+            return false;
+        }
+
+        for held in &actor.roles {
+            if held.id == role.id || self.inherits_from(&held.id, &role.id).await {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    // acting on a user needs every role the user holds, so admins can't reach super admins or
+    // users above them
+    pub async fn can_manage_user(&self, actor: &UserFull, user_roles: &[Role]) -> bool {
+        for role in user_roles {
+            if !self.can_manage_role(actor, role).await {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    // replace the roles that `role_id` inherits from, e.g. editor -> viewer
+    pub async fn set_role_parents(
+        &self,
+        role_id: &str,
+        parent_ids: &[String],
+    ) -> Result<(), casbin::Error> {
+        let mut enforcer = self.enforcer.write().await;
+
+        let current_parents: Vec<String> = enforcer
+            .get_filtered_grouping_policy(0, vec![role_id.to_string()])
+            .into_iter()
+            .filter_map(|grouping| grouping.get(1).cloned())
+            .collect();
+
+        for parent_id in current_parents.iter() {
+            if !parent_ids.contains(parent_id) {
+                info!("Removing role inheritance {} -> {}", role_id, parent_id);
+                enforcer
+                    .remove_grouping_policy(vec![role_id.to_string(), parent_id.clone()])
+                    .await?;
+            }
+        }
+
+        for parent_id in parent_ids {
+            if parent_id != role_id && !current_parents.contains(parent_id) {
+                info!("Adding role inheritance {} -> {}", role_id, parent_id);
+                enforcer
+                    .add_grouping_policy(vec![role_id.to_string(), parent_id.clone()])
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // remove every grouping where the role is a member (inheritance) or a parent (users & child roles)
+    pub async fn remove_role_groupings(&self, role_id: &str) -> Result<(), casbin::Error> {
+        let mut enforcer = self.enforcer.write().await;
+
+        for field_index in [0, 1] {
+            let groupings =
+                enforcer.get_filtered_grouping_policy(field_index, vec![role_id.to_string()]);
+            if !groupings.is_empty() {
+                enforcer.remove_grouping_policies(groupings).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // mirror `user_roles` into `g` policies, stale user groupings are dropped while role hierarchies are kept
+    pub async fn sync_user_roles(
+        &self,
+        user_roles: &[UserRole],
+        role_ids: &[String],
+    ) -> Result<(), casbin::Error> {
+        info!("Syncing User Roles into Casbin...");
+
+        let mut enforcer = self.enforcer.write().await;
+
+        let expected_groupings: Vec<Vec<String>> = user_roles
+            .iter()
+            .map(|user_role| vec![user_role.user_id.clone(), user_role.role_id.clone()])
+            .collect();
+
+        let stale_groupings: Vec<Vec<String>> = enforcer
+            .get_grouping_policy()
+            .into_iter()
+            .filter(|grouping| {
+                !role_ids.contains(&grouping[0]) && !expected_groupings.contains(grouping)
+            })
+            .collect();
+
+        if !stale_groupings.is_empty() {
+            info!("Removing {} stale user groupings", stale_groupings.len());
+            enforcer.remove_grouping_policies(stale_groupings).await?;
+        }
+
+        let missing_groupings: Vec<Vec<String>> = expected_groupings
+            .into_iter()
+            .filter(|grouping| !enforcer.has_grouping_policy(grouping.clone()))
+            .collect();
+
+        if !missing_groupings.is_empty() {
+            info!("Adding {} missing user groupings", missing_groupings.len());
+            enforcer.add_grouping_policies(missing_groupings).await?;
+        }
 
-        let has_access = enforcer.enforce((subject, object, action))?;
+        info!("User Roles Sync Completed!");
 
-        Ok(has_access)
+        Ok(())
     }
 
     pub async fn setup_roles_and_permissions(&self) {
@@ -1,5 +1,8 @@
 pub mod pg_oauth_provider;
 pub mod pg_role_repo;
+pub mod pg_user_mfa;
 pub mod pg_user_repo;
 pub mod pg_user_session;
+pub mod pg_user_token;
+pub mod pg_webauthn_credential;
 pub mod redis_repo_impl;
@@ -35,4 +35,39 @@ impl OauthProviderRepository for PgOauthProviderRepository {
 
         Ok(oauth_provider)
     }
+
+    async fn find_by_user_id(&self, user_id: &str) -> Result<Vec<UserOauthProvider>, AppError> {
+        let oauth_providers = sqlx::query_as!(
+            UserOauthProvider,
+            "SELECT * FROM user_oauth_providers WHERE user_id = $1",
+            user_id
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(oauth_providers)
+    }
+
+    async fn create(&self, entity: &UserOauthProvider) -> Result<UserOauthProvider, AppError> {
+        let oauth_provider = sqlx::query_as!(
+            UserOauthProvider,
+            "INSERT INTO user_oauth_providers (id, user_id, provider, provider_user_id) VALUES ($1, $2, $3, $4) RETURNING *",
+            entity.id,
+            entity.user_id,
+            entity.provider,
+            entity.provider_user_id
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(oauth_provider)
+    }
+
+    async fn delete_by_id(&self, id: &str) -> Result<(), AppError> {
+        sqlx::query!("DELETE FROM user_oauth_providers WHERE id = $1", id)
+            .execute(&self.db_pool)
+            .await?;
+
+        Ok(())
+    }
 }
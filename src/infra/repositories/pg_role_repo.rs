@@ -1,6 +1,9 @@
 use crate::{
     domain::{
-        entities::role::{Role, RoleCount},
+        entities::{
+            role::{Role, RoleCount},
+            user_role::UserRole,
+        },
         repositories::role_repo::RoleRepository,
     },
     infra::errors::app_error::AppError,
@@ -87,10 +90,11 @@ impl RoleRepository for PgRoleRepository {
     async fn create(&self, entity: Role) -> Result<Role, AppError> {
         let role = sqlx::query_as!(
             Role,
-            "INSERT INTO roles (id, name, is_default) VALUES ($1, $2, $3) RETURNING *",
+            "INSERT INTO roles (id, name, is_default, mfa_required) VALUES ($1, $2, $3, $4) RETURNING *",
             entity.id,
             entity.name,
-            entity.is_default
+            entity.is_default,
+            entity.mfa_required
         )
         .fetch_one(&self.db_pool)
         .await?;
@@ -105,10 +109,11 @@ impl RoleRepository for PgRoleRepository {
     ) -> Result<Role, AppError> {
         let role = sqlx::query_as!(
             Role,
-            "INSERT INTO roles (id, name, is_default) VALUES ($1, $2, $3) RETURNING *",
+            "INSERT INTO roles (id, name, is_default, mfa_required) VALUES ($1, $2, $3, $4) RETURNING *",
             entity.id,
             entity.name,
-            entity.is_default
+            entity.is_default,
+            entity.mfa_required
         )
         .fetch_one(&mut **tx)
         .await?;
@@ -118,9 +123,10 @@ impl RoleRepository for PgRoleRepository {
 
     async fn update(&self, id: &str, entity: Role) -> Result<(), AppError> {
         sqlx::query!(
-            "UPDATE roles SET name = $1, is_default = $2 WHERE id = $3",
+            "UPDATE roles SET name = $1, is_default = $2, mfa_required = $3 WHERE id = $4",
             entity.name,
             entity.is_default,
+            entity.mfa_required,
             id
         )
         .execute(&self.db_pool)
@@ -149,4 +155,42 @@ impl RoleRepository for PgRoleRepository {
 
         Ok(roles)
     }
+
+    async fn find_all_user_roles(&self) -> Result<Vec<UserRole>, AppError> {
+        let user_roles = sqlx::query_as!(
+            UserRole,
+            "SELECT user_roles.* FROM user_roles INNER JOIN roles ON roles.id = user_roles.role_id WHERE roles.deleted_at IS NULL"
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(user_roles)
+    }
+
+    async fn assign_to_user(&self, entity: UserRole) -> Result<UserRole, AppError> {
+        let user_role = sqlx::query_as!(
+            UserRole,
+            "INSERT INTO user_roles (user_id, role_id, created_at, updated_at) VALUES ($1, $2, $3, $4) RETURNING *",
+            entity.user_id,
+            entity.role_id,
+            entity.created_at,
+            entity.updated_at,
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(user_role)
+    }
+
+    async fn revoke_from_user(&self, user_id: &str, role_id: &str) -> Result<(), AppError> {
+        sqlx::query!(
+            "DELETE FROM user_roles WHERE user_id = $1 AND role_id = $2",
+            user_id,
+            role_id
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,153 @@
+use crate::{
+    domain::{
+        entities::user_mfa::{UserRecoveryCode, UserTotp},
+        repositories::user_mfa_repo::UserMfaRepository,
+    },
+    infra::errors::app_error::AppError,
+};
+
+#[derive(Clone, Debug)]
+pub struct PgUserMfaRepository {
+    db_pool: sqlx::PgPool,
+}
+
+impl PgUserMfaRepository {
+    pub fn new(db_pool: sqlx::PgPool) -> Self {
+        Self { db_pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl UserMfaRepository for PgUserMfaRepository {
+    async fn find_totp(&self, user_id: &str) -> Result<UserTotp, AppError> {
+        let user_totp = sqlx::query_as!(
+            UserTotp,
+            "SELECT * FROM user_totp WHERE user_id = $1",
+            user_id
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(user_totp)
+    }
+
+    async fn save_totp(&self, entity: &UserTotp) -> Result<UserTotp, AppError> {
+        let user_totp = sqlx::query_as!(
+            UserTotp,
+            "INSERT INTO user_totp (user_id, secret, confirmed_at, last_used_step, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (user_id) DO UPDATE SET secret = EXCLUDED.secret, confirmed_at = EXCLUDED.confirmed_at, last_used_step = EXCLUDED.last_used_step, created_at = EXCLUDED.created_at, updated_at = EXCLUDED.updated_at
+            RETURNING *",
+            entity.user_id,
+            entity.secret,
+            entity.confirmed_at,
+            entity.last_used_step,
+            entity.created_at,
+            entity.updated_at
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(user_totp)
+    }
+
+    async fn use_totp_step(&self, user_id: &str, step: i64) -> Result<bool, AppError> {
+        let result = sqlx::query!(
+            "UPDATE user_totp SET last_used_step = $2, updated_at = $3 WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)",
+            user_id,
+            step,
+            chrono::Utc::now()
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn confirm_totp(&self, user_id: &str, step: i64) -> Result<(), AppError> {
+        let now = chrono::Utc::now();
+        sqlx::query!(
+            "UPDATE user_totp SET confirmed_at = $2, last_used_step = $3, updated_at = $2 WHERE user_id = $1",
+            user_id,
+            now,
+            step
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_totp(&self, user_id: &str) -> Result<(), AppError> {
+        let mut tx = self.db_pool.begin().await?;
+
+        sqlx::query!("DELETE FROM user_totp WHERE user_id = $1", user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            "DELETE FROM user_recovery_codes WHERE user_id = $1",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn replace_recovery_codes(
+        &self,
+        user_id: &str,
+        codes: &[UserRecoveryCode],
+    ) -> Result<(), AppError> {
+        let mut tx = self.db_pool.begin().await?;
+
+        sqlx::query!(
+            "DELETE FROM user_recovery_codes WHERE user_id = $1",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for code in codes {
+            sqlx::query!(
+                "INSERT INTO user_recovery_codes (id, user_id, code_hash, used_at, created_at) VALUES ($1, $2, $3, $4, $5)",
+                code.id,
+                code.user_id,
+                code.code_hash,
+                code.used_at,
+                code.created_at
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn use_recovery_code(&self, user_id: &str, code_hash: &str) -> Result<bool, AppError> {
+        let result = sqlx::query!(
+            "UPDATE user_recovery_codes SET used_at = $3 WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL",
+            user_id,
+            code_hash,
+            chrono::Utc::now()
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn count_unused_recovery_codes(&self, user_id: &str) -> Result<i64, AppError> {
+        let count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM user_recovery_codes WHERE user_id = $1 AND used_at IS NULL",
+            user_id
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(count.unwrap_or(0))
+    }
+}
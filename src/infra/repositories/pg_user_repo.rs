@@ -1,7 +1,9 @@
 use crate::{
     domain::{
-        entities::user::User, entities::user_oauth_provider::UserOauthProvider,
-        entities::user_role::UserRole, repositories::user_repo::UserRepository,
+        entities::user::{User, UserCount},
+        entities::user_oauth_provider::UserOauthProvider,
+        entities::user_role::UserRole,
+        repositories::user_repo::UserRepository,
     },
     infra::errors::app_error::AppError,
 };
@@ -19,6 +21,38 @@ impl PgUserRepository {
 
 #[async_trait::async_trait]
 impl UserRepository for PgUserRepository {
+    async fn paginate(
+        &self,
+        page: i64,
+        limit: i64,
+        search: Option<String>,
+    ) -> Result<(Vec<User>, i64), AppError> {
+        let offset = (page - 1) * limit;
+        let search = search
+            .filter(|keyword| !keyword.trim().is_empty())
+            .map(|keyword| format!("%{}%", keyword.trim()));
+
+        let users = sqlx::query_as!(
+            User,
+            "SELECT * FROM users WHERE deleted_at IS NULL AND ($3::TEXT IS NULL OR email ILIKE $3 OR fullname ILIKE $3) ORDER BY created_at DESC LIMIT $1 OFFSET $2",
+            limit,
+            offset,
+            search
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let count = sqlx::query_as!(
+            UserCount,
+            "SELECT COUNT(*) AS total_items FROM users WHERE deleted_at IS NULL AND ($1::TEXT IS NULL OR email ILIKE $1 OR fullname ILIKE $1)",
+            search
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok((users, count.total_items.unwrap_or(0)))
+    }
+
     async fn find_by_email(&self, email: &str) -> Result<User, AppError> {
         let user = sqlx::query_as!(User, "SELECT * FROM users WHERE email = $1", email)
             .fetch_one(&self.pool)
@@ -42,7 +76,7 @@ impl UserRepository for PgUserRepository {
     ) -> Result<crate::domain::entities::user::User, AppError> {
         let user = sqlx::query_as!(
             crate::domain::entities::user::User,
-            "INSERT INTO users (id, email, password_hash, fullname, avatar_url, is_active, created_at, updated_at, deleted_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING *",
+            "INSERT INTO users (id, email, password_hash, fullname, avatar_url, is_active, created_at, updated_at, deleted_at, email_verified_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING *",
             entity.id,
             entity.email,
             entity.password_hash,
@@ -51,7 +85,8 @@ impl UserRepository for PgUserRepository {
             entity.is_active,
             entity.created_at,
             entity.updated_at,
-            entity.deleted_at
+            entity.deleted_at,
+            entity.email_verified_at
         ).fetch_one(&mut **tx).await?;
 
         Ok(user)
@@ -66,7 +101,7 @@ impl UserRepository for PgUserRepository {
     ) -> Result<(User, UserOauthProvider, UserRole), AppError> {
         let user = sqlx::query_as!(
             User,
-            "INSERT INTO users (id, email, password_hash, fullname, avatar_url, is_active, created_at, updated_at, deleted_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING *",
+            "INSERT INTO users (id, email, password_hash, fullname, avatar_url, is_active, created_at, updated_at, deleted_at, email_verified_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING *",
             user.id,
             user.email,
             user.password_hash,
@@ -75,7 +110,8 @@ impl UserRepository for PgUserRepository {
             user.is_active,
             user.created_at,
             user.updated_at,
-            user.deleted_at
+            user.deleted_at,
+            user.email_verified_at
         ).fetch_one(&mut **tx).await?;
 
         let user_oauth_provider = sqlx::query_as!(
@@ -100,4 +136,35 @@ impl UserRepository for PgUserRepository {
 
         Ok((user, user_oauth_provider, user_role))
     }
+
+    async fn update(&self, id: &str, entity: User) -> Result<(), AppError> {
+        sqlx::query!(
+            "UPDATE users SET email = $1, fullname = $2, avatar_url = $3, is_active = $4, updated_at = $5, deleted_at = $6, email_verified_at = $7 WHERE id = $8",
+            entity.email,
+            entity.fullname,
+            entity.avatar_url,
+            entity.is_active,
+            entity.updated_at,
+            entity.deleted_at,
+            entity.email_verified_at,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn update_password(&self, id: &str, entity: &User) -> Result<(), AppError> {
+        sqlx::query!(
+            "UPDATE users SET password_hash = $1, updated_at = $2 WHERE id = $3",
+            entity.password_hash,
+            entity.updated_at,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
 }
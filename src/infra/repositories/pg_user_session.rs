@@ -1,6 +1,7 @@
 use crate::{
     domain::{
-        entities::user_session::UserSession, repositories::user_session_repo::UserSessionRepository,
+        entities::{refresh_token::RefreshToken, user_session::UserSession},
+        repositories::user_session_repo::UserSessionRepository,
     },
     infra::errors::app_error::AppError,
 };
@@ -18,23 +19,35 @@ impl PgUserSessionRepository {
 
 #[async_trait::async_trait]
 impl UserSessionRepository for PgUserSessionRepository {
-    async fn find_by_user_id(&self, user_id: &str) -> Result<UserSession, AppError> {
+    async fn find_by_id(&self, session_id: &str) -> Result<UserSession, AppError> {
+        let session = sqlx::query_as!(
+            UserSession,
+            "SELECT * FROM user_sessions WHERE id = $1",
+            session_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(session)
+    }
+
+    async fn find_all_by_user_id(&self, user_id: &str) -> Result<Vec<UserSession>, AppError> {
         let sessions = sqlx::query_as!(
             UserSession,
-            "SELECT * FROM user_sessions WHERE user_id = $1",
+            "SELECT * FROM user_sessions WHERE user_id = $1 ORDER BY last_used_at DESC",
             user_id
         )
-        .fetch_one(&self.pool)
+        .fetch_all(&self.pool)
         .await?;
 
         Ok(sessions)
     }
 
-    async fn find_by_refresh_token(&self, refresh_token: &str) -> Result<UserSession, AppError> {
+    async fn find_by_refresh_token_hash(&self, token_hash: &str) -> Result<UserSession, AppError> {
         let sessions = sqlx::query_as!(
             UserSession,
-            "SELECT * FROM user_sessions WHERE refresh_token = $1",
-            refresh_token
+            "SELECT user_sessions.* FROM user_sessions INNER JOIN refresh_tokens ON user_sessions.id = refresh_tokens.session_id WHERE refresh_tokens.token_hash = $1 AND refresh_tokens.rotated_at IS NULL",
+            token_hash
         )
         .fetch_one(&self.pool)
         .await?;
@@ -42,15 +55,34 @@ impl UserSessionRepository for PgUserSessionRepository {
         Ok(sessions)
     }
 
+    async fn find_by_id_token_hash(&self, id_token_hash: &str) -> Result<UserSession, AppError> {
+        let session = sqlx::query_as!(
+            UserSession,
+            "SELECT * FROM user_sessions WHERE id_token_hash = $1",
+            id_token_hash
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(session)
+    }
+
     async fn create(&self, entity: UserSession) -> Result<UserSession, AppError> {
         let session = sqlx::query_as!(
             UserSession,
-            "INSERT INTO user_sessions (id, user_id, access_token, refresh_token, created_at) VALUES ($1, $2, $3, $4, $5) RETURNING *",
+            "INSERT INTO user_sessions (id, user_id, provider, access_token, refresh_token, user_agent, ip_address, expires_at, last_used_at, created_at, token_key_version, id_token_hash) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) RETURNING *",
             entity.id,
             entity.user_id,
+            entity.provider,
             entity.access_token,
             entity.refresh_token,
-            entity.created_at
+            entity.user_agent,
+            entity.ip_address,
+            entity.expires_at,
+            entity.last_used_at,
+            entity.created_at,
+            entity.token_key_version,
+            entity.id_token_hash
         )
         .fetch_one(&self.pool)
         .await?;
@@ -60,10 +92,27 @@ impl UserSessionRepository for PgUserSessionRepository {
 
     async fn update_token(&self, session: &UserSession) -> Result<(), AppError> {
         sqlx::query!(
-            "UPDATE user_sessions SET refresh_token = $1, access_token = $2 WHERE user_id = $3",
+            "UPDATE user_sessions SET refresh_token = $1, access_token = $2, expires_at = $3, last_used_at = $4, token_key_version = $5, id_token_hash = $6 WHERE id = $7",
             session.refresh_token,
             session.access_token,
-            session.user_id
+            session.expires_at,
+            session.last_used_at,
+            session.token_key_version,
+            session.id_token_hash,
+            session.id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn touch(&self, session_id: &str) -> Result<(), AppError> {
+        let now = chrono::Utc::now();
+        sqlx::query!(
+            "UPDATE user_sessions SET last_used_at = $2 WHERE id = $1",
+            session_id,
+            now
         )
         .execute(&self.pool)
         .await?;
@@ -78,4 +127,90 @@ impl UserSessionRepository for PgUserSessionRepository {
 
         Ok(())
     }
+
+    async fn delete_by_user_id(&self, user_id: &str) -> Result<(), AppError> {
+        sqlx::query!("DELETE FROM user_sessions WHERE user_id = $1", user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn find_refresh_token(&self, token_hash: &str) -> Result<RefreshToken, AppError> {
+        let refresh_token = sqlx::query_as!(
+            RefreshToken,
+            "SELECT * FROM refresh_tokens WHERE token_hash = $1",
+            token_hash
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(refresh_token)
+    }
+
+    async fn create_refresh_token(&self, entity: RefreshToken) -> Result<RefreshToken, AppError> {
+        let refresh_token = sqlx::query_as!(
+            RefreshToken,
+            "INSERT INTO refresh_tokens (id, session_id, token_hash, rotated_at, created_at, keyed) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+            entity.id,
+            entity.session_id,
+            entity.token_hash,
+            entity.rotated_at,
+            entity.created_at,
+            entity.keyed
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(refresh_token)
+    }
+
+    async fn rotate_refresh_token(&self, id: &str) -> Result<bool, AppError> {
+        let now = chrono::Utc::now();
+        let result = sqlx::query!(
+            "UPDATE refresh_tokens SET rotated_at = $2 WHERE id = $1 AND rotated_at IS NULL",
+            id,
+            now
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn find_all_unsealed(&self, key_version: i32) -> Result<Vec<UserSession>, AppError> {
+        // email sessions only hold hashes, those never need a new key
+        let sessions = sqlx::query_as!(
+            UserSession,
+            "SELECT * FROM user_sessions WHERE token_key_version IS NULL OR (token_key_version <> $1 AND provider NOT IN ('email', 'webauthn', 'magic_link'))",
+            key_version
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    async fn find_all_unkeyed_refresh_tokens(&self) -> Result<Vec<RefreshToken>, AppError> {
+        let refresh_tokens = sqlx::query_as!(
+            RefreshToken,
+            "SELECT * FROM refresh_tokens WHERE keyed = FALSE"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(refresh_tokens)
+    }
+
+    async fn update_refresh_token_hash(&self, id: &str, token_hash: &str) -> Result<(), AppError> {
+        sqlx::query!(
+            "UPDATE refresh_tokens SET token_hash = $2, keyed = TRUE WHERE id = $1",
+            id,
+            token_hash
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,72 @@
+use crate::{
+    domain::{entities::user_token::UserToken, repositories::user_token_repo::UserTokenRepository},
+    infra::errors::app_error::AppError,
+};
+
+#[derive(Clone, Debug)]
+pub struct PgUserTokenRepository {
+    db_pool: sqlx::PgPool,
+}
+
+impl PgUserTokenRepository {
+    pub fn new(db_pool: sqlx::PgPool) -> Self {
+        Self { db_pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl UserTokenRepository for PgUserTokenRepository {
+    async fn create(&self, entity: &UserToken) -> Result<UserToken, AppError> {
+        let user_token = sqlx::query_as!(
+            UserToken,
+            "INSERT INTO user_tokens (id, user_id, purpose, token_hash, expires_at, consumed_at, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+            entity.id,
+            entity.user_id,
+            entity.purpose,
+            entity.token_hash,
+            entity.expires_at,
+            entity.consumed_at,
+            entity.created_at
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(user_token)
+    }
+
+    async fn find_by_hash(&self, purpose: &str, token_hash: &str) -> Result<UserToken, AppError> {
+        let user_token = sqlx::query_as!(
+            UserToken,
+            "SELECT * FROM user_tokens WHERE purpose = $1 AND token_hash = $2",
+            purpose,
+            token_hash
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(user_token)
+    }
+
+    async fn consume(&self, id: &str) -> Result<bool, AppError> {
+        let result = sqlx::query!(
+            "UPDATE user_tokens SET consumed_at = NOW() WHERE id = $1 AND consumed_at IS NULL AND expires_at > NOW()",
+            id
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn delete_unconsumed(&self, user_id: &str, purpose: &str) -> Result<(), AppError> {
+        sqlx::query!(
+            "DELETE FROM user_tokens WHERE user_id = $1 AND purpose = $2 AND consumed_at IS NULL",
+            user_id,
+            purpose
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{
+    domain::{
+        entities::{
+            user_oauth_provider::UserOauthProvider, webauthn_credential::WebauthnCredential,
+        },
+        repositories::webauthn_credential_repo::WebauthnCredentialRepository,
+    },
+    infra::errors::app_error::AppError,
+};
+
+#[derive(Clone, Debug)]
+pub struct PgWebauthnCredentialRepository {
+    db_pool: sqlx::PgPool,
+}
+
+impl PgWebauthnCredentialRepository {
+    pub fn new(db_pool: sqlx::PgPool) -> Self {
+        Self { db_pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl WebauthnCredentialRepository for PgWebauthnCredentialRepository {
+    async fn find_by_credential_id(
+        &self,
+        credential_id: &str,
+    ) -> Result<WebauthnCredential, AppError> {
+        let credential = sqlx::query_as!(
+            WebauthnCredential,
+            "SELECT * FROM webauthn_credentials WHERE credential_id = $1",
+            credential_id
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(credential)
+    }
+
+    async fn find_by_user_id(&self, user_id: &str) -> Result<Vec<WebauthnCredential>, AppError> {
+        let credentials = sqlx::query_as!(
+            WebauthnCredential,
+            "SELECT * FROM webauthn_credentials WHERE user_id = $1 ORDER BY created_at",
+            user_id
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(credentials)
+    }
+
+    async fn create_with_link(
+        &self,
+        oauth_provider: &UserOauthProvider,
+        entity: &WebauthnCredential,
+    ) -> Result<WebauthnCredential, AppError> {
+        let mut tx = self.db_pool.begin().await?;
+
+        sqlx::query!(
+            "INSERT INTO user_oauth_providers (id, user_id, provider, provider_user_id) VALUES ($1, $2, $3, $4)",
+            oauth_provider.id,
+            oauth_provider.user_id,
+            oauth_provider.provider,
+            oauth_provider.provider_user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let credential = sqlx::query_as!(
+            WebauthnCredential,
+            "INSERT INTO webauthn_credentials (id, user_id, oauth_provider_id, credential_id, public_key, sign_count, name, created_at, last_used_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING *",
+            entity.id,
+            entity.user_id,
+            entity.oauth_provider_id,
+            entity.credential_id,
+            entity.public_key,
+            entity.sign_count,
+            entity.name,
+            entity.created_at,
+            entity.last_used_at
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(credential)
+    }
+
+    async fn update_sign_count(
+        &self,
+        id: &str,
+        previous_sign_count: i64,
+        sign_count: i64,
+    ) -> Result<bool, AppError> {
+        let result = sqlx::query!(
+            "UPDATE webauthn_credentials SET sign_count = $3, last_used_at = $4 WHERE id = $1 AND sign_count = $2",
+            id,
+            previous_sign_count,
+            sign_count,
+            chrono::Utc::now()
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
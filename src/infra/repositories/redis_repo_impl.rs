@@ -1,9 +1,22 @@
-use bb8_redis::{bb8::Pool, redis::AsyncCommands, RedisConnectionManager};
+use bb8_redis::{
+    bb8::Pool,
+    redis::{self, AsyncCommands},
+    RedisConnectionManager,
+};
 
 use crate::{
     domain::repositories::redis_repo::RedisRepository, infra::errors::app_error::AppError,
 };
 
+// the expiry is only set on a counter without one, so the window starts with the first increment
+const INCREMENT_SCRIPT: &str = r"
+local value = redis.call('INCR', KEYS[1])
+if redis.call('TTL', KEYS[1]) < 0 then
+    redis.call('EXPIRE', KEYS[1], ARGV[1])
+end
+return value
+";
+
 #[derive(Clone, Debug)]
 pub struct RedisRepositoryImpl {
     pool: Pool<RedisConnectionManager>,
@@ -28,7 +41,7 @@ impl RedisRepository for RedisRepositoryImpl {
     async fn set_value(&self, key: &str, value: &str) -> Result<(), AppError> {
         let mut conn = self.pool.get().await.unwrap();
 
-        let _ = conn.set(key, value).await?;
+        let _: () = conn.set(key, value).await?;
 
         Ok(())
     }
@@ -41,7 +54,7 @@ impl RedisRepository for RedisRepositoryImpl {
     ) -> Result<(), AppError> {
         let mut conn = self.pool.get().await.unwrap();
 
-        let _ = conn.set_ex(key, value, expiry).await?;
+        let _: () = conn.set_ex(key, value, expiry).await?;
 
         Ok(())
     }
@@ -49,16 +62,39 @@ impl RedisRepository for RedisRepositoryImpl {
     async fn delete_value(&self, key: &str) -> Result<(), AppError> {
         let mut conn = self.pool.get().await.unwrap();
 
-        let _ = conn.del(key).await?;
+        let _: () = conn.del(key).await?;
 
         Ok(())
     }
 
+    async fn take_value(&self, key: &str) -> Result<Option<String>, AppError> {
+        let mut conn = self.pool.get().await?;
+
+        let value = conn.get_del(key).await?;
+
+        Ok(value)
+    }
+
     async fn set_expiry(&self, key: &str, expiry: i64) -> Result<(), AppError> {
         let mut conn = self.pool.get().await.unwrap();
 
-        let _ = conn.expire(key, expiry).await?;
+        let _: () = conn.expire(key, expiry).await?;
 
         Ok(())
     }
+
+    async fn increment(&self, key: &str, expiry: u64) -> Result<i64, AppError> {
+        let mut conn = self.pool.get().await?;
+
+        // one script so the counter can't be left without an expiry between the two commands
+        let value: i64 = redis::cmd("EVAL")
+            .arg(INCREMENT_SCRIPT)
+            .arg(1)
+            .arg(key)
+            .arg(expiry)
+            .query_async(&mut *conn)
+            .await?;
+
+        Ok(value)
+    }
 }
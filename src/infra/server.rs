@@ -1,11 +1,12 @@
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc};
 
 use axum::{
     http::{
         header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE},
         HeaderValue, Method,
     },
-    Router,
+    middleware::from_fn_with_state,
+    Extension, Router,
 };
 use casbin::{CoreApi, DefaultModel, Enforcer};
 use sqlx_adapter::SqlxAdapter;
@@ -15,11 +16,16 @@ use tracing::{debug, info};
 
 use crate::{
     application::state::AppState,
-    infra::{graceful::shutdown_signal, rbac::Rbac},
+    infra::{graceful::shutdown_signal, rbac::Rbac, utils::client_info::TrustedProxies},
     interface::api::{
         auth_handler::setup_auth_routes, permission_handler::setup_permission_handler,
         public_oauth_handler::setup_public_oauth_handler, role_handler::setup_role_routes,
-        super_handler::setup_super_handler,
+        super_handler::setup_super_handler, user_handler::setup_user_routes,
+        well_known_handler::setup_well_known_handler,
+    },
+    interface::middleware::rate_limit_mw::{
+        rate_limit, RateLimit, RateLimitKey, RateLimiter, RATELIMIT_LIMIT, RATELIMIT_REMAINING,
+        RATELIMIT_RESET,
     },
 };
 
@@ -63,9 +69,28 @@ impl ServerBuilder {
 
         let app_state = Arc::new(AppState::new(self.cfg.clone(), db_pool, redis_pool, rbac));
 
+        // mirror user roles into casbin grouping policies
+        app_state
+            .uc
+            .role
+            .sync_user_roles
+            .execute()
+            .await
+            .expect("Failed to sync user roles into casbin");
+
+        // hash and encrypt session tokens left in plaintext or under an old key
+        app_state
+            .uc
+            .auth
+            .seal_session_tokens
+            .execute()
+            .await
+            .expect("Failed to seal session tokens");
+
         let app_router = self
             .setup_router(app_state.clone())
             .layer(self.setup_cors())
+            .layer(Extension(TrustedProxies::from_config(&self.cfg)))
             .with_state(app_state);
 
         // Run Server
@@ -75,19 +100,57 @@ impl ServerBuilder {
             .expect("Failed to bind address");
 
         debug!("🚀 API Started on {}", addr);
-        axum::serve(listener, app_router.into_make_service())
-            .with_graceful_shutdown(shutdown_signal())
-            .await
-            .expect("API Server Error");
+        axum::serve(
+            listener,
+            app_router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(shutdown_signal())
+        .await
+        .expect("API Server Error");
     }
 
     fn setup_router(&self, app_state: Arc<AppState>) -> Router<Arc<AppState>> {
+        // each group keeps its own counters, a route layer runs once the route matched
+        let oauth_limit = RateLimit::from_spec(&self.cfg.rate_limit_oauth, RateLimitKey::Ip);
+        let api_limit = RateLimit::from_spec(&self.cfg.rate_limit_api, RateLimitKey::User);
+        let limited = |router: Router<Arc<AppState>>, group: &'static str, limit: &RateLimit| {
+            router.route_layer(from_fn_with_state(
+                RateLimiter::new(
+                    app_state.svc.redis.clone(),
+                    app_state.jwt_maker.clone(),
+                    group,
+                    limit.clone(),
+                ),
+                rate_limit,
+            ))
+        };
+
         Router::new()
-            .nest("/api/v1/permissions", setup_permission_handler())
-            .nest("/api/v1/roles", setup_role_routes(app_state.clone()))
-            .nest("/oauth", setup_public_oauth_handler())
-            .nest("/api/v1/auth", setup_auth_routes(app_state.clone()))
-            .nest("/api/v1/super", setup_super_handler(app_state.clone()))
+            .nest(
+                "/api/v1/permissions",
+                limited(setup_permission_handler(), "permissions", &api_limit),
+            )
+            .nest(
+                "/api/v1/roles",
+                limited(setup_role_routes(app_state.clone()), "roles", &api_limit),
+            )
+            .nest(
+                "/oauth",
+                limited(setup_public_oauth_handler(), "oauth", &oauth_limit),
+            )
+            .nest(
+                "/api/v1/auth",
+                limited(setup_auth_routes(app_state.clone()), "auth", &api_limit),
+            )
+            .nest(
+                "/api/v1/super",
+                limited(setup_super_handler(app_state.clone()), "super", &api_limit),
+            )
+            .nest(
+                "/api/v1/users",
+                limited(setup_user_routes(app_state.clone()), "users", &api_limit),
+            )
+            .nest("/.well-known", setup_well_known_handler())
     }
 
     fn setup_cors(&self) -> CorsLayer {
@@ -111,6 +174,7 @@ impl ServerBuilder {
             ])
             .allow_credentials(true)
             .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE])
+            .expose_headers([RATELIMIT_LIMIT, RATELIMIT_REMAINING, RATELIMIT_RESET])
     }
 
     async fn setup_casbin(&self) -> Enforcer {
@@ -0,0 +1,141 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header, request::Parts},
+};
+
+use crate::infra::{config::AppConfig, errors::app_error::AppError};
+
+// information about the device a request comes from, used to describe user sessions
+#[derive(Clone, Debug, Default)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+// proxies whose forwarded headers are believed, anyone else could put any address in them
+#[derive(Clone, Debug, Default)]
+pub struct TrustedProxies(Vec<(IpAddr, u8)>);
+
+impl TrustedProxies {
+    pub fn from_config(cfg: &AppConfig) -> Self {
+        let proxies = cfg
+            .trusted_proxies
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|proxy| !proxy.is_empty())
+            .map(|proxy| {
+                Self::parse(proxy)
+                    .unwrap_or_else(|| panic!("TRUSTED_PROXIES {} is not an ip or cidr", proxy))
+            })
+            .collect();
+
+        Self(proxies)
+    }
+
+    // `10.0.0.1` or `10.0.0.0/8`
+    fn parse(proxy: &str) -> Option<(IpAddr, u8)> {
+        let (ip, prefix) = match proxy.split_once('/') {
+            Some((ip, prefix)) => (ip.parse::<IpAddr>().ok()?, Some(prefix.parse::<u8>().ok()?)),
+            None => (proxy.parse::<IpAddr>().ok()?, None),
+        };
+        let max_prefix = if ip.is_ipv4() { 32 } else { 128 };
+        let prefix = prefix.unwrap_or(max_prefix);
+
+        (prefix <= max_prefix).then_some((ip.to_canonical(), prefix))
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        let ip = ip.to_canonical();
+
+        self.0.iter().any(|(network, prefix)| match (network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - *prefix as u32).unwrap_or(0);
+                u32::from(*network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - *prefix as u32).unwrap_or(0);
+                u128::from(*network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        })
+    }
+
+    // the peer unless it's one of our proxies, then the nearest hop they forwarded for
+    // that isn't a proxy of ours too
+    fn client_ip(&self, peer: IpAddr, parts: &Parts) -> IpAddr {
+        if !self.contains(&peer) {
+            return peer;
+        }
+
+        let forwarded_for = parts
+            .headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .collect::<Vec<_>>();
+
+        if forwarded_for.is_empty() {
+            return parts
+                .headers
+                .get("x-real-ip")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<IpAddr>().ok())
+                .unwrap_or(peer);
+        }
+
+        let mut client = peer;
+        for hop in forwarded_for.iter().rev() {
+            // whatever comes before a broken entry can't be told apart from made up ones
+            let Ok(hop) = hop.parse::<IpAddr>() else {
+                break;
+            };
+
+            client = hop;
+            if !self.contains(&hop) {
+                break;
+            }
+        }
+
+        client
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let user_agent = parts
+            .headers
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+
+        // the peer address, proxy headers only count when the peer is a trusted proxy
+        let trusted_proxies = parts
+            .extensions
+            .get::<TrustedProxies>()
+            .cloned()
+            .unwrap_or_default();
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip().to_canonical());
+        let ip_address = peer.map(|peer| trusted_proxies.client_ip(peer, parts).to_string());
+
+        Ok(Self {
+            user_agent,
+            ip_address,
+        })
+    }
+}
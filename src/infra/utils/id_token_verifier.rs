@@ -0,0 +1,169 @@
+use std::sync::{Arc, RwLock};
+
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use reqwest::Client;
+use serde::Deserialize;
+use tokio::time::{Duration, Instant};
+
+use crate::{
+    application::dto::auth::jwt_claims::IdTokenClaims,
+    infra::{
+        errors::app_error::AppError,
+        oauth2::google::{GOOGLE_ISSUERS, GOOGLE_JWKS_ENDPOINT},
+    },
+};
+
+// verifies openid connect id tokens against the provider's published jwks
+#[derive(Clone)]
+pub struct IdTokenVerifier {
+    client_id: String,
+    issuers: Vec<String>,
+    jwks_uri: String,
+    http: Client,
+    jwks_cache: Arc<RwLock<Option<JwksCache>>>,
+}
+
+#[derive(Deserialize, Clone)]
+struct Jwk {
+    kid: String,
+    kty: String,
+    // rsa keys
+    n: Option<String>,
+    e: Option<String>,
+    // ec keys
+    x: Option<String>,
+    y: Option<String>,
+}
+
+#[derive(Deserialize, Clone)]
+struct Jwks {
+    keys: Vec<Jwk>,
+}
+
+struct JwksCache {
+    jwks: Jwks,
+    fetched_at: Instant,
+}
+
+impl IdTokenVerifier {
+    pub fn new(client_id: String, issuers: Vec<String>, jwks_uri: String) -> Self {
+        Self {
+            client_id,
+            issuers,
+            jwks_uri,
+            http: Client::new(),
+            jwks_cache: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub fn google(client_id: String) -> Self {
+        Self::new(
+            client_id,
+            GOOGLE_ISSUERS.iter().map(|iss| iss.to_string()).collect(),
+            GOOGLE_JWKS_ENDPOINT.to_string(),
+        )
+    }
+
+    pub async fn verify_token(&self, token: &str) -> Result<IdTokenClaims, AppError> {
+        let header = decode_header(token)?;
+        let kid = match header.kid {
+            Some(kid) => kid,
+            None => return Err(AppError::InvalidToken),
+        };
+
+        // providers rotate their keys, an unknown kid means our cached set is outdated
+        let jwk = match self.find_jwk_by_kid(&self.get_jwks(false).await?, &kid) {
+            Some(jwk) => jwk,
+            None => self
+                .find_jwk_by_kid(&self.get_jwks(true).await?, &kid)
+                .ok_or(AppError::InvalidToken)?,
+        };
+
+        let decoding_key = decoding_key(&jwk, header.alg)?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_audience(&[&self.client_id]);
+        validation.set_issuer(&self.issuers);
+
+        let decoded = decode::<IdTokenClaims>(token, &decoding_key, &validation)?;
+
+        Ok(decoded.claims)
+    }
+
+    async fn get_jwks(&self, force_refresh: bool) -> Result<Jwks, AppError> {
+        {
+            let cache = self
+                .jwks_cache
+                .read()
+                .map_err(|err| AppError::ProcessError(err.to_string()))?;
+
+            if let Some(cached) = &*cache {
+                // forced refreshes are throttled so made up kids can't hammer the provider
+                let fresh_enough = if force_refresh {
+                    cached.fetched_at + Duration::from_secs(60) > Instant::now()
+                } else {
+                    cached.fetched_at + Duration::from_secs(3600) > Instant::now()
+                    // Cache for 1 hour
+                };
+
+                if fresh_enough {
+                    return Ok(cached.jwks.clone());
+                }
+            }
+        }
+
+        let fresh_jwks = self.fetch_jwks().await?;
+
+        {
+            let mut cache = self
+                .jwks_cache
+                .write()
+                .map_err(|err| AppError::ProcessError(err.to_string()))?; // Acquire write lock
+            *cache = Some(JwksCache {
+                jwks: fresh_jwks.clone(),
+                fetched_at: Instant::now(),
+            });
+        }
+
+        Ok(fresh_jwks)
+    }
+
+    async fn fetch_jwks(&self) -> Result<Jwks, AppError> {
+        let res = self
+            .http
+            .get(&self.jwks_uri)
+            .send()
+            .await?
+            .json::<Jwks>()
+            .await?;
+
+        Ok(res)
+    }
+
+    fn find_jwk_by_kid(&self, jwks: &Jwks, kid: &str) -> Option<Jwk> {
+        jwks.keys.iter().find(|key| key.kid == kid).cloned()
+    }
+}
+
+// only asymmetric algorithms matching the key type are accepted, never `none` or HMAC
+fn decoding_key(jwk: &Jwk, alg: Algorithm) -> Result<DecodingKey, AppError> {
+    match (jwk.kty.as_str(), alg) {
+        (
+            "RSA",
+            Algorithm::RS256
+            | Algorithm::RS384
+            | Algorithm::RS512
+            | Algorithm::PS256
+            | Algorithm::PS384
+            | Algorithm::PS512,
+        ) => match (&jwk.n, &jwk.e) {
+            (Some(n), Some(e)) => Ok(DecodingKey::from_rsa_components(n, e)?),
+            _ => Err(AppError::InvalidToken),
+        },
+        ("EC", Algorithm::ES256 | Algorithm::ES384) => match (&jwk.x, &jwk.y) {
+            (Some(x), Some(y)) => Ok(DecodingKey::from_ec_components(x, y)?),
+            _ => Err(AppError::InvalidToken),
+        },
+        _ => Err(AppError::InvalidToken),
+    }
+}
@@ -1,57 +1,172 @@
+use std::{collections::HashMap, fs, sync::Arc};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{
+    decode, decode_header,
+    errors::ErrorKind,
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+        OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    },
+    Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use rsa::{pkcs8::DecodePublicKey, traits::PublicKeyParts, RsaPublicKey};
+use serde::de::DeserializeOwned;
 use tracing::error;
+use uuid::Uuid;
+
+use crate::infra::config::AppConfig;
+
+const RSA_OID: &str = "1.2.840.113549.1.1.1";
+const ED25519_OID: &str = "1.3.101.112";
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Claims {
     pub exp: usize,
     pub iat: usize,
     pub sub: String,
+    pub sid: String, // session id, one session per login
     pub iss: String,
+    #[serde(default)]
+    pub aud: String,
     pub name: String,
     pub roles: Vec<String>,
     pub scopes: Vec<String>,
 }
 
+// user details embedded in access tokens so other services can authorize without calling us
+#[derive(Debug, Clone, Default)]
+pub struct TokenProfile {
+    pub name: String,
+    pub roles: Vec<String>,
+    pub scopes: Vec<String>, // "object:action"
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct RefreshTokenClaims {
     pub exp: usize,
     pub iat: usize,
     pub sub: String,
+    pub sid: String,
     pub iss: String,
+    #[serde(default)]
+    pub aud: String,
+    pub jti: String, // refreshing twice within a second must still give a new token
+}
+
+#[derive(serde::Deserialize)]
+struct UnverifiedIssuer {
+    iss: String,
 }
 
-#[derive(Clone, Debug)]
+struct VerificationKey {
+    algorithm: Algorithm,
+    key: DecodingKey,
+}
+
+// signs with the current private key, verifies with every configured public key
+// so tokens signed by a rotated key stay valid until they expire
+#[derive(Clone)]
 pub struct JwtMaker {
-    secret: String,
+    issuer: String,
+    audience: String,
+    signing_kid: String,
+    signing_algorithm: Algorithm,
+    signing_key: EncodingKey,
+    verification_keys: Arc<HashMap<String, VerificationKey>>,
+    jwks: JwkSet,
 }
 
 impl JwtMaker {
-    pub fn new(secret: String) -> Self {
-        Self { secret }
+    pub fn new(cfg: &AppConfig) -> Self {
+        let mut verification_keys = HashMap::new();
+        let mut jwks = JwkSet { keys: vec![] };
+
+        // JWT_VERIFICATION_KEYS=kid:path/to/public.pem,kid2:path/to/public2.pem
+        for (kid, path) in parse_key_list(&cfg.jwt_verification_keys) {
+            let jwk = read_public_jwk(&kid, &path);
+            let algorithm = match jwk.algorithm {
+                AlgorithmParameters::RSA(_) => Algorithm::RS256,
+                _ => Algorithm::EdDSA,
+            };
+            let key = DecodingKey::from_jwk(&jwk)
+                .unwrap_or_else(|err| panic!("Invalid JWT verification key {}: {}", kid, err));
+
+            verification_keys.insert(kid, VerificationKey { algorithm, key });
+            jwks.keys.push(jwk);
+        }
+
+        // JWT_SIGNING_KEY=kid:path/to/private.pem
+        let (signing_kid, path) = parse_key_list(&cfg.jwt_signing_key)
+            .into_iter()
+            .next()
+            .expect("JWT_SIGNING_KEY must look like <kid>:<path to private pem>");
+        let signing_algorithm = verification_keys
+            .get(&signing_kid)
+            .map(|key| key.algorithm)
+            .unwrap_or_else(|| {
+                panic!(
+                    "JWT_SIGNING_KEY kid {} is not in JWT_VERIFICATION_KEYS",
+                    signing_kid
+                )
+            });
+        let pem = fs::read(&path)
+            .unwrap_or_else(|err| panic!("Failed to read JWT signing key {}: {}", path, err));
+        let signing_key = match signing_algorithm {
+            Algorithm::RS256 => EncodingKey::from_rsa_pem(&pem),
+            _ => EncodingKey::from_ed_pem(&pem),
+        }
+        .unwrap_or_else(|err| panic!("Invalid JWT signing key {}: {}", path, err));
+
+        Self {
+            issuer: cfg.jwt_issuer.clone(),
+            audience: cfg.jwt_audience.clone(),
+            signing_kid,
+            signing_algorithm,
+            signing_key,
+            verification_keys: Arc::new(verification_keys),
+            jwks,
+        }
+    }
+
+    // public keys for the jwks endpoint
+    pub fn jwks(&self) -> &JwkSet {
+        &self.jwks
+    }
+
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    fn header(&self) -> Header {
+        let mut header = Header::new(self.signing_algorithm);
+        header.kid = Some(self.signing_kid.clone());
+
+        header
     }
 
     pub fn make_token(
         &self,
         user_id: String,
+        session_id: String,
+        profile: TokenProfile,
         expiration_hours: i64,
     ) -> Result<String, jsonwebtoken::errors::Error> {
-        // create claims with expiration time for 7 days
         let now = chrono::Utc::now();
         let exp = now + chrono::Duration::hours(expiration_hours);
         let claims = Claims {
             exp: exp.timestamp() as usize,
             iat: now.timestamp() as usize,
             sub: user_id.clone(),
-            iss: "API_NAME".to_owned(),
-            name: String::default(),
-            roles: vec![],
-            scopes: vec![],
+            sid: session_id,
+            iss: self.issuer.clone(),
+            aud: self.audience.clone(),
+            name: profile.name,
+            roles: profile.roles,
+            scopes: profile.scopes,
         };
 
-        let token = jsonwebtoken::encode(
-            &jsonwebtoken::Header::default(),
-            &claims,
-            &jsonwebtoken::EncodingKey::from_secret(self.secret.as_bytes()),
-        )?;
+        let token = jsonwebtoken::encode(&self.header(), &claims, &self.signing_key)?;
 
         Ok(token)
     }
@@ -59,6 +174,7 @@ impl JwtMaker {
     pub fn make_refresh_token(
         &self,
         user_id: String,
+        session_id: String,
         expiration_hours: i64,
     ) -> Result<String, jsonwebtoken::errors::Error> {
         // create claims with expiration time for 7 days
@@ -68,42 +184,43 @@ impl JwtMaker {
             exp: exp.timestamp() as usize,
             iat: now.timestamp() as usize,
             sub: user_id.clone(),
-            iss: "API_NAME".to_owned(),
+            sid: session_id,
+            iss: self.issuer.clone(),
+            aud: self.audience.clone(),
+            jti: Uuid::new_v4().to_string(),
         };
 
-        let token = jsonwebtoken::encode(
-            &jsonwebtoken::Header::default(),
-            &claims,
-            &jsonwebtoken::EncodingKey::from_secret(self.secret.as_bytes()),
-        )?;
+        let token = jsonwebtoken::encode(&self.header(), &claims, &self.signing_key)?;
 
         Ok(token)
     }
 
     pub fn verify_access_token(&self, token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
-        let claims = jsonwebtoken::decode::<Claims>(
-            token,
-            &jsonwebtoken::DecodingKey::from_secret(self.secret.as_bytes()),
-            &jsonwebtoken::Validation::default(),
-        )
-        .map_err(|err| {
-            error!("[JWT->verify_token] Failed to verify token: {}", err);
-            err
-        })?;
-
-        Ok(claims.claims)
+        self.verify::<Claims>(token)
     }
 
     pub fn verify_refresh_token(
         &self,
         token: &str,
     ) -> Result<RefreshTokenClaims, jsonwebtoken::errors::Error> {
-        let claims = jsonwebtoken::decode::<RefreshTokenClaims>(
-            token,
-            &jsonwebtoken::DecodingKey::from_secret(self.secret.as_bytes()),
-            &jsonwebtoken::Validation::default(),
-        )
-        .map_err(|err| {
+        self.verify::<RefreshTokenClaims>(token)
+    }
+
+    fn verify<T: DeserializeOwned>(&self, token: &str) -> Result<T, jsonwebtoken::errors::Error> {
+        let header = decode_header(token)?;
+
+        // tokens without a kid were signed with the old HS256 secret & are no longer accepted
+        let kid = header.kid.ok_or(ErrorKind::InvalidToken)?;
+        let key = self
+            .verification_keys
+            .get(&kid)
+            .ok_or(jsonwebtoken::errors::Error::from(ErrorKind::InvalidToken))?;
+
+        let mut validation = Validation::new(key.algorithm);
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_audience(&[&self.audience]);
+
+        let claims = decode::<T>(token, &key.key, &validation).map_err(|err| {
             error!("[JWT->verify_token] Failed to verify token: {}", err);
             err
         })?;
@@ -111,3 +228,72 @@ impl JwtMaker {
         Ok(claims.claims)
     }
 }
+
+// the unverified `iss` claim, only meant to pick the verifier for a token,
+// the verifier itself still checks the signature & issuer
+pub fn unverified_issuer(token: &str) -> Option<String> {
+    let payload = token.split('.').nth(1)?;
+    let payload = URL_SAFE_NO_PAD.decode(payload).ok()?;
+    let claims = serde_json::from_slice::<UnverifiedIssuer>(&payload).ok()?;
+
+    Some(claims.iss)
+}
+
+fn parse_key_list(value: &str) -> Vec<(String, String)> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (kid, path) = entry
+                .split_once(':')
+                .unwrap_or_else(|| panic!("JWT key entry {} must look like <kid>:<path>", entry));
+
+            (kid.to_string(), path.to_string())
+        })
+        .collect()
+}
+
+fn read_public_jwk(kid: &str, path: &str) -> Jwk {
+    let content = fs::read(path)
+        .unwrap_or_else(|err| panic!("Failed to read JWT verification key {}: {}", path, err));
+    let pem = pem::parse(content)
+        .unwrap_or_else(|err| panic!("Invalid JWT verification key {}: {}", path, err));
+    let spki = spki::SubjectPublicKeyInfoRef::try_from(pem.contents())
+        .unwrap_or_else(|err| panic!("Invalid JWT verification key {}: {}", path, err));
+
+    let (key_algorithm, algorithm) = match spki.algorithm.oid.to_string().as_str() {
+        RSA_OID => {
+            let key = RsaPublicKey::from_public_key_der(pem.contents())
+                .unwrap_or_else(|err| panic!("Invalid RSA key {}: {}", path, err));
+
+            (
+                KeyAlgorithm::RS256,
+                AlgorithmParameters::RSA(RSAKeyParameters {
+                    key_type: RSAKeyType::RSA,
+                    n: URL_SAFE_NO_PAD.encode(key.n().to_bytes_be()),
+                    e: URL_SAFE_NO_PAD.encode(key.e().to_bytes_be()),
+                }),
+            )
+        }
+        ED25519_OID => (
+            KeyAlgorithm::EdDSA,
+            AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                key_type: OctetKeyPairType::OctetKeyPair,
+                curve: EllipticCurve::Ed25519,
+                x: URL_SAFE_NO_PAD.encode(spki.subject_public_key.raw_bytes()),
+            }),
+        ),
+        oid => panic!("Unsupported JWT key algorithm {} in {}", oid, path),
+    };
+
+    Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(key_algorithm),
+            key_id: Some(kid.to_string()),
+            ..Default::default()
+        },
+        algorithm,
+    }
+}
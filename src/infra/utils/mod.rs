@@ -1,5 +1,11 @@
-pub mod google_jwt;
+pub mod client_info;
+pub mod id_token_verifier;
 pub mod jwt_maker;
 pub mod pagination;
 pub mod password;
 pub mod response;
+pub mod return_to;
+pub mod token_cipher;
+pub mod token_hash;
+pub mod totp;
+pub mod webauthn;
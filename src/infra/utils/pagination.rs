@@ -19,4 +19,5 @@ pub struct PaginationMeta {
 pub struct PaginationQuery {
     pub limit: Option<i64>,
     pub page: Option<i64>,
+    pub search: Option<String>,
 }
@@ -1,8 +1,12 @@
+use std::sync::OnceLock;
+
 use argon2::{
     password_hash::{rand_core::OsRng, SaltString},
     Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
 };
 
+use super::token_hash::random_token;
+
 pub fn hash_password(password: &[u8]) -> Result<String, argon2::password_hash::Error> {
     let salt = SaltString::generate(&mut OsRng);
 
@@ -18,3 +22,13 @@ pub fn verify_password(hash: &str, password: &[u8]) -> Result<(), argon2::passwo
 
     Argon2::default().verify_password(password, &parsed_hash)
 }
+
+// hash of a random password, checked when there's no real hash so unknown emails & users
+// without a password take as long as a wrong password
+pub fn dummy_password_hash() -> &'static str {
+    static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+
+    DUMMY_HASH.get_or_init(|| {
+        hash_password(random_token().as_bytes()).expect("Failed to hash the dummy password")
+    })
+}
@@ -0,0 +1,45 @@
+use crate::infra::{config::AppConfig, errors::app_error::AppError};
+
+// origins the login flow may send the browser back to, anything else would be an open redirect
+#[derive(Clone, Debug, Default)]
+pub struct ReturnToAllowlist {
+    origins: Vec<String>,
+}
+
+impl ReturnToAllowlist {
+    pub fn from_config(cfg: &AppConfig) -> Self {
+        let origins = cfg
+            .return_to_origins
+            .clone()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|origin| !origin.is_empty())
+            .map(|origin| {
+                reqwest::Url::parse(origin)
+                    .map(|url| url.origin().ascii_serialization())
+                    .unwrap_or_else(|_| panic!("RETURN_TO_ORIGINS {} is not a valid url", origin))
+            })
+            .collect();
+
+        Self { origins }
+    }
+
+    // only absolute http(s) urls on an allowed origin, compared after parsing so
+    // tricks like `https://app.example.com@evil.com` don't slip through
+    pub fn check(&self, return_to: &str) -> Result<String, AppError> {
+        let url = reqwest::Url::parse(return_to)
+            .map_err(|_| AppError::InvalidReturnTo(return_to.to_string()))?;
+
+        let allowed = matches!(url.scheme(), "http" | "https")
+            && self
+                .origins
+                .iter()
+                .any(|origin| *origin == url.origin().ascii_serialization());
+        if !allowed {
+            return Err(AppError::InvalidReturnTo(return_to.to_string()));
+        }
+
+        Ok(url.to_string())
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::infra::{config::AppConfig, errors::app_error::AppError};
+
+use super::token_hash::hash_token;
+
+const NONCE_SIZE: usize = 12;
+
+// keyed hashes for our own tokens and versioned AES-GCM encryption for provider tokens,
+// encrypted values are stored as `v{version}:{base64(nonce + ciphertext)}`
+#[derive(Clone)]
+pub struct TokenCipher {
+    hash_key: Vec<u8>,
+    keys: HashMap<i32, Key<Aes256Gcm>>,
+    current_version: i32,
+}
+
+impl TokenCipher {
+    pub fn new(cfg: &AppConfig) -> Self {
+        let mut keys = HashMap::new();
+
+        // TOKEN_ENCRYPTION_KEYS=1:base64key,2:base64key
+        for entry in cfg.token_encryption_keys.split(',').map(str::trim) {
+            let (version, key) = entry
+                .split_once(':')
+                .expect("TOKEN_ENCRYPTION_KEYS entries must look like <version>:<base64 key>");
+            let version = version
+                .parse::<i32>()
+                .expect("TOKEN_ENCRYPTION_KEYS version must be a number");
+            let key = STANDARD
+                .decode(key)
+                .expect("TOKEN_ENCRYPTION_KEYS key must be base64 encoded");
+
+            if key.len() != 32 {
+                panic!(
+                    "TOKEN_ENCRYPTION_KEYS key version {} must be 32 bytes",
+                    version
+                );
+            }
+
+            keys.insert(version, *Key::<Aes256Gcm>::from_slice(&key));
+        }
+
+        if !keys.contains_key(&cfg.token_encryption_key_version) {
+            panic!(
+                "TOKEN_ENCRYPTION_KEY_VERSION {} is not in TOKEN_ENCRYPTION_KEYS",
+                cfg.token_encryption_key_version
+            );
+        }
+
+        Self {
+            hash_key: cfg.token_hash_key.as_bytes().to_vec(),
+            keys,
+            current_version: cfg.token_encryption_key_version,
+        }
+    }
+
+    pub fn current_version(&self) -> i32 {
+        self.current_version
+    }
+
+    pub fn hash(&self, token: &str) -> String {
+        self.keyed_digest(&hash_token(token))
+    }
+
+    // the key is applied over the plain sha256 digest, so digests stored before
+    // keyed hashing can be upgraded without knowing the token
+    pub fn keyed_digest(&self, digest: &str) -> String {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&self.hash_key)
+            .expect("HMAC accepts any key length");
+        mac.update(digest.as_bytes());
+
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, AppError> {
+        let cipher = Aes256Gcm::new(&self.keys[&self.current_version]);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| AppError::ProcessError("Failed to encrypt token".to_string()))?;
+
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+
+        Ok(format!(
+            "v{}:{}",
+            self.current_version,
+            STANDARD.encode(payload)
+        ))
+    }
+
+    pub fn decrypt(&self, value: &str) -> Result<String, AppError> {
+        let invalid = || AppError::ProcessError("Invalid encrypted token".to_string());
+
+        let (version, payload) = value
+            .strip_prefix('v')
+            .and_then(|value| value.split_once(':'))
+            .ok_or_else(invalid)?;
+        let version = version.parse::<i32>().map_err(|_| invalid())?;
+        let key = self.keys.get(&version).ok_or_else(|| {
+            AppError::ProcessError(format!(
+                "Token encryption key version {} is missing",
+                version
+            ))
+        })?;
+
+        let payload = STANDARD.decode(payload).map_err(|_| invalid())?;
+        if payload.len() < NONCE_SIZE {
+            return Err(invalid());
+        }
+        let (nonce, ciphertext) = payload.split_at(NONCE_SIZE);
+
+        let plaintext = Aes256Gcm::new(key)
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| invalid())?;
+
+        String::from_utf8(plaintext).map_err(|_| invalid())
+    }
+}
@@ -0,0 +1,317 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ciborium::value::Value;
+use p256::{
+    ecdsa::{signature::Verifier, Signature as EcdsaSignature, VerifyingKey as EcdsaVerifyingKey},
+    EncodedPoint,
+};
+use rsa::{
+    pkcs1v15::{Signature as RsaSignature, VerifyingKey as RsaVerifyingKey},
+    BigUint, RsaPublicKey,
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::infra::{config::AppConfig, errors::app_error::AppError};
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+
+pub const COSE_ALG_ES256: i64 = -7;
+pub const COSE_ALG_RS256: i64 = -257;
+
+#[derive(Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    kind: String,
+    challenge: String,
+    origin: String,
+}
+
+struct AuthenticatorData {
+    rp_id_hash: Vec<u8>,
+    flags: u8,
+    sign_count: u32,
+    credential: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+pub struct RegisteredCredential {
+    // base64url, the same value browsers send as `id`
+    pub credential_id: String,
+    // COSE encoded, kept as is so it can be parsed again for every login
+    pub public_key: Vec<u8>,
+    pub sign_count: i64,
+}
+
+// minimal relying party for passkeys (WebAuthn level 2), attestation statements are not
+// checked since we ask for `none`, user verification is always required
+#[derive(Clone, Debug)]
+pub struct WebauthnVerifier {
+    rp_id: String,
+    rp_name: String,
+    origins: Vec<String>,
+}
+
+impl WebauthnVerifier {
+    pub fn from_config(cfg: &AppConfig) -> Self {
+        let origins = cfg
+            .webauthn_origins
+            .split(',')
+            .map(str::trim)
+            .filter(|origin| !origin.is_empty())
+            .map(|origin| {
+                reqwest::Url::parse(origin)
+                    .map(|url| url.origin().ascii_serialization())
+                    .unwrap_or_else(|_| panic!("WEBAUTHN_ORIGINS {} is not a valid url", origin))
+            })
+            .collect();
+
+        Self {
+            rp_id: cfg.webauthn_rp_id.clone(),
+            rp_name: cfg
+                .webauthn_rp_name
+                .clone()
+                .unwrap_or_else(|| cfg.app_name.clone()),
+            origins,
+        }
+    }
+
+    pub fn rp_id(&self) -> &str {
+        &self.rp_id
+    }
+
+    pub fn rp_name(&self) -> &str {
+        &self.rp_name
+    }
+
+    // the challenge a login answered, needed to find its state before anything is verified
+    pub fn client_challenge(&self, client_data_json: &str) -> Result<String, AppError> {
+        let client_data: ClientData = serde_json::from_slice(&decode(client_data_json)?)
+            .map_err(|_| invalid("malformed client data"))?;
+
+        Ok(client_data.challenge)
+    }
+
+    pub fn verify_registration(
+        &self,
+        challenge: &str,
+        client_data_json: &str,
+        attestation_object: &str,
+    ) -> Result<RegisteredCredential, AppError> {
+        self.verify_client_data(&decode(client_data_json)?, "webauthn.create", challenge)?;
+
+        let attestation: Value = ciborium::de::from_reader(decode(attestation_object)?.as_slice())
+            .map_err(|_| invalid("malformed attestation object"))?;
+        let auth_data = match map_get(&attestation, &Value::Text("authData".to_string())) {
+            Some(Value::Bytes(auth_data)) => auth_data,
+            _ => return Err(invalid("attestation object without authenticator data")),
+        };
+
+        let auth_data = self.verify_authenticator_data(auth_data)?;
+        let (credential_id, public_key) = auth_data
+            .credential
+            .ok_or(invalid("no credential was created"))?;
+
+        // refuse keys we could never verify a login with
+        CoseKey::parse(&public_key)?;
+
+        Ok(RegisteredCredential {
+            credential_id: URL_SAFE_NO_PAD.encode(credential_id),
+            public_key,
+            sign_count: auth_data.sign_count as i64,
+        })
+    }
+
+    // returns the sign count to store for the credential
+    pub fn verify_assertion(
+        &self,
+        challenge: &str,
+        client_data_json: &str,
+        authenticator_data: &str,
+        signature: &str,
+        public_key: &[u8],
+        stored_sign_count: i64,
+    ) -> Result<i64, AppError> {
+        let client_data_json = decode(client_data_json)?;
+        self.verify_client_data(&client_data_json, "webauthn.get", challenge)?;
+
+        let raw_auth_data = decode(authenticator_data)?;
+        let auth_data = self.verify_authenticator_data(&raw_auth_data)?;
+
+        let mut message = raw_auth_data;
+        message.extend_from_slice(&Sha256::digest(&client_data_json));
+        CoseKey::parse(public_key)?.verify(&message, &decode(signature)?)?;
+
+        // authenticators without a counter always send 0, otherwise it has to move forward
+        // or the credential was cloned
+        let sign_count = auth_data.sign_count as i64;
+        if (sign_count != 0 || stored_sign_count != 0) && sign_count <= stored_sign_count {
+            return Err(invalid("sign count did not increase"));
+        }
+
+        Ok(sign_count)
+    }
+
+    fn verify_client_data(
+        &self,
+        client_data_json: &[u8],
+        kind: &str,
+        challenge: &str,
+    ) -> Result<(), AppError> {
+        let client_data: ClientData = serde_json::from_slice(client_data_json)
+            .map_err(|_| invalid("malformed client data"))?;
+
+        if client_data.kind != kind {
+            return Err(invalid("unexpected ceremony type"));
+        }
+        if client_data.challenge != challenge {
+            return Err(invalid("challenge does not match"));
+        }
+        if !self.origins.contains(&client_data.origin) {
+            return Err(invalid("origin is not allowed"));
+        }
+
+        Ok(())
+    }
+
+    fn verify_authenticator_data(&self, raw: &[u8]) -> Result<AuthenticatorData, AppError> {
+        let auth_data = parse_authenticator_data(raw)?;
+
+        if auth_data.rp_id_hash != Sha256::digest(self.rp_id.as_bytes()).as_slice() {
+            return Err(invalid("credential belongs to another relying party"));
+        }
+        if auth_data.flags & FLAG_USER_PRESENT == 0 {
+            return Err(invalid("user was not present"));
+        }
+        if auth_data.flags & FLAG_USER_VERIFIED == 0 {
+            return Err(invalid("user was not verified"));
+        }
+
+        Ok(auth_data)
+    }
+}
+
+// rpIdHash (32) | flags (1) | signCount (4) | [aaguid (16) | idLength (2) | id | COSE key]
+fn parse_authenticator_data(raw: &[u8]) -> Result<AuthenticatorData, AppError> {
+    if raw.len() < 37 {
+        return Err(invalid("authenticator data is too short"));
+    }
+
+    let flags = raw[32];
+    let sign_count = u32::from_be_bytes([raw[33], raw[34], raw[35], raw[36]]);
+
+    let credential = if flags & FLAG_ATTESTED_CREDENTIAL != 0 {
+        let rest = &raw[37..];
+        if rest.len() < 18 {
+            return Err(invalid("attested credential data is too short"));
+        }
+
+        let id_length = u16::from_be_bytes([rest[16], rest[17]]) as usize;
+        let rest = &rest[18..];
+        if rest.len() < id_length {
+            return Err(invalid("attested credential data is too short"));
+        }
+        let (credential_id, mut key_bytes) = rest.split_at(id_length);
+
+        // the key is followed by extensions when there are any, keep only its own bytes
+        let available = key_bytes.len();
+        let _: Value = ciborium::de::from_reader(&mut key_bytes)
+            .map_err(|_| invalid("malformed credential public key"))?;
+        let key_length = available - key_bytes.len();
+
+        Some((
+            credential_id.to_vec(),
+            rest[id_length..id_length + key_length].to_vec(),
+        ))
+    } else {
+        None
+    };
+
+    Ok(AuthenticatorData {
+        rp_id_hash: raw[..32].to_vec(),
+        flags,
+        sign_count,
+        credential,
+    })
+}
+
+enum CoseKey {
+    Es256(EcdsaVerifyingKey),
+    Rs256(RsaVerifyingKey<Sha256>),
+}
+
+impl CoseKey {
+    fn parse(raw: &[u8]) -> Result<Self, AppError> {
+        let key: Value =
+            ciborium::de::from_reader(raw).map_err(|_| invalid("malformed public key"))?;
+        let bytes = |label: i64| match map_get(&key, &Value::Integer(label.into())) {
+            Some(Value::Bytes(bytes)) => Ok(bytes.as_slice()),
+            _ => Err(invalid("public key is missing parameters")),
+        };
+
+        match map_get(&key, &Value::Integer(3.into())).and_then(Value::as_integer) {
+            Some(alg) if alg == COSE_ALG_ES256.into() => {
+                let (x, y) = (bytes(-2)?, bytes(-3)?);
+                // coordinates of any other size would panic in the conversion below
+                if x.len() != 32 || y.len() != 32 {
+                    return Err(invalid("invalid ES256 public key"));
+                }
+                let point = EncodedPoint::from_affine_coordinates(x.into(), y.into(), false);
+                let key = EcdsaVerifyingKey::from_encoded_point(&point)
+                    .map_err(|_| invalid("invalid ES256 public key"))?;
+
+                Ok(CoseKey::Es256(key))
+            }
+            Some(alg) if alg == COSE_ALG_RS256.into() => {
+                let key = RsaPublicKey::new(
+                    BigUint::from_bytes_be(bytes(-1)?),
+                    BigUint::from_bytes_be(bytes(-2)?),
+                )
+                .map_err(|_| invalid("invalid RS256 public key"))?;
+
+                Ok(CoseKey::Rs256(RsaVerifyingKey::new(key)))
+            }
+            _ => Err(invalid("unsupported public key algorithm")),
+        }
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), AppError> {
+        let verified = match self {
+            CoseKey::Es256(key) => EcdsaSignature::from_der(signature)
+                .map(|signature| key.verify(message, &signature).is_ok())
+                .unwrap_or(false),
+            CoseKey::Rs256(key) => RsaSignature::try_from(signature)
+                .map(|signature| key.verify(message, &signature).is_ok())
+                .unwrap_or(false),
+        };
+
+        if !verified {
+            return Err(invalid("signature is invalid"));
+        }
+
+        Ok(())
+    }
+}
+
+// the user handle stored in a discoverable credential, it comes back with every login
+pub fn user_handle(user_id: &str) -> String {
+    URL_SAFE_NO_PAD.encode(user_id.as_bytes())
+}
+
+fn map_get<'a>(map: &'a Value, label: &Value) -> Option<&'a Value> {
+    map.as_map()?
+        .iter()
+        .find(|(key, _)| key == label)
+        .map(|(_, value)| value)
+}
+
+// browsers send unpadded base64url, some client libraries pad it
+fn decode(value: &str) -> Result<Vec<u8>, AppError> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(|_| invalid("value is not base64url encoded"))
+}
+
+fn invalid(reason: &str) -> AppError {
+    AppError::InvalidPasskey(reason.to_string())
+}
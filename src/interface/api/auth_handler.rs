@@ -1,27 +1,63 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::State,
+    extract::{Path, Query, State},
     http::header,
     middleware::from_fn_with_state,
     response::IntoResponse,
-    routing::{delete, get},
-    Extension, Router,
+    routing::{delete, get, post, put},
+    Extension, Json, Router,
 };
 use axum_extra::extract::cookie::{self, Cookie, Expiration};
 use time::OffsetDateTime;
 
 use crate::{
-    application::state::AppState,
-    domain::entities::user::UserFull,
+    application::{
+        dto::auth::{
+            email_request::ChangePasswordRequest,
+            mfa_request::MfaCodeRequest,
+            mfa_response::{MfaStatusResponse, RecoveryCodesResponse, TotpEnrolmentResponse},
+            oauth2_request::OauthUrlRequest,
+            session_response::SessionResponse,
+            webauthn_request::WebauthnRegistrationRequest,
+            webauthn_response::WebauthnCreationOptions,
+        },
+        state::AppState,
+    },
+    domain::entities::{
+        user::UserFull, user_oauth_provider::UserOauthProvider,
+        webauthn_credential::WebauthnCredential,
+    },
     infra::{errors::app_error::AppError, utils::response::SuccessResponse},
-    interface::middleware::auth_mw::is_authorized,
+    interface::{
+        api::public_oauth_handler::set_oauth_state_cookie,
+        middleware::auth_mw::{is_authorized, CurrentSession},
+    },
 };
 
 pub fn setup_auth_routes(app_state: Arc<AppState>) -> Router<Arc<AppState>> {
     Router::new()
         .route("/current-user", get(current_user))
         .route("/logout", delete(logout))
+        .route("/sessions", get(get_sessions).delete(revoke_other_sessions))
+        .route("/sessions/:id", delete(revoke_session))
+        .route("/password", put(change_password))
+        .route("/mfa", get(get_mfa_status))
+        .route("/mfa/totp", post(start_totp_enrolment).delete(disable_totp))
+        .route("/mfa/totp/confirm", post(confirm_totp_enrolment))
+        .route("/mfa/recovery-codes", post(regenerate_recovery_codes))
+        .route("/webauthn/credentials", get(get_webauthn_credentials))
+        .route(
+            "/webauthn/register/start",
+            post(start_webauthn_registration),
+        )
+        .route(
+            "/webauthn/register/finish",
+            post(finish_webauthn_registration),
+        )
+        .route("/links", get(get_oauth_links))
+        .route("/links/:id", delete(unlink_oauth_provider))
+        .route("/links/:provider/get-url", get(get_link_url))
         .layer(from_fn_with_state(app_state, is_authorized))
 }
 
@@ -33,13 +69,14 @@ pub async fn current_user(
 
 pub async fn logout(
     Extension(current_user): Extension<UserFull>,
+    Extension(current_session): Extension<CurrentSession>,
     State(app_state): State<Arc<AppState>>,
 ) -> Result<impl IntoResponse, AppError> {
     app_state
         .uc
         .auth
         .oauth2_logout
-        .execute(&current_user.oauth_provider.provider, &current_user.user.id)
+        .execute(&current_user.user.id, current_session.id.as_deref())
         .await?;
 
     let mut access_cookie = Cookie::build(("access_token", ""))
@@ -54,6 +91,7 @@ pub async fn logout(
         .same_site(cookie::SameSite::Lax)
         .expires(Expiration::from(OffsetDateTime::now_utc()));
 
+    // no longer set, browsers of older logins may still hold it
     let mut provider_cookie = Cookie::build(("provider", ""))
         .path("/")
         .http_only(true)
@@ -79,3 +117,247 @@ pub async fn logout(
 
     Ok(resp)
 }
+
+pub async fn get_sessions(
+    Extension(current_user): Extension<UserFull>,
+    Extension(current_session): Extension<CurrentSession>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<SuccessResponse<Vec<SessionResponse>>, AppError> {
+    let sessions = app_state
+        .uc
+        .auth
+        .get_user_sessions
+        .execute(&current_user.user.id, current_session.id.as_deref())
+        .await?;
+
+    Ok(SuccessResponse::with_data(200, sessions))
+}
+
+pub async fn revoke_session(
+    Extension(current_user): Extension<UserFull>,
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<SuccessResponse<String>, AppError> {
+    app_state
+        .uc
+        .auth
+        .revoke_session
+        .execute(&current_user.user.id, &id)
+        .await?;
+
+    tracing::info!("[API:Auth->revoke_session] Session {} revoked", id);
+
+    Ok(SuccessResponse::with_data(200, id))
+}
+
+pub async fn revoke_other_sessions(
+    Extension(current_user): Extension<UserFull>,
+    Extension(current_session): Extension<CurrentSession>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<SuccessResponse<()>, AppError> {
+    app_state
+        .uc
+        .auth
+        .revoke_other_sessions
+        .execute(&current_user.user.id, current_session.id.as_deref())
+        .await?;
+
+    tracing::info!("[API:Auth->revoke_other_sessions] User logged out from other sessions");
+
+    Ok(SuccessResponse::with_data(200, ()))
+}
+
+pub async fn get_oauth_links(
+    Extension(current_user): Extension<UserFull>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<SuccessResponse<Vec<UserOauthProvider>>, AppError> {
+    let oauth_providers = app_state
+        .uc
+        .auth
+        .get_oauth_links
+        .execute(&current_user.user.id)
+        .await?;
+
+    Ok(SuccessResponse::with_data(200, oauth_providers))
+}
+
+// same as the login url, the callback links the provider instead of logging in
+pub async fn get_link_url(
+    Extension(current_user): Extension<UserFull>,
+    State(app_state): State<Arc<AppState>>,
+    Path(provider): Path<String>,
+    Query(req): Query<OauthUrlRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let (url, state) = app_state
+        .uc
+        .auth
+        .get_oauth_auth_url
+        .execute(&provider, req, Some(current_user.user.id.clone()))
+        .await?;
+
+    let mut resp = SuccessResponse::with_data(200, url).into_response();
+    set_oauth_state_cookie(&app_state, &mut resp, &state)?;
+
+    Ok(resp)
+}
+
+pub async fn unlink_oauth_provider(
+    Extension(current_user): Extension<UserFull>,
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<SuccessResponse<String>, AppError> {
+    app_state
+        .uc
+        .auth
+        .unlink_oauth_provider
+        .execute(&current_user.user.id, &id)
+        .await?;
+
+    tracing::info!(
+        "[API:Auth->unlink_oauth_provider] Login provider {} unlinked",
+        id
+    );
+
+    Ok(SuccessResponse::with_data(200, id))
+}
+
+pub async fn change_password(
+    Extension(current_user): Extension<UserFull>,
+    Extension(current_session): Extension<CurrentSession>,
+    State(app_state): State<Arc<AppState>>,
+    Json(req): Json<ChangePasswordRequest>,
+) -> Result<SuccessResponse<()>, AppError> {
+    app_state
+        .uc
+        .auth
+        .change_password
+        .execute(&current_user.user.id, current_session.id.as_deref(), req)
+        .await?;
+
+    tracing::info!("[API:Auth->change_password] Password changed, other sessions revoked");
+
+    Ok(SuccessResponse::with_data(200, ()))
+}
+
+pub async fn get_mfa_status(
+    Extension(current_user): Extension<UserFull>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<SuccessResponse<MfaStatusResponse>, AppError> {
+    let status = app_state
+        .uc
+        .auth
+        .get_mfa_status
+        .execute(&current_user.user.id)
+        .await?;
+
+    Ok(SuccessResponse::with_data(200, status))
+}
+
+pub async fn start_totp_enrolment(
+    Extension(current_user): Extension<UserFull>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<SuccessResponse<TotpEnrolmentResponse>, AppError> {
+    let enrolment = app_state
+        .uc
+        .auth
+        .start_totp_enrolment
+        .execute(&current_user.user)
+        .await?;
+
+    Ok(SuccessResponse::with_data(200, enrolment))
+}
+
+pub async fn confirm_totp_enrolment(
+    Extension(current_user): Extension<UserFull>,
+    State(app_state): State<Arc<AppState>>,
+    Json(req): Json<MfaCodeRequest>,
+) -> Result<SuccessResponse<RecoveryCodesResponse>, AppError> {
+    let recovery_codes = app_state
+        .uc
+        .auth
+        .confirm_totp_enrolment
+        .execute(&current_user.user, req)
+        .await?;
+
+    tracing::info!("[API:Auth->confirm_totp_enrolment] Totp enabled");
+
+    Ok(SuccessResponse::with_data(200, recovery_codes))
+}
+
+pub async fn disable_totp(
+    Extension(current_user): Extension<UserFull>,
+    State(app_state): State<Arc<AppState>>,
+    Json(req): Json<MfaCodeRequest>,
+) -> Result<SuccessResponse<()>, AppError> {
+    app_state
+        .uc
+        .auth
+        .disable_totp
+        .execute(&current_user.user, req)
+        .await?;
+
+    tracing::info!("[API:Auth->disable_totp] Totp disabled");
+
+    Ok(SuccessResponse::with_data(200, ()))
+}
+
+pub async fn regenerate_recovery_codes(
+    Extension(current_user): Extension<UserFull>,
+    State(app_state): State<Arc<AppState>>,
+    Json(req): Json<MfaCodeRequest>,
+) -> Result<SuccessResponse<RecoveryCodesResponse>, AppError> {
+    let recovery_codes = app_state
+        .uc
+        .auth
+        .regenerate_recovery_codes
+        .execute(&current_user.user, req)
+        .await?;
+
+    Ok(SuccessResponse::with_data(200, recovery_codes))
+}
+
+// passkeys are removed through `DELETE /links/:id` with their `webauthn` link
+pub async fn get_webauthn_credentials(
+    Extension(current_user): Extension<UserFull>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<SuccessResponse<Vec<WebauthnCredential>>, AppError> {
+    let credentials = app_state
+        .uc
+        .auth
+        .get_webauthn_credentials
+        .execute(&current_user.user.id)
+        .await?;
+
+    Ok(SuccessResponse::with_data(200, credentials))
+}
+
+pub async fn start_webauthn_registration(
+    Extension(current_user): Extension<UserFull>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<SuccessResponse<WebauthnCreationOptions>, AppError> {
+    let options = app_state
+        .uc
+        .auth
+        .start_webauthn_registration
+        .execute(&current_user.user)
+        .await?;
+
+    Ok(SuccessResponse::with_data(200, options))
+}
+
+pub async fn finish_webauthn_registration(
+    Extension(current_user): Extension<UserFull>,
+    State(app_state): State<Arc<AppState>>,
+    Json(req): Json<WebauthnRegistrationRequest>,
+) -> Result<SuccessResponse<WebauthnCredential>, AppError> {
+    let credential = app_state
+        .uc
+        .auth
+        .finish_webauthn_registration
+        .execute(&current_user.user, req)
+        .await?;
+
+    tracing::info!("[API:Auth->finish_webauthn_registration] Passkey registered");
+
+    Ok(SuccessResponse::with_data(200, credential))
+}
@@ -3,3 +3,5 @@ pub mod permission_handler;
 pub mod public_oauth_handler;
 pub mod role_handler;
 pub mod super_handler;
+pub mod user_handler;
+pub mod well_known_handler;
@@ -2,8 +2,8 @@ use std::sync::Arc;
 
 use axum::{
     extract::{Path, Query, State},
-    http::header,
-    response::IntoResponse,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
@@ -15,18 +15,33 @@ use axum_extra::extract::{
 use crate::{
     application::{
         dto::auth::{
-            email_request::{EmailLoginRequest, EmailRegisterRequest},
-            oauth2_request::Oauth2Request,
+            email_request::{
+                EmailLoginRequest, EmailRegisterRequest, ForgotPasswordRequest, MagicLinkRequest,
+                RedeemMagicLinkRequest, ResendEmailVerificationRequest, ResetPasswordRequest,
+                VerifyEmailRequest,
+            },
+            mfa_request::{MfaLoginRequest, MfaTokenRequest},
+            mfa_response::{RecoveryCodesResponse, TotpEnrolmentResponse},
+            oauth2_request::{Oauth2Request, OauthUrlRequest},
+            oauth2_response::OauthCallbackResult,
+            token_response::{
+                EmailLoginResult, RefreshTokenRequest, TokenDeliveryQuery, TokenResponse,
+            },
+            webauthn_request::WebauthnLoginRequest,
+            webauthn_response::WebauthnRequestOptions,
         },
         state::AppState,
     },
     infra::{
+        common::constants::OAUTH_LOGIN_STATE_LIFETIME_SECONDS,
         errors::app_error::AppError,
-        oauth2::constants::{EMAIL_PROVIDER, GOOGLE_PROVIDER},
-        utils::response::SuccessResponse,
+        utils::{client_info::ClientInfo, response::SuccessResponse},
     },
 };
 
+// the state of the oauth login started by this browser
+pub const OAUTH_STATE_COOKIE: &str = "oauth_state";
+
 pub fn setup_public_oauth_handler() -> Router<Arc<AppState>> {
     Router::new()
         .route("/:provider/get-url", get(get_oauth_url))
@@ -34,65 +49,138 @@ pub fn setup_public_oauth_handler() -> Router<Arc<AppState>> {
         .route("/:provider/intercept", get(intercept_oauth_code))
         .route("/email/register", post(register_with_email))
         .route("/email/login", post(login_with_email))
-        .route("/refresh-token", get(refresh_token))
+        .route(
+            "/email/verify",
+            get(verify_email).post(verify_email_from_body),
+        )
+        .route("/email/verify/resend", post(resend_email_verification))
+        .route("/email/password/forgot", post(forgot_password))
+        .route("/email/password/reset", post(reset_password))
+        .route("/email/magic-link", post(send_magic_link))
+        .route("/email/magic-link/redeem", post(redeem_magic_link))
+        .route("/mfa/verify", post(verify_mfa_login))
+        .route("/mfa/totp/setup", post(setup_totp_for_login))
+        .route("/webauthn/login/start", post(start_webauthn_login))
+        .route("/webauthn/login/finish", post(finish_webauthn_login))
+        .route(
+            "/refresh-token",
+            get(refresh_token).post(refresh_token_from_body),
+        )
 }
 
 pub async fn get_oauth_url(
     State(app_state): State<Arc<AppState>>,
     Path(provider): Path<String>,
-) -> Result<SuccessResponse<String>, AppError> {
-    if provider == GOOGLE_PROVIDER {
-        let url = app_state.uc.auth.get_google_auth_url.execute().await?;
+    Query(req): Query<OauthUrlRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let (url, state) = app_state
+        .uc
+        .auth
+        .get_oauth_auth_url
+        .execute(&provider, req, None)
+        .await?;
 
-        return Ok(SuccessResponse::with_data(200, url));
-    }
+    let mut resp = SuccessResponse::with_data(200, url).into_response();
+    set_oauth_state_cookie(&app_state, &mut resp, &state)?;
 
-    Err(AppError::Unauthorized)
+    Ok(resp)
 }
 
 pub async fn handle_oauth2_callback(
+    jar: CookieJar,
     State(app_state): State<Arc<AppState>>,
     Path(provider): Path<String>,
+    client: ClientInfo,
     Query(req): Query<Oauth2Request>,
+    Query(delivery): Query<TokenDeliveryQuery>,
 ) -> Result<impl IntoResponse, AppError> {
-    let (access_token, refresh_token) = app_state
+    let state_cookie = jar
+        .get(OAUTH_STATE_COOKIE)
+        .map(|cookie| cookie.value().to_string());
+    let result = app_state
         .uc
         .auth
         .oauth2_login
-        .execute(&app_state.db_pool, provider.clone(), req)
-        .await?;
+        .execute(
+            &app_state.db_pool,
+            provider.clone(),
+            req,
+            state_cookie.as_deref(),
+            &client,
+        )
+        .await;
+
+    // the state is used up either way
+    let mut resp = oauth_callback_response(&app_state, &provider, &delivery, result)?;
+    set_oauth_state_cookie(&app_state, &mut resp, "")?;
 
-    let mut access_cookie = Cookie::build(("access_token", access_token.clone()))
-        .path("/")
-        .http_only(true)
-        .same_site(cookie::SameSite::Lax);
-
-    let mut refresh_cookie = Cookie::build(("refresh_token", refresh_token.clone()))
-        .path("/")
-        .http_only(true)
-        .same_site(cookie::SameSite::Lax);
-
-    let mut provider_cookie = Cookie::build(("provider", provider.clone()))
-        .path("/")
-        .http_only(true)
-        .same_site(cookie::SameSite::Lax);
+    Ok(resp)
+}
 
-    if &app_state.cfg.app_env != "local" {
-        access_cookie = access_cookie.secure(true);
-        refresh_cookie = refresh_cookie.secure(true);
-        provider_cookie = provider_cookie.secure(true);
+fn oauth_callback_response(
+    app_state: &AppState,
+    provider: &str,
+    delivery: &TokenDeliveryQuery,
+    result: Result<OauthCallbackResult, AppError>,
+) -> Result<Response, AppError> {
+    // browsers land here straight from the provider, send them back to the app instead of json
+    match result {
+        Ok(OauthCallbackResult::LoggedIn {
+            access_token,
+            refresh_token,
+            return_to: Some(return_to),
+        }) if !delivery.in_body() => {
+            let mut resp = redirect_response(&return_to)?;
+            set_token_cookies(app_state, &mut resp, access_token, refresh_token)?;
+
+            Ok(resp)
+        }
+        Ok(OauthCallbackResult::LoggedIn {
+            access_token,
+            refresh_token,
+            ..
+        }) => token_response(app_state, access_token, refresh_token, delivery),
+        // linking keeps the current session, no new tokens
+        Ok(OauthCallbackResult::Linked {
+            return_to: Some(return_to),
+            ..
+        }) if !delivery.in_body() => redirect_response(&return_to),
+        Ok(OauthCallbackResult::Linked { oauth_provider, .. }) => {
+            Ok(SuccessResponse::with_data(200, oauth_provider).into_response())
+        }
+        // the app asks for the code & continues at /mfa/verify. the token goes into the fragment,
+        // it never reaches a server log or a referer header
+        Ok(OauthCallbackResult::MfaRequired {
+            challenge,
+            return_to: Some(return_to),
+        }) if !delivery.in_body() => {
+            let mut mfa_url = reqwest::Url::parse(&return_to)
+                .map_err(|err| AppError::ProcessError(err.to_string()))?;
+            mfa_url.set_fragment(Some(&format!(
+                "mfa_token={}&enrolment_required={}",
+                challenge.mfa_token, challenge.enrolment_required
+            )));
+
+            redirect_response(mfa_url.as_str())
+        }
+        Ok(OauthCallbackResult::MfaRequired { challenge, .. }) => {
+            Ok(SuccessResponse::with_data(200, challenge).into_response())
+        }
+        Err(err) => match &app_state.cfg.login_error_url {
+            Some(login_error_url) if !delivery.in_body() => {
+                tracing::info!("[Oauth:Callback] {} login failed: {}", provider, err);
+
+                let error_url = reqwest::Url::parse_with_params(
+                    login_error_url,
+                    &[("error", err.error_code())],
+                )
+                .map_err(|err| AppError::ProcessError(err.to_string()))?;
+
+                redirect_response(error_url.as_str())
+            }
+            _ => Ok(err.into_response()),
+        },
     }
-
-    let mut resp = SuccessResponse::<u16>::with_code(200).into_response();
-
-    resp.headers_mut()
-        .append(header::SET_COOKIE, access_cookie.to_string().parse()?);
-    resp.headers_mut()
-        .append(header::SET_COOKIE, refresh_cookie.to_string().parse()?);
-    resp.headers_mut()
-        .append(header::SET_COOKIE, provider_cookie.to_string().parse()?);
-
-    Ok(resp)
 }
 
 /* this function only for testing on postman
@@ -103,10 +191,12 @@ pub async fn handle_oauth2_callback(
 *
 *
 * */
+// the state has to be sent back with the code, so hand both to the api client, the callback also
+// needs the oauth_state cookie set by get-url
 pub async fn intercept_oauth_code(
     Query(req): Query<Oauth2Request>,
-) -> Result<SuccessResponse<String>, AppError> {
-    Ok(SuccessResponse::with_data(200, req.code))
+) -> Result<SuccessResponse<Oauth2Request>, AppError> {
+    Ok(SuccessResponse::with_data(200, req))
 }
 
 /*
@@ -132,41 +222,223 @@ pub async fn register_with_email(
 
 pub async fn login_with_email(
     State(app_state): State<Arc<AppState>>,
+    client: ClientInfo,
+    Query(delivery): Query<TokenDeliveryQuery>,
     Json(req): Json<EmailLoginRequest>,
 ) -> Result<impl IntoResponse, AppError> {
-    let (access_token, refresh_token) = app_state.uc.auth.email_login.execute(req).await?;
+    let (access_token, refresh_token) =
+        match app_state.uc.auth.email_login.execute(req, &client).await? {
+            EmailLoginResult::LoggedIn {
+                access_token,
+                refresh_token,
+            } => (access_token, refresh_token),
+            // no tokens yet, the client continues at /mfa/verify with the mfa token
+            EmailLoginResult::MfaRequired(challenge) => {
+                return Ok(SuccessResponse::with_data(200, challenge).into_response())
+            }
+        };
+
+    token_response(&app_state, access_token, refresh_token, &delivery)
+}
 
-    let mut access_cookie = Cookie::build(("access_token", access_token.clone()))
-        .path("/")
-        .http_only(true)
-        .same_site(cookie::SameSite::Lax);
+// target of the mailed verification link
+pub async fn verify_email(
+    State(app_state): State<Arc<AppState>>,
+    Query(req): Query<VerifyEmailRequest>,
+) -> Result<SuccessResponse<u16>, AppError> {
+    app_state.uc.auth.verify_email.execute(req).await?;
 
-    let mut refresh_cookie = Cookie::build(("refresh_token", refresh_token.clone()))
-        .path("/")
-        .http_only(true)
-        .same_site(cookie::SameSite::Lax);
+    Ok(SuccessResponse::with_code(200))
+}
 
-    let mut provider_cookie = Cookie::build(("provider", EMAIL_PROVIDER.to_string()))
-        .path("/")
-        .http_only(true)
-        .same_site(cookie::SameSite::Lax);
+pub async fn verify_email_from_body(
+    State(app_state): State<Arc<AppState>>,
+    Json(req): Json<VerifyEmailRequest>,
+) -> Result<SuccessResponse<u16>, AppError> {
+    app_state.uc.auth.verify_email.execute(req).await?;
+
+    Ok(SuccessResponse::with_code(200))
+}
+
+pub async fn resend_email_verification(
+    State(app_state): State<Arc<AppState>>,
+    Json(req): Json<ResendEmailVerificationRequest>,
+) -> Result<SuccessResponse<u16>, AppError> {
+    app_state
+        .uc
+        .auth
+        .resend_email_verification
+        .execute(req)
+        .await?;
+
+    Ok(SuccessResponse::with_code(200))
+}
+
+pub async fn forgot_password(
+    State(app_state): State<Arc<AppState>>,
+    Json(req): Json<ForgotPasswordRequest>,
+) -> Result<SuccessResponse<u16>, AppError> {
+    app_state.uc.auth.forgot_password.execute(req).await?;
+
+    Ok(SuccessResponse::with_code(200))
+}
+
+pub async fn reset_password(
+    State(app_state): State<Arc<AppState>>,
+    Json(req): Json<ResetPasswordRequest>,
+) -> Result<SuccessResponse<u16>, AppError> {
+    app_state.uc.auth.reset_password.execute(req).await?;
 
-    if &app_state.cfg.app_env != "local" {
-        access_cookie = access_cookie.secure(true);
-        refresh_cookie = refresh_cookie.secure(true);
-        provider_cookie = provider_cookie.secure(true);
+    tracing::info!("[API:Oauth->reset_password] Password reset, sessions revoked");
+
+    Ok(SuccessResponse::with_code(200))
+}
+
+// always answers the same, whether the email is registered or not
+pub async fn send_magic_link(
+    State(app_state): State<Arc<AppState>>,
+    client: ClientInfo,
+    Json(req): Json<MagicLinkRequest>,
+) -> Result<SuccessResponse<u16>, AppError> {
+    app_state
+        .uc
+        .auth
+        .send_magic_link
+        .execute(req, &client)
+        .await?;
+
+    Ok(SuccessResponse::with_code(200))
+}
+
+// posted by the page the mailed link opens, redeeming on a plain GET would let mail scanners &
+// link previews use up the single use token
+pub async fn redeem_magic_link(
+    State(app_state): State<Arc<AppState>>,
+    client: ClientInfo,
+    Query(delivery): Query<TokenDeliveryQuery>,
+    Json(req): Json<RedeemMagicLinkRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    magic_link_response(&app_state, &client, &delivery, req).await
+}
+
+async fn magic_link_response(
+    app_state: &AppState,
+    client: &ClientInfo,
+    delivery: &TokenDeliveryQuery,
+    req: RedeemMagicLinkRequest,
+) -> Result<Response, AppError> {
+    let (access_token, refresh_token) = match app_state
+        .uc
+        .auth
+        .redeem_magic_link
+        .execute(req, client)
+        .await?
+    {
+        EmailLoginResult::LoggedIn {
+            access_token,
+            refresh_token,
+        } => (access_token, refresh_token),
+        // no tokens yet, the client continues at /mfa/verify with the mfa token
+        EmailLoginResult::MfaRequired(challenge) => {
+            return Ok(SuccessResponse::with_data(200, challenge).into_response())
+        }
+    };
+
+    token_response(app_state, access_token, refresh_token, delivery)
+}
+
+/*
+*
+* MFA second step of the email login
+*
+* */
+pub async fn verify_mfa_login(
+    State(app_state): State<Arc<AppState>>,
+    client: ClientInfo,
+    Query(delivery): Query<TokenDeliveryQuery>,
+    Json(req): Json<MfaLoginRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let result = app_state
+        .uc
+        .auth
+        .verify_mfa_login
+        .execute(req, &client)
+        .await?;
+
+    // a login that enrolled totp also hands out the recovery codes
+    match result.recovery_codes {
+        Some(recovery_codes) if delivery.in_body() => Ok(SuccessResponse::with_data(
+            200,
+            TokenResponse {
+                recovery_codes: Some(recovery_codes),
+                ..TokenResponse::bearer(result.access_token, result.refresh_token)
+            },
+        )
+        .into_response()),
+        Some(recovery_codes) => {
+            let mut resp =
+                SuccessResponse::with_data(200, RecoveryCodesResponse { recovery_codes })
+                    .into_response();
+            set_token_cookies(
+                &app_state,
+                &mut resp,
+                result.access_token,
+                result.refresh_token,
+            )?;
+
+            Ok(resp)
+        }
+        None => token_response(
+            &app_state,
+            result.access_token,
+            result.refresh_token,
+            &delivery,
+        ),
     }
+}
 
-    let mut resp = SuccessResponse::<u16>::with_code(200).into_response();
+// enrolment for users whose role requires mfa, confirmed by the first /mfa/verify
+pub async fn setup_totp_for_login(
+    State(app_state): State<Arc<AppState>>,
+    Json(req): Json<MfaTokenRequest>,
+) -> Result<SuccessResponse<TotpEnrolmentResponse>, AppError> {
+    let enrolment = app_state
+        .uc
+        .auth
+        .start_totp_enrolment
+        .execute_with_challenge(req)
+        .await?;
 
-    resp.headers_mut()
-        .append(header::SET_COOKIE, access_cookie.to_string().parse()?);
-    resp.headers_mut()
-        .append(header::SET_COOKIE, refresh_cookie.to_string().parse()?);
-    resp.headers_mut()
-        .append(header::SET_COOKIE, provider_cookie.to_string().parse()?);
+    Ok(SuccessResponse::with_data(200, enrolment))
+}
 
-    Ok(resp)
+/*
+*
+* Passkey login, discoverable credentials so no email is asked first
+*
+* */
+pub async fn start_webauthn_login(
+    State(app_state): State<Arc<AppState>>,
+) -> Result<SuccessResponse<WebauthnRequestOptions>, AppError> {
+    let options = app_state.uc.auth.start_webauthn_login.execute().await?;
+
+    Ok(SuccessResponse::with_data(200, options))
+}
+
+pub async fn finish_webauthn_login(
+    State(app_state): State<Arc<AppState>>,
+    client: ClientInfo,
+    Query(delivery): Query<TokenDeliveryQuery>,
+    Json(req): Json<WebauthnLoginRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let (access_token, refresh_token) = app_state
+        .uc
+        .auth
+        .finish_webauthn_login
+        .execute(req, &client)
+        .await?;
+
+    token_response(&app_state, access_token, refresh_token, &delivery)
 }
 
 /*
@@ -177,49 +449,116 @@ pub async fn login_with_email(
 pub async fn refresh_token(
     jar: CookieJar,
     State(app_state): State<Arc<AppState>>,
+    Query(delivery): Query<TokenDeliveryQuery>,
 ) -> Result<impl IntoResponse, AppError> {
     let refresh_token = match jar.get("refresh_token") {
         Some(cookie) => cookie.value().to_string(),
         None => return Err(AppError::Unauthorized),
     };
 
-    let provider = match jar.get("provider") {
-        Some(cookie) => cookie.value().to_string(),
-        None => {
-            return Err(AppError::UnauthorizedError(
-                "invalid oauth provider".to_string(),
-            ))
-        }
-    };
-
     let (access_token, refresh_token) = app_state
         .uc
         .auth
         .refresh_oauth_token
-        .execute(&provider, &refresh_token)
+        .execute(&refresh_token)
         .await?;
 
-    let mut access_cookie = Cookie::build(("access_token", access_token.clone()))
-        .path("/")
-        .http_only(true)
-        .same_site(cookie::SameSite::Lax);
+    token_response(&app_state, access_token, refresh_token, &delivery)
+}
 
-    let mut refresh_cookie = Cookie::build(("refresh_token", refresh_token.clone()))
-        .path("/")
-        .http_only(true)
-        .same_site(cookie::SameSite::Lax);
+// same as `refresh_token` for clients that keep the refresh token themselves
+pub async fn refresh_token_from_body(
+    State(app_state): State<Arc<AppState>>,
+    Json(req): Json<RefreshTokenRequest>,
+) -> Result<SuccessResponse<TokenResponse>, AppError> {
+    let (access_token, refresh_token) = app_state
+        .uc
+        .auth
+        .refresh_oauth_token
+        .execute(&req.refresh_token)
+        .await?;
+
+    Ok(SuccessResponse::with_data(
+        200,
+        TokenResponse::bearer(access_token, refresh_token),
+    ))
+}
 
-    if &app_state.cfg.app_env != "local" {
-        access_cookie = access_cookie.secure(true);
-        refresh_cookie = refresh_cookie.secure(true);
+// tokens go into http only cookies unless the client asked for them in the body
+fn token_response(
+    app_state: &AppState,
+    access_token: String,
+    refresh_token: String,
+    delivery: &TokenDeliveryQuery,
+) -> Result<Response, AppError> {
+    if delivery.in_body() {
+        return Ok(SuccessResponse::with_data(
+            200,
+            TokenResponse::bearer(access_token, refresh_token),
+        )
+        .into_response());
     }
 
     let mut resp = SuccessResponse::<u16>::with_code(200).into_response();
+    set_token_cookies(app_state, &mut resp, access_token, refresh_token)?;
+
+    Ok(resp)
+}
+
+fn set_token_cookies(
+    app_state: &AppState,
+    resp: &mut Response,
+    access_token: String,
+    refresh_token: String,
+) -> Result<(), AppError> {
+    let cookies = [
+        Cookie::build(("access_token", access_token)),
+        Cookie::build(("refresh_token", refresh_token)),
+    ];
+
+    for cookie in cookies {
+        let cookie = cookie
+            .path("/")
+            .http_only(true)
+            .same_site(cookie::SameSite::Lax)
+            .secure(app_state.cfg.app_env != "local");
+
+        resp.headers_mut()
+            .append(header::SET_COOKIE, cookie.to_string().parse()?);
+    }
+
+    Ok(())
+}
+
+// holds the state of the login this browser started, an empty state removes it
+pub fn set_oauth_state_cookie(
+    app_state: &AppState,
+    resp: &mut Response,
+    state: &str,
+) -> Result<(), AppError> {
+    let max_age = if state.is_empty() {
+        time::Duration::ZERO
+    } else {
+        time::Duration::seconds(OAUTH_LOGIN_STATE_LIFETIME_SECONDS as i64)
+    };
+    let cookie = Cookie::build((OAUTH_STATE_COOKIE, state.to_string()))
+        .path("/oauth")
+        .http_only(true)
+        // sent along when the provider redirects the browser back to the callback
+        .same_site(cookie::SameSite::Lax)
+        .secure(app_state.cfg.app_env != "local")
+        .max_age(max_age);
 
     resp.headers_mut()
-        .append(header::SET_COOKIE, access_cookie.to_string().parse()?);
-    resp.headers_mut()
-        .append(header::SET_COOKIE, refresh_cookie.to_string().parse()?);
+        .append(header::SET_COOKIE, cookie.to_string().parse()?);
 
-    Ok(resp)
+    Ok(())
+}
+
+fn redirect_response(location: &str) -> Result<Response, AppError> {
+    Ok((
+        StatusCode::FOUND,
+        [(header::LOCATION, location.parse::<header::HeaderValue>()?)],
+    )
+        .into_response())
 }
@@ -6,6 +6,7 @@ use axum::{
     routing::get,
     Extension, Json, Router,
 };
+use tracing::info;
 
 use crate::{
     application::{
@@ -44,23 +45,26 @@ async fn get_paginated_roles(
     State(state): State<Arc<AppState>>,
     Query(query): Query<PaginationQuery>,
 ) -> Result<SuccessResponse<PaginatedResponse<Role>>, AppError> {
-    let has_access = state
+    let access = state
         .rbac
-        .check_access(&current_user.roles, "role-management", "read")
+        .check_access(&current_user, "role-management", "read")
         .await?;
 
-    if !has_access {
+    if !access.allowed {
         return Err(AppError::Forbidden);
     }
 
+    info!(
+        "[API:Role->get_paginated_roles] User {} granted role-management:read via role {}",
+        current_user.user.id,
+        access.matched_role_name()
+    );
+
     let roles = state
         .uc
         .role
         .get_paginated_role
-        .execute(
-            query.page.unwrap_or(1 as i64),
-            query.limit.unwrap_or(15 as i64),
-        )
+        .execute(query.page.unwrap_or(1), query.limit.unwrap_or(15))
         .await?;
 
     Ok(SuccessResponse::with_data(200, roles))
@@ -70,15 +74,21 @@ async fn get_all_roles(
     Extension(current_user): Extension<UserFull>,
     State(state): State<Arc<AppState>>,
 ) -> Result<SuccessResponse<Vec<Role>>, AppError> {
-    let has_access = state
+    let access = state
         .rbac
-        .check_access(&current_user.roles, "role-management", "read")
+        .check_access(&current_user, "role-management", "read")
         .await?;
 
-    if !has_access {
+    if !access.allowed {
         return Err(AppError::Forbidden);
     }
 
+    info!(
+        "[API:Role->get_all_roles] User {} granted role-management:read via role {}",
+        current_user.user.id,
+        access.matched_role_name()
+    );
+
     let roles = state.uc.role.get_all_role.execute().await?;
 
     Ok(SuccessResponse::with_data(200, roles))
@@ -89,15 +99,21 @@ async fn get_role_by_id(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
 ) -> Result<SuccessResponse<RoleWithPermission>, AppError> {
-    let has_access = state
+    let access = state
         .rbac
-        .check_access(&current_user.roles, "role-management", "read")
+        .check_access(&current_user, "role-management", "read")
         .await?;
 
-    if !has_access {
+    if !access.allowed {
         return Err(AppError::Forbidden);
     }
 
+    info!(
+        "[API:Role->get_role_by_id] User {} granted role-management:read via role {}",
+        current_user.user.id,
+        access.matched_role_name()
+    );
+
     let role = state.uc.role.get_role_by_id.execute(&id).await?;
 
     Ok(SuccessResponse::with_data(200, role))
@@ -108,15 +124,21 @@ async fn create_role(
     State(state): State<Arc<AppState>>,
     Json(req): Json<CreateOrUpdateRole>,
 ) -> Result<SuccessResponse<String>, AppError> {
-    let has_access = state
+    let access = state
         .rbac
-        .check_access(&current_user.roles, "role-management", "write")
+        .check_access(&current_user, "role-management", "write")
         .await?;
 
-    if !has_access {
+    if !access.allowed {
         return Err(AppError::Forbidden);
     }
 
+    info!(
+        "[API:Role->create_role] User {} granted role-management:write via role {}",
+        current_user.user.id,
+        access.matched_role_name()
+    );
+
     let role = state.uc.role.create_role.execute(req).await?;
 
     Ok(SuccessResponse::with_data(200, role.id))
@@ -128,15 +150,21 @@ async fn update_role(
     Path(id): Path<String>,
     Json(req): Json<CreateOrUpdateRole>,
 ) -> Result<SuccessResponse<String>, AppError> {
-    let has_access = state
+    let access = state
         .rbac
-        .check_access(&current_user.roles, "role-management", "write")
+        .check_access(&current_user, "role-management", "write")
         .await?;
 
-    if !has_access {
+    if !access.allowed {
         return Err(AppError::Forbidden);
     }
 
+    info!(
+        "[API:Role->update_role] User {} granted role-management:write via role {}",
+        current_user.user.id,
+        access.matched_role_name()
+    );
+
     state.uc.role.update_role_by_id.execute(&id, req).await?;
 
     Ok(SuccessResponse::with_data(200, id))
@@ -147,15 +175,21 @@ async fn delete_role(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
 ) -> Result<SuccessResponse<String>, AppError> {
-    let has_access = state
+    let access = state
         .rbac
-        .check_access(&current_user.roles, "role-management", "write")
+        .check_access(&current_user, "role-management", "write")
         .await?;
 
-    if !has_access {
+    if !access.allowed {
         return Err(AppError::Forbidden);
     }
 
+    info!(
+        "[API:Role->delete_role] User {} granted role-management:write via role {}",
+        current_user.user.id,
+        access.matched_role_name()
+    );
+
     state.uc.role.delete_role_by_id.execute(&id).await?;
 
     Ok(SuccessResponse::with_data(200, id))
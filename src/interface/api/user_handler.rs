@@ -0,0 +1,347 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    middleware,
+    routing::{delete, get, patch},
+    Extension, Json, Router,
+};
+use tracing::info;
+
+use crate::{
+    application::{
+        dto::user::{
+            assign_role_request::AssignRoleRequest, get_user_request::UserDetail,
+            update_user_request::UpdateUserRequest,
+        },
+        state::AppState,
+    },
+    domain::entities::{
+        role::Role,
+        user::{User, UserFull},
+        user_role::UserRole,
+    },
+    infra::{
+        errors::app_error::AppError,
+        utils::{
+            pagination::{PaginatedResponse, PaginationQuery},
+            response::SuccessResponse,
+        },
+    },
+    interface::middleware::auth_mw::is_authorized,
+};
+
+pub fn setup_user_routes(app_state: Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(get_paginated_users))
+        .route(
+            "/:id",
+            get(get_user_by_id).put(update_user).delete(delete_user),
+        )
+        .route("/:id/deactivate", patch(deactivate_user))
+        .route("/:id/reactivate", patch(reactivate_user))
+        .route("/:id/unlock", patch(unlock_user))
+        .route("/:id/roles", get(get_user_roles).post(assign_user_role))
+        .route("/:id/roles/:role_id", delete(revoke_user_role))
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            is_authorized,
+        ))
+}
+
+async fn get_paginated_users(
+    Extension(current_user): Extension<UserFull>,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PaginationQuery>,
+) -> Result<SuccessResponse<PaginatedResponse<User>>, AppError> {
+    let access = state
+        .rbac
+        .check_access(&current_user, "user-management", "read")
+        .await?;
+
+    if !access.allowed {
+        return Err(AppError::Forbidden);
+    }
+
+    info!(
+        "[API:User->get_paginated_users] User {} granted user-management:read via role {}",
+        current_user.user.id,
+        access.matched_role_name()
+    );
+
+    let users = state
+        .uc
+        .user
+        .get_paginated_user
+        .execute(
+            query.page.unwrap_or(1),
+            query.limit.unwrap_or(15),
+            query.search,
+        )
+        .await?;
+
+    Ok(SuccessResponse::with_data(200, users))
+}
+
+async fn get_user_by_id(
+    Extension(current_user): Extension<UserFull>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<SuccessResponse<UserDetail>, AppError> {
+    let access = state
+        .rbac
+        .check_access(&current_user, "user-management", "read")
+        .await?;
+
+    if !access.allowed {
+        return Err(AppError::Forbidden);
+    }
+
+    info!(
+        "[API:User->get_user_by_id] User {} granted user-management:read via role {}",
+        current_user.user.id,
+        access.matched_role_name()
+    );
+
+    let user = state.uc.user.get_user_by_id.execute(&id).await?;
+
+    Ok(SuccessResponse::with_data(200, user))
+}
+
+async fn update_user(
+    Extension(current_user): Extension<UserFull>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<UpdateUserRequest>,
+) -> Result<SuccessResponse<User>, AppError> {
+    let access = state
+        .rbac
+        .check_access(&current_user, "user-management", "write")
+        .await?;
+
+    if !access.allowed {
+        return Err(AppError::Forbidden);
+    }
+
+    info!(
+        "[API:User->update_user] User {} granted user-management:write via role {}",
+        current_user.user.id,
+        access.matched_role_name()
+    );
+
+    let user = state
+        .uc
+        .user
+        .update_user_by_id
+        .execute(&current_user, &id, req)
+        .await?;
+
+    Ok(SuccessResponse::with_data(200, user))
+}
+
+async fn deactivate_user(
+    Extension(current_user): Extension<UserFull>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<SuccessResponse<String>, AppError> {
+    let access = state
+        .rbac
+        .check_access(&current_user, "user-management", "write")
+        .await?;
+
+    if !access.allowed {
+        return Err(AppError::Forbidden);
+    }
+
+    info!(
+        "[API:User->deactivate_user] User {} granted user-management:write via role {}",
+        current_user.user.id,
+        access.matched_role_name()
+    );
+
+    state
+        .uc
+        .user
+        .deactivate_user
+        .execute(&current_user, &id)
+        .await?;
+
+    Ok(SuccessResponse::with_data(200, id))
+}
+
+async fn reactivate_user(
+    Extension(current_user): Extension<UserFull>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<SuccessResponse<String>, AppError> {
+    let access = state
+        .rbac
+        .check_access(&current_user, "user-management", "write")
+        .await?;
+
+    if !access.allowed {
+        return Err(AppError::Forbidden);
+    }
+
+    info!(
+        "[API:User->reactivate_user] User {} granted user-management:write via role {}",
+        current_user.user.id,
+        access.matched_role_name()
+    );
+
+    state
+        .uc
+        .user
+        .reactivate_user
+        .execute(&current_user, &id)
+        .await?;
+
+    Ok(SuccessResponse::with_data(200, id))
+}
+
+// lifts the lockout after too many failed email logins
+async fn unlock_user(
+    Extension(current_user): Extension<UserFull>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<SuccessResponse<String>, AppError> {
+    let access = state
+        .rbac
+        .check_access(&current_user, "user-management", "write")
+        .await?;
+
+    if !access.allowed {
+        return Err(AppError::Forbidden);
+    }
+
+    info!(
+        "[API:User->unlock_user] User {} granted user-management:write via role {}",
+        current_user.user.id,
+        access.matched_role_name()
+    );
+
+    state
+        .uc
+        .user
+        .unlock_user
+        .execute(&current_user, &id)
+        .await?;
+
+    Ok(SuccessResponse::with_data(200, id))
+}
+
+async fn delete_user(
+    Extension(current_user): Extension<UserFull>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<SuccessResponse<String>, AppError> {
+    let access = state
+        .rbac
+        .check_access(&current_user, "user-management", "write")
+        .await?;
+
+    if !access.allowed {
+        return Err(AppError::Forbidden);
+    }
+
+    info!(
+        "[API:User->delete_user] User {} granted user-management:write via role {}",
+        current_user.user.id,
+        access.matched_role_name()
+    );
+
+    state
+        .uc
+        .user
+        .delete_user_by_id
+        .execute(&current_user, &id)
+        .await?;
+
+    Ok(SuccessResponse::with_data(200, id))
+}
+
+async fn get_user_roles(
+    Extension(current_user): Extension<UserFull>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<SuccessResponse<Vec<Role>>, AppError> {
+    let access = state
+        .rbac
+        .check_access(&current_user, "user-management", "read")
+        .await?;
+
+    if !access.allowed {
+        return Err(AppError::Forbidden);
+    }
+
+    info!(
+        "[API:User->get_user_roles] User {} granted user-management:read via role {}",
+        current_user.user.id,
+        access.matched_role_name()
+    );
+
+    let roles = state.uc.user.get_user_roles.execute(&id).await?;
+
+    Ok(SuccessResponse::with_data(200, roles))
+}
+
+async fn assign_user_role(
+    Extension(current_user): Extension<UserFull>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<AssignRoleRequest>,
+) -> Result<SuccessResponse<UserRole>, AppError> {
+    let access = state
+        .rbac
+        .check_access(&current_user, "user-management", "write")
+        .await?;
+
+    if !access.allowed {
+        return Err(AppError::Forbidden);
+    }
+
+    info!(
+        "[API:User->assign_user_role] User {} granted user-management:write via role {}",
+        current_user.user.id,
+        access.matched_role_name()
+    );
+
+    let user_role = state
+        .uc
+        .user
+        .assign_user_role
+        .execute(&current_user, &id, req)
+        .await?;
+
+    Ok(SuccessResponse::with_data(200, user_role))
+}
+
+async fn revoke_user_role(
+    Extension(current_user): Extension<UserFull>,
+    State(state): State<Arc<AppState>>,
+    Path((id, role_id)): Path<(String, String)>,
+) -> Result<SuccessResponse<String>, AppError> {
+    let access = state
+        .rbac
+        .check_access(&current_user, "user-management", "write")
+        .await?;
+
+    if !access.allowed {
+        return Err(AppError::Forbidden);
+    }
+
+    info!(
+        "[API:User->revoke_user_role] User {} granted user-management:write via role {}",
+        current_user.user.id,
+        access.matched_role_name()
+    );
+
+    state
+        .uc
+        .user
+        .revoke_user_role
+        .execute(&current_user, &id, &role_id)
+        .await?;
+
+    Ok(SuccessResponse::with_data(200, role_id))
+}
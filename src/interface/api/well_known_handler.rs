@@ -0,0 +1,17 @@
+use std::sync::Arc;
+
+use axum::{extract::State, http::header, response::IntoResponse, routing::get, Json, Router};
+
+use crate::application::state::AppState;
+
+pub fn setup_well_known_handler() -> Router<Arc<AppState>> {
+    Router::new().route("/jwks.json", get(get_jwks))
+}
+
+// plain jwk set instead of SuccessResponse, jwt libraries expect the standard shape
+pub async fn get_jwks(State(app_state): State<Arc<AppState>>) -> impl IntoResponse {
+    (
+        [(header::CACHE_CONTROL, "public, max-age=3600")],
+        Json(app_state.jwt_maker.jwks().clone()),
+    )
+}
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use axum::{
     extract::{Request, State},
+    http::{header, HeaderMap},
     middleware::Next,
     response::IntoResponse,
 };
@@ -11,10 +12,31 @@ use crate::{
     application::state::AppState,
     infra::{
         errors::app_error::AppError,
-        oauth2::constants::{EMAIL_PROVIDER, GOOGLE_PROVIDER},
+        oauth2::constants::{EMAIL_PROVIDER, MAGIC_LINK_PROVIDER, WEBAUTHN_PROVIDER},
+        utils::jwt_maker::unverified_issuer,
     },
 };
 
+// session of the current request, `None` when it can't be resolved from the token
+#[derive(Clone, Debug, Default)]
+pub struct CurrentSession {
+    pub id: Option<String>,
+}
+
+// api clients send a bearer token, browsers rely on the cookies
+pub fn access_token(headers: &HeaderMap, cookie_jar: &CookieJar) -> Option<String> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|value| value.trim().to_string())
+        .or_else(|| {
+            cookie_jar
+                .get("access_token")
+                .map(|cookie| cookie.value().to_string())
+        })
+}
+
 pub async fn is_authorized(
     cookie_jar: CookieJar,
     State(app_state): State<Arc<AppState>>,
@@ -23,85 +45,109 @@ pub async fn is_authorized(
 ) -> Result<impl IntoResponse, AppError> {
     tracing::info!("[Middleware:Auth->is_authorized] Checking if user is authorized");
 
-    let token = cookie_jar
-        .get("access_token")
-        .map(|cookie| cookie.value().to_string());
+    let token = access_token(req.headers(), &cookie_jar).ok_or(AppError::Unauthorized)?;
 
-    if token.is_none() {
-        return Err(AppError::Unauthorized);
-    }
+    // tokens are routed by their issuer, ours or one of the providers
+    let provider = infer_provider(&app_state, &token).ok_or(AppError::InvalidOauthProvider)?;
 
-    let provider = cookie_jar
-        .get("provider")
-        .map(|cookie| cookie.value().to_string())
-        .unwrap_or_default();
-
-    if provider.is_empty() {
-        return Err(AppError::InvalidOauthProvider);
-    }
+    let mut current_session = CurrentSession::default();
 
     let (from_cache, current_user) = match provider.as_str() {
-        GOOGLE_PROVIDER => {
+        EMAIL_PROVIDER | WEBAUTHN_PROVIDER | MAGIC_LINK_PROVIDER => {
             let claims = app_state
-                .google_jwt_maker
-                .verify_token(&token.unwrap_or_default())
-                .await
+                .jwt_maker
+                .verify_access_token(&token)
                 .map_err(|err| {
                     tracing::info!(
-                        "[Middleware:Auth->is_authorized->GOOGLE_PROVIDER] User is not authorized with error: {}",
+                        "[Middleware:Auth->is_authorized->EMAIL_PROVIDER] User is not authorized with error: {}",
                         err
                     );
                     AppError::SessionExpired
                 })?;
 
+            // revoked sessions must not be usable even when the access token is still valid
+            let session = app_state
+                .svc
+                .oauth
+                .get_active_session(&claims.sid, &claims.sub)
+                .await?;
+            current_session.id = Some(session.id.clone());
+
             match app_state.svc.redis.get_current_user(&claims.sub).await {
                 Ok(existing_current_user) => (true, existing_current_user),
                 Err(_) => {
+                    // providers without id tokens get our tokens too, the session knows which one it was
                     let current_user = app_state
                         .svc
                         .oauth
-                        .get_current_oauth_user(&provider, &claims.sub)
+                        .get_session_user(&session)
                         .await
-                        .map_err(|err| AppError::UnauthorizedError(err.to_string()))?;
+                        .map_err(|err| match err {
+                            AppError::UserInactive => err,
+                            _ => AppError::UnauthorizedError(err.to_string()),
+                        })?;
 
                     (false, current_user)
                 }
             }
         }
-        EMAIL_PROVIDER => {
-            let claims = app_state
-                .jwt_maker
-                .verify_access_token(&token.unwrap_or_default())
+        _ => {
+            let oauth_provider = app_state.oauth_providers.get(&provider)?;
+            let claims = oauth_provider
+                .verify_id_token(&token)
+                .await
                 .map_err(|err| {
                     tracing::info!(
-                        "[Middleware:Auth->is_authorized->EMAIL_PROVIDER] User is not authorized with error: {}",
+                        "[Middleware:Auth->is_authorized->OAUTH_PROVIDER] User is not authorized with error: {}",
                         err
                     );
                     AppError::SessionExpired
                 })?;
 
-            match app_state.svc.redis.get_current_user(&claims.sub).await {
-                Ok(existing_current_user) => (true, existing_current_user),
-                Err(_) => {
-                    let current_user = app_state
-                        .svc
-                        .oauth
-                        .get_current_oauth_user(&provider, &claims.sub)
-                        .await
-                        .map_err(|err| AppError::UnauthorizedError(err.to_string()))?;
-
-                    (false, current_user)
-                }
-            }
-        }
-        _ => {
-            tracing::info!("[Middleware:Auth->is_authorized] User is not authorized because of Invalid Oauth Provider");
-            return Err(AppError::UnauthorizedError(
-                "User is not authorized because of Invalid Oauth Provider".to_string(),
-            ));
+            let (from_cache, current_user) =
+                match app_state.svc.redis.get_current_user(&claims.sub).await {
+                    Ok(existing_current_user) => (true, existing_current_user),
+                    Err(_) => {
+                        let current_user = app_state
+                            .svc
+                            .oauth
+                            .get_current_oauth_user(&provider, &claims.sub)
+                            .await
+                            .map_err(|err| match err {
+                                AppError::UserInactive => err,
+                                _ => AppError::UnauthorizedError(err.to_string()),
+                            })?;
+
+                        (false, current_user)
+                    }
+                };
+
+            // a valid id token alone isn't enough, it has to belong to a live session of a login
+            // through our callback. revoked sessions & tokens minted elsewhere end here
+            let session = app_state
+                .svc
+                .oauth
+                .get_active_id_token_session(&provider, &token, &current_user.user.id)
+                .await?;
+            current_session.id = Some(session.id);
+
+            (from_cache, current_user)
         }
     };
 
+    if !current_user.user.is_accessible() {
+        tracing::info!(
+            "[Middleware:Auth->is_authorized] User {} is deactivated",
+            &current_user.user.id
+        );
+        app_state
+            .svc
+            .redis
+            .remove_current_user(&current_user.user.id)
+            .await?;
+        return Err(AppError::UserInactive);
+    }
+
     if !from_cache {
         tracing::info!("dapet cache, return bro");
         app_state.svc.redis.set_current_user(&current_user).await?;
@@ -113,8 +159,23 @@ pub async fn is_authorized(
     );
 
     req.extensions_mut().insert(current_user);
+    req.extensions_mut().insert(current_session);
 
     let response = next.run(req).await;
 
     Ok(response.into_response())
 }
+
+// our own tokens carry our issuer, provider id tokens the issuer of their provider
+fn infer_provider(app_state: &AppState, token: &str) -> Option<String> {
+    let issuer = unverified_issuer(token)?;
+
+    if issuer == app_state.jwt_maker.issuer() {
+        return Some(EMAIL_PROVIDER.to_string());
+    }
+
+    app_state
+        .oauth_providers
+        .find_by_issuer(&issuer)
+        .map(|provider| provider.name().to_string())
+}
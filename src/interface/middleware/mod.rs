@@ -1,2 +1,3 @@
 pub mod auth_mw;
+pub mod rate_limit_mw;
 pub mod super_mw;
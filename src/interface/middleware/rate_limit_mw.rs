@@ -0,0 +1,184 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::CookieJar;
+
+use crate::{
+    application::services::redis_svc::RedisService,
+    domain::repositories::redis_repo::RedisRepository,
+    infra::{
+        errors::app_error::AppError,
+        utils::{client_info::ClientInfo, jwt_maker::JwtMaker},
+    },
+    interface::middleware::auth_mw::access_token,
+};
+
+pub const RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+pub const RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+pub const RATELIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+
+// what a limit counts requests by
+#[derive(Clone, Copy, Debug)]
+pub enum RateLimitKey {
+    // the peer address, forwarded headers only count behind TRUSTED_PROXIES
+    Ip,
+    // the subject of our own access token, everything else falls back to the ip
+    User,
+}
+
+#[derive(Clone, Debug)]
+pub struct RateLimit {
+    pub requests: u64,
+    pub window_seconds: u64,
+    pub key: RateLimitKey,
+}
+
+impl RateLimit {
+    // `<requests>/<seconds>` like `30/60`
+    pub fn from_spec(spec: &str, key: RateLimitKey) -> Self {
+        let (requests, window_seconds) = spec
+            .split_once('/')
+            .and_then(|(requests, window_seconds)| {
+                Some((
+                    requests.trim().parse::<u64>().ok()?,
+                    window_seconds.trim().parse::<u64>().ok()?,
+                ))
+            })
+            .filter(|(requests, window_seconds)| *requests > 0 && *window_seconds > 0)
+            .unwrap_or_else(|| panic!("Rate limit {} must look like <requests>/<seconds>", spec));
+
+        Self {
+            requests,
+            window_seconds,
+            key,
+        }
+    }
+}
+
+// a limit of one route group, the group name keeps the counters of groups apart
+pub struct RateLimiter<C> {
+    redis_svc: Arc<RedisService<C>>,
+    jwt_maker: Arc<JwtMaker>,
+    group: &'static str,
+    limit: RateLimit,
+}
+
+// a derive would want the redis repository to be `Clone` as well
+impl<C> Clone for RateLimiter<C> {
+    fn clone(&self) -> Self {
+        Self {
+            redis_svc: self.redis_svc.clone(),
+            jwt_maker: self.jwt_maker.clone(),
+            group: self.group,
+            limit: self.limit.clone(),
+        }
+    }
+}
+
+impl<C> RateLimiter<C>
+where
+    C: RedisRepository,
+{
+    pub fn new(
+        redis_svc: Arc<RedisService<C>>,
+        jwt_maker: Arc<JwtMaker>,
+        group: &'static str,
+        limit: RateLimit,
+    ) -> Self {
+        Self {
+            redis_svc,
+            jwt_maker,
+            group,
+            limit,
+        }
+    }
+
+    fn key(&self, req: &Request, client: &ClientInfo, cookie_jar: &CookieJar) -> String {
+        // without a peer address (only outside the server) all callers share one counter
+        let ip_address = client
+            .ip_address
+            .clone()
+            .unwrap_or_else(|| String::from("unknown"));
+
+        let key = match self.limit.key {
+            RateLimitKey::Ip => format!("ip_{}", ip_address),
+            RateLimitKey::User => {
+                let token = access_token(req.headers(), cookie_jar).unwrap_or_default();
+
+                // only verified tokens, so nobody can use up the limit of someone else
+                match self.jwt_maker.verify_access_token(&token) {
+                    Ok(claims) => format!("user_{}", claims.sub),
+                    Err(_) => format!("ip_{}", ip_address),
+                }
+            }
+        };
+
+        format!("{}_{}", self.group, key)
+    }
+}
+
+pub async fn rate_limit<C>(
+    State(limiter): State<RateLimiter<C>>,
+    client: ClientInfo,
+    cookie_jar: CookieJar,
+    req: Request,
+    next: Next,
+) -> Result<Response, AppError>
+where
+    C: RedisRepository,
+{
+    let key = limiter.key(&req, &client, &cookie_jar);
+    let limit = &limiter.limit;
+
+    // an unreachable redis shouldn't take the whole api down with it
+    let (hits, reset) = match limiter
+        .redis_svc
+        .count_sliding_window(&key, limit.window_seconds)
+        .await
+    {
+        Ok(counted) => counted,
+        Err(err) => {
+            tracing::warn!(
+                "[Middleware:RateLimit->rate_limit] Skipping rate limit of {}: {}",
+                key,
+                err
+            );
+            return Ok(next.run(req).await);
+        }
+    };
+
+    if hits > limit.requests {
+        tracing::info!(
+            "[Middleware:RateLimit->rate_limit] Rate limit of {} exceeded",
+            key
+        );
+
+        let mut resp = AppError::TooManyRequests.into_response();
+        set_rate_limit_headers(resp.headers_mut(), limit.requests, 0, reset);
+        resp.headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(reset));
+
+        return Ok(resp);
+    }
+
+    let mut resp = next.run(req).await;
+    set_rate_limit_headers(
+        resp.headers_mut(),
+        limit.requests,
+        limit.requests - hits,
+        reset,
+    );
+
+    Ok(resp)
+}
+
+fn set_rate_limit_headers(headers: &mut HeaderMap, limit: u64, remaining: u64, reset: u64) {
+    headers.insert(RATELIMIT_LIMIT, HeaderValue::from(limit));
+    headers.insert(RATELIMIT_REMAINING, HeaderValue::from(remaining));
+    headers.insert(RATELIMIT_RESET, HeaderValue::from(reset));
+}
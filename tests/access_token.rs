@@ -0,0 +1,65 @@
+mod common;
+
+use casbin::MgmtApi;
+use rust_ddd_oauth_casbin::{
+    domain::{
+        entities::{role::Role, user_role::UserRole},
+        repositories::role_repo::RoleRepository,
+    },
+    infra::utils::client_info::ClientInfo,
+};
+
+use common::auth::{auth, logged_in, login_request, user_with_password};
+
+#[tokio::test]
+async fn access_tokens_carry_the_roles_and_scopes_of_the_user() {
+    let auth = auth().await;
+    let user = auth.users.insert(user_with_password("claims@token.test"));
+    for (role_id, object, action) in [("editor", "post", "write"), ("viewer", "post", "read")] {
+        auth.roles
+            .create(Role::new(role_id.to_string(), role_id.to_string(), false))
+            .await
+            .unwrap();
+        auth.rbac
+            .enforcer
+            .write()
+            .await
+            .add_policy(vec![
+                role_id.to_string(),
+                object.to_string(),
+                action.to_string(),
+            ])
+            .await
+            .unwrap();
+    }
+    auth.roles
+        .assign_to_user(UserRole::new(user.id.clone(), "editor".to_string()))
+        .await
+        .unwrap();
+    auth.rbac.assign_role(&user.id, "editor").await.unwrap();
+
+    let (access_token, refresh_token) = logged_in(
+        auth.email_login
+            .execute(login_request("claims@token.test"), &ClientInfo::default())
+            .await
+            .unwrap(),
+    );
+    let claims = auth.jwt_maker.verify_access_token(&access_token).unwrap();
+    assert_eq!(claims.iss, "http://localhost:8800");
+    assert_eq!(claims.aud, "crate-test");
+    assert_eq!(claims.name, "claims");
+    assert_eq!(claims.roles, ["editor"]);
+    assert_eq!(claims.scopes, ["post:write"]);
+
+    // role changes show up with the next refresh
+    auth.roles
+        .assign_to_user(UserRole::new(user.id.clone(), "viewer".to_string()))
+        .await
+        .unwrap();
+    auth.rbac.assign_role(&user.id, "viewer").await.unwrap();
+
+    let (access_token, _) = auth.refresh.execute(&refresh_token).await.unwrap();
+    let claims = auth.jwt_maker.verify_access_token(&access_token).unwrap();
+    assert_eq!(claims.roles, ["editor", "viewer"]);
+    assert_eq!(claims.scopes, ["post:read", "post:write"]);
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use std::net::SocketAddr;
+
+use axum::{routing::get, Extension, Router};
+use rust_ddd_oauth_casbin::infra::utils::client_info::{ClientInfo, TrustedProxies};
+
+use common::test_config;
+
+// serves the address rate limits & login failures are counted by, the test client connects
+// from 127.0.0.1
+async fn spawn_echo(trusted_proxies: &str) -> String {
+    let cfg = test_config(&[("TRUSTED_PROXIES", trusted_proxies)]);
+    let router = Router::new()
+        .route(
+            "/",
+            get(|client: ClientInfo| async move { client.ip_address.unwrap_or_default() }),
+        )
+        .layer(Extension(TrustedProxies::from_config(&cfg)));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .unwrap()
+    });
+
+    format!("http://{}", addr)
+}
+
+async fn client_ip(base_url: &str, headers: &[(&str, &str)]) -> String {
+    let mut req = reqwest::Client::new().get(base_url);
+    for (name, value) in headers {
+        req = req.header(*name, *value);
+    }
+
+    req.send().await.unwrap().text().await.unwrap()
+}
+
+#[tokio::test]
+async fn forwarded_headers_of_untrusted_peers_are_ignored() {
+    let base_url = spawn_echo("").await;
+
+    assert_eq!(client_ip(&base_url, &[]).await, "127.0.0.1");
+    assert_eq!(
+        client_ip(&base_url, &[("x-forwarded-for", "203.0.113.7")]).await,
+        "127.0.0.1"
+    );
+    assert_eq!(
+        client_ip(&base_url, &[("x-real-ip", "203.0.113.7")]).await,
+        "127.0.0.1"
+    );
+
+    // a proxy somewhere else doesn't make the peer trusted
+    let base_url = spawn_echo("10.0.0.0/8").await;
+    assert_eq!(
+        client_ip(&base_url, &[("x-forwarded-for", "203.0.113.7")]).await,
+        "127.0.0.1"
+    );
+}
+
+#[tokio::test]
+async fn trusted_proxies_forward_the_nearest_untrusted_hop() {
+    let base_url = spawn_echo("127.0.0.1, 10.0.0.0/8").await;
+
+    assert_eq!(
+        client_ip(&base_url, &[("x-forwarded-for", "203.0.113.7")]).await,
+        "203.0.113.7"
+    );
+
+    // the client can only prepend made up addresses, our proxies append the real one
+    assert_eq!(
+        client_ip(
+            &base_url,
+            &[("x-forwarded-for", "198.51.100.1, 203.0.113.7, 10.1.2.3")]
+        )
+        .await,
+        "203.0.113.7"
+    );
+    assert_eq!(
+        client_ip(
+            &base_url,
+            &[
+                ("x-forwarded-for", "198.51.100.1"),
+                ("x-forwarded-for", "203.0.113.7")
+            ]
+        )
+        .await,
+        "203.0.113.7"
+    );
+
+    assert_eq!(
+        client_ip(&base_url, &[("x-real-ip", "203.0.113.7")]).await,
+        "203.0.113.7"
+    );
+    assert_eq!(
+        client_ip(
+            &base_url,
+            &[
+                ("x-forwarded-for", "203.0.113.7"),
+                ("x-real-ip", "198.51.100.1")
+            ]
+        )
+        .await,
+        "203.0.113.7"
+    );
+}
+
+#[tokio::test]
+async fn broken_forwarded_hops_stop_the_lookup() {
+    let base_url = spawn_echo("127.0.0.1").await;
+
+    assert_eq!(
+        client_ip(&base_url, &[("x-forwarded-for", "198.51.100.1, not-an-ip")]).await,
+        "127.0.0.1"
+    );
+    assert_eq!(
+        client_ip(&base_url, &[("x-real-ip", "not-an-ip")]).await,
+        "127.0.0.1"
+    );
+}
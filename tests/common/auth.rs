@@ -0,0 +1,282 @@
+use std::sync::Arc;
+
+use rust_ddd_oauth_casbin::{
+    application::{
+        dto::auth::{email_request::EmailLoginRequest, token_response::EmailLoginResult},
+        services::{
+            login_guard_svc::LoginGuardService, mail_svc::MailService, mfa_svc::MfaService,
+            oauth_svc::OauthService, redis_svc::RedisService, user_token_svc::UserTokenService,
+        },
+        usecases::auth::{
+            change_password::ChangePassword, confirm_totp_enrolment::ConfirmTotpEnrolment,
+            email_login::EmailLogin, forgot_password::ForgotPassword,
+            redeem_magic_link::RedeemMagicLink, refresh_oauth_token::RefreshOauthToken,
+            resend_email_verification::ResendEmailVerification, reset_password::ResetPassword,
+            send_magic_link::SendMagicLink, start_totp_enrolment::StartTotpEnrolment,
+            verify_email::VerifyEmail, verify_mfa_login::VerifyMfaLogin,
+        },
+    },
+    domain::entities::{
+        user::{User, UserFull},
+        user_oauth_provider::UserOauthProvider,
+    },
+    infra::{
+        oauth2::registry::OauthProviderRegistry,
+        rbac::Rbac,
+        utils::{jwt_maker::JwtMaker, password::hash_password, token_cipher::TokenCipher},
+    },
+};
+
+use super::{
+    memory::{
+        MemoryMailer, MemoryOauthProviderRepo, MemoryRedis, MemoryRoleRepo, MemorySessionRepo,
+        MemoryUserMfaRepo, MemoryUserRepo, MemoryUserTokenRepo,
+    },
+    memory_rbac, test_config,
+};
+
+pub const PASSWORD: &str = "correct horse";
+
+pub type Oauth =
+    OauthService<MemoryUserRepo, MemoryRoleRepo, MemorySessionRepo, MemoryOauthProviderRepo>;
+pub type Mfa = MfaService<MemoryUserMfaRepo, MemoryRoleRepo>;
+
+// the auth use cases wired to in-memory repositories
+pub struct Auth {
+    pub users: Arc<MemoryUserRepo>,
+    pub roles: Arc<MemoryRoleRepo>,
+    pub sessions: Arc<MemorySessionRepo>,
+    pub oauth_providers: Arc<MemoryOauthProviderRepo>,
+    pub user_tokens: Arc<MemoryUserTokenRepo>,
+    pub user_mfa: Arc<MemoryUserMfaRepo>,
+    pub redis: Arc<MemoryRedis>,
+    pub mailer: Arc<MemoryMailer>,
+    pub rbac: Arc<Rbac>,
+    pub providers: Arc<OauthProviderRegistry>,
+    pub jwt_maker: Arc<JwtMaker>,
+    pub token_cipher: Arc<TokenCipher>,
+    pub oauth_svc: Arc<Oauth>,
+    pub user_token_svc: Arc<UserTokenService<MemoryUserTokenRepo>>,
+    pub mail_svc: Arc<MailService>,
+    pub mfa_svc: Arc<Mfa>,
+    pub redis_svc: Arc<RedisService<MemoryRedis>>,
+    pub login_guard_svc: Arc<LoginGuardService<MemoryRedis>>,
+    pub email_login: EmailLogin<
+        MemoryUserRepo,
+        MemoryRoleRepo,
+        MemorySessionRepo,
+        MemoryOauthProviderRepo,
+        MemoryUserMfaRepo,
+        MemoryRedis,
+    >,
+    pub refresh: RefreshOauthToken<
+        MemoryUserRepo,
+        MemoryRoleRepo,
+        MemorySessionRepo,
+        MemoryOauthProviderRepo,
+    >,
+    pub resend_email_verification: ResendEmailVerification<MemoryUserRepo, MemoryUserTokenRepo>,
+    pub verify_email: VerifyEmail<MemoryUserRepo, MemoryUserTokenRepo, MemoryRedis>,
+    pub forgot_password: ForgotPassword<MemoryUserRepo, MemoryUserTokenRepo>,
+    pub reset_password: ResetPassword<
+        MemoryUserRepo,
+        MemoryRoleRepo,
+        MemorySessionRepo,
+        MemoryOauthProviderRepo,
+        MemoryUserTokenRepo,
+        MemoryRedis,
+    >,
+    pub change_password:
+        ChangePassword<MemoryUserRepo, MemoryRoleRepo, MemorySessionRepo, MemoryOauthProviderRepo>,
+    pub start_totp_enrolment:
+        StartTotpEnrolment<MemoryUserRepo, MemoryRoleRepo, MemoryUserMfaRepo, MemoryRedis>,
+    pub confirm_totp_enrolment: ConfirmTotpEnrolment<MemoryRoleRepo, MemoryUserMfaRepo>,
+    pub verify_mfa_login: VerifyMfaLogin<
+        MemoryUserRepo,
+        MemoryRoleRepo,
+        MemorySessionRepo,
+        MemoryOauthProviderRepo,
+        MemoryUserMfaRepo,
+        MemoryRedis,
+    >,
+    pub send_magic_link: SendMagicLink<MemoryUserRepo, MemoryRedis>,
+    pub redeem_magic_link: RedeemMagicLink<
+        MemoryUserRepo,
+        MemoryRoleRepo,
+        MemorySessionRepo,
+        MemoryOauthProviderRepo,
+        MemoryUserMfaRepo,
+        MemoryRedis,
+    >,
+}
+
+pub async fn auth() -> Auth {
+    let cfg = test_config(&[]);
+    auth_with_providers(OauthProviderRegistry::from_config(&cfg), &[]).await
+}
+
+// same as `auth`, with the given providers instead of the configured ones
+pub async fn auth_with_providers(
+    providers: OauthProviderRegistry,
+    overrides: &[(&str, &str)],
+) -> Auth {
+    let cfg = Arc::new(test_config(overrides));
+    let users = Arc::new(MemoryUserRepo::default());
+    let roles = Arc::new(MemoryRoleRepo::default());
+    let sessions = Arc::new(MemorySessionRepo::default());
+    let oauth_providers = Arc::new(MemoryOauthProviderRepo::default());
+    let user_tokens = Arc::new(MemoryUserTokenRepo::default());
+    let user_mfa = Arc::new(MemoryUserMfaRepo::default());
+    let redis = Arc::new(MemoryRedis::default());
+    let mailer = Arc::new(MemoryMailer::default());
+    let jwt_maker = Arc::new(JwtMaker::new(&cfg));
+    let token_cipher = Arc::new(TokenCipher::new(&cfg));
+    let rbac = Arc::new(memory_rbac().await);
+    let providers = Arc::new(providers);
+    let oauth_svc = Arc::new(OauthService::new(
+        providers.clone(),
+        rbac.clone(),
+        token_cipher.clone(),
+        users.clone(),
+        roles.clone(),
+        sessions.clone(),
+        oauth_providers.clone(),
+        cfg.oauth_auto_link_verified_email,
+    ));
+    let user_token_svc = Arc::new(UserTokenService::new(
+        user_tokens.clone(),
+        token_cipher.clone(),
+    ));
+    let mail_svc = Arc::new(MailService::new(
+        mailer.clone(),
+        cfg.app_name.clone(),
+        cfg.email_verification_url.clone(),
+        cfg.password_reset_url.clone(),
+        cfg.magic_link_url.clone(),
+    ));
+    let mfa_svc = Arc::new(MfaService::new(
+        user_mfa.clone(),
+        roles.clone(),
+        token_cipher.clone(),
+        cfg.app_name.clone(),
+    ));
+    let redis_svc = Arc::new(RedisService::new(redis.clone()));
+    let login_guard_svc = Arc::new(LoginGuardService::new(redis.clone()));
+
+    Auth {
+        email_login: EmailLogin::new(
+            users.clone(),
+            jwt_maker.clone(),
+            oauth_svc.clone(),
+            mfa_svc.clone(),
+            redis_svc.clone(),
+            login_guard_svc.clone(),
+            cfg.email_verification_required,
+            cfg.email_verification_required_since,
+        ),
+        refresh: RefreshOauthToken::new(
+            jwt_maker.clone(),
+            token_cipher.clone(),
+            users.clone(),
+            sessions.clone(),
+            oauth_svc.clone(),
+        ),
+        resend_email_verification: ResendEmailVerification::new(
+            users.clone(),
+            user_token_svc.clone(),
+            mail_svc.clone(),
+        ),
+        verify_email: VerifyEmail::new(users.clone(), user_token_svc.clone(), redis_svc.clone()),
+        forgot_password: ForgotPassword::new(
+            users.clone(),
+            user_token_svc.clone(),
+            mail_svc.clone(),
+        ),
+        reset_password: ResetPassword::new(
+            users.clone(),
+            oauth_svc.clone(),
+            user_token_svc.clone(),
+            redis_svc.clone(),
+        ),
+        change_password: ChangePassword::new(users.clone(), oauth_svc.clone()),
+        start_totp_enrolment: StartTotpEnrolment::new(
+            users.clone(),
+            mfa_svc.clone(),
+            redis_svc.clone(),
+        ),
+        confirm_totp_enrolment: ConfirmTotpEnrolment::new(mfa_svc.clone()),
+        verify_mfa_login: VerifyMfaLogin::new(
+            users.clone(),
+            jwt_maker.clone(),
+            oauth_svc.clone(),
+            mfa_svc.clone(),
+            redis_svc.clone(),
+        ),
+        send_magic_link: SendMagicLink::new(
+            users.clone(),
+            token_cipher.clone(),
+            redis_svc.clone(),
+            mail_svc.clone(),
+        ),
+        redeem_magic_link: RedeemMagicLink::new(
+            users.clone(),
+            jwt_maker.clone(),
+            token_cipher.clone(),
+            oauth_svc.clone(),
+            mfa_svc.clone(),
+            redis_svc.clone(),
+        ),
+        users,
+        roles,
+        sessions,
+        oauth_providers,
+        user_tokens,
+        user_mfa,
+        redis,
+        mailer,
+        rbac,
+        providers,
+        jwt_maker,
+        token_cipher,
+        oauth_svc,
+        user_token_svc,
+        mail_svc,
+        mfa_svc,
+        redis_svc,
+        login_guard_svc,
+    }
+}
+
+pub fn login_request(email: &str) -> EmailLoginRequest {
+    EmailLoginRequest {
+        email: email.to_string(),
+        password: PASSWORD.to_string(),
+    }
+}
+
+pub fn user_with_password(email: &str) -> User {
+    User::new(
+        email.to_string(),
+        Some(hash_password(PASSWORD.as_bytes()).unwrap()),
+    )
+}
+
+// the user as the auth middleware caches it after an email login, without roles
+pub fn current_user(user: &User) -> UserFull {
+    UserFull::new(
+        user.clone(),
+        UserOauthProvider::new(user.id.clone(), "email".to_string(), user.email.clone()),
+        vec![],
+    )
+}
+
+// the tokens of a login that didn't stop at the second factor
+pub fn logged_in(result: EmailLoginResult) -> (String, String) {
+    match result {
+        EmailLoginResult::LoggedIn {
+            access_token,
+            refresh_token,
+        } => (access_token, refresh_token),
+        EmailLoginResult::MfaRequired(_) => panic!("the login asks for a second factor"),
+    }
+}
@@ -0,0 +1,721 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use rust_ddd_oauth_casbin::{
+    domain::{
+        entities::{
+            refresh_token::RefreshToken,
+            role::Role,
+            user::User,
+            user_mfa::{UserRecoveryCode, UserTotp},
+            user_oauth_provider::UserOauthProvider,
+            user_role::UserRole,
+            user_session::UserSession,
+            user_token::UserToken,
+        },
+        repositories::{
+            oauth_provider_repo::OauthProviderRepository, redis_repo::RedisRepository,
+            role_repo::RoleRepository, user_mfa_repo::UserMfaRepository, user_repo::UserRepository,
+            user_session_repo::UserSessionRepository, user_token_repo::UserTokenRepository,
+        },
+    },
+    infra::{
+        errors::app_error::AppError,
+        mail::mailer::{Mail, Mailer},
+    },
+};
+
+// in-memory stand-ins for the postgres repositories, missing rows fail like sqlx does
+
+fn not_found() -> AppError {
+    AppError::SqlxError(sqlx::Error::RowNotFound)
+}
+
+#[derive(Default)]
+pub struct MemoryUserRepo {
+    pub users: Mutex<Vec<User>>,
+}
+
+impl MemoryUserRepo {
+    pub fn insert(&self, user: User) -> User {
+        self.users.lock().unwrap().push(user.clone());
+        user
+    }
+}
+
+#[async_trait::async_trait]
+impl UserRepository for MemoryUserRepo {
+    async fn paginate(
+        &self,
+        _page: i64,
+        _limit: i64,
+        _search: Option<String>,
+    ) -> Result<(Vec<User>, i64), AppError> {
+        let users = self.users.lock().unwrap().clone();
+        let total = users.len() as i64;
+        Ok((users, total))
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<User, AppError> {
+        self.users
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|user| user.email == email)
+            .cloned()
+            .ok_or_else(not_found)
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<User, AppError> {
+        self.users
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|user| user.id == id)
+            .cloned()
+            .ok_or_else(not_found)
+    }
+
+    async fn tx_create(
+        &self,
+        _tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        entity: User,
+    ) -> Result<User, AppError> {
+        Ok(self.insert(entity))
+    }
+
+    async fn tx_register_user(
+        &self,
+        _tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        user: &User,
+        user_oauth_provider: &UserOauthProvider,
+        user_role: &UserRole,
+    ) -> Result<(User, UserOauthProvider, UserRole), AppError> {
+        Ok((
+            self.insert(user.clone()),
+            user_oauth_provider.clone(),
+            user_role.clone(),
+        ))
+    }
+
+    async fn update(&self, id: &str, entity: User) -> Result<(), AppError> {
+        let mut users = self.users.lock().unwrap();
+        let user = users
+            .iter_mut()
+            .find(|user| user.id == id)
+            .ok_or_else(not_found)?;
+        *user = entity;
+        Ok(())
+    }
+
+    async fn update_password(&self, id: &str, entity: &User) -> Result<(), AppError> {
+        let mut users = self.users.lock().unwrap();
+        let user = users
+            .iter_mut()
+            .find(|user| user.id == id)
+            .ok_or_else(not_found)?;
+        user.password_hash = entity.password_hash.clone();
+        user.updated_at = entity.updated_at;
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryRoleRepo {
+    pub roles: Mutex<Vec<Role>>,
+    pub user_roles: Mutex<Vec<UserRole>>,
+}
+
+#[async_trait::async_trait]
+impl RoleRepository for MemoryRoleRepo {
+    async fn paginate(&self, _page: i64, _limit: i64) -> Result<(Vec<Role>, i64), AppError> {
+        let roles = self.roles.lock().unwrap().clone();
+        let total = roles.len() as i64;
+        Ok((roles, total))
+    }
+
+    async fn find_all(&self) -> Result<Vec<Role>, AppError> {
+        Ok(self.roles.lock().unwrap().clone())
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<Role, AppError> {
+        self.roles
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|role| role.id == id)
+            .cloned()
+            .ok_or_else(not_found)
+    }
+
+    async fn find_default(&self) -> Result<Role, AppError> {
+        self.roles
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|role| role.is_default)
+            .cloned()
+            .ok_or_else(not_found)
+    }
+
+    async fn find_by_name(&self, role_name: &str) -> Result<Role, AppError> {
+        self.roles
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|role| role.name == role_name)
+            .cloned()
+            .ok_or_else(not_found)
+    }
+
+    async fn create(&self, entity: Role) -> Result<Role, AppError> {
+        self.roles.lock().unwrap().push(entity.clone());
+        Ok(entity)
+    }
+
+    async fn tx_create(
+        &self,
+        _tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        entity: Role,
+    ) -> Result<Role, AppError> {
+        self.create(entity).await
+    }
+
+    async fn update(&self, id: &str, entity: Role) -> Result<(), AppError> {
+        let mut roles = self.roles.lock().unwrap();
+        let role = roles
+            .iter_mut()
+            .find(|role| role.id == id)
+            .ok_or_else(not_found)?;
+        *role = entity;
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), AppError> {
+        self.roles.lock().unwrap().retain(|role| role.id != id);
+        Ok(())
+    }
+
+    async fn get_roles_by_user_id(&self, user_id: &str) -> Result<Vec<Role>, AppError> {
+        let user_roles = self.user_roles.lock().unwrap();
+        Ok(self
+            .roles
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|role| {
+                user_roles
+                    .iter()
+                    .any(|user_role| user_role.user_id == user_id && user_role.role_id == role.id)
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn find_all_user_roles(&self) -> Result<Vec<UserRole>, AppError> {
+        Ok(self.user_roles.lock().unwrap().clone())
+    }
+
+    async fn assign_to_user(&self, entity: UserRole) -> Result<UserRole, AppError> {
+        self.user_roles.lock().unwrap().push(entity.clone());
+        Ok(entity)
+    }
+
+    async fn revoke_from_user(&self, user_id: &str, role_id: &str) -> Result<(), AppError> {
+        self.user_roles
+            .lock()
+            .unwrap()
+            .retain(|user_role| !(user_role.user_id == user_id && user_role.role_id == role_id));
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct MemorySessionRepo {
+    pub sessions: Mutex<Vec<UserSession>>,
+    pub refresh_tokens: Mutex<Vec<RefreshToken>>,
+}
+
+impl MemorySessionRepo {
+    pub fn all(&self) -> Vec<UserSession> {
+        self.sessions.lock().unwrap().clone()
+    }
+
+    // refresh tokens go with their session, like the foreign key cascade does
+    fn remove_sessions(&self, remove: impl Fn(&UserSession) -> bool) {
+        let mut sessions = self.sessions.lock().unwrap();
+        let removed: Vec<String> = sessions
+            .iter()
+            .filter(|session| remove(session))
+            .map(|session| session.id.clone())
+            .collect();
+        sessions.retain(|session| !removed.contains(&session.id));
+        self.refresh_tokens
+            .lock()
+            .unwrap()
+            .retain(|token| !removed.contains(&token.session_id));
+    }
+}
+
+#[async_trait::async_trait]
+impl UserSessionRepository for MemorySessionRepo {
+    async fn find_by_id(&self, session_id: &str) -> Result<UserSession, AppError> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|session| session.id == session_id)
+            .cloned()
+            .ok_or_else(not_found)
+    }
+
+    async fn find_all_by_user_id(&self, user_id: &str) -> Result<Vec<UserSession>, AppError> {
+        Ok(self
+            .sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|session| session.user_id == user_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn find_by_refresh_token_hash(&self, token_hash: &str) -> Result<UserSession, AppError> {
+        let session_id = self
+            .refresh_tokens
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|token| token.token_hash == token_hash && !token.is_rotated())
+            .map(|token| token.session_id.clone())
+            .ok_or_else(not_found)?;
+
+        self.find_by_id(&session_id).await
+    }
+
+    async fn find_by_id_token_hash(&self, id_token_hash: &str) -> Result<UserSession, AppError> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|session| session.id_token_hash.as_deref() == Some(id_token_hash))
+            .cloned()
+            .ok_or_else(not_found)
+    }
+
+    async fn create(&self, entity: UserSession) -> Result<UserSession, AppError> {
+        self.sessions.lock().unwrap().push(entity.clone());
+        Ok(entity)
+    }
+
+    async fn update_token(&self, session: &UserSession) -> Result<(), AppError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        if let Some(stored) = sessions.iter_mut().find(|stored| stored.id == session.id) {
+            *stored = session.clone();
+        }
+        Ok(())
+    }
+
+    async fn touch(&self, session_id: &str) -> Result<(), AppError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        if let Some(stored) = sessions.iter_mut().find(|stored| stored.id == session_id) {
+            stored.last_used_at = chrono::Utc::now();
+        }
+        Ok(())
+    }
+
+    async fn delete_by_id(&self, session_id: &str) -> Result<(), AppError> {
+        self.remove_sessions(|session| session.id == session_id);
+        Ok(())
+    }
+
+    async fn delete_by_user_id(&self, user_id: &str) -> Result<(), AppError> {
+        self.remove_sessions(|session| session.user_id == user_id);
+        Ok(())
+    }
+
+    async fn find_refresh_token(&self, token_hash: &str) -> Result<RefreshToken, AppError> {
+        self.refresh_tokens
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|token| token.token_hash == token_hash)
+            .cloned()
+            .ok_or_else(not_found)
+    }
+
+    async fn create_refresh_token(&self, entity: RefreshToken) -> Result<RefreshToken, AppError> {
+        let mut refresh_tokens = self.refresh_tokens.lock().unwrap();
+        // token_hash is unique
+        if refresh_tokens
+            .iter()
+            .any(|token| token.token_hash == entity.token_hash)
+        {
+            return Err(AppError::ProcessError("duplicate token hash".to_string()));
+        }
+        refresh_tokens.push(entity.clone());
+        Ok(entity)
+    }
+
+    async fn rotate_refresh_token(&self, id: &str) -> Result<bool, AppError> {
+        let mut refresh_tokens = self.refresh_tokens.lock().unwrap();
+        match refresh_tokens
+            .iter_mut()
+            .find(|token| token.id == id && !token.is_rotated())
+        {
+            Some(token) => {
+                token.rotated_at = Some(chrono::Utc::now());
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn find_all_unsealed(&self, key_version: i32) -> Result<Vec<UserSession>, AppError> {
+        Ok(self
+            .sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|session| match session.token_key_version {
+                None => true,
+                Some(version) => version != key_version && session.provider != "email",
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn find_all_unkeyed_refresh_tokens(&self) -> Result<Vec<RefreshToken>, AppError> {
+        Ok(self
+            .refresh_tokens
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|token| !token.keyed)
+            .cloned()
+            .collect())
+    }
+
+    async fn update_refresh_token_hash(&self, id: &str, token_hash: &str) -> Result<(), AppError> {
+        let mut refresh_tokens = self.refresh_tokens.lock().unwrap();
+        if let Some(token) = refresh_tokens.iter_mut().find(|token| token.id == id) {
+            token.token_hash = token_hash.to_string();
+            token.keyed = true;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryOauthProviderRepo {
+    pub oauth_providers: Mutex<Vec<UserOauthProvider>>,
+}
+
+impl MemoryOauthProviderRepo {
+    pub fn insert(&self, oauth_provider: UserOauthProvider) -> UserOauthProvider {
+        self.oauth_providers
+            .lock()
+            .unwrap()
+            .push(oauth_provider.clone());
+        oauth_provider
+    }
+}
+
+#[async_trait::async_trait]
+impl OauthProviderRepository for MemoryOauthProviderRepo {
+    async fn get_by_provider_and_id(
+        &self,
+        provider: &str,
+        provider_id: &str,
+    ) -> Result<UserOauthProvider, AppError> {
+        self.oauth_providers
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|oauth_provider| {
+                oauth_provider.provider == provider
+                    && oauth_provider.provider_user_id == provider_id
+            })
+            .cloned()
+            .ok_or_else(not_found)
+    }
+
+    async fn find_by_user_id(&self, user_id: &str) -> Result<Vec<UserOauthProvider>, AppError> {
+        Ok(self
+            .oauth_providers
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|oauth_provider| oauth_provider.user_id == user_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn create(&self, entity: &UserOauthProvider) -> Result<UserOauthProvider, AppError> {
+        Ok(self.insert(entity.clone()))
+    }
+
+    async fn delete_by_id(&self, id: &str) -> Result<(), AppError> {
+        self.oauth_providers
+            .lock()
+            .unwrap()
+            .retain(|oauth_provider| oauth_provider.id != id);
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryUserTokenRepo {
+    pub user_tokens: Mutex<Vec<UserToken>>,
+}
+
+impl MemoryUserTokenRepo {
+    pub fn all(&self) -> Vec<UserToken> {
+        self.user_tokens.lock().unwrap().clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl UserTokenRepository for MemoryUserTokenRepo {
+    async fn create(&self, entity: &UserToken) -> Result<UserToken, AppError> {
+        self.user_tokens.lock().unwrap().push(entity.clone());
+        Ok(entity.clone())
+    }
+
+    async fn find_by_hash(&self, purpose: &str, token_hash: &str) -> Result<UserToken, AppError> {
+        self.user_tokens
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|user_token| user_token.purpose == purpose && user_token.token_hash == token_hash)
+            .cloned()
+            .ok_or_else(not_found)
+    }
+
+    async fn consume(&self, id: &str) -> Result<bool, AppError> {
+        let mut user_tokens = self.user_tokens.lock().unwrap();
+        match user_tokens
+            .iter_mut()
+            .find(|user_token| user_token.id == id && user_token.consumed_at.is_none())
+        {
+            Some(user_token) => {
+                user_token.consumed_at = Some(chrono::Utc::now());
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn delete_unconsumed(&self, user_id: &str, purpose: &str) -> Result<(), AppError> {
+        self.user_tokens.lock().unwrap().retain(|user_token| {
+            user_token.user_id != user_id
+                || user_token.purpose != purpose
+                || user_token.consumed_at.is_some()
+        });
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryUserMfaRepo {
+    pub totps: Mutex<Vec<UserTotp>>,
+    pub recovery_codes: Mutex<Vec<UserRecoveryCode>>,
+}
+
+#[async_trait::async_trait]
+impl UserMfaRepository for MemoryUserMfaRepo {
+    async fn find_totp(&self, user_id: &str) -> Result<UserTotp, AppError> {
+        self.totps
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|user_totp| user_totp.user_id == user_id)
+            .cloned()
+            .ok_or_else(not_found)
+    }
+
+    async fn save_totp(&self, entity: &UserTotp) -> Result<UserTotp, AppError> {
+        let mut totps = self.totps.lock().unwrap();
+        totps.retain(|user_totp| user_totp.user_id != entity.user_id);
+        totps.push(entity.clone());
+        Ok(entity.clone())
+    }
+
+    async fn use_totp_step(&self, user_id: &str, step: i64) -> Result<bool, AppError> {
+        let mut totps = self.totps.lock().unwrap();
+        let user_totp = totps
+            .iter_mut()
+            .find(|user_totp| user_totp.user_id == user_id)
+            .ok_or_else(not_found)?;
+        if user_totp
+            .last_used_step
+            .is_some_and(|last_used_step| last_used_step >= step)
+        {
+            return Ok(false);
+        }
+
+        user_totp.last_used_step = Some(step);
+        Ok(true)
+    }
+
+    async fn confirm_totp(&self, user_id: &str, step: i64) -> Result<(), AppError> {
+        let mut totps = self.totps.lock().unwrap();
+        let user_totp = totps
+            .iter_mut()
+            .find(|user_totp| user_totp.user_id == user_id)
+            .ok_or_else(not_found)?;
+        user_totp.confirmed_at = Some(chrono::Utc::now());
+        user_totp.last_used_step = Some(step);
+        Ok(())
+    }
+
+    async fn delete_totp(&self, user_id: &str) -> Result<(), AppError> {
+        self.totps
+            .lock()
+            .unwrap()
+            .retain(|user_totp| user_totp.user_id != user_id);
+        self.recovery_codes
+            .lock()
+            .unwrap()
+            .retain(|recovery_code| recovery_code.user_id != user_id);
+        Ok(())
+    }
+
+    async fn replace_recovery_codes(
+        &self,
+        user_id: &str,
+        codes: &[UserRecoveryCode],
+    ) -> Result<(), AppError> {
+        let mut recovery_codes = self.recovery_codes.lock().unwrap();
+        recovery_codes.retain(|recovery_code| recovery_code.user_id != user_id);
+        recovery_codes.extend_from_slice(codes);
+        Ok(())
+    }
+
+    async fn use_recovery_code(&self, user_id: &str, code_hash: &str) -> Result<bool, AppError> {
+        let mut recovery_codes = self.recovery_codes.lock().unwrap();
+        match recovery_codes.iter_mut().find(|recovery_code| {
+            recovery_code.user_id == user_id
+                && recovery_code.code_hash == code_hash
+                && recovery_code.used_at.is_none()
+        }) {
+            Some(recovery_code) => {
+                recovery_code.used_at = Some(chrono::Utc::now());
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn count_unused_recovery_codes(&self, user_id: &str) -> Result<i64, AppError> {
+        Ok(self
+            .recovery_codes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|recovery_code| {
+                recovery_code.user_id == user_id && recovery_code.used_at.is_none()
+            })
+            .count() as i64)
+    }
+}
+
+// keeps the mails instead of delivering them
+#[derive(Default)]
+pub struct MemoryMailer {
+    pub mails: Mutex<Vec<Mail>>,
+}
+
+impl MemoryMailer {
+    pub fn all(&self) -> Vec<Mail> {
+        self.mails.lock().unwrap().clone()
+    }
+
+    // the `token` query parameter of the last link mailed to `to`
+    pub fn last_token(&self, to: &str) -> Option<String> {
+        let mail = self
+            .mails
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|mail| mail.to == to)
+            .cloned()?;
+        let link = mail
+            .body
+            .split_whitespace()
+            .find(|word| word.starts_with("http"))?;
+
+        reqwest::Url::parse(link)
+            .ok()?
+            .query_pairs()
+            .find(|(key, _)| key == "token")
+            .map(|(_, token)| token.into_owned())
+    }
+}
+
+#[async_trait::async_trait]
+impl Mailer for MemoryMailer {
+    async fn send(&self, mail: &Mail) -> Result<(), AppError> {
+        self.mails.lock().unwrap().push(mail.clone());
+        Ok(())
+    }
+}
+
+// expiries are ignored, values live until they are deleted or taken
+#[derive(Default)]
+pub struct MemoryRedis {
+    pub values: Mutex<HashMap<String, String>>,
+}
+
+#[async_trait::async_trait]
+impl RedisRepository for MemoryRedis {
+    async fn get_value(&self, key: &str) -> Result<String, AppError> {
+        self.values
+            .lock()
+            .unwrap()
+            .get(key)
+            .cloned()
+            .ok_or(AppError::ProcessError(format!("{} is not set", key)))
+    }
+
+    async fn set_value(&self, key: &str, value: &str) -> Result<(), AppError> {
+        self.values
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    async fn set_value_with_expiry(
+        &self,
+        key: &str,
+        value: &str,
+        _expiry: u64,
+    ) -> Result<(), AppError> {
+        self.set_value(key, value).await
+    }
+
+    async fn delete_value(&self, key: &str) -> Result<(), AppError> {
+        self.values.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn take_value(&self, key: &str) -> Result<Option<String>, AppError> {
+        Ok(self.values.lock().unwrap().remove(key))
+    }
+
+    async fn set_expiry(&self, _key: &str, _expiry: i64) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    async fn increment(&self, key: &str, _expiry: u64) -> Result<i64, AppError> {
+        let mut values = self.values.lock().unwrap();
+        let value = values
+            .get(key)
+            .and_then(|value| value.parse::<i64>().ok())
+            .unwrap_or(0)
+            + 1;
+        values.insert(key.to_string(), value.to_string());
+        Ok(value)
+    }
+}
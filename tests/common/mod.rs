@@ -0,0 +1,103 @@
+#![allow(dead_code)]
+
+pub mod auth;
+pub mod memory;
+pub mod oauth_flow;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use axum::{extract::Request, middleware::Next, response::Response, Router};
+use casbin::{CoreApi, DefaultModel, Enforcer, MemoryAdapter};
+use envconfig::Envconfig;
+use rust_ddd_oauth_casbin::infra::{config::AppConfig, rbac::Rbac};
+use tokio::sync::RwLock;
+
+// the required settings, `overrides` adds or replaces entries
+pub fn test_config(overrides: &[(&str, &str)]) -> AppConfig {
+    let mut env: HashMap<String, String> = [
+        ("APP_NAME", "crate-test"),
+        ("APP_PORT", "8800"),
+        ("APP_ENV", "test"),
+        ("DATABASE_URL", "postgres://localhost/crate_test"),
+        ("REDIS_URL", "redis://localhost"),
+        ("JWT_ISSUER", "http://localhost:8800"),
+        ("JWT_AUDIENCE", "crate-test"),
+        ("JWT_SIGNING_KEY", "current:tests/fixtures/jwt-current.pem"),
+        (
+            "JWT_VERIFICATION_KEYS",
+            "current:tests/fixtures/jwt-current.pub.pem",
+        ),
+        ("ALLOWED_ORIGINS", "http://localhost:3000"),
+        ("GOOGLE_CLIENT_ID", "google-client"),
+        ("GOOGLE_CLIENT_SECRET", "google-secret"),
+        (
+            "GOOGLE_REDIRECT_URI",
+            "http://localhost:8800/oauth/google/callback",
+        ),
+        ("TOKEN_HASH_KEY", "test-hash-key"),
+        (
+            "TOKEN_ENCRYPTION_KEYS",
+            "1:AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=",
+        ),
+        ("TOKEN_ENCRYPTION_KEY_VERSION", "1"),
+        ("SUPER_KEY", "test-super-key"),
+    ]
+    .into_iter()
+    .chain(overrides.iter().copied())
+    .map(|(key, value)| (key.to_string(), value.to_string()))
+    .collect();
+    env.retain(|_, value| !value.is_empty());
+
+    AppConfig::init_from_hashmap(&env).expect("test config is incomplete")
+}
+
+// the app's casbin model with policies kept in memory instead of postgres
+pub async fn memory_rbac() -> Rbac {
+    let model = DefaultModel::from_file("etc/rbac_model.conf")
+        .await
+        .unwrap();
+    let enforcer = Enforcer::new(model, MemoryAdapter::default())
+        .await
+        .unwrap();
+
+    Rbac::new(Arc::new(RwLock::new(enforcer)))
+}
+
+// requests a stand-in server received as `METHOD /path`
+#[derive(Clone, Default)]
+pub struct RequestLog(Arc<Mutex<Vec<String>>>);
+
+impl RequestLog {
+    pub fn entries(&self) -> Vec<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+// serves `router` on a free local port in place of a provider, returns its base url
+pub async fn spawn_stand_in(router: Router) -> (String, RequestLog) {
+    let log = RequestLog::default();
+    let recorder = log.clone();
+    let router = router.layer(axum::middleware::from_fn(
+        move |req: Request, next: Next| {
+            let recorder = recorder.clone();
+            async move {
+                recorder
+                    .0
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} {}", req.method(), req.uri().path()));
+                let resp: Response = next.run(req).await;
+                resp
+            }
+        },
+    ));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    (format!("http://{}", addr), log)
+}
@@ -0,0 +1,236 @@
+use std::sync::{Arc, Mutex};
+
+use rust_ddd_oauth_casbin::{
+    application::{
+        dto::auth::{
+            jwt_claims::IdTokenClaims,
+            oauth2_request::{Oauth2Request, OauthUrlRequest},
+            oauth2_response::{OauthCallbackResult, OauthTokenResponse, OauthUserInfo},
+        },
+        usecases::auth::{
+            get_oauth_auth_url::GetOauthAuthUrl, oauth2_login::Oauth2Login,
+            unlink_oauth_provider::UnlinkOauthProvider,
+        },
+    },
+    domain::entities::{user::User, user_oauth_provider::UserOauthProvider},
+    infra::{
+        errors::app_error::AppError,
+        oauth2::{
+            provider::{AuthorizeRequest, OauthProvider},
+            registry::OauthProviderRegistry,
+        },
+        utils::{client_info::ClientInfo, return_to::ReturnToAllowlist},
+    },
+};
+use sqlx::postgres::PgPoolOptions;
+
+use super::{
+    auth::{auth_with_providers, Auth},
+    memory::{
+        MemoryOauthProviderRepo, MemoryRedis, MemoryRoleRepo, MemorySessionRepo, MemoryUserMfaRepo,
+        MemoryUserRepo,
+    },
+    test_config,
+};
+
+pub const STAND_IN: &str = "stand-in";
+pub const STAND_IN_USER_ID: &str = "stand-in-user";
+pub const STAND_IN_EMAIL: &str = "nelly@stand-in.test";
+
+pub const STAND_IN_ID_TOKEN: &str = "id-token";
+
+// an oauth provider that records what the login sent it, `issues_id_tokens` switches it to an
+// openid connect provider, `id_token_nonce` to one answering with the nonce of another login
+#[derive(Default)]
+pub struct StandInProvider {
+    pub code_challenge: Mutex<Option<String>>,
+    pub code_verifier: Mutex<Option<String>>,
+    pub nonce: Mutex<Option<String>>,
+    pub issues_id_tokens: bool,
+    pub id_token_nonce: Option<String>,
+    pub email_verified: bool,
+}
+
+#[async_trait::async_trait]
+impl OauthProvider for StandInProvider {
+    fn name(&self) -> &str {
+        STAND_IN
+    }
+
+    async fn authorize_url(&self, req: &AuthorizeRequest) -> Result<String, AppError> {
+        *self.code_challenge.lock().unwrap() = Some(req.code_challenge.clone());
+        *self.nonce.lock().unwrap() = Some(req.nonce.clone());
+        Ok(format!(
+            "https://stand-in.test/authorize?state={}",
+            req.state
+        ))
+    }
+
+    async fn exchange_code(
+        &self,
+        _code: &str,
+        code_verifier: &str,
+    ) -> Result<OauthTokenResponse, AppError> {
+        *self.code_verifier.lock().unwrap() = Some(code_verifier.to_string());
+        Ok(OauthTokenResponse {
+            access_token: "stand-in-access".to_string(),
+            token_type: None,
+            expires_in: None,
+            refresh_token: None,
+            id_token: (self.issues_id_tokens || self.id_token_nonce.is_some())
+                .then(|| STAND_IN_ID_TOKEN.to_string()),
+            scope: None,
+        })
+    }
+
+    async fn fetch_user_info(
+        &self,
+        _tokens: &OauthTokenResponse,
+    ) -> Result<OauthUserInfo, AppError> {
+        Ok(OauthUserInfo {
+            provider_user_id: STAND_IN_USER_ID.to_string(),
+            email: STAND_IN_EMAIL.to_string(),
+            email_verified: self.email_verified,
+            name: None,
+            picture: None,
+        })
+    }
+
+    async fn refresh_token(&self, _refresh_token: &str) -> Result<OauthTokenResponse, AppError> {
+        Err(AppError::Oauth2FailedToAuthorize)
+    }
+
+    async fn revoke_token(
+        &self,
+        _access_token: &str,
+        _refresh_token: Option<&str>,
+    ) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    async fn verify_id_token(&self, _id_token: &str) -> Result<IdTokenClaims, AppError> {
+        Ok(IdTokenClaims {
+            sub: STAND_IN_USER_ID.to_string(),
+            nonce: self
+                .id_token_nonce
+                .clone()
+                .or_else(|| self.nonce.lock().unwrap().clone()),
+            ..Default::default()
+        })
+    }
+}
+
+// the oauth login use cases around a stand-in provider
+pub struct OauthFlow {
+    pub auth: Auth,
+    pub get_url: GetOauthAuthUrl<MemoryRedis>,
+    pub login: Oauth2Login<
+        MemoryUserRepo,
+        MemoryRoleRepo,
+        MemorySessionRepo,
+        MemoryOauthProviderRepo,
+        MemoryUserMfaRepo,
+        MemoryRedis,
+    >,
+    pub unlink: UnlinkOauthProvider<
+        MemoryUserRepo,
+        MemoryRoleRepo,
+        MemorySessionRepo,
+        MemoryOauthProviderRepo,
+        MemoryRedis,
+    >,
+    db_pool: sqlx::PgPool,
+}
+
+// logins of new users need postgres, so tests create their users first & the pool stays unused
+pub async fn oauth_flow(provider: Arc<StandInProvider>, overrides: &[(&str, &str)]) -> OauthFlow {
+    let mut providers = OauthProviderRegistry::default();
+    providers.register(provider);
+    let auth = auth_with_providers(providers, overrides).await;
+
+    OauthFlow {
+        get_url: GetOauthAuthUrl::new(
+            auth.providers.clone(),
+            Arc::new(ReturnToAllowlist::from_config(&test_config(&[(
+                "RETURN_TO_ORIGINS",
+                "https://app.example.com",
+            )]))),
+            auth.redis_svc.clone(),
+        ),
+        login: Oauth2Login::new(
+            auth.jwt_maker.clone(),
+            auth.oauth_svc.clone(),
+            auth.mfa_svc.clone(),
+            auth.redis_svc.clone(),
+        ),
+        unlink: UnlinkOauthProvider::new(
+            auth.oauth_providers.clone(),
+            auth.sessions.clone(),
+            auth.oauth_svc.clone(),
+            auth.redis_svc.clone(),
+        ),
+        db_pool: PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/crate_test")
+            .unwrap(),
+        auth,
+    }
+}
+
+impl OauthFlow {
+    // a user with the stand-in's email, optionally linked to the stand-in identity
+    pub fn user(&self, linked: bool) -> User {
+        let user = self
+            .auth
+            .users
+            .insert(User::new(STAND_IN_EMAIL.to_string(), None));
+        if linked {
+            self.auth.oauth_providers.insert(UserOauthProvider::new(
+                user.id.clone(),
+                STAND_IN.to_string(),
+                STAND_IN_USER_ID.to_string(),
+            ));
+        }
+
+        user
+    }
+
+    // returns the authorize url & the state
+    pub async fn start(
+        &self,
+        return_to: Option<&str>,
+        link_user_id: Option<&str>,
+    ) -> Result<(String, String), AppError> {
+        let req = OauthUrlRequest {
+            return_to: return_to.map(str::to_string),
+        };
+        self.get_url
+            .execute(STAND_IN, req, link_user_id.map(str::to_string))
+            .await
+    }
+
+    pub async fn callback(
+        &self,
+        state: &str,
+        state_cookie: Option<&str>,
+    ) -> Result<OauthCallbackResult, AppError> {
+        let req = Oauth2Request {
+            code: "stand-in-code".to_string(),
+            state: state.to_string(),
+        };
+        self.login
+            .execute(
+                &self.db_pool,
+                STAND_IN.to_string(),
+                req,
+                state_cookie,
+                &ClientInfo::default(),
+            )
+            .await
+    }
+
+    // the whole round trip from the same browser
+    pub async fn login(&self, link_user_id: Option<&str>) -> Result<OauthCallbackResult, AppError> {
+        let (_, state) = self.start(None, link_user_id).await?;
+        self.callback(&state, Some(&state)).await
+    }
+}
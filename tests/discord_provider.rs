@@ -0,0 +1,130 @@
+mod common;
+
+use std::collections::HashMap;
+
+use axum::{
+    http::{header, HeaderMap, StatusCode},
+    routing::{get, post},
+    Form, Json, Router,
+};
+use rust_ddd_oauth_casbin::infra::oauth2::{
+    provider::AuthorizeRequest, registry::OauthProviderRegistry,
+};
+use serde_json::{json, Value};
+
+use common::{spawn_stand_in, test_config};
+
+async fn token(Form(form): Form<HashMap<String, String>>) -> (StatusCode, Json<Value>) {
+    let expected = [
+        ("grant_type", "authorization_code"),
+        ("code", "discord-code"),
+        ("code_verifier", "discord-verifier"),
+        ("client_id", "discord-client"),
+        ("client_secret", "discord-secret"),
+    ];
+    if expected
+        .iter()
+        .any(|(key, value)| form.get(*key).map(String::as_str) != Some(*value))
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "invalid_grant" })),
+        );
+    }
+
+    (
+        StatusCode::OK,
+        Json(json!({
+            "access_token": "discord-access",
+            "token_type": "Bearer",
+            "expires_in": 604800,
+            "refresh_token": "discord-refresh",
+            "scope": "identify email",
+        })),
+    )
+}
+
+async fn me(headers: HeaderMap) -> (StatusCode, Json<Value>) {
+    if headers.get(header::AUTHORIZATION).unwrap() != "Bearer discord-access" {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "unauthorized" })),
+        );
+    }
+
+    (
+        StatusCode::OK,
+        Json(json!({
+            "id": "80351110224678912",
+            "username": "nelly",
+            "global_name": null,
+            "avatar": "8342729096ea3675442027381ff50dfe",
+            "email": "nelly@discord.test",
+            "verified": true,
+        })),
+    )
+}
+
+#[tokio::test]
+async fn exchanges_the_code_and_maps_the_discord_user() {
+    let (api_url, requests) = spawn_stand_in(
+        Router::new()
+            .route("/oauth2/token", post(token))
+            .route("/users/@me", get(me)),
+    )
+    .await;
+    let authorize_url = format!("{}/oauth2/authorize", api_url);
+
+    let cfg = test_config(&[
+        ("DISCORD_CLIENT_ID", "discord-client"),
+        ("DISCORD_CLIENT_SECRET", "discord-secret"),
+        (
+            "DISCORD_REDIRECT_URI",
+            "http://localhost:8800/oauth/discord/callback",
+        ),
+        ("DISCORD_AUTHORIZE_URL", &authorize_url),
+        ("DISCORD_API_URL", &api_url),
+    ]);
+    let discord = OauthProviderRegistry::from_config(&cfg)
+        .get("discord")
+        .unwrap();
+
+    let url = discord
+        .authorize_url(&AuthorizeRequest {
+            state: "state".to_string(),
+            code_challenge: "challenge".to_string(),
+            nonce: "nonce".to_string(),
+        })
+        .await
+        .unwrap();
+    assert!(url.starts_with(&authorize_url));
+    assert!(url.contains("code_challenge=challenge"));
+
+    let tokens = discord
+        .exchange_code("discord-code", "discord-verifier")
+        .await
+        .unwrap();
+    assert_eq!(tokens.access_token, "discord-access");
+    assert_eq!(tokens.refresh_token.as_deref(), Some("discord-refresh"));
+
+    let user = discord.fetch_user_info(&tokens).await.unwrap();
+    assert_eq!(user.provider_user_id, "80351110224678912");
+    assert_eq!(user.email, "nelly@discord.test");
+    assert!(user.email_verified);
+    // no display name, the username is used instead
+    assert_eq!(user.name.as_deref(), Some("nelly"));
+    assert_eq!(
+        user.picture.as_deref(),
+        Some("https://cdn.discordapp.com/avatars/80351110224678912/8342729096ea3675442027381ff50dfe.png")
+    );
+
+    assert!(discord
+        .exchange_code("discord-code", "wrong-verifier")
+        .await
+        .is_err());
+
+    assert_eq!(
+        requests.entries(),
+        ["POST /oauth2/token", "GET /users/@me", "POST /oauth2/token"]
+    );
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use rust_ddd_oauth_casbin::{
+    application::dto::auth::email_request::{ResendEmailVerificationRequest, VerifyEmailRequest},
+    domain::repositories::user_repo::UserRepository,
+    infra::{
+        errors::app_error::AppError, oauth2::registry::OauthProviderRegistry,
+        utils::client_info::ClientInfo,
+    },
+};
+
+use common::{
+    auth::{auth_with_providers, current_user, login_request, user_with_password, Auth},
+    test_config,
+};
+
+const EMAIL: &str = "vera@verify.test";
+
+// email logins need a verified address
+async fn verification() -> Auth {
+    let overrides = [("EMAIL_VERIFICATION_REQUIRED", "true")];
+    auth_with_providers(
+        OauthProviderRegistry::from_config(&test_config(&overrides)),
+        &overrides,
+    )
+    .await
+}
+
+async fn resend(auth: &Auth, email: &str) {
+    auth.resend_email_verification
+        .execute(ResendEmailVerificationRequest {
+            email: email.to_string(),
+        })
+        .await
+        .unwrap();
+}
+
+async fn verify(auth: &Auth, token: &str) -> Result<(), AppError> {
+    auth.verify_email
+        .execute(VerifyEmailRequest {
+            token: token.to_string(),
+        })
+        .await
+}
+
+#[tokio::test]
+async fn unverified_users_can_login_after_opening_the_mailed_link() {
+    let auth = verification().await;
+    let user = auth.users.insert(user_with_password(EMAIL));
+
+    assert!(matches!(
+        auth.email_login
+            .execute(login_request(EMAIL), &ClientInfo::default())
+            .await,
+        Err(AppError::EmailNotVerified(_))
+    ));
+    assert!(auth.sessions.all().is_empty());
+
+    resend(&auth, EMAIL).await;
+    let token = auth.mailer.last_token(EMAIL).unwrap();
+    // only the hash is stored
+    assert!(auth
+        .user_tokens
+        .all()
+        .iter()
+        .all(|user_token| user_token.token_hash != token));
+
+    auth.redis_svc
+        .set_current_user(&current_user(&user))
+        .await
+        .unwrap();
+
+    verify(&auth, &token).await.unwrap();
+    // the cached user is still unverified
+    assert!(auth.redis_svc.get_current_user(&user.id).await.is_err());
+    assert!(auth
+        .users
+        .find_by_id(&user.id)
+        .await
+        .unwrap()
+        .is_email_verified());
+
+    auth.email_login
+        .execute(login_request(EMAIL), &ClientInfo::default())
+        .await
+        .unwrap();
+    assert_eq!(auth.sessions.all().len(), 1);
+}
+
+#[tokio::test]
+async fn links_work_once_and_a_resend_replaces_the_previous_one() {
+    let auth = verification().await;
+    auth.users.insert(user_with_password(EMAIL));
+
+    resend(&auth, EMAIL).await;
+    let first = auth.mailer.last_token(EMAIL).unwrap();
+    resend(&auth, EMAIL).await;
+    let second = auth.mailer.last_token(EMAIL).unwrap();
+    assert_ne!(first, second);
+
+    assert!(matches!(
+        verify(&auth, &first).await,
+        Err(AppError::UnauthorizedError(_))
+    ));
+    verify(&auth, &second).await.unwrap();
+    assert!(matches!(
+        verify(&auth, &second).await,
+        Err(AppError::UnauthorizedError(_))
+    ));
+}
+
+#[tokio::test]
+async fn resending_does_not_reveal_which_emails_are_registered() {
+    let auth = verification().await;
+    let mut verified = user_with_password(EMAIL);
+    verified.verify_email();
+    auth.users.insert(verified);
+
+    resend(&auth, EMAIL).await;
+    resend(&auth, "nobody@verify.test").await;
+
+    assert!(auth.mailer.all().is_empty());
+}
+
+#[tokio::test]
+async fn accounts_from_before_verification_was_required_login_unverified() {
+    let overrides = [
+        ("EMAIL_VERIFICATION_REQUIRED", "true"),
+        ("EMAIL_VERIFICATION_REQUIRED_SINCE", "2024-10-18T00:00:00Z"),
+    ];
+    let auth = auth_with_providers(
+        OauthProviderRegistry::from_config(&test_config(&overrides)),
+        &overrides,
+    )
+    .await;
+    let mut existing = user_with_password("existing@verify.test");
+    existing.created_at = "2024-10-17T23:59:59Z".parse().unwrap();
+    auth.users.insert(existing);
+    auth.users.insert(user_with_password(EMAIL));
+
+    auth.email_login
+        .execute(
+            login_request("existing@verify.test"),
+            &ClientInfo::default(),
+        )
+        .await
+        .unwrap();
+    assert!(matches!(
+        auth.email_login
+            .execute(login_request(EMAIL), &ClientInfo::default())
+            .await,
+        Err(AppError::EmailNotVerified(_))
+    ));
+    assert_eq!(auth.sessions.all().len(), 1);
+}
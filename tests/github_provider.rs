@@ -0,0 +1,153 @@
+mod common;
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::Path,
+    http::{header, HeaderMap, StatusCode},
+    routing::{delete, get, post},
+    Form, Json, Router,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rust_ddd_oauth_casbin::infra::oauth2::registry::OauthProviderRegistry;
+use serde_json::{json, Value};
+
+use common::{spawn_stand_in, test_config};
+
+async fn access_token(Form(form): Form<HashMap<String, String>>) -> Json<Value> {
+    // github answers failed exchanges with 200 & an error body
+    if form.get("code").map(String::as_str) != Some("github-code")
+        || form.get("code_verifier").map(String::as_str) != Some("github-verifier")
+        || form.get("client_secret").map(String::as_str) != Some("github-secret")
+    {
+        return Json(json!({ "error": "bad_verification_code" }));
+    }
+
+    Json(json!({
+        "access_token": "github-access",
+        "token_type": "bearer",
+        "scope": "read:user,user:email",
+    }))
+}
+
+fn authorized(headers: &HeaderMap) -> bool {
+    headers.get(header::AUTHORIZATION).unwrap() == "Bearer github-access"
+        && headers.contains_key(header::USER_AGENT)
+}
+
+async fn user(headers: HeaderMap) -> (StatusCode, Json<Value>) {
+    if !authorized(&headers) {
+        return (StatusCode::UNAUTHORIZED, Json(json!({})));
+    }
+
+    (
+        StatusCode::OK,
+        Json(json!({
+            "id": 583231,
+            "login": "octocat",
+            "name": "The Octocat",
+            "avatar_url": "https://avatars.githubusercontent.com/u/583231",
+            "email": "public@octocat.test",
+        })),
+    )
+}
+
+async fn emails(headers: HeaderMap) -> (StatusCode, Json<Value>) {
+    if !authorized(&headers) {
+        return (StatusCode::UNAUTHORIZED, Json(json!([])));
+    }
+
+    (
+        StatusCode::OK,
+        Json(json!([
+            { "email": "public@octocat.test", "primary": false, "verified": true },
+            { "email": "unverified@octocat.test", "primary": true, "verified": false },
+            { "email": "octocat@github.test", "primary": true, "verified": true },
+        ])),
+    )
+}
+
+async fn revoke_token(
+    Path(client_id): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> StatusCode {
+    let basic = format!("Basic {}", STANDARD.encode("github-client:github-secret"));
+    if client_id != "github-client" || headers.get(header::AUTHORIZATION).unwrap() != &basic {
+        return StatusCode::UNAUTHORIZED;
+    }
+    if body["access_token"] != "github-access" {
+        return StatusCode::UNPROCESSABLE_ENTITY;
+    }
+
+    StatusCode::NO_CONTENT
+}
+
+#[tokio::test]
+async fn logs_in_with_the_primary_verified_email_and_revokes_only_the_token() {
+    let (oauth_url, oauth_requests) =
+        spawn_stand_in(Router::new().route("/access_token", post(access_token))).await;
+    let (api_url, api_requests) = spawn_stand_in(
+        Router::new()
+            .route("/user", get(user))
+            .route("/user/emails", get(emails))
+            .route("/applications/:client_id/token", delete(revoke_token))
+            .route(
+                "/applications/:client_id/grant",
+                delete(|| async { StatusCode::NO_CONTENT }),
+            ),
+    )
+    .await;
+
+    let cfg = test_config(&[
+        ("GITHUB_CLIENT_ID", "github-client"),
+        ("GITHUB_CLIENT_SECRET", "github-secret"),
+        (
+            "GITHUB_REDIRECT_URI",
+            "http://localhost:8800/oauth/github/callback",
+        ),
+        ("GITHUB_OAUTH_URL", &oauth_url),
+        ("GITHUB_API_URL", &api_url),
+    ]);
+    let github = OauthProviderRegistry::from_config(&cfg)
+        .get("github")
+        .unwrap();
+
+    assert!(github
+        .exchange_code("github-code", "wrong-verifier")
+        .await
+        .is_err());
+
+    let tokens = github
+        .exchange_code("github-code", "github-verifier")
+        .await
+        .unwrap();
+    assert_eq!(tokens.access_token, "github-access");
+
+    let user = github.fetch_user_info(&tokens).await.unwrap();
+    assert_eq!(user.provider_user_id, "583231");
+    assert_eq!(user.email, "octocat@github.test");
+    assert!(user.email_verified);
+    assert_eq!(user.name.as_deref(), Some("The Octocat"));
+
+    github
+        .revoke_token(&tokens.access_token, None)
+        .await
+        .unwrap();
+    assert!(github.revoke_token("unknown-access", None).await.is_err());
+
+    assert_eq!(
+        oauth_requests.entries(),
+        ["POST /access_token", "POST /access_token"]
+    );
+    // the grant stays, other sessions of the user keep their tokens
+    assert_eq!(
+        api_requests.entries(),
+        [
+            "GET /user",
+            "GET /user/emails",
+            "DELETE /applications/github-client/token",
+            "DELETE /applications/github-client/token"
+        ]
+    );
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use rust_ddd_oauth_casbin::{
+    domain::repositories::user_repo::UserRepository,
+    infra::{errors::app_error::AppError, utils::client_info::ClientInfo},
+};
+
+use common::auth::{auth, logged_in, login_request, user_with_password};
+
+#[tokio::test]
+async fn deactivated_and_deleted_users_can_not_login() {
+    let auth = auth().await;
+
+    let mut deactivated = user_with_password("deactivated@inactive.test");
+    deactivated.deactivate();
+    auth.users.insert(deactivated);
+    let mut deleted = user_with_password("deleted@inactive.test");
+    deleted.delete();
+    auth.users.insert(deleted);
+    auth.users
+        .insert(user_with_password("active@inactive.test"));
+
+    for email in ["deactivated@inactive.test", "deleted@inactive.test"] {
+        let result = auth
+            .email_login
+            .execute(login_request(email), &ClientInfo::default())
+            .await;
+        assert!(matches!(result, Err(AppError::UserInactive)), "{}", email);
+    }
+    assert!(auth.sessions.all().is_empty());
+
+    auth.email_login
+        .execute(
+            login_request("active@inactive.test"),
+            &ClientInfo::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(auth.sessions.all().len(), 1);
+}
+
+#[tokio::test]
+async fn refreshing_for_a_deactivated_user_ends_the_session() {
+    let auth = auth().await;
+    let mut user = auth
+        .users
+        .insert(user_with_password("refresh@inactive.test"));
+    let (_, refresh_token) = logged_in(
+        auth.email_login
+            .execute(
+                login_request("refresh@inactive.test"),
+                &ClientInfo::default(),
+            )
+            .await
+            .unwrap(),
+    );
+
+    user.deactivate();
+    auth.users.update(&user.id.clone(), user).await.unwrap();
+
+    let result = auth.refresh.execute(&refresh_token).await;
+    assert!(matches!(result, Err(AppError::UserInactive)));
+    assert!(auth.sessions.all().is_empty());
+}
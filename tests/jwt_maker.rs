@@ -0,0 +1,158 @@
+mod common;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use rust_ddd_oauth_casbin::infra::utils::jwt_maker::{
+    unverified_issuer, Claims, JwtMaker, TokenProfile,
+};
+
+use common::test_config;
+
+const BOTH_KEYS: &str =
+    "current:tests/fixtures/jwt-current.pub.pem,next:tests/fixtures/jwt-next.pub.pem";
+
+#[test]
+fn tokens_are_signed_with_the_configured_key() {
+    let jwt_maker = JwtMaker::new(&test_config(&[]));
+
+    let token = jwt_maker
+        .make_token(
+            "user-1".to_string(),
+            "session-1".to_string(),
+            TokenProfile::default(),
+            1,
+        )
+        .unwrap();
+    let header = jsonwebtoken::decode_header(&token).unwrap();
+    assert_eq!(header.kid.as_deref(), Some("current"));
+    assert_eq!(header.alg, jsonwebtoken::Algorithm::EdDSA);
+
+    let claims = jwt_maker.verify_access_token(&token).unwrap();
+    assert_eq!(claims.sub, "user-1");
+    assert_eq!(claims.sid, "session-1");
+
+    let jwks = jwt_maker.jwks();
+    assert_eq!(jwks.keys.len(), 1);
+    assert_eq!(jwks.keys[0].common.key_id.as_deref(), Some("current"));
+}
+
+#[test]
+fn tokens_of_the_previous_key_stay_valid_while_it_is_published() {
+    let current = JwtMaker::new(&test_config(&[("JWT_VERIFICATION_KEYS", BOTH_KEYS)]));
+    let token = current
+        .make_token(
+            "user-1".to_string(),
+            "session-1".to_string(),
+            TokenProfile::default(),
+            1,
+        )
+        .unwrap();
+
+    let next = JwtMaker::new(&test_config(&[
+        ("JWT_SIGNING_KEY", "next:tests/fixtures/jwt-next.pem"),
+        ("JWT_VERIFICATION_KEYS", BOTH_KEYS),
+    ]));
+    assert_eq!(next.jwks().keys.len(), 2);
+    assert!(next.verify_access_token(&token).is_ok());
+
+    // the old public key was removed
+    let next_only = JwtMaker::new(&test_config(&[
+        ("JWT_SIGNING_KEY", "next:tests/fixtures/jwt-next.pem"),
+        (
+            "JWT_VERIFICATION_KEYS",
+            "next:tests/fixtures/jwt-next.pub.pem",
+        ),
+    ]));
+    assert!(next_only.verify_access_token(&token).is_err());
+    assert!(next_only
+        .verify_refresh_token(
+            &current
+                .make_refresh_token("user-1".to_string(), "session-1".to_string(), 1)
+                .unwrap()
+        )
+        .is_err());
+}
+
+#[test]
+fn tokens_of_another_issuer_or_audience_are_rejected() {
+    let jwt_maker = JwtMaker::new(&test_config(&[]));
+    let token = jwt_maker
+        .make_token(
+            "user-1".to_string(),
+            "session-1".to_string(),
+            TokenProfile::default(),
+            1,
+        )
+        .unwrap();
+
+    // same signing key, only the configured issuer or audience differs
+    let other_issuer = JwtMaker::new(&test_config(&[("JWT_ISSUER", "https://other.test")]));
+    assert!(other_issuer.verify_access_token(&token).is_err());
+    let other_audience = JwtMaker::new(&test_config(&[("JWT_AUDIENCE", "other-api")]));
+    assert!(other_audience.verify_access_token(&token).is_err());
+}
+
+#[test]
+fn tokens_signed_with_the_old_shared_secret_are_rejected() {
+    let jwt_maker = JwtMaker::new(&test_config(&[("JWT_SECRET", "old-secret")]));
+    let now = chrono::Utc::now();
+    let claims = Claims {
+        exp: (now + chrono::Duration::hours(1)).timestamp() as usize,
+        iat: now.timestamp() as usize,
+        sub: "user-1".to_string(),
+        sid: "session-1".to_string(),
+        iss: jwt_maker.issuer().to_string(),
+        aud: "crate-test".to_string(),
+        name: "User".to_string(),
+        roles: vec![],
+        scopes: vec![],
+    };
+
+    // HS256 & no kid, like the tokens issued before asymmetric signing
+    let token = jsonwebtoken::encode(
+        &Header::new(Algorithm::HS256),
+        &claims,
+        &EncodingKey::from_secret(b"old-secret"),
+    )
+    .unwrap();
+    assert!(jwt_maker.verify_access_token(&token).is_err());
+
+    // nor does claiming the current kid get it past the key's algorithm
+    let mut header = Header::new(Algorithm::HS256);
+    header.kid = Some("current".to_string());
+    let token =
+        jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(b"old-secret")).unwrap();
+    assert!(jwt_maker.verify_access_token(&token).is_err());
+}
+
+// only the payload is read to pick a verifier, so the signature doesn't matter here
+fn unsigned_token(issuer: &str) -> String {
+    format!(
+        "{}.{}.signature",
+        URL_SAFE_NO_PAD.encode(r#"{"alg":"RS256"}"#),
+        URL_SAFE_NO_PAD.encode(serde_json::json!({ "iss": issuer }).to_string())
+    )
+}
+
+#[test]
+fn the_issuer_is_read_from_the_unverified_token() {
+    let jwt_maker = JwtMaker::new(&test_config(&[]));
+    let own_token = jwt_maker
+        .make_token(
+            "user-1".to_string(),
+            "session-1".to_string(),
+            TokenProfile::default(),
+            1,
+        )
+        .unwrap();
+
+    assert_eq!(
+        unverified_issuer(&own_token).as_deref(),
+        Some(jwt_maker.issuer())
+    );
+    assert_eq!(
+        unverified_issuer(&unsigned_token("https://accounts.google.com")).as_deref(),
+        Some("https://accounts.google.com")
+    );
+    assert_eq!(unverified_issuer("not-a-jwt"), None);
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use rust_ddd_oauth_casbin::{
+    application::dto::auth::email_request::EmailLoginRequest,
+    infra::{
+        common::constants::{
+            LOGIN_DELAY_AFTER_FAILURES, LOGIN_LOCKOUT_AFTER_FAILURES, LOGIN_MAX_FAILURES_PER_IP,
+        },
+        errors::app_error::AppError,
+        utils::client_info::ClientInfo,
+    },
+};
+
+use common::auth::{auth, login_request, user_with_password, Auth};
+
+const EMAIL: &str = "lou@lockout.test";
+const IP_ADDRESS: &str = "192.0.2.10";
+
+fn client() -> ClientInfo {
+    ClientInfo {
+        user_agent: None,
+        ip_address: Some(IP_ADDRESS.to_string()),
+    }
+}
+
+async fn login(auth: &Auth, email: &str, password: &str) -> Result<(), AppError> {
+    auth.email_login
+        .execute(
+            EmailLoginRequest {
+                email: email.to_string(),
+                password: password.to_string(),
+            },
+            &client(),
+        )
+        .await
+        .map(|_| ())
+}
+
+#[tokio::test]
+async fn repeated_failures_hold_back_even_the_right_password() {
+    let auth = auth().await;
+    auth.users.insert(user_with_password(EMAIL));
+
+    for _ in 0..LOGIN_DELAY_AFTER_FAILURES {
+        assert!(matches!(
+            login(&auth, EMAIL, "wrong password").await,
+            Err(AppError::UnauthorizedError(_))
+        ));
+    }
+
+    // the email is checked without its case
+    let result = auth
+        .email_login
+        .execute(login_request(&EMAIL.to_uppercase()), &client())
+        .await;
+    assert!(matches!(result, Err(AppError::TooManyRequests)));
+    assert!(auth.sessions.all().is_empty());
+}
+
+#[tokio::test]
+async fn unknown_emails_answer_and_count_like_wrong_passwords() {
+    let auth = auth().await;
+
+    for _ in 0..LOGIN_DELAY_AFTER_FAILURES {
+        assert!(matches!(
+            login(&auth, "nobody@lockout.test", "wrong password").await,
+            Err(AppError::UnauthorizedError(_))
+        ));
+    }
+    assert!(matches!(
+        login(&auth, "nobody@lockout.test", "wrong password").await,
+        Err(AppError::TooManyRequests)
+    ));
+}
+
+#[tokio::test]
+async fn a_successful_login_or_an_unlock_resets_the_failures() {
+    let auth = auth().await;
+    auth.users.insert(user_with_password(EMAIL));
+
+    for _ in 0..LOGIN_DELAY_AFTER_FAILURES - 1 {
+        login(&auth, EMAIL, "wrong password").await.unwrap_err();
+    }
+    auth.email_login
+        .execute(login_request(EMAIL), &client())
+        .await
+        .unwrap();
+    for _ in 0..LOGIN_DELAY_AFTER_FAILURES - 1 {
+        login(&auth, EMAIL, "wrong password").await.unwrap_err();
+    }
+    auth.email_login
+        .execute(login_request(EMAIL), &client())
+        .await
+        .unwrap();
+
+    for _ in 0..LOGIN_LOCKOUT_AFTER_FAILURES {
+        auth.login_guard_svc
+            .record_failure(EMAIL, Some(IP_ADDRESS))
+            .await
+            .unwrap();
+    }
+    assert!(matches!(
+        auth.email_login
+            .execute(login_request(EMAIL), &client())
+            .await,
+        Err(AppError::TooManyRequests)
+    ));
+
+    auth.login_guard_svc.clear(EMAIL).await.unwrap();
+    auth.email_login
+        .execute(login_request(EMAIL), &client())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn an_ip_guessing_across_emails_is_stopped() {
+    let auth = auth().await;
+    auth.users.insert(user_with_password(EMAIL));
+
+    for i in 0..LOGIN_MAX_FAILURES_PER_IP {
+        auth.login_guard_svc
+            .record_failure(&format!("{}@lockout.test", i), Some(IP_ADDRESS))
+            .await
+            .unwrap();
+    }
+
+    assert!(matches!(
+        auth.email_login
+            .execute(login_request(EMAIL), &client())
+            .await,
+        Err(AppError::TooManyRequests)
+    ));
+    // other addresses aren't affected
+    auth.email_login
+        .execute(login_request(EMAIL), &ClientInfo::default())
+        .await
+        .unwrap();
+}
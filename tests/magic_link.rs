@@ -0,0 +1,119 @@
+mod common;
+
+use rust_ddd_oauth_casbin::{
+    application::dto::auth::{
+        email_request::{MagicLinkRequest, RedeemMagicLinkRequest},
+        token_response::EmailLoginResult,
+    },
+    domain::{entities::user::User, repositories::user_repo::UserRepository},
+    infra::{
+        common::constants::{MAGIC_LINK_MAX_PER_EMAIL, MAGIC_LINK_MAX_PER_IP},
+        errors::app_error::AppError,
+        oauth2::constants::MAGIC_LINK_PROVIDER,
+        utils::client_info::ClientInfo,
+    },
+};
+
+use common::auth::{auth, Auth};
+
+const EMAIL: &str = "mila@magic.test";
+
+fn client(ip_address: &str) -> ClientInfo {
+    ClientInfo {
+        user_agent: None,
+        ip_address: Some(ip_address.to_string()),
+    }
+}
+
+async fn send(auth: &Auth, email: &str, client: &ClientInfo) -> Result<(), AppError> {
+    auth.send_magic_link
+        .execute(
+            MagicLinkRequest {
+                email: email.to_string(),
+            },
+            client,
+        )
+        .await
+}
+
+async fn redeem(auth: &Auth, token: &str) -> Result<EmailLoginResult, AppError> {
+    auth.redeem_magic_link
+        .execute(
+            RedeemMagicLinkRequest {
+                token: token.to_string(),
+            },
+            &ClientInfo::default(),
+        )
+        .await
+}
+
+#[tokio::test]
+async fn a_link_logs_in_once_and_verifies_the_email() {
+    let auth = auth().await;
+    let user = auth.users.insert(User::new(EMAIL.to_string(), None));
+
+    send(&auth, EMAIL, &ClientInfo::default()).await.unwrap();
+    let token = auth.mailer.last_token(EMAIL).unwrap();
+
+    assert!(matches!(
+        redeem(&auth, &token).await.unwrap(),
+        EmailLoginResult::LoggedIn { .. }
+    ));
+    let sessions = auth.sessions.all();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].provider, MAGIC_LINK_PROVIDER);
+    assert!(auth
+        .users
+        .find_by_id(&user.id)
+        .await
+        .unwrap()
+        .is_email_verified());
+
+    assert!(matches!(
+        redeem(&auth, &token).await,
+        Err(AppError::UnauthorizedError(_))
+    ));
+    assert_eq!(auth.sessions.all().len(), 1);
+}
+
+#[tokio::test]
+async fn unknown_and_inactive_emails_get_no_link() {
+    let auth = auth().await;
+    let mut deactivated = User::new(EMAIL.to_string(), None);
+    deactivated.deactivate();
+    auth.users.insert(deactivated);
+
+    send(&auth, EMAIL, &ClientInfo::default()).await.unwrap();
+    send(&auth, "nobody@magic.test", &ClientInfo::default())
+        .await
+        .unwrap();
+
+    assert!(auth.mailer.all().is_empty());
+}
+
+#[tokio::test]
+async fn links_are_limited_per_email_and_per_ip() {
+    let auth = auth().await;
+    auth.users.insert(User::new(EMAIL.to_string(), None));
+
+    for _ in 0..MAGIC_LINK_MAX_PER_EMAIL {
+        send(&auth, EMAIL, &client("192.0.2.1")).await.unwrap();
+    }
+    // the limit ignores the case of the address
+    assert!(matches!(
+        send(&auth, &EMAIL.to_uppercase(), &client("192.0.2.2")).await,
+        Err(AppError::TooManyRequests)
+    ));
+    assert_eq!(auth.mailer.all().len() as i64, MAGIC_LINK_MAX_PER_EMAIL);
+
+    // unknown emails count for the ip too
+    for i in 0..MAGIC_LINK_MAX_PER_IP {
+        send(&auth, &format!("{}@magic.test", i), &client("192.0.2.3"))
+            .await
+            .unwrap();
+    }
+    assert!(matches!(
+        send(&auth, "another@magic.test", &client("192.0.2.3")).await,
+        Err(AppError::TooManyRequests)
+    ));
+}
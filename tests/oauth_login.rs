@@ -0,0 +1,228 @@
+mod common;
+
+use std::sync::Arc;
+
+use rust_ddd_oauth_casbin::{
+    application::dto::auth::oauth2_response::OauthCallbackResult,
+    domain::repositories::{
+        oauth_provider_repo::OauthProviderRepository, user_repo::UserRepository,
+    },
+    infra::{errors::app_error::AppError, utils::token_hash::pkce_challenge},
+};
+
+use common::oauth_flow::{
+    oauth_flow, StandInProvider, STAND_IN, STAND_IN_ID_TOKEN, STAND_IN_USER_ID,
+};
+
+#[tokio::test]
+async fn the_callback_needs_the_state_of_the_browser_that_started_the_login() {
+    let flow = oauth_flow(Arc::new(StandInProvider::default()), &[]).await;
+    flow.user(true);
+
+    let (url, state) = flow.start(None, None).await.unwrap();
+    assert!(url.contains(&state));
+
+    let (_, other_state) = flow.start(None, None).await.unwrap();
+    assert!(matches!(
+        flow.callback(&state, Some(&other_state)).await,
+        Err(AppError::UnauthorizedError(_))
+    ));
+    assert!(matches!(
+        flow.callback(&state, None).await,
+        Err(AppError::UnauthorizedError(_))
+    ));
+
+    flow.callback(&state, Some(&state)).await.unwrap();
+    assert_eq!(flow.auth.sessions.all().len(), 1);
+
+    // the state is used up
+    assert!(matches!(
+        flow.callback(&state, Some(&state)).await,
+        Err(AppError::UnauthorizedError(_))
+    ));
+    assert!(matches!(
+        flow.callback("made-up", Some("made-up")).await,
+        Err(AppError::UnauthorizedError(_))
+    ));
+    assert_eq!(flow.auth.sessions.all().len(), 1);
+}
+
+#[tokio::test]
+async fn the_code_is_exchanged_with_the_verifier_of_the_challenge() {
+    let provider = Arc::new(StandInProvider::default());
+    let flow = oauth_flow(provider.clone(), &[]).await;
+    flow.user(true);
+
+    flow.login(None).await.unwrap();
+
+    let code_challenge = provider.code_challenge.lock().unwrap().clone().unwrap();
+    let code_verifier = provider.code_verifier.lock().unwrap().clone().unwrap();
+    assert_ne!(code_challenge, code_verifier);
+    assert_eq!(pkce_challenge(&code_verifier), code_challenge);
+}
+
+#[tokio::test]
+async fn id_tokens_of_another_login_are_rejected() {
+    let provider = Arc::new(StandInProvider {
+        id_token_nonce: Some("nonce-of-another-login".to_string()),
+        ..Default::default()
+    });
+    let flow = oauth_flow(provider, &[]).await;
+    flow.user(true);
+
+    assert!(matches!(
+        flow.login(None).await,
+        Err(AppError::Oauth2FailedToAuthorize)
+    ));
+    assert!(flow.auth.sessions.all().is_empty());
+}
+
+#[tokio::test]
+async fn the_browser_returns_to_the_allowlisted_return_to() {
+    let flow = oauth_flow(Arc::new(StandInProvider::default()), &[]).await;
+    flow.user(true);
+
+    assert!(matches!(
+        flow.start(Some("https://evil.com/"), None).await,
+        Err(AppError::InvalidReturnTo(_))
+    ));
+
+    let (_, state) = flow
+        .start(Some("https://app.example.com/settings"), None)
+        .await
+        .unwrap();
+    assert!(matches!(
+        flow.callback(&state, Some(&state)).await.unwrap(),
+        OauthCallbackResult::LoggedIn { return_to: Some(return_to), .. }
+            if return_to == "https://app.example.com/settings"
+    ));
+
+    assert!(matches!(
+        flow.login(None).await.unwrap(),
+        OauthCallbackResult::LoggedIn {
+            return_to: None,
+            ..
+        }
+    ));
+}
+
+#[tokio::test]
+async fn an_account_with_the_same_email_is_not_taken_over() {
+    let flow = oauth_flow(Arc::new(StandInProvider::default()), &[]).await;
+    let user = flow.user(false);
+
+    assert!(matches!(
+        flow.login(None).await,
+        Err(AppError::AccountLinkRequired(_))
+    ));
+    assert!(flow.auth.sessions.all().is_empty());
+    assert!(flow
+        .auth
+        .oauth_providers
+        .find_by_user_id(&user.id)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn verified_emails_are_linked_when_the_policy_allows_it() {
+    let auto_link = [("OAUTH_AUTO_LINK_VERIFIED_EMAIL", "true")];
+
+    let flow = oauth_flow(Arc::new(StandInProvider::default()), &auto_link).await;
+    flow.user(false);
+    assert!(matches!(
+        flow.login(None).await,
+        Err(AppError::AccountLinkRequired(_))
+    ));
+
+    let provider = Arc::new(StandInProvider {
+        email_verified: true,
+        ..Default::default()
+    });
+    let flow = oauth_flow(provider, &auto_link).await;
+    let mut user = flow.user(false);
+    // the local account could have been registered by someone else with this email
+    assert!(matches!(
+        flow.login(None).await,
+        Err(AppError::AccountLinkRequired(_))
+    ));
+
+    user.verify_email();
+    flow.auth
+        .users
+        .update(&user.id, user.clone())
+        .await
+        .unwrap();
+    flow.login(None).await.unwrap();
+
+    let links = flow
+        .auth
+        .oauth_providers
+        .find_by_user_id(&user.id)
+        .await
+        .unwrap();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].provider_user_id, STAND_IN_USER_ID);
+}
+
+#[tokio::test]
+async fn logged_in_users_link_the_provider_to_their_account() {
+    let flow = oauth_flow(Arc::new(StandInProvider::default()), &[]).await;
+    let user = flow.user(false);
+
+    let OauthCallbackResult::Linked { oauth_provider, .. } =
+        flow.login(Some(&user.id)).await.unwrap()
+    else {
+        panic!("the provider was not linked");
+    };
+    assert_eq!(oauth_provider.user_id, user.id);
+    assert_eq!(oauth_provider.provider, STAND_IN);
+    // linking doesn't log in
+    assert!(flow.auth.sessions.all().is_empty());
+
+    assert!(matches!(
+        flow.login(None).await.unwrap(),
+        OauthCallbackResult::LoggedIn { .. }
+    ));
+
+    // the identity already belongs to the first user
+    assert!(matches!(
+        flow.login(Some("another-user")).await,
+        Err(AppError::ResourceExist(_))
+    ));
+}
+
+#[tokio::test]
+async fn provider_id_tokens_only_work_with_the_session_of_their_login() {
+    let provider = Arc::new(StandInProvider {
+        issues_id_tokens: true,
+        ..Default::default()
+    });
+    let flow = oauth_flow(provider, &[]).await;
+    let user = flow.user(true);
+    let id_token_session = |id_token: &'static str| {
+        flow.auth
+            .oauth_svc
+            .get_active_id_token_session(STAND_IN, id_token, &user.id)
+    };
+
+    let OauthCallbackResult::LoggedIn { access_token, .. } = flow.login(None).await.unwrap() else {
+        panic!("the login did not finish");
+    };
+    assert_eq!(access_token, STAND_IN_ID_TOKEN);
+    let session = id_token_session(STAND_IN_ID_TOKEN).await.unwrap();
+
+    // valid at the provider, but not handed out by our callback
+    assert!(matches!(
+        id_token_session("minted-elsewhere").await,
+        Err(AppError::SessionExpired)
+    ));
+
+    // logged out, e.g. from another device
+    flow.auth.oauth_svc.revoke_session(&session).await.unwrap();
+    assert!(matches!(
+        id_token_session(STAND_IN_ID_TOKEN).await,
+        Err(AppError::SessionExpired)
+    ));
+}
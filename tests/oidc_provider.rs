@@ -0,0 +1,375 @@
+mod common;
+
+use std::sync::Arc;
+
+use aes_gcm::aead::OsRng;
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use p256::{
+    elliptic_curve::sec1::ToEncodedPoint,
+    pkcs8::{EncodePrivateKey, LineEnding},
+    SecretKey,
+};
+use rust_ddd_oauth_casbin::{
+    application::dto::auth::oauth2_response::OauthTokenResponse,
+    infra::{
+        config::OidcProviderConfig,
+        errors::app_error::AppError,
+        oauth2::{
+            oidc::OidcProvider,
+            provider::{AuthorizeRequest, OauthProvider},
+        },
+    },
+};
+use serde_json::{json, Value};
+
+use common::{spawn_stand_in, test_config, RequestLog};
+
+const CLIENT_ID: &str = "idp-client";
+const KID: &str = "idp-key";
+const SUB: &str = "idp-user";
+
+// an openid connect provider serving discovery, jwks & userinfo, `issuer` makes its discovery
+// describe another issuer & `userinfo_sub` answers userinfo for another subject
+struct StandInIdp {
+    key: SecretKey,
+    issuer: Option<String>,
+    userinfo_sub: String,
+}
+
+impl Default for StandInIdp {
+    fn default() -> Self {
+        Self {
+            key: SecretKey::random(&mut OsRng),
+            issuer: None,
+            userinfo_sub: SUB.to_string(),
+        }
+    }
+}
+
+async fn discovery(State(idp): State<Arc<StandInIdp>>, headers: HeaderMap) -> Json<Value> {
+    let url = format!(
+        "http://{}",
+        headers.get(header::HOST).unwrap().to_str().unwrap()
+    );
+
+    Json(json!({
+        "issuer": idp.issuer.clone().unwrap_or(url.clone()),
+        "authorization_endpoint": format!("{}/authorize", url),
+        "token_endpoint": format!("{}/token", url),
+        "userinfo_endpoint": format!("{}/userinfo", url),
+        "jwks_uri": format!("{}/jwks", url),
+    }))
+}
+
+async fn jwks(State(idp): State<Arc<StandInIdp>>) -> Json<Value> {
+    let point = idp.key.public_key().to_encoded_point(false);
+
+    Json(json!({
+        "keys": [{
+            "kid": KID,
+            "kty": "EC",
+            "crv": "P-256",
+            "x": URL_SAFE_NO_PAD.encode(point.x().unwrap()),
+            "y": URL_SAFE_NO_PAD.encode(point.y().unwrap()),
+        }],
+    }))
+}
+
+async fn userinfo(
+    State(idp): State<Arc<StandInIdp>>,
+    headers: HeaderMap,
+) -> (StatusCode, Json<Value>) {
+    if headers.get(header::AUTHORIZATION).unwrap() != "Bearer idp-access" {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "invalid_token" })),
+        );
+    }
+
+    (
+        StatusCode::OK,
+        Json(json!({
+            "sub": idp.userinfo_sub,
+            "email": "olga@idp.test",
+            "email_verified": true,
+            "given_name": "Olga",
+            "family_name": "Idp",
+        })),
+    )
+}
+
+struct Idp {
+    url: String,
+    requests: RequestLog,
+    key: SecretKey,
+    provider: OidcProvider,
+}
+
+impl Idp {
+    // an id token for us with the given claims on top of the required ones
+    fn id_token(&self, claims: Value) -> String {
+        let now = chrono::Utc::now().timestamp();
+        let mut payload = json!({
+            "iss": self.url,
+            "sub": SUB,
+            "aud": CLIENT_ID,
+            "iat": now,
+            "exp": now + 3600,
+        });
+        payload
+            .as_object_mut()
+            .unwrap()
+            .extend(claims.as_object().unwrap().clone());
+
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some(KID.to_string());
+        let pem = self.key.to_pkcs8_pem(LineEnding::LF).unwrap();
+
+        jsonwebtoken::encode(
+            &header,
+            &payload,
+            &EncodingKey::from_ec_pem(pem.as_bytes()).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn tokens(&self, claims: Value) -> OauthTokenResponse {
+        OauthTokenResponse {
+            access_token: "idp-access".to_string(),
+            token_type: Some("Bearer".to_string()),
+            expires_in: None,
+            refresh_token: None,
+            id_token: Some(self.id_token(claims)),
+            scope: None,
+        }
+    }
+
+    fn userinfo_requests(&self) -> usize {
+        self.requests
+            .entries()
+            .iter()
+            .filter(|entry| *entry == "GET /userinfo")
+            .count()
+    }
+}
+
+async fn idp(stand_in: StandInIdp) -> Idp {
+    let key = stand_in.key.clone();
+    let (url, requests) = spawn_stand_in(
+        Router::new()
+            .route("/.well-known/openid-configuration", get(discovery))
+            .route("/jwks", get(jwks))
+            .route("/userinfo", get(userinfo))
+            .with_state(Arc::new(stand_in)),
+    )
+    .await;
+
+    let provider = OidcProvider::new(OidcProviderConfig {
+        name: "acme".to_string(),
+        issuer: url.clone(),
+        client_id: CLIENT_ID.to_string(),
+        client_secret: "idp-secret".to_string(),
+        redirect_url: "http://localhost:8800/oauth/acme/callback".to_string(),
+        scopes: "openid email profile".to_string(),
+    });
+
+    Idp {
+        url,
+        requests,
+        key,
+        provider,
+    }
+}
+
+fn authorize_request() -> AuthorizeRequest {
+    AuthorizeRequest {
+        state: "state".to_string(),
+        code_challenge: "challenge".to_string(),
+        nonce: "nonce".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn endpoints_and_keys_are_discovered_from_the_issuer() {
+    let idp = idp(StandInIdp::default()).await;
+    assert_eq!(idp.provider.issuers(), vec![idp.url.clone()]);
+
+    let url = idp
+        .provider
+        .authorize_url(&authorize_request())
+        .await
+        .unwrap();
+    assert!(url.starts_with(&format!("{}/authorize?", idp.url)));
+    assert!(url.contains("client_id=idp-client"));
+    assert!(url.contains("code_challenge_method=S256"));
+    assert!(url.contains("nonce=nonce"));
+
+    let claims = idp
+        .provider
+        .verify_id_token(&idp.id_token(json!({})))
+        .await
+        .unwrap();
+    assert_eq!(claims.sub, SUB);
+
+    // discovered once, then reused
+    let requests = idp.requests.entries();
+    assert_eq!(
+        requests
+            .iter()
+            .filter(|entry| *entry == "GET /.well-known/openid-configuration")
+            .count(),
+        1
+    );
+    assert!(requests.contains(&"GET /jwks".to_string()));
+}
+
+#[tokio::test]
+async fn discovery_describing_another_issuer_is_rejected() {
+    let idp = idp(StandInIdp {
+        issuer: Some("https://elsewhere.test".to_string()),
+        ..Default::default()
+    })
+    .await;
+
+    assert!(matches!(
+        idp.provider.authorize_url(&authorize_request()).await,
+        Err(AppError::ProcessError(_))
+    ));
+    assert!(idp
+        .provider
+        .verify_id_token(&idp.id_token(json!({})))
+        .await
+        .is_err());
+    assert!(!idp.requests.entries().contains(&"GET /jwks".to_string()));
+}
+
+#[tokio::test]
+async fn the_user_is_made_from_the_id_token_claims() {
+    let idp = idp(StandInIdp::default()).await;
+
+    let user = idp
+        .provider
+        .fetch_user_info(&idp.tokens(json!({
+            "email": "olga@idp.test",
+            "email_verified": true,
+            "given_name": "Olga",
+            "family_name": "Idp",
+            "preferred_username": "olga",
+            "picture": "https://idp.test/olga.png",
+        })))
+        .await
+        .unwrap();
+    assert_eq!(user.provider_user_id, SUB);
+    assert_eq!(user.email, "olga@idp.test");
+    assert!(user.email_verified);
+    // no name claim, given & family name come before the username
+    assert_eq!(user.name.as_deref(), Some("Olga Idp"));
+    assert_eq!(user.picture.as_deref(), Some("https://idp.test/olga.png"));
+
+    // unverified unless the provider says so
+    let user = idp
+        .provider
+        .fetch_user_info(&idp.tokens(json!({
+            "email": "olga@idp.test",
+            "preferred_username": "olga",
+        })))
+        .await
+        .unwrap();
+    assert!(!user.email_verified);
+    assert_eq!(user.name.as_deref(), Some("olga"));
+
+    assert_eq!(idp.userinfo_requests(), 0);
+}
+
+#[tokio::test]
+async fn userinfo_fills_in_what_the_id_token_left_out() {
+    let idp = idp(StandInIdp::default()).await;
+
+    let user = idp
+        .provider
+        .fetch_user_info(&idp.tokens(json!({ "name": "Olga from the token" })))
+        .await
+        .unwrap();
+    assert_eq!(idp.userinfo_requests(), 1);
+    assert_eq!(user.provider_user_id, SUB);
+    assert_eq!(user.email, "olga@idp.test");
+    assert!(user.email_verified);
+    // the id token's own claims win
+    assert_eq!(user.name.as_deref(), Some("Olga from the token"));
+}
+
+#[tokio::test]
+async fn userinfo_of_another_subject_is_rejected() {
+    let idp = idp(StandInIdp {
+        userinfo_sub: "someone-else".to_string(),
+        ..Default::default()
+    })
+    .await;
+
+    assert!(matches!(
+        idp.provider.fetch_user_info(&idp.tokens(json!({}))).await,
+        Err(AppError::Oauth2FailedToAuthorize)
+    ));
+    assert_eq!(idp.userinfo_requests(), 1);
+}
+
+// every test sets the settings of its own provider names, the environment is shared
+fn set_provider_env(prefix: &str) {
+    for (key, value) in [
+        ("ISSUER", "https://idp.test"),
+        ("CLIENT_ID", "idp-client"),
+        ("CLIENT_SECRET", "idp-secret"),
+        ("REDIRECT_URI", "http://localhost:8800/oauth/idp/callback"),
+    ] {
+        std::env::set_var(format!("{}_{}", prefix, key), value);
+    }
+}
+
+#[test]
+fn providers_are_read_from_their_prefixed_settings() {
+    set_provider_env("OIDC_CORP_SSO");
+    std::env::set_var("OIDC_CORP_SSO_SCOPES", "openid email");
+    set_provider_env("OIDC_PARTNER");
+
+    let configs = test_config(&[("OIDC_PROVIDERS", "corp-sso, partner")]).oidc_provider_configs();
+    assert_eq!(configs.len(), 2);
+    assert_eq!(configs[0].name, "corp-sso");
+    assert_eq!(configs[0].issuer, "https://idp.test");
+    assert_eq!(configs[0].client_id, "idp-client");
+    assert_eq!(configs[0].scopes, "openid email");
+    assert_eq!(configs[1].name, "partner");
+    assert_eq!(configs[1].scopes, "openid email profile");
+
+    assert!(test_config(&[]).oidc_provider_configs().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "OIDC_PROVIDERS name google is already in use")]
+fn built_in_provider_names_are_reserved() {
+    test_config(&[("OIDC_PROVIDERS", "google")]).oidc_provider_configs();
+}
+
+#[test]
+#[should_panic(expected = "OIDC_PROVIDERS name twice is already in use")]
+fn provider_names_are_unique() {
+    set_provider_env("OIDC_TWICE");
+
+    test_config(&[("OIDC_PROVIDERS", "twice,twice")]).oidc_provider_configs();
+}
+
+#[test]
+#[should_panic(
+    expected = "OIDC_PROVIDERS names shared-env and shared_env both read OIDC_SHARED_ENV_* settings"
+)]
+fn provider_names_sharing_their_settings_are_rejected() {
+    set_provider_env("OIDC_SHARED_ENV");
+
+    test_config(&[("OIDC_PROVIDERS", "shared-env,shared_env")]).oidc_provider_configs();
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use rust_ddd_oauth_casbin::{
+    application::dto::auth::email_request::{
+        ChangePasswordRequest, EmailLoginRequest, ForgotPasswordRequest, ResetPasswordRequest,
+    },
+    domain::entities::user::User,
+    infra::{errors::app_error::AppError, utils::client_info::ClientInfo},
+};
+
+use common::auth::{
+    auth, current_user, logged_in, login_request, user_with_password, Auth, PASSWORD,
+};
+
+const EMAIL: &str = "paula@password.test";
+const NEW_PASSWORD: &str = "battery staple";
+
+async fn login(auth: &Auth, password: &str) -> Result<(String, String), AppError> {
+    auth.email_login
+        .execute(
+            EmailLoginRequest {
+                email: EMAIL.to_string(),
+                password: password.to_string(),
+            },
+            &ClientInfo::default(),
+        )
+        .await
+        .map(logged_in)
+}
+
+async fn forgot(auth: &Auth, email: &str) {
+    auth.forgot_password
+        .execute(ForgotPasswordRequest {
+            email: email.to_string(),
+        })
+        .await
+        .unwrap();
+}
+
+async fn reset(auth: &Auth, token: &str) -> Result<(), AppError> {
+    auth.reset_password
+        .execute(ResetPasswordRequest {
+            token: token.to_string(),
+            new_password: NEW_PASSWORD.to_string(),
+        })
+        .await
+}
+
+#[tokio::test]
+async fn a_reset_sets_the_new_password_and_logs_out_everywhere() {
+    let auth = auth().await;
+    let user = auth.users.insert(user_with_password(EMAIL));
+    login(&auth, PASSWORD).await.unwrap();
+    login(&auth, PASSWORD).await.unwrap();
+    auth.redis_svc
+        .set_current_user(&current_user(&user))
+        .await
+        .unwrap();
+
+    forgot(&auth, EMAIL).await;
+    let token = auth.mailer.last_token(EMAIL).unwrap();
+    reset(&auth, &token).await.unwrap();
+
+    assert!(auth.sessions.all().is_empty());
+    // the cached user goes with the sessions
+    assert!(auth.redis_svc.get_current_user(&user.id).await.is_err());
+    assert!(matches!(
+        login(&auth, PASSWORD).await,
+        Err(AppError::UnauthorizedError(_))
+    ));
+    login(&auth, NEW_PASSWORD).await.unwrap();
+
+    // the link is used up
+    assert!(matches!(
+        reset(&auth, &token).await,
+        Err(AppError::UnauthorizedError(_))
+    ));
+}
+
+#[tokio::test]
+async fn only_registered_password_accounts_get_a_reset_mail() {
+    let auth = auth().await;
+    auth.users.insert(User::new(EMAIL.to_string(), None));
+
+    forgot(&auth, EMAIL).await;
+    forgot(&auth, "nobody@password.test").await;
+
+    assert!(auth.mailer.all().is_empty());
+    assert!(auth.user_tokens.all().is_empty());
+}
+
+#[tokio::test]
+async fn changing_the_password_keeps_only_the_current_session() {
+    let auth = auth().await;
+    let user = auth.users.insert(user_with_password(EMAIL));
+    login(&auth, PASSWORD).await.unwrap();
+    login(&auth, PASSWORD).await.unwrap();
+    let current = auth.sessions.all()[0].id.clone();
+
+    let change = |current_password: &str| ChangePasswordRequest {
+        current_password: current_password.to_string(),
+        new_password: NEW_PASSWORD.to_string(),
+    };
+    assert!(matches!(
+        auth.change_password
+            .execute(&user.id, Some(&current), change("wrong password"))
+            .await,
+        Err(AppError::UnauthorizedError(_))
+    ));
+    assert_eq!(auth.sessions.all().len(), 2);
+
+    auth.change_password
+        .execute(&user.id, Some(&current), change(PASSWORD))
+        .await
+        .unwrap();
+
+    let sessions = auth.sessions.all();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].id, current);
+    login(&auth, NEW_PASSWORD).await.unwrap();
+}
+
+#[tokio::test]
+async fn provider_only_accounts_have_no_password_to_change() {
+    let auth = auth().await;
+    let user = auth.users.insert(User::new(EMAIL.to_string(), None));
+
+    let result = auth
+        .change_password
+        .execute(
+            &user.id,
+            Some("session"),
+            ChangePasswordRequest {
+                current_password: PASSWORD.to_string(),
+                new_password: NEW_PASSWORD.to_string(),
+            },
+        )
+        .await;
+    assert!(matches!(result, Err(AppError::ProcessError(_))));
+    assert!(matches!(
+        auth.email_login
+            .execute(login_request(EMAIL), &ClientInfo::default())
+            .await,
+        Err(AppError::UnauthorizedError(_))
+    ));
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use std::sync::Arc;
+
+use rust_ddd_oauth_casbin::{
+    application::dto::auth::oauth2_response::{OauthTokenResponse, OauthUserInfo},
+    infra::{
+        errors::app_error::AppError,
+        oauth2::{
+            provider::{AuthorizeRequest, OauthProvider},
+            registry::OauthProviderRegistry,
+        },
+    },
+};
+
+use common::test_config;
+
+struct StandInProvider;
+
+#[async_trait::async_trait]
+impl OauthProvider for StandInProvider {
+    fn name(&self) -> &str {
+        "stand-in"
+    }
+
+    fn issuers(&self) -> Vec<String> {
+        vec!["https://stand-in.test".to_string()]
+    }
+
+    async fn authorize_url(&self, _req: &AuthorizeRequest) -> Result<String, AppError> {
+        Ok("https://stand-in.test/authorize".to_string())
+    }
+
+    async fn exchange_code(
+        &self,
+        _code: &str,
+        _code_verifier: &str,
+    ) -> Result<OauthTokenResponse, AppError> {
+        Err(AppError::Oauth2FailedToAuthorize)
+    }
+
+    async fn fetch_user_info(
+        &self,
+        _tokens: &OauthTokenResponse,
+    ) -> Result<OauthUserInfo, AppError> {
+        Err(AppError::Oauth2FailedToAuthorize)
+    }
+
+    async fn refresh_token(&self, _refresh_token: &str) -> Result<OauthTokenResponse, AppError> {
+        Err(AppError::Oauth2FailedToAuthorize)
+    }
+
+    async fn revoke_token(
+        &self,
+        _access_token: &str,
+        _refresh_token: Option<&str>,
+    ) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+#[test]
+fn google_is_only_registered_when_configured() {
+    let registry = OauthProviderRegistry::from_config(&test_config(&[]));
+    assert_eq!(registry.get("google").unwrap().name(), "google");
+    assert_eq!(
+        registry
+            .find_by_issuer("https://accounts.google.com")
+            .unwrap()
+            .name(),
+        "google"
+    );
+
+    let registry = OauthProviderRegistry::from_config(&test_config(&[("GOOGLE_CLIENT_ID", "")]));
+    assert!(matches!(
+        registry.get("google"),
+        Err(AppError::InvalidOauthProvider)
+    ));
+    assert!(registry
+        .find_by_issuer("https://accounts.google.com")
+        .is_none());
+}
+
+#[test]
+fn registered_providers_are_found_by_name_and_issuer() {
+    let mut registry = OauthProviderRegistry::default();
+    registry.register(Arc::new(StandInProvider));
+
+    assert_eq!(registry.get("stand-in").unwrap().name(), "stand-in");
+    assert!(registry.get("unknown").is_err());
+    assert_eq!(
+        registry
+            .find_by_issuer("https://stand-in.test")
+            .unwrap()
+            .name(),
+        "stand-in"
+    );
+    assert!(registry.find_by_issuer("https://other.test").is_none());
+}
@@ -0,0 +1,215 @@
+mod common;
+
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{middleware::from_fn_with_state, routing::get, Extension, Router};
+use reqwest::{header::RETRY_AFTER, StatusCode};
+use rust_ddd_oauth_casbin::{
+    application::services::redis_svc::RedisService,
+    domain::repositories::redis_repo::RedisRepository,
+    infra::utils::{
+        client_info::TrustedProxies,
+        jwt_maker::{JwtMaker, TokenProfile},
+    },
+    interface::middleware::rate_limit_mw::{
+        rate_limit, RateLimit, RateLimitKey, RateLimiter, RATELIMIT_LIMIT, RATELIMIT_REMAINING,
+        RATELIMIT_RESET,
+    },
+};
+
+use common::{memory::MemoryRedis, test_config};
+
+// serves one route behind a limit of 2 requests per hour, the test client connects from
+// 127.0.0.1 which is trusted to forward other addresses
+async fn spawn_limited(key: RateLimitKey, jwt_maker: Arc<JwtMaker>) -> String {
+    let cfg = test_config(&[("TRUSTED_PROXIES", "127.0.0.1")]);
+    let limiter = RateLimiter::new(
+        Arc::new(RedisService::new(Arc::new(MemoryRedis::default()))),
+        jwt_maker,
+        "api",
+        RateLimit::from_spec("2/3600", key),
+    );
+    let router = Router::new()
+        .route("/", get(|| async { "ok" }))
+        .route_layer(from_fn_with_state(limiter, rate_limit))
+        .layer(Extension(TrustedProxies::from_config(&cfg)));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .unwrap()
+    });
+
+    format!("http://{}", addr)
+}
+
+async fn call(base_url: &str, ip: &str, token: Option<&str>) -> reqwest::Response {
+    let mut req = reqwest::Client::new()
+        .get(base_url)
+        .header("x-forwarded-for", ip);
+    if let Some(token) = token {
+        req = req.bearer_auth(token);
+    }
+
+    req.send().await.unwrap()
+}
+
+fn header(resp: &reqwest::Response, name: impl reqwest::header::AsHeaderName) -> u64 {
+    resp.headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .unwrap()
+}
+
+fn access_token(jwt_maker: &JwtMaker, user_id: &str) -> String {
+    jwt_maker
+        .make_token(
+            user_id.to_string(),
+            "session".to_string(),
+            TokenProfile::default(),
+            1,
+        )
+        .unwrap()
+}
+
+#[tokio::test]
+async fn hits_are_counted_per_key_within_the_window() {
+    let redis_svc = RedisService::new(Arc::new(MemoryRedis::default()));
+
+    for expected in 1..=3 {
+        let (hits, reset) = redis_svc
+            .count_sliding_window("public_ip_192.0.2.1", 3600)
+            .await
+            .unwrap();
+        assert_eq!(hits, expected);
+        assert!(reset > 0 && reset <= 3600);
+    }
+
+    let (hits, _) = redis_svc
+        .count_sliding_window("public_ip_192.0.2.2", 3600)
+        .await
+        .unwrap();
+    assert_eq!(hits, 1);
+}
+
+#[tokio::test]
+async fn the_previous_window_counts_with_its_overlap() {
+    let redis = Arc::new(MemoryRedis::default());
+    let redis_svc = RedisService::new(redis.clone());
+    let window = chrono::Utc::now().timestamp() as u64 / 60;
+    redis
+        .set_value(&format!("rate_limit_api_user_1_{}", window - 1), "10")
+        .await
+        .unwrap();
+
+    let (hits, reset) = redis_svc
+        .count_sliding_window("api_user_1", 60)
+        .await
+        .unwrap();
+
+    // the later in the current window, the less of the previous one is left
+    assert!((1..=11).contains(&hits));
+    assert!(hits - 1 <= 10 * reset / 60);
+}
+
+#[test]
+fn limits_are_read_as_requests_per_seconds() {
+    let limit = RateLimit::from_spec(" 30 / 60 ", RateLimitKey::Ip);
+    assert_eq!(limit.requests, 30);
+    assert_eq!(limit.window_seconds, 60);
+}
+
+#[test]
+#[should_panic(expected = "must look like <requests>/<seconds>")]
+fn limits_without_a_window_are_rejected() {
+    RateLimit::from_spec("30", RateLimitKey::User);
+}
+
+#[tokio::test]
+async fn requests_over_the_limit_are_turned_away_until_the_reset() {
+    let jwt_maker = Arc::new(JwtMaker::new(&test_config(&[])));
+    let base_url = spawn_limited(RateLimitKey::Ip, jwt_maker).await;
+
+    for remaining in [1, 0] {
+        let resp = call(&base_url, "192.0.2.1", None).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(header(&resp, RATELIMIT_LIMIT), 2);
+        assert_eq!(header(&resp, RATELIMIT_REMAINING), remaining);
+        assert!(resp.headers().get(RETRY_AFTER).is_none());
+    }
+
+    let resp = call(&base_url, "192.0.2.1", None).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(header(&resp, RATELIMIT_LIMIT), 2);
+    assert_eq!(header(&resp, RATELIMIT_REMAINING), 0);
+    let reset = header(&resp, RATELIMIT_RESET);
+    assert!(reset > 0 && reset <= 3600);
+    assert_eq!(header(&resp, RETRY_AFTER), reset);
+
+    // other addresses keep their own counter
+    let resp = call(&base_url, "192.0.2.2", None).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn verified_users_are_counted_wherever_they_call_from() {
+    let jwt_maker = Arc::new(JwtMaker::new(&test_config(&[])));
+    let base_url = spawn_limited(RateLimitKey::User, jwt_maker.clone()).await;
+    let alice = access_token(&jwt_maker, "alice");
+    let bob = access_token(&jwt_maker, "bob");
+
+    // a new address doesn't give a user a fresh limit
+    assert_eq!(
+        call(&base_url, "192.0.2.1", Some(&alice)).await.status(),
+        StatusCode::OK
+    );
+    assert_eq!(
+        call(&base_url, "192.0.2.2", Some(&alice)).await.status(),
+        StatusCode::OK
+    );
+    assert_eq!(
+        call(&base_url, "192.0.2.3", Some(&alice)).await.status(),
+        StatusCode::TOO_MANY_REQUESTS
+    );
+
+    // nor does the same address use up the limit of another user
+    assert_eq!(
+        call(&base_url, "192.0.2.1", Some(&bob)).await.status(),
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn unverified_tokens_are_counted_by_address() {
+    let jwt_maker = Arc::new(JwtMaker::new(&test_config(&[])));
+    let base_url = spawn_limited(RateLimitKey::User, jwt_maker.clone()).await;
+    let alice = access_token(&jwt_maker, "alice");
+
+    // a made up token can't be used to drain the limit of alice
+    for forged in ["not-a-token", "also-not-a-token"] {
+        assert_eq!(
+            call(&base_url, "192.0.2.1", Some(forged)).await.status(),
+            StatusCode::OK
+        );
+    }
+    assert_eq!(
+        call(&base_url, "192.0.2.1", None).await.status(),
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    assert_eq!(
+        call(&base_url, "192.0.2.1", Some(&alice)).await.status(),
+        StatusCode::OK
+    );
+    assert_eq!(
+        call(&base_url, "192.0.2.2", Some("not-a-token"))
+            .await
+            .status(),
+        StatusCode::OK
+    );
+}
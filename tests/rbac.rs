@@ -0,0 +1,157 @@
+mod common;
+
+use casbin::MgmtApi;
+use rust_ddd_oauth_casbin::{
+    domain::entities::{
+        role::Role,
+        user::{User, UserFull},
+        user_oauth_provider::UserOauthProvider,
+        user_role::UserRole,
+    },
+    infra::{common::constants::SUPER_ADMIN_ROLE, rbac::Rbac},
+};
+
+use common::memory_rbac;
+
+fn role(id: &str) -> Role {
+    Role::new(id.to_string(), id.to_string(), false)
+}
+
+fn user_with(roles: &[&str]) -> UserFull {
+    let user = User::new(format!("{}@rbac.test", roles.join("-")), None);
+    let oauth_provider =
+        UserOauthProvider::new(user.id.clone(), "email".to_string(), user.email.clone());
+
+    UserFull::new(
+        user,
+        oauth_provider,
+        roles.iter().map(|id| role(id)).collect(),
+    )
+}
+
+async fn add_policies(rbac: &Rbac, policies: &[[&str; 3]]) {
+    let mut enforcer = rbac.enforcer.write().await;
+    for policy in policies {
+        enforcer
+            .add_policy(policy.iter().map(|field| field.to_string()).collect())
+            .await
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn any_role_of_the_user_grants_access() {
+    let rbac = memory_rbac().await;
+    add_policies(
+        &rbac,
+        &[["viewer", "post", "read"], ["editor", "post", "*"]],
+    )
+    .await;
+    let user = user_with(&["viewer", "editor"]);
+    rbac.assign_role(&user.user.id, "viewer").await.unwrap();
+    rbac.assign_role(&user.user.id, "editor").await.unwrap();
+
+    let decision = rbac.check_access(&user, "post", "read").await.unwrap();
+    assert!(decision.allowed);
+    assert_eq!(decision.matched_role_name(), "viewer");
+
+    // only the second role has it
+    let decision = rbac.check_access(&user, "post", "write").await.unwrap();
+    assert!(decision.allowed);
+    assert_eq!(decision.matched_role_name(), "editor");
+
+    let decision = rbac.check_access(&user, "user", "read").await.unwrap();
+    assert!(!decision.allowed);
+    assert!(decision.matched_role.is_none());
+
+    rbac.revoke_role(&user.user.id, "editor").await.unwrap();
+    assert!(
+        !rbac
+            .check_access(&user, "post", "write")
+            .await
+            .unwrap()
+            .allowed
+    );
+}
+
+#[tokio::test]
+async fn roles_inherit_the_permissions_of_their_parents() {
+    let rbac = memory_rbac().await;
+    add_policies(
+        &rbac,
+        &[["viewer", "post", "read"], ["editor", "post", "write"]],
+    )
+    .await;
+    rbac.set_role_parents("editor", &["viewer".to_string()])
+        .await
+        .unwrap();
+    rbac.set_role_parents("admin", &["editor".to_string()])
+        .await
+        .unwrap();
+    let user = user_with(&["admin"]);
+    rbac.assign_role(&user.user.id, "admin").await.unwrap();
+
+    // granted through admin -> editor -> viewer, admin is the role the user holds
+    let decision = rbac.check_access(&user, "post", "read").await.unwrap();
+    assert!(decision.allowed);
+    assert_eq!(decision.matched_role_name(), "admin");
+
+    assert!(rbac.inherits_from("admin", "viewer").await);
+    assert!(!rbac.inherits_from("viewer", "admin").await);
+    assert_eq!(rbac.get_role_parents("admin").await, ["editor"]);
+
+    rbac.set_role_parents("editor", &[]).await.unwrap();
+    assert!(!rbac.inherits_from("admin", "viewer").await);
+    assert!(
+        !rbac
+            .check_access(&user, "post", "read")
+            .await
+            .unwrap()
+            .allowed
+    );
+
+    rbac.remove_role_groupings("editor").await.unwrap();
+    assert!(rbac.get_role_parents("admin").await.is_empty());
+}
+
+#[tokio::test]
+async fn sync_replaces_user_groupings_and_keeps_role_hierarchies() {
+    let rbac = memory_rbac().await;
+    rbac.set_role_parents("editor", &["viewer".to_string()])
+        .await
+        .unwrap();
+    rbac.assign_role("stale-user", "editor").await.unwrap();
+
+    let user_roles = [UserRole::new("user-1".to_string(), "editor".to_string())];
+    rbac.sync_user_roles(&user_roles, &["editor".to_string(), "viewer".to_string()])
+        .await
+        .unwrap();
+
+    {
+        let enforcer = rbac.enforcer.read().await;
+        assert!(enforcer.has_grouping_policy(vec!["user-1".to_string(), "editor".to_string()]));
+        assert!(!enforcer.has_grouping_policy(vec!["stale-user".to_string(), "editor".to_string()]));
+    }
+    assert_eq!(rbac.get_role_parents("editor").await, ["viewer"]);
+}
+
+#[tokio::test]
+async fn admins_only_manage_roles_they_hold_or_inherit() {
+    let rbac = memory_rbac().await;
+    rbac.set_role_parents("editor", &["viewer".to_string()])
+        .await
+        .unwrap();
+    let editor = user_with(&["editor"]);
+    let super_admin = Role::new("root".to_string(), SUPER_ADMIN_ROLE.to_string(), false);
+
+    assert!(rbac.can_manage_role(&editor, &role("editor")).await);
+    assert!(rbac.can_manage_role(&editor, &role("viewer")).await);
+    // a role above the actor's own
+    assert!(!rbac.can_manage_role(&editor, &role("admin")).await);
+    assert!(!rbac.can_manage_role(&editor, &super_admin).await);
+
+    let mut root = user_with(&[]);
+    root.roles.push(super_admin.clone());
+    assert!(rbac.can_manage_role(&root, &super_admin).await);
+    assert!(rbac.can_manage_role(&root, &role("admin")).await);
+}
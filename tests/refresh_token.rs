@@ -0,0 +1,64 @@
+mod common;
+
+use rust_ddd_oauth_casbin::infra::{
+    errors::app_error::AppError,
+    utils::{client_info::ClientInfo, token_hash::hash_token},
+};
+
+use common::auth::{auth, logged_in, login_request, user_with_password, Auth};
+
+async fn login(auth: &Auth, email: &str) -> String {
+    auth.users.insert(user_with_password(email));
+    let (_, refresh_token) = logged_in(
+        auth.email_login
+            .execute(login_request(email), &ClientInfo::default())
+            .await
+            .unwrap(),
+    );
+
+    refresh_token
+}
+
+#[tokio::test]
+async fn every_refresh_hands_out_a_new_refresh_token() {
+    let auth = auth().await;
+    let first = login(&auth, "rotate@refresh.test").await;
+
+    let (_, second) = auth.refresh.execute(&first).await.unwrap();
+    let (_, third) = auth.refresh.execute(&second).await.unwrap();
+
+    assert_ne!(first, second);
+    assert_ne!(second, third);
+    assert_eq!(auth.sessions.all().len(), 1);
+}
+
+#[tokio::test]
+async fn reusing_a_rotated_refresh_token_revokes_the_session() {
+    let auth = auth().await;
+    let stolen = login(&auth, "reuse@refresh.test").await;
+    let (_, current) = auth.refresh.execute(&stolen).await.unwrap();
+
+    let replayed = auth.refresh.execute(&stolen).await;
+    assert!(matches!(replayed, Err(AppError::UnauthorizedError(_))));
+    assert!(auth.sessions.all().is_empty());
+
+    // the whole family is gone, including the token of the legitimate client
+    let refreshed = auth.refresh.execute(&current).await;
+    assert!(matches!(refreshed, Err(AppError::UnauthorizedError(_))));
+}
+
+#[tokio::test]
+async fn refresh_tokens_are_only_stored_hashed() {
+    let auth = auth().await;
+    let refresh_token = login(&auth, "hashed@refresh.test").await;
+
+    let stored = auth.sessions.refresh_tokens.lock().unwrap().clone();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].token_hash, auth.token_cipher.hash(&refresh_token));
+    // keyed, a plain digest of a leaked token can't be matched against the table
+    assert_ne!(stored[0].token_hash, hash_token(&refresh_token));
+
+    let session = &auth.sessions.all()[0];
+    assert!(session.refresh_token.is_none());
+    assert_eq!(session.token_key_version, Some(1));
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use rust_ddd_oauth_casbin::infra::{
+    errors::app_error::AppError, utils::return_to::ReturnToAllowlist,
+};
+
+use common::test_config;
+
+fn allowlist() -> ReturnToAllowlist {
+    ReturnToAllowlist::from_config(&test_config(&[(
+        "RETURN_TO_ORIGINS",
+        "https://app.example.com, http://localhost:3000/",
+    )]))
+}
+
+#[test]
+fn urls_on_an_allowed_origin_are_accepted() {
+    let allowlist = allowlist();
+
+    assert_eq!(
+        allowlist
+            .check("https://app.example.com/settings?tab=sessions")
+            .unwrap(),
+        "https://app.example.com/settings?tab=sessions"
+    );
+    assert!(allowlist.check("http://localhost:3000").is_ok());
+}
+
+#[test]
+fn other_origins_and_lookalikes_are_rejected() {
+    let allowlist = allowlist();
+
+    for return_to in [
+        "https://evil.com/",
+        "https://app.example.com@evil.com/",
+        "https://app.example.com.evil.com/",
+        "http://app.example.com/",
+        "https://app.example.com:8443/",
+        "//evil.com/",
+        "/settings",
+        "javascript:alert(1)",
+        "not a url",
+    ] {
+        assert!(
+            matches!(
+                allowlist.check(return_to),
+                Err(AppError::InvalidReturnTo(_))
+            ),
+            "{} was accepted",
+            return_to
+        );
+    }
+}
+
+#[test]
+fn nothing_is_allowed_without_configured_origins() {
+    let allowlist = ReturnToAllowlist::from_config(&test_config(&[]));
+
+    assert!(allowlist.check("http://localhost:3000/").is_err());
+}
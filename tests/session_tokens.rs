@@ -0,0 +1,92 @@
+mod common;
+
+use std::sync::Arc;
+
+use rust_ddd_oauth_casbin::{
+    application::usecases::auth::seal_session_tokens::SealSessionTokens,
+    domain::{
+        entities::{refresh_token::RefreshToken, user_session::UserSession},
+        repositories::user_session_repo::UserSessionRepository,
+    },
+    infra::utils::{client_info::ClientInfo, token_cipher::TokenCipher, token_hash::hash_token},
+};
+
+use common::{memory::MemorySessionRepo, test_config};
+
+const KEYS: &str =
+    "1:AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=,2:AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=";
+
+#[tokio::test]
+async fn rotating_the_key_re_encrypts_stored_provider_tokens() {
+    let old_cipher = TokenCipher::new(&test_config(&[]));
+    let cipher = TokenCipher::new(&test_config(&[
+        ("TOKEN_ENCRYPTION_KEYS", KEYS),
+        ("TOKEN_ENCRYPTION_KEY_VERSION", "2"),
+    ]));
+    let sessions = Arc::new(MemorySessionRepo::default());
+
+    let mut session = UserSession::new(
+        "session-1".to_string(),
+        "user-1".to_string(),
+        "google".to_string(),
+        "google-access".to_string(),
+        Some("google-refresh".to_string()),
+        None,
+        &ClientInfo::default(),
+    );
+    session.seal(&old_cipher).unwrap();
+    assert!(!session.access_token.contains("google-access"));
+    sessions.create(session).await.unwrap();
+
+    // a digest stored before refresh token hashes were keyed
+    let mut legacy_token = RefreshToken::new("session-1".to_string(), hash_token("legacy-refresh"));
+    legacy_token.keyed = false;
+    sessions.create_refresh_token(legacy_token).await.unwrap();
+
+    SealSessionTokens::new(sessions.clone(), Arc::new(cipher.clone()))
+        .execute()
+        .await
+        .unwrap();
+
+    let mut stored = sessions.all()[0].clone();
+    assert_eq!(stored.token_key_version, Some(2));
+    stored.unseal(&cipher).unwrap();
+    assert_eq!(stored.access_token, "google-access");
+    assert_eq!(stored.refresh_token.as_deref(), Some("google-refresh"));
+
+    assert!(sessions
+        .find_refresh_token(&cipher.hash("legacy-refresh"))
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn plaintext_sessions_keep_their_bound_id_token() {
+    let cipher = TokenCipher::new(&test_config(&[]));
+    let sessions = Arc::new(MemorySessionRepo::default());
+
+    // a provider session stored before tokens were sealed, its id token bound by the plain digest
+    let mut session = UserSession::new(
+        "session-1".to_string(),
+        "user-1".to_string(),
+        "google".to_string(),
+        "google-access".to_string(),
+        Some("google-refresh".to_string()),
+        None,
+        &ClientInfo::default(),
+    );
+    session.id_token_hash = Some(hash_token("google-id"));
+    sessions.create(session).await.unwrap();
+
+    SealSessionTokens::new(sessions.clone(), Arc::new(cipher.clone()))
+        .execute()
+        .await
+        .unwrap();
+
+    let stored = sessions
+        .find_by_id_token_hash(&cipher.hash("google-id"))
+        .await
+        .unwrap();
+    assert_eq!(stored.id, "session-1");
+    assert!(!stored.access_token.contains("google-access"));
+}
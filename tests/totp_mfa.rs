@@ -0,0 +1,259 @@
+mod common;
+
+use std::sync::Arc;
+
+use rust_ddd_oauth_casbin::{
+    application::dto::auth::{
+        mfa_request::{MfaCodeRequest, MfaLoginRequest, MfaTokenRequest},
+        mfa_response::MfaChallengeResponse,
+        oauth2_response::OauthCallbackResult,
+        token_response::{EmailLoginResult, MfaLoginResult},
+    },
+    domain::{
+        entities::{role::Role, user::User, user_role::UserRole},
+        repositories::role_repo::RoleRepository,
+    },
+    infra::{
+        common::constants::MFA_CHALLENGE_MAX_ATTEMPTS, errors::app_error::AppError,
+        utils::client_info::ClientInfo,
+    },
+};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+use common::{
+    auth::{auth, login_request, user_with_password, Auth},
+    oauth_flow::{oauth_flow, StandInProvider, STAND_IN, STAND_IN_ID_TOKEN},
+};
+
+const EMAIL: &str = "tomas@totp.test";
+
+// what the user's authenticator app shows `steps` time steps from now
+fn code_at(secret: &str, steps: i64) -> String {
+    let totp = TOTP::new(
+        Algorithm::SHA1,
+        6,
+        1,
+        30,
+        Secret::Encoded(secret.to_string()).to_bytes().unwrap(),
+        None,
+        EMAIL.to_string(),
+    )
+    .unwrap();
+
+    totp.generate((chrono::Utc::now().timestamp() + steps * 30) as u64)
+}
+
+fn current_code(secret: &str) -> String {
+    code_at(secret, 0)
+}
+
+// returns the secret & the recovery codes. confirmed with the code of the previous step, a
+// step can only be used once & the tests login with the current one
+async fn enrol(auth: &Auth, user: &User) -> (String, Vec<String>) {
+    let enrolment = auth.start_totp_enrolment.execute(user).await.unwrap();
+    let recovery_codes = auth
+        .confirm_totp_enrolment
+        .execute(
+            user,
+            MfaCodeRequest {
+                code: code_at(&enrolment.secret, -1),
+            },
+        )
+        .await
+        .unwrap()
+        .recovery_codes;
+
+    (enrolment.secret, recovery_codes)
+}
+
+async fn login_challenge(auth: &Auth) -> MfaChallengeResponse {
+    match auth
+        .email_login
+        .execute(login_request(EMAIL), &ClientInfo::default())
+        .await
+        .unwrap()
+    {
+        EmailLoginResult::MfaRequired(challenge) => challenge,
+        EmailLoginResult::LoggedIn { .. } => panic!("the login skipped the second factor"),
+    }
+}
+
+async fn verify(auth: &Auth, mfa_token: &str, code: &str) -> Result<MfaLoginResult, AppError> {
+    auth.verify_mfa_login
+        .execute(
+            MfaLoginRequest {
+                mfa_token: mfa_token.to_string(),
+                code: code.to_string(),
+            },
+            &ClientInfo::default(),
+        )
+        .await
+}
+
+#[tokio::test]
+async fn enrolled_users_finish_the_login_with_a_totp_code() {
+    let auth = auth().await;
+    let user = auth.users.insert(user_with_password(EMAIL));
+    let (secret, recovery_codes) = enrol(&auth, &user).await;
+    assert_eq!(recovery_codes.len(), 10);
+
+    let challenge = login_challenge(&auth).await;
+    assert!(!challenge.enrolment_required);
+    assert!(auth.sessions.all().is_empty());
+
+    let code = current_code(&secret);
+    let tokens = verify(&auth, &challenge.mfa_token, &code).await.unwrap();
+    assert!(tokens.recovery_codes.is_none());
+    assert_eq!(auth.sessions.all().len(), 1);
+
+    // the challenge is used up & the code can't be replayed in another login
+    assert!(matches!(
+        verify(&auth, &challenge.mfa_token, &code).await,
+        Err(AppError::UnauthorizedError(_))
+    ));
+    let challenge = login_challenge(&auth).await;
+    assert!(matches!(
+        verify(&auth, &challenge.mfa_token, &code).await,
+        Err(AppError::InvalidMfaCode)
+    ));
+}
+
+#[tokio::test]
+async fn recovery_codes_work_once() {
+    let auth = auth().await;
+    let user = auth.users.insert(user_with_password(EMAIL));
+    let (_, recovery_codes) = enrol(&auth, &user).await;
+
+    // typed by hand
+    let typed = recovery_codes[0].to_uppercase().replace('-', " ");
+    let challenge = login_challenge(&auth).await;
+    verify(&auth, &challenge.mfa_token, &typed).await.unwrap();
+
+    let challenge = login_challenge(&auth).await;
+    assert!(matches!(
+        verify(&auth, &challenge.mfa_token, &recovery_codes[0]).await,
+        Err(AppError::InvalidMfaCode)
+    ));
+    verify(&auth, &challenge.mfa_token, &recovery_codes[1])
+        .await
+        .unwrap();
+    assert_eq!(auth.sessions.all().len(), 2);
+}
+
+#[tokio::test]
+async fn a_challenge_runs_out_of_attempts() {
+    let auth = auth().await;
+    let user = auth.users.insert(user_with_password(EMAIL));
+    let (secret, _) = enrol(&auth, &user).await;
+
+    let challenge = login_challenge(&auth).await;
+    for _ in 0..MFA_CHALLENGE_MAX_ATTEMPTS {
+        assert!(matches!(
+            verify(&auth, &challenge.mfa_token, "wrong-code").await,
+            Err(AppError::InvalidMfaCode)
+        ));
+    }
+
+    assert!(matches!(
+        verify(&auth, &challenge.mfa_token, &current_code(&secret)).await,
+        Err(AppError::UnauthorizedError(_))
+    ));
+    assert!(auth.sessions.all().is_empty());
+}
+
+async fn require_mfa(auth: &Auth, user: &User) {
+    let mut role = Role::new("admin".to_string(), "admin".to_string(), false);
+    role.require_mfa();
+    auth.roles.create(role).await.unwrap();
+    auth.roles
+        .assign_to_user(UserRole::new(user.id.clone(), "admin".to_string()))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn roles_requiring_mfa_enrol_their_users_during_login() {
+    let auth = auth().await;
+    let user = auth.users.insert(user_with_password(EMAIL));
+    require_mfa(&auth, &user).await;
+
+    let challenge = login_challenge(&auth).await;
+    assert!(challenge.enrolment_required);
+
+    let enrolment = auth
+        .start_totp_enrolment
+        .execute_with_challenge(MfaTokenRequest {
+            mfa_token: challenge.mfa_token.clone(),
+        })
+        .await
+        .unwrap();
+    let tokens = verify(
+        &auth,
+        &challenge.mfa_token,
+        &current_code(&enrolment.secret),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(tokens.recovery_codes.unwrap().len(), 10);
+    assert_eq!(auth.sessions.all().len(), 1);
+    assert!(auth.mfa_svc.is_totp_enabled(&user.id).await.unwrap());
+}
+
+#[tokio::test]
+async fn provider_id_tokens_do_not_skip_the_second_factor() {
+    let provider = Arc::new(StandInProvider {
+        issues_id_tokens: true,
+        ..Default::default()
+    });
+    let flow = oauth_flow(provider, &[]).await;
+    let user = flow.user(true);
+    require_mfa(&flow.auth, &user).await;
+    let id_token_session = || {
+        flow.auth
+            .oauth_svc
+            .get_active_id_token_session(STAND_IN, STAND_IN_ID_TOKEN, &user.id)
+    };
+
+    let OauthCallbackResult::MfaRequired { challenge, .. } = flow.login(None).await.unwrap() else {
+        panic!("the login skipped the second factor");
+    };
+    assert!(flow.auth.sessions.all().is_empty());
+    // the same id token can be had outside our callback, e.g. with response_type=id_token
+    assert!(matches!(
+        id_token_session().await,
+        Err(AppError::SessionExpired)
+    ));
+
+    let enrolment = flow
+        .auth
+        .start_totp_enrolment
+        .execute_with_challenge(MfaTokenRequest {
+            mfa_token: challenge.mfa_token.clone(),
+        })
+        .await
+        .unwrap();
+    let tokens = verify(
+        &flow.auth,
+        &challenge.mfa_token,
+        &current_code(&enrolment.secret),
+    )
+    .await
+    .unwrap();
+
+    // the finished login hands out our own tokens, the id token still opens no session
+    let claims = flow
+        .auth
+        .jwt_maker
+        .verify_access_token(&tokens.access_token)
+        .unwrap();
+    flow.auth
+        .oauth_svc
+        .get_active_session(&claims.sid, &user.id)
+        .await
+        .unwrap();
+    assert!(matches!(
+        id_token_session().await,
+        Err(AppError::SessionExpired)
+    ));
+}
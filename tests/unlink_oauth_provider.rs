@@ -0,0 +1,80 @@
+mod common;
+
+use std::sync::Arc;
+
+use rust_ddd_oauth_casbin::{
+    domain::{
+        entities::user_oauth_provider::UserOauthProvider,
+        repositories::oauth_provider_repo::OauthProviderRepository,
+    },
+    infra::{errors::app_error::AppError, oauth2::constants::EMAIL_PROVIDER},
+};
+
+use common::oauth_flow::{oauth_flow, StandInProvider, STAND_IN};
+
+#[tokio::test]
+async fn the_last_login_method_can_not_be_unlinked() {
+    let flow = oauth_flow(Arc::new(StandInProvider::default()), &[]).await;
+    let user = flow.user(true);
+    let links = flow
+        .auth
+        .oauth_providers
+        .find_by_user_id(&user.id)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        flow.unlink.execute(&user.id, &links[0].id).await,
+        Err(AppError::ProcessError(_))
+    ));
+    assert!(matches!(
+        flow.unlink.execute(&user.id, "unknown").await,
+        Err(AppError::ResourceNotFound)
+    ));
+    assert_eq!(
+        flow.auth
+            .oauth_providers
+            .find_by_user_id(&user.id)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn unlinking_a_provider_ends_its_sessions() {
+    let flow = oauth_flow(Arc::new(StandInProvider::default()), &[]).await;
+    let user = flow.user(true);
+    let password_login = flow.auth.oauth_providers.insert(UserOauthProvider::new(
+        user.id.clone(),
+        EMAIL_PROVIDER.to_string(),
+        user.email.clone(),
+    ));
+    flow.login(None).await.unwrap();
+    assert_eq!(flow.auth.sessions.all().len(), 1);
+
+    // the password stays, there is no other way to set a new one
+    assert!(matches!(
+        flow.unlink.execute(&user.id, &password_login.id).await,
+        Err(AppError::ProcessError(_))
+    ));
+
+    let stand_in = flow
+        .auth
+        .oauth_providers
+        .get_by_provider_and_id(STAND_IN, "stand-in-user")
+        .await
+        .unwrap();
+    flow.unlink.execute(&user.id, &stand_in.id).await.unwrap();
+
+    let links = flow
+        .auth
+        .oauth_providers
+        .find_by_user_id(&user.id)
+        .await
+        .unwrap();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].provider, EMAIL_PROVIDER);
+    assert!(flow.auth.sessions.all().is_empty());
+}
@@ -0,0 +1,526 @@
+mod common;
+
+use casbin::MgmtApi;
+
+use rust_ddd_oauth_casbin::{
+    application::{
+        dto::user::{
+            assign_role_request::AssignRoleRequest, update_user_request::UpdateUserRequest,
+        },
+        usecases::user::{
+            assign_user_role::AssignUserRole, deactivate_user::DeactivateUser,
+            delete_user_by_id::DeleteUserById, get_paginated_user::GetPaginatedUser,
+            get_user_by_id::GetUserById, reactivate_user::ReactivateUser,
+            revoke_user_role::RevokeUserRole, unlock_user::UnlockUser,
+            update_user_by_id::UpdateUserById,
+        },
+    },
+    domain::{
+        entities::{
+            role::Role,
+            user::{User, UserFull},
+            user_oauth_provider::UserOauthProvider,
+            user_role::UserRole,
+        },
+        repositories::{role_repo::RoleRepository, user_repo::UserRepository},
+    },
+    infra::{
+        common::constants::{LOGIN_LOCKOUT_AFTER_FAILURES, SUPER_ADMIN_ROLE},
+        errors::app_error::AppError,
+        utils::client_info::ClientInfo,
+    },
+};
+
+use common::{
+    auth::{auth, login_request, user_with_password, Auth},
+    memory::{
+        MemoryOauthProviderRepo, MemoryRedis, MemoryRoleRepo, MemorySessionRepo, MemoryUserRepo,
+    },
+};
+
+struct Users {
+    auth: Auth,
+    get: GetUserById<MemoryUserRepo, MemoryRoleRepo, MemoryOauthProviderRepo>,
+    list: GetPaginatedUser<MemoryUserRepo>,
+    update: UpdateUserById<MemoryUserRepo, MemoryRoleRepo, MemoryRedis>,
+    deactivate: DeactivateUser<MemoryUserRepo, MemoryRoleRepo, MemorySessionRepo, MemoryRedis>,
+    reactivate: ReactivateUser<MemoryUserRepo, MemoryRoleRepo, MemoryRedis>,
+    delete: DeleteUserById<MemoryUserRepo, MemoryRoleRepo, MemorySessionRepo, MemoryRedis>,
+    assign: AssignUserRole<MemoryUserRepo, MemoryRoleRepo, MemoryRedis>,
+    revoke: RevokeUserRole<MemoryUserRepo, MemoryRoleRepo, MemoryRedis>,
+    unlock: UnlockUser<MemoryUserRepo, MemoryRoleRepo, MemoryRedis>,
+}
+
+// admins inherit members, auditors are neither
+async fn users() -> Users {
+    let auth = auth().await;
+    for role in [SUPER_ADMIN_ROLE, "admin", "member", "auditor"] {
+        auth.roles
+            .create(Role::new(role.to_string(), role.to_string(), false))
+            .await
+            .unwrap();
+    }
+    auth.rbac
+        .set_role_parents("admin", &["member".to_string()])
+        .await
+        .unwrap();
+
+    Users {
+        get: GetUserById::new(
+            auth.users.clone(),
+            auth.roles.clone(),
+            auth.oauth_providers.clone(),
+        ),
+        list: GetPaginatedUser::new(auth.users.clone()),
+        update: UpdateUserById::new(
+            auth.users.clone(),
+            auth.roles.clone(),
+            auth.rbac.clone(),
+            auth.redis_svc.clone(),
+        ),
+        deactivate: DeactivateUser::new(
+            auth.users.clone(),
+            auth.roles.clone(),
+            auth.sessions.clone(),
+            auth.rbac.clone(),
+            auth.redis_svc.clone(),
+        ),
+        reactivate: ReactivateUser::new(
+            auth.users.clone(),
+            auth.roles.clone(),
+            auth.rbac.clone(),
+            auth.redis_svc.clone(),
+        ),
+        delete: DeleteUserById::new(
+            auth.users.clone(),
+            auth.roles.clone(),
+            auth.sessions.clone(),
+            auth.rbac.clone(),
+            auth.redis_svc.clone(),
+        ),
+        assign: AssignUserRole::new(
+            auth.users.clone(),
+            auth.roles.clone(),
+            auth.rbac.clone(),
+            auth.redis_svc.clone(),
+        ),
+        revoke: RevokeUserRole::new(
+            auth.users.clone(),
+            auth.roles.clone(),
+            auth.rbac.clone(),
+            auth.redis_svc.clone(),
+        ),
+        unlock: UnlockUser::new(
+            auth.users.clone(),
+            auth.roles.clone(),
+            auth.rbac.clone(),
+            auth.login_guard_svc.clone(),
+        ),
+        auth,
+    }
+}
+
+impl Users {
+    // a user with a password & the given roles
+    async fn user(&self, email: &str, roles: &[&str]) -> UserFull {
+        let user = self.auth.users.insert(user_with_password(email));
+        for role in roles {
+            self.auth
+                .roles
+                .assign_to_user(UserRole::new(user.id.clone(), role.to_string()))
+                .await
+                .unwrap();
+        }
+
+        UserFull::new(
+            user.clone(),
+            UserOauthProvider::new(user.id.clone(), "email".to_string(), user.email.clone()),
+            self.auth
+                .roles
+                .get_roles_by_user_id(&user.id)
+                .await
+                .unwrap(),
+        )
+    }
+
+    async fn login(&self, email: &str) -> Result<(), AppError> {
+        self.auth
+            .email_login
+            .execute(login_request(email), &ClientInfo::default())
+            .await
+            .map(|_| ())
+    }
+
+    async fn lock_out(&self, email: &str) {
+        for _ in 0..LOGIN_LOCKOUT_AFTER_FAILURES {
+            self.auth
+                .login_guard_svc
+                .record_failure(email, None)
+                .await
+                .unwrap();
+        }
+        assert!(matches!(
+            self.login(email).await,
+            Err(AppError::TooManyRequests)
+        ));
+    }
+
+    async fn find(&self, id: &str) -> User {
+        self.auth.users.find_by_id(id).await.unwrap()
+    }
+
+    // whether casbin groups the user under the role
+    async fn grouped(&self, user_id: &str, role_id: &str) -> bool {
+        self.auth
+            .rbac
+            .enforcer
+            .read()
+            .await
+            .has_grouping_policy(vec![user_id.to_string(), role_id.to_string()])
+    }
+
+    async fn role_ids(&self, user_id: &str) -> Vec<String> {
+        self.auth
+            .roles
+            .get_roles_by_user_id(user_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|role| role.id)
+            .collect()
+    }
+}
+
+fn assign(role_id: &str) -> AssignRoleRequest {
+    AssignRoleRequest {
+        role_id: role_id.to_string(),
+    }
+}
+
+fn rename(fullname: &str) -> UpdateUserRequest {
+    UpdateUserRequest {
+        fullname: Some(fullname.to_string()),
+        avatar_url: None,
+    }
+}
+
+#[tokio::test]
+async fn users_are_listed_and_read_with_their_roles() {
+    let flow = users().await;
+    let member = flow.user("member@users.test", &["member"]).await;
+    flow.user("other@users.test", &[]).await;
+
+    let page = flow.list.execute(1, 15, None).await.unwrap();
+    assert_eq!(page.pagination.total_items, 2);
+    assert!(matches!(
+        flow.list.execute(0, 15, None).await,
+        Err(AppError::ProcessError(_))
+    ));
+
+    let detail = flow.get.execute(&member.user.id).await.unwrap();
+    assert_eq!(detail.user.email, "member@users.test");
+    assert_eq!(detail.roles.len(), 1);
+    assert_eq!(detail.roles[0].name, "member");
+    assert!(matches!(
+        flow.get.execute("unknown").await,
+        Err(AppError::SqlxError(sqlx::Error::RowNotFound))
+    ));
+}
+
+#[tokio::test]
+async fn admins_update_the_users_they_manage() {
+    let flow = users().await;
+    let admin = flow.user("admin@users.test", &["admin"]).await;
+    let member = flow.user("member@users.test", &["member"]).await;
+    flow.auth.redis_svc.set_current_user(&member).await.unwrap();
+
+    let user = flow
+        .update
+        .execute(&admin, &member.user.id, rename("Mona Member"))
+        .await
+        .unwrap();
+    assert_eq!(user.fullname.as_deref(), Some("Mona Member"));
+    assert_eq!(
+        flow.find(&member.user.id).await.fullname.as_deref(),
+        Some("Mona Member")
+    );
+    // the cached user still has the old name
+    assert!(flow
+        .auth
+        .redis_svc
+        .get_current_user(&member.user.id)
+        .await
+        .is_err());
+
+    assert!(matches!(
+        flow.update
+            .execute(
+                &admin,
+                &member.user.id,
+                UpdateUserRequest {
+                    fullname: None,
+                    avatar_url: Some("not a url".to_string()),
+                },
+            )
+            .await,
+        Err(AppError::ValidationError(_))
+    ));
+}
+
+#[tokio::test]
+async fn deactivating_logs_the_user_out_until_reactivated() {
+    let flow = users().await;
+    let admin = flow.user("admin@users.test", &["admin"]).await;
+    let member = flow.user("member@users.test", &["member"]).await;
+    flow.login("member@users.test").await.unwrap();
+    flow.login("member@users.test").await.unwrap();
+    flow.auth.redis_svc.set_current_user(&member).await.unwrap();
+
+    flow.deactivate
+        .execute(&admin, &member.user.id)
+        .await
+        .unwrap();
+
+    assert!(flow.auth.sessions.all().is_empty());
+    assert!(flow
+        .auth
+        .redis_svc
+        .get_current_user(&member.user.id)
+        .await
+        .is_err());
+    assert!(!flow.find(&member.user.id).await.is_accessible());
+    assert!(matches!(
+        flow.login("member@users.test").await,
+        Err(AppError::UserInactive)
+    ));
+
+    flow.reactivate
+        .execute(&admin, &member.user.id)
+        .await
+        .unwrap();
+    flow.login("member@users.test").await.unwrap();
+    assert_eq!(flow.auth.sessions.all().len(), 1);
+}
+
+#[tokio::test]
+async fn deleted_users_are_logged_out_and_gone() {
+    let flow = users().await;
+    let admin = flow.user("admin@users.test", &["admin"]).await;
+    let member = flow.user("member@users.test", &["member"]).await;
+    flow.login("member@users.test").await.unwrap();
+
+    flow.delete.execute(&admin, &member.user.id).await.unwrap();
+
+    assert!(flow.auth.sessions.all().is_empty());
+    assert!(matches!(
+        flow.get.execute(&member.user.id).await,
+        Err(AppError::ResourceNotFound)
+    ));
+    assert!(matches!(
+        flow.update
+            .execute(&admin, &member.user.id, rename("Mona Member"))
+            .await,
+        Err(AppError::ResourceNotFound)
+    ));
+    assert!(matches!(
+        flow.reactivate.execute(&admin, &member.user.id).await,
+        Err(AppError::ResourceNotFound)
+    ));
+    assert!(matches!(
+        flow.delete.execute(&admin, &member.user.id).await,
+        Err(AppError::ResourceNotFound)
+    ));
+}
+
+#[tokio::test]
+async fn admins_can_not_touch_users_with_roles_they_do_not_hold() {
+    let flow = users().await;
+    let admin = flow.user("admin@users.test", &["admin"]).await;
+    let super_admin = flow.user("super@users.test", &[SUPER_ADMIN_ROLE]).await;
+    let auditor = flow
+        .user("auditor@users.test", &["member", "auditor"])
+        .await;
+    flow.login("super@users.test").await.unwrap();
+
+    for target in [&super_admin, &auditor] {
+        let id = &target.user.id;
+        assert!(matches!(
+            flow.update.execute(&admin, id, rename("Taken Over")).await,
+            Err(AppError::Forbidden)
+        ));
+        assert!(matches!(
+            flow.deactivate.execute(&admin, id).await,
+            Err(AppError::Forbidden)
+        ));
+        assert!(matches!(
+            flow.reactivate.execute(&admin, id).await,
+            Err(AppError::Forbidden)
+        ));
+        assert!(matches!(
+            flow.delete.execute(&admin, id).await,
+            Err(AppError::Forbidden)
+        ));
+
+        let user = flow.find(id).await;
+        assert!(user.is_accessible());
+        assert_ne!(user.fullname.as_deref(), Some("Taken Over"));
+    }
+    assert_eq!(flow.auth.sessions.all().len(), 1);
+
+    // super admins manage everyone
+    flow.deactivate
+        .execute(&super_admin, &auditor.user.id)
+        .await
+        .unwrap();
+    flow.deactivate
+        .execute(&super_admin, &admin.user.id)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn nobody_deactivates_reactivates_or_deletes_their_own_account() {
+    let flow = users().await;
+    let super_admin = flow.user("super@users.test", &[SUPER_ADMIN_ROLE]).await;
+    let id = &super_admin.user.id;
+
+    assert!(matches!(
+        flow.deactivate.execute(&super_admin, id).await,
+        Err(AppError::ProcessError(_))
+    ));
+    assert!(matches!(
+        flow.reactivate.execute(&super_admin, id).await,
+        Err(AppError::ProcessError(_))
+    ));
+    assert!(matches!(
+        flow.delete.execute(&super_admin, id).await,
+        Err(AppError::ProcessError(_))
+    ));
+    assert!(flow.find(id).await.is_accessible());
+
+    // editing the own profile is fine
+    flow.update
+        .execute(&super_admin, id, rename("Sam Super"))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn assigned_roles_are_granted_until_revoked() {
+    let flow = users().await;
+    let admin = flow.user("admin@users.test", &["admin"]).await;
+    let member = flow.user("member@users.test", &[]).await;
+    let id = &member.user.id;
+    flow.auth.redis_svc.set_current_user(&member).await.unwrap();
+
+    flow.assign
+        .execute(&admin, id, assign("member"))
+        .await
+        .unwrap();
+    assert_eq!(flow.role_ids(id).await, vec!["member".to_string()]);
+    assert!(flow.grouped(id, "member").await);
+    // the cached user doesn't hold the new role yet
+    assert!(flow.auth.redis_svc.get_current_user(id).await.is_err());
+
+    assert!(matches!(
+        flow.assign.execute(&admin, id, assign("member")).await,
+        Err(AppError::ResourceExist(_))
+    ));
+    assert_eq!(flow.role_ids(id).await.len(), 1);
+
+    flow.auth.redis_svc.set_current_user(&member).await.unwrap();
+    flow.revoke.execute(&admin, id, "member").await.unwrap();
+    assert!(flow.role_ids(id).await.is_empty());
+    assert!(!flow.grouped(id, "member").await);
+    assert!(flow.auth.redis_svc.get_current_user(id).await.is_err());
+
+    assert!(matches!(
+        flow.revoke.execute(&admin, id, "member").await,
+        Err(AppError::ResourceNotFound)
+    ));
+}
+
+#[tokio::test]
+async fn admins_only_hand_out_roles_they_hold() {
+    let flow = users().await;
+    let admin = flow.user("admin@users.test", &["admin"]).await;
+    let super_admin = flow.user("super@users.test", &[SUPER_ADMIN_ROLE]).await;
+    let member = flow.user("member@users.test", &["auditor"]).await;
+    let id = &member.user.id;
+    flow.auth.rbac.assign_role(id, "auditor").await.unwrap();
+
+    for role in [SUPER_ADMIN_ROLE, "auditor"] {
+        assert!(matches!(
+            flow.assign.execute(&admin, id, assign(role)).await,
+            Err(AppError::Forbidden)
+        ));
+    }
+    assert!(matches!(
+        flow.revoke.execute(&admin, id, "auditor").await,
+        Err(AppError::Forbidden)
+    ));
+    assert_eq!(flow.role_ids(id).await, vec!["auditor".to_string()]);
+    assert!(!flow.grouped(id, SUPER_ADMIN_ROLE).await);
+    assert!(flow.grouped(id, "auditor").await);
+
+    // inherited roles can be handed out
+    flow.assign
+        .execute(&admin, id, assign("member"))
+        .await
+        .unwrap();
+    flow.assign
+        .execute(&super_admin, id, assign(SUPER_ADMIN_ROLE))
+        .await
+        .unwrap();
+    assert!(flow.grouped(id, SUPER_ADMIN_ROLE).await);
+}
+
+#[tokio::test]
+async fn nobody_changes_their_own_roles() {
+    let flow = users().await;
+    let super_admin = flow.user("super@users.test", &[SUPER_ADMIN_ROLE]).await;
+    let id = &super_admin.user.id;
+
+    assert!(matches!(
+        flow.assign
+            .execute(&super_admin, id, assign("member"))
+            .await,
+        Err(AppError::ProcessError(_))
+    ));
+    assert!(matches!(
+        flow.revoke
+            .execute(&super_admin, id, SUPER_ADMIN_ROLE)
+            .await,
+        Err(AppError::ProcessError(_))
+    ));
+    assert_eq!(flow.role_ids(id).await, vec![SUPER_ADMIN_ROLE.to_string()]);
+    assert!(!flow.grouped(id, "member").await);
+}
+
+#[tokio::test]
+async fn admins_only_unlock_the_users_they_manage() {
+    let flow = users().await;
+    let admin = flow.user("admin@users.test", &["admin"]).await;
+    let member = flow.user("member@users.test", &["member"]).await;
+    let super_admin = flow.user("super@users.test", &[SUPER_ADMIN_ROLE]).await;
+    for email in ["member@users.test", "super@users.test"] {
+        flow.lock_out(email).await;
+    }
+
+    assert!(matches!(
+        flow.unlock.execute(&admin, &super_admin.user.id).await,
+        Err(AppError::Forbidden)
+    ));
+    assert!(matches!(
+        flow.login("super@users.test").await,
+        Err(AppError::TooManyRequests)
+    ));
+
+    flow.unlock.execute(&admin, &member.user.id).await.unwrap();
+    flow.login("member@users.test").await.unwrap();
+    flow.unlock
+        .execute(&super_admin, &super_admin.user.id)
+        .await
+        .unwrap();
+    flow.login("super@users.test").await.unwrap();
+}
@@ -0,0 +1,164 @@
+mod common;
+
+use rust_ddd_oauth_casbin::{
+    application::usecases::auth::{
+        get_user_sessions::GetUserSessions, revoke_other_sessions::RevokeOtherSessions,
+        revoke_session::RevokeSession,
+    },
+    domain::repositories::user_session_repo::UserSessionRepository,
+    infra::{errors::app_error::AppError, utils::client_info::ClientInfo},
+};
+
+use common::{
+    auth::{auth, logged_in, login_request, user_with_password, Auth},
+    memory::{MemoryOauthProviderRepo, MemoryRoleRepo, MemorySessionRepo, MemoryUserRepo},
+};
+
+const EMAIL: &str = "sid@sessions.test";
+
+struct Sessions {
+    auth: Auth,
+    list: GetUserSessions<MemorySessionRepo>,
+    revoke:
+        RevokeSession<MemoryUserRepo, MemoryRoleRepo, MemorySessionRepo, MemoryOauthProviderRepo>,
+    revoke_others: RevokeOtherSessions<
+        MemoryUserRepo,
+        MemoryRoleRepo,
+        MemorySessionRepo,
+        MemoryOauthProviderRepo,
+    >,
+}
+
+async fn sessions() -> Sessions {
+    let auth = auth().await;
+
+    Sessions {
+        list: GetUserSessions::new(auth.sessions.clone()),
+        revoke: RevokeSession::new(auth.sessions.clone(), auth.oauth_svc.clone()),
+        revoke_others: RevokeOtherSessions::new(auth.oauth_svc.clone()),
+        auth,
+    }
+}
+
+impl Sessions {
+    // returns the id of the new session
+    async fn login(&self, email: &str, user_agent: &str) -> String {
+        let (access_token, _) = logged_in(
+            self.auth
+                .email_login
+                .execute(
+                    login_request(email),
+                    &ClientInfo {
+                        user_agent: Some(user_agent.to_string()),
+                        ip_address: None,
+                    },
+                )
+                .await
+                .unwrap(),
+        );
+
+        self.auth
+            .jwt_maker
+            .verify_access_token(&access_token)
+            .unwrap()
+            .sid
+    }
+}
+
+#[tokio::test]
+async fn every_login_is_listed_as_its_own_session() {
+    let flow = sessions().await;
+    let user = flow.auth.users.insert(user_with_password(EMAIL));
+    let laptop = flow.login(EMAIL, "laptop").await;
+    flow.login(EMAIL, "phone").await;
+    let tablet = flow.login(EMAIL, "tablet").await;
+
+    // expired sessions are left out
+    let mut expired = flow.auth.sessions.find_by_id(&tablet).await.unwrap();
+    expired.expires_at = Some(chrono::Utc::now() - chrono::Duration::minutes(1));
+    flow.auth.sessions.update_token(&expired).await.unwrap();
+
+    let listed = flow.list.execute(&user.id, Some(&laptop)).await.unwrap();
+    assert_eq!(listed.len(), 2);
+    for listed in &listed {
+        assert_eq!(listed.current, listed.session.id == laptop);
+    }
+    assert!(listed
+        .iter()
+        .any(|listed| listed.session.user_agent.as_deref() == Some("phone")));
+}
+
+#[tokio::test]
+async fn a_single_session_can_be_revoked() {
+    let flow = sessions().await;
+    let user = flow.auth.users.insert(user_with_password(EMAIL));
+    let other = flow
+        .auth
+        .users
+        .insert(user_with_password("other@sessions.test"));
+    let laptop = flow.login(EMAIL, "laptop").await;
+    let phone = flow.login(EMAIL, "phone").await;
+    let others = flow.login("other@sessions.test", "laptop").await;
+
+    flow.revoke.execute(&user.id, &phone).await.unwrap();
+
+    assert!(matches!(
+        flow.auth
+            .oauth_svc
+            .get_active_session(&phone, &user.id)
+            .await,
+        Err(AppError::SessionExpired)
+    ));
+    flow.auth
+        .oauth_svc
+        .get_active_session(&laptop, &user.id)
+        .await
+        .unwrap();
+
+    // sessions of other users look like they don't exist
+    assert!(matches!(
+        flow.revoke.execute(&user.id, &others).await,
+        Err(AppError::ResourceNotFound)
+    ));
+    flow.auth
+        .oauth_svc
+        .get_active_session(&others, &other.id)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn logging_out_the_others_keeps_the_current_session() {
+    let flow = sessions().await;
+    let user = flow.auth.users.insert(user_with_password(EMAIL));
+    flow.auth
+        .users
+        .insert(user_with_password("other@sessions.test"));
+    let laptop = flow.login(EMAIL, "laptop").await;
+    flow.login(EMAIL, "phone").await;
+    flow.login(EMAIL, "tablet").await;
+    let others = flow.login("other@sessions.test", "laptop").await;
+
+    assert!(matches!(
+        flow.revoke_others.execute(&user.id, None).await,
+        Err(AppError::UnauthorizedError(_))
+    ));
+    assert_eq!(flow.auth.sessions.all().len(), 4);
+
+    flow.revoke_others
+        .execute(&user.id, Some(&laptop))
+        .await
+        .unwrap();
+
+    let mut left: Vec<String> = flow
+        .auth
+        .sessions
+        .all()
+        .into_iter()
+        .map(|session| session.id)
+        .collect();
+    left.sort();
+    let mut kept = vec![laptop, others];
+    kept.sort();
+    assert_eq!(left, kept);
+}
@@ -0,0 +1,357 @@
+mod common;
+
+use aes_gcm::aead::OsRng;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ciborium::value::Value;
+use p256::ecdsa::{signature::Signer, Signature, SigningKey};
+use rsa::{
+    pkcs1v15::SigningKey as RsaSigningKey, signature::SignatureEncoding, traits::PublicKeyParts,
+    RsaPrivateKey,
+};
+use rust_ddd_oauth_casbin::infra::utils::webauthn::{
+    WebauthnVerifier, COSE_ALG_ES256, COSE_ALG_RS256,
+};
+use sha2::{Digest, Sha256};
+
+use common::test_config;
+
+const RP_ID: &str = "localhost";
+const ORIGIN: &str = "http://localhost:8800";
+const CREDENTIAL_ID: &[u8] = b"software-authenticator-credential";
+
+const USER_PRESENT: u8 = 0x01;
+const USER_VERIFIED: u8 = 0x04;
+const ATTESTED_CREDENTIAL: u8 = 0x40;
+const EXTENSION_DATA: u8 = 0x80;
+
+fn verifier() -> WebauthnVerifier {
+    WebauthnVerifier::from_config(&test_config(&[
+        ("WEBAUTHN_RP_ID", RP_ID),
+        ("WEBAUTHN_ORIGINS", ORIGIN),
+    ]))
+}
+
+fn b64(bytes: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn cbor(value: &Value) -> Vec<u8> {
+    let mut bytes = vec![];
+    ciborium::ser::into_writer(value, &mut bytes).unwrap();
+    bytes
+}
+
+fn int(value: i64) -> Value {
+    Value::Integer(value.into())
+}
+
+fn client_data(kind: &str, challenge: &str, origin: &str) -> Vec<u8> {
+    serde_json::json!({ "type": kind, "challenge": challenge, "origin": origin })
+        .to_string()
+        .into_bytes()
+}
+
+// a platform authenticator that keeps its key in memory & signs with it
+enum SoftwareAuthenticator {
+    Es256(SigningKey),
+    Rs256(RsaPrivateKey),
+}
+
+impl SoftwareAuthenticator {
+    fn es256() -> Self {
+        Self::Es256(SigningKey::random(&mut OsRng))
+    }
+
+    fn rs256() -> Self {
+        Self::Rs256(RsaPrivateKey::new(&mut OsRng, 2048).unwrap())
+    }
+
+    fn cose_key(&self) -> Vec<u8> {
+        let params = match self {
+            Self::Es256(key) => {
+                let point = key.verifying_key().to_encoded_point(false);
+                vec![
+                    (int(1), int(2)),
+                    (int(3), int(COSE_ALG_ES256)),
+                    (int(-1), int(1)),
+                    (int(-2), Value::Bytes(point.x().unwrap().to_vec())),
+                    (int(-3), Value::Bytes(point.y().unwrap().to_vec())),
+                ]
+            }
+            Self::Rs256(key) => vec![
+                (int(1), int(3)),
+                (int(3), int(COSE_ALG_RS256)),
+                (int(-1), Value::Bytes(key.n().to_bytes_be())),
+                (int(-2), Value::Bytes(key.e().to_bytes_be())),
+            ],
+        };
+
+        cbor(&Value::Map(params))
+    }
+
+    fn sign(&self, message: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Es256(key) => {
+                let signature: Signature = key.sign(message);
+                signature.to_der().as_bytes().to_vec()
+            }
+            Self::Rs256(key) => RsaSigningKey::<Sha256>::new(key.clone())
+                .sign(message)
+                .to_vec(),
+        }
+    }
+
+    fn authenticator_data(rp_id: &str, flags: u8, sign_count: u32) -> Vec<u8> {
+        let mut data = Sha256::digest(rp_id.as_bytes()).to_vec();
+        data.push(flags);
+        data.extend_from_slice(&sign_count.to_be_bytes());
+        data
+    }
+
+    // (clientDataJSON, attestationObject) of navigator.credentials.create with `none` attestation
+    fn register(&self, challenge: &str, extensions: Option<Value>) -> (String, String) {
+        let mut flags = USER_PRESENT | USER_VERIFIED | ATTESTED_CREDENTIAL;
+        if extensions.is_some() {
+            flags |= EXTENSION_DATA;
+        }
+
+        let mut auth_data = Self::authenticator_data(RP_ID, flags, 0);
+        auth_data.extend_from_slice(&[0; 16]);
+        auth_data.extend_from_slice(&(CREDENTIAL_ID.len() as u16).to_be_bytes());
+        auth_data.extend_from_slice(CREDENTIAL_ID);
+        auth_data.extend_from_slice(&self.cose_key());
+        if let Some(extensions) = extensions {
+            auth_data.extend_from_slice(&cbor(&extensions));
+        }
+
+        let attestation = Value::Map(vec![
+            (Value::Text("fmt".into()), Value::Text("none".into())),
+            (Value::Text("attStmt".into()), Value::Map(vec![])),
+            (Value::Text("authData".into()), Value::Bytes(auth_data)),
+        ]);
+
+        (
+            b64(&client_data("webauthn.create", challenge, ORIGIN)),
+            b64(&cbor(&attestation)),
+        )
+    }
+
+    // (clientDataJSON, authenticatorData, signature) of navigator.credentials.get
+    fn assert(
+        &self,
+        challenge: &str,
+        origin: &str,
+        rp_id: &str,
+        flags: u8,
+        sign_count: u32,
+    ) -> (String, String, String) {
+        let client_data = client_data("webauthn.get", challenge, origin);
+        let auth_data = Self::authenticator_data(rp_id, flags, sign_count);
+
+        let mut message = auth_data.clone();
+        message.extend_from_slice(&Sha256::digest(&client_data));
+
+        (
+            b64(&client_data),
+            b64(&auth_data),
+            b64(&self.sign(&message)),
+        )
+    }
+}
+
+fn round_trip(authenticator: SoftwareAuthenticator) {
+    let verifier = verifier();
+    let verified = USER_PRESENT | USER_VERIFIED;
+
+    let (client_data, attestation) = authenticator.register("register-challenge", None);
+    let credential = verifier
+        .verify_registration("register-challenge", &client_data, &attestation)
+        .unwrap();
+    assert_eq!(credential.credential_id, b64(CREDENTIAL_ID));
+    assert_eq!(credential.public_key, authenticator.cose_key());
+    assert_eq!(credential.sign_count, 0);
+
+    let login = |challenge: &str,
+                 (client_data, auth_data, signature): (String, String, String),
+                 stored_sign_count: i64| {
+        verifier.verify_assertion(
+            challenge,
+            &client_data,
+            &auth_data,
+            &signature,
+            &credential.public_key,
+            stored_sign_count,
+        )
+    };
+
+    // the counter moves forward with every login
+    let sign_count = login(
+        "login-challenge",
+        authenticator.assert("login-challenge", ORIGIN, RP_ID, verified, 1),
+        credential.sign_count,
+    )
+    .unwrap();
+    assert_eq!(sign_count, 1);
+
+    let sign_count = login(
+        "login-challenge",
+        authenticator.assert("login-challenge", ORIGIN, RP_ID, verified, 5),
+        sign_count,
+    )
+    .unwrap();
+    assert_eq!(sign_count, 5);
+
+    // a counter that didn't increase points to a cloned authenticator
+    assert!(login(
+        "login-challenge",
+        authenticator.assert("login-challenge", ORIGIN, RP_ID, verified, 5),
+        sign_count,
+    )
+    .is_err());
+    assert!(login(
+        "login-challenge",
+        authenticator.assert("login-challenge", ORIGIN, RP_ID, verified, 0),
+        sign_count,
+    )
+    .is_err());
+
+    // answered another challenge
+    assert!(login(
+        "login-challenge",
+        authenticator.assert("other-challenge", ORIGIN, RP_ID, verified, 6),
+        sign_count,
+    )
+    .is_err());
+
+    // phishing page & credential of another relying party
+    assert!(login(
+        "login-challenge",
+        authenticator.assert("login-challenge", "http://evil.test", RP_ID, verified, 6),
+        sign_count,
+    )
+    .is_err());
+    assert!(login(
+        "login-challenge",
+        authenticator.assert("login-challenge", ORIGIN, "evil.test", verified, 6),
+        sign_count,
+    )
+    .is_err());
+
+    // the user only touched the authenticator without verifying
+    assert!(login(
+        "login-challenge",
+        authenticator.assert("login-challenge", ORIGIN, RP_ID, USER_PRESENT, 6),
+        sign_count,
+    )
+    .is_err());
+
+    // signed by another key
+    let other = match authenticator {
+        SoftwareAuthenticator::Es256(_) => SoftwareAuthenticator::es256(),
+        SoftwareAuthenticator::Rs256(_) => SoftwareAuthenticator::rs256(),
+    };
+    assert!(login(
+        "login-challenge",
+        other.assert("login-challenge", ORIGIN, RP_ID, verified, 6),
+        sign_count,
+    )
+    .is_err());
+
+    // authenticators without a counter always send 0
+    let counterless_login = |stored_sign_count: i64| {
+        login(
+            "login-challenge",
+            authenticator.assert("login-challenge", ORIGIN, RP_ID, verified, 0),
+            stored_sign_count,
+        )
+    };
+    assert_eq!(counterless_login(0).unwrap(), 0);
+    assert_eq!(counterless_login(0).unwrap(), 0);
+}
+
+#[test]
+fn es256_registration_and_login() {
+    round_trip(SoftwareAuthenticator::es256());
+}
+
+#[test]
+fn rs256_registration_and_login() {
+    round_trip(SoftwareAuthenticator::rs256());
+}
+
+#[test]
+fn registration_keeps_only_the_key_when_extensions_follow() {
+    let authenticator = SoftwareAuthenticator::es256();
+    let extensions = Value::Map(vec![(Value::Text("credProtect".into()), int(2))]);
+
+    let (client_data, attestation) = authenticator.register("challenge", Some(extensions));
+    let credential = verifier()
+        .verify_registration("challenge", &client_data, &attestation)
+        .unwrap();
+
+    assert_eq!(credential.public_key, authenticator.cose_key());
+}
+
+#[test]
+fn registration_is_bound_to_its_challenge_and_ceremony() {
+    let verifier = verifier();
+    let authenticator = SoftwareAuthenticator::es256();
+
+    let (client_data, attestation) = authenticator.register("challenge", None);
+    assert!(verifier
+        .verify_registration("another-challenge", &client_data, &attestation)
+        .is_err());
+
+    // a login response can't be used to register
+    let (login_client_data, _, _) =
+        authenticator.assert("challenge", ORIGIN, RP_ID, USER_PRESENT | USER_VERIFIED, 1);
+    assert!(verifier
+        .verify_registration("challenge", &login_client_data, &attestation)
+        .is_err());
+}
+
+#[test]
+fn registration_rejects_keys_that_cant_be_verified() {
+    let cose_keys = [
+        // EdDSA is not supported, the passkey could never be used to login
+        Value::Map(vec![
+            (int(1), int(1)),
+            (int(3), int(-8)),
+            (int(-1), int(6)),
+            (int(-2), Value::Bytes(vec![0; 32])),
+        ]),
+        // ES256 coordinates are 32 bytes each
+        Value::Map(vec![
+            (int(1), int(2)),
+            (int(3), int(-7)),
+            (int(-1), int(1)),
+            (int(-2), Value::Bytes(vec![1; 31])),
+            (int(-3), Value::Bytes(vec![1; 33])),
+        ]),
+    ];
+
+    for cose_key in cose_keys {
+        let mut auth_data = SoftwareAuthenticator::authenticator_data(
+            RP_ID,
+            USER_PRESENT | USER_VERIFIED | ATTESTED_CREDENTIAL,
+            0,
+        );
+        auth_data.extend_from_slice(&[0; 16]);
+        auth_data.extend_from_slice(&(CREDENTIAL_ID.len() as u16).to_be_bytes());
+        auth_data.extend_from_slice(CREDENTIAL_ID);
+        auth_data.extend_from_slice(&cbor(&cose_key));
+        let attestation = Value::Map(vec![
+            (Value::Text("fmt".into()), Value::Text("none".into())),
+            (Value::Text("attStmt".into()), Value::Map(vec![])),
+            (Value::Text("authData".into()), Value::Bytes(auth_data)),
+        ]);
+
+        assert!(verifier()
+            .verify_registration(
+                "challenge",
+                &b64(&client_data("webauthn.create", "challenge", ORIGIN)),
+                &b64(&cbor(&attestation)),
+            )
+            .is_err());
+    }
+}